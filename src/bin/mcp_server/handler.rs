@@ -6,7 +6,7 @@ use rust_mcp_sdk::schema::{
 use rust_mcp_sdk::{mcp_server::ServerHandler, McpServer};
 use std::sync::Arc;
 
-use crate::tools::{ConverterTools, ListDtsTracks};
+use crate::tools::{CheckSensorHealth, ConvertDtsToUff, ConverterTools, ListDtsTracks};
 
 #[derive(Default)]
 pub struct ConverterServerHandler;
@@ -20,11 +20,17 @@ impl ServerHandler for ConverterServerHandler {
     ) -> std::result::Result<ListToolsResult, RpcError> {
         let mut tools = ConverterTools::tools();
 
-        if let Some(tool) = tools
-            .iter_mut()
-            .find(|tool| tool.name == ListDtsTracks::tool_name())
-        {
-            if let Some(output_schema) = ListDtsTracks::output_schema() {
+        for tool in tools.iter_mut() {
+            let output_schema = if tool.name == ListDtsTracks::tool_name() {
+                ListDtsTracks::output_schema()
+            } else if tool.name == ConvertDtsToUff::tool_name() {
+                ConvertDtsToUff::output_schema()
+            } else if tool.name == CheckSensorHealth::tool_name() {
+                CheckSensorHealth::output_schema()
+            } else {
+                None
+            };
+            if let Some(output_schema) = output_schema {
                 tool.output_schema = Some(output_schema);
             }
         }
@@ -47,6 +53,8 @@ impl ServerHandler for ConverterServerHandler {
         match tool_params {
             ConverterTools::ConvertDtsToUff(tool) => tool.call_tool().await,
             ConverterTools::ListDtsTracks(tool) => tool.call_tool().await,
+            ConverterTools::CheckSensorHealth(tool) => tool.call_tool().await,
+            ConverterTools::PlotDtsTracks(tool) => tool.call_tool().await,
         }
     }
 }
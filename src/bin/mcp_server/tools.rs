@@ -1,8 +1,29 @@
 use anyhow::Context as _;
-use dts_to_uff_converter::conversion::{self, OutputFormat, SampleSlice};
+use base64::Engine as _;
+use dts_to_uff_converter::conversion::{
+    self, ConversionOptions, FrfOptions, InjuryOptions, OutputFormat, SampleSlice, SrsOptions,
+};
+use dts_to_uff_converter::csv::{parse_delimiter, CsvRateHandling, CsvSettings};
+use dts_to_uff_converter::derived::DerivedChannelSpec;
 use dts_to_uff_converter::dts;
+use dts_to_uff_converter::expression::ExpressionChannel;
+use dts_to_uff_converter::filter::{
+    CfcClass, CfcFiltering, ChannelCfcTarget, ChannelFilterTarget, FilterChain, FilterSpec,
+};
+use dts_to_uff_converter::health::{self, HealthSettings, SensorHealth};
+use dts_to_uff_converter::id_template::{IdLineOverride, IdPreset, IdTemplate};
+use dts_to_uff_converter::injury::{NijIntercepts, RoleAssignment};
+use dts_to_uff_converter::mme::{MmeChannelCode, MmeChannelCodes};
+use dts_to_uff_converter::plot::{self, PlotFormat, PlotSettings};
+use dts_to_uff_converter::quality::QualitySettings;
+use dts_to_uff_converter::spectral::{
+    Averaging, FrfEstimator, SpectralSettings, SpectrumKind, Window,
+};
+use dts_to_uff_converter::srs::{SrsKind, SrsSettings};
+use dts_to_uff_converter::statistics::ChannelStatistics;
+use dts_to_uff_converter::units::{ChannelUnitTarget, QuantityTarget, UnitConversion};
 use rust_mcp_sdk::schema::{
-    schema_utils::CallToolError, CallToolResult, TextContent, ToolOutputSchema,
+    schema_utils::CallToolError, CallToolResult, ContentBlock, TextContent, ToolOutputSchema,
 };
 use rust_mcp_sdk::{macros::mcp_tool, macros::JsonSchema, tool_box};
 use serde::{Deserialize, Serialize};
@@ -12,11 +33,20 @@ use std::fmt::Write as _;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-tool_box!(ConverterTools, [ConvertDtsToUff, ListDtsTracks]);
+tool_box!(
+    ConverterTools,
+    [
+        ConvertDtsToUff,
+        ListDtsTracks,
+        CheckSensorHealth,
+        PlotDtsTracks
+    ]
+);
 
 #[mcp_tool(
     name = "convert_dts_to_uff",
-    description = "Convert a DTS test folder into a UFF Type 58 file.",
+    description = "Convert a DTS test folder into a UFF Type 58 file, or export it as ISO 13499 \
+                   MME, MATLAB .mat, CSV/TSV, WAV, NumPy .npz, MDF4, TDMS or raw ADC counts.",
     title = "Convert DTS folder to UFF",
     idempotent_hint = false,
     destructive_hint = true,
@@ -33,9 +63,16 @@ pub struct ConvertDtsToUff {
     /// file path, not a directory.
     tracks_file: String,
     /// Absolute path (including filename) where the generated `.uff` file should be written.
-    /// Pass a file path; the parent directory must already exist.
+    /// Pass a file path; the parent directory must already exist. For `mme`, pass the test
+    /// directory to create; its files are named after it. For `raw`, pass the directory to
+    /// create. For `csv`, a `.tsv` extension selects tab-separated text.
     output_path: String,
-    /// Output format (`ascii` or `binary`). Defaults to `ascii`.
+    /// Output format: `ascii` or `binary` UFF, `mme` for an ISO/TS 13499 test directory, `mat`
+    /// for a MATLAB MAT-file with the `DTS.read` fields except `ADC`, `csv` for delimited text
+    /// with a time column, `wav` for a 32-bit float multichannel WAVE file, `npz` for a NumPy
+    /// archive, `mdf` for an ASAM MDF 4 file or `tdms` for an NI TDMS file, each holding the time
+    /// histories, or `raw` for a directory with the unscaled ADC counts of the tracks and a JSON
+    /// manifest of their exact scaling. Defaults to `ascii`.
     #[serde(default)]
     format: Option<String>,
     /// Optional comma-separated list of track names to write.
@@ -49,9 +86,440 @@ pub struct ConvertDtsToUff {
     /// to export the full range.
     #[serde(default)]
     slice: Option<String>,
+    /// Optional SAE J211 / ISO 6487 channel frequency class applied to every channel before
+    /// export: `60`, `180`, `600` or `1000`. The class is recorded in ID record 3.
+    #[serde(default)]
+    cfc: Option<String>,
+    /// Optional comma-separated list of `track=class` CFC overrides, e.g. `A1=180,A2=none`.
+    /// These override `cfc` for the named tracks.
+    #[serde(default)]
+    channel_cfc: Option<String>,
+    /// Optional comma-separated list of IIR filters applied in order to every channel after the
+    /// CFC filter, as `type:frequency[:option...]`, e.g. `highpass:0.5:order=2,notch:50:q=30`.
+    /// Types are `lowpass`, `highpass`, `bandpass` and `bandstop` (`low-high` range) and `notch`;
+    /// options are `order=N` (1-8), `design=butterworth|bessel`, `q=Q` and `causal`. Filters run
+    /// forward and backward (zero phase) by default.
+    #[serde(default)]
+    filters: Option<String>,
+    /// Optional comma-separated list of `track=spec` filters for individual tracks, e.g.
+    /// `A1=lowpass:1000:design=bessel`.
+    #[serde(default)]
+    channel_filters: Option<String>,
+    /// Optional absolute path of a JSON filter configuration applied before `filters`, e.g.
+    /// `{"filters": [{"type": "bandpass", "low": 10, "high": 2000, "tracks": ["A1"]}]}`.
+    #[serde(default)]
+    filter_config: Option<String>,
+    /// Optional sample rate, in Hz, every channel is resampled to after filtering, e.g. `20000`.
+    /// Rate ratios are approximated by integer factors up to 10000; the effective rates are
+    /// listed in the summary.
+    #[serde(default)]
+    resample_rate: Option<f64>,
+    /// Optional comma-separated list of `quantity=unit` targets applied to every channel of that
+    /// quantity, e.g. `acceleration=m/s^2,force=kN`. Supported quantities are `acceleration`
+    /// (`g`, `m/s^2`, `mm/s^2`), `force` (`N`, `kN`, `lbf`) and `pressure` (`Pa`, `kPa`, `MPa`,
+    /// `bar`, `psi`).
+    #[serde(default)]
+    units: Option<String>,
+    /// Optional comma-separated list of `track=unit` targets for individual tracks. These
+    /// override `units` and `si_units` for the named tracks.
+    #[serde(default)]
+    channel_units: Option<String>,
+    /// When true, convert acceleration, force and pressure channels to SI units
+    /// (`m/s^2`, `N`, `Pa`). Entries in `units` override the preset for their quantity.
+    #[serde(default)]
+    si_units: Option<bool>,
+    /// Layout of the UFF ID records 1-5: `matlab` (default, matches the MATLAB reference output)
+    /// or `metadata` (test, description, date, serial, sensitivity, sample rate and excitation).
+    #[serde(default)]
+    id_preset: Option<String>,
+    /// Optional per-record template overrides, each written as `line=template` with `line`
+    /// between 1 and 5, e.g. `4=SN:{serial} {sensitivity} mV/{eu}`. Placeholders: `{track}`,
+    /// `{name}`, `{description}`, `{serial}`, `{sensitivity}`, `{eu}`, `{units}`,
+    /// `{excitation}`, `{sample_rate}`, `{test}`, `{test_description}`, `{date}`, `{filter}`.
+    #[serde(default)]
+    id_lines: Option<Vec<String>>,
+    /// When true, write a dataset 1858 block before every 58 record describing the windowing,
+    /// averaging, filtering, weighting and scaling applied during conversion.
+    #[serde(default)]
+    write_1858: Option<bool>,
+    /// Optional comma-separated list of frequency-domain records written after every time
+    /// history: `auto-power` (function type 2), `psd` (9) or `linear` (spectrum, 12).
+    #[serde(default)]
+    spectra: Option<String>,
+    /// FFT window for `spectra`: `rectangular`, `hann` (default) or `flat-top`.
+    #[serde(default)]
+    window: Option<String>,
+    /// Samples per FFT block for `spectra`. Defaults to 4096.
+    #[serde(default)]
+    block_size: Option<u64>,
+    /// Overlap between FFT blocks in percent (0 to <100). Defaults to 50.
+    #[serde(default)]
+    overlap: Option<f64>,
+    /// Block averaging for `spectra`: `linear` (default), `peak-hold` or `vector` for a
+    /// synchronous complex mean of the linear spectrum. FRFs always average linearly.
+    #[serde(default)]
+    averaging: Option<String>,
+    /// Optional reference (excitation) track. When set, complex FRF records (function type 4)
+    /// are written after every response track, using `window`, `block_size` and `overlap`.
+    #[serde(default)]
+    frf_reference: Option<String>,
+    /// Optional comma-separated response tracks for `frf_reference`. Defaults to every other
+    /// exported track.
+    #[serde(default)]
+    frf_responses: Option<String>,
+    /// FRF estimator: `h1` (default) or `h2`.
+    #[serde(default)]
+    frf_estimator: Option<String>,
+    /// When true, also write the coherence (function type 6) of every FRF.
+    #[serde(default)]
+    coherence: Option<bool>,
+    /// Optional comma-separated shock response spectrum curves written after every time
+    /// history: `maximax`, `primary` and/or `residual` (Smallwood ramp-invariant filter,
+    /// uneven log-spaced frequency abscissa).
+    #[serde(default)]
+    srs: Option<String>,
+    /// SRS damping as a fraction of critical damping. Defaults to 0.05 (Q = 10).
+    #[serde(default)]
+    srs_damping: Option<f64>,
+    /// Lowest SRS natural frequency in Hz. Defaults to 10.
+    #[serde(default)]
+    srs_min_frequency: Option<f64>,
+    /// Highest SRS natural frequency in Hz, below the Nyquist frequency. Defaults to 10000.
+    #[serde(default)]
+    srs_max_frequency: Option<f64>,
+    /// Number of SRS natural frequencies per octave. Defaults to 12.
+    #[serde(default)]
+    srs_points_per_octave: Option<u32>,
+    /// Optional absolute path of a CSV file receiving the SRS curves of every exported track.
+    #[serde(default)]
+    srs_csv: Option<String>,
+    /// Optional comma-separated list of derived channels appended after the exported tracks, as
+    /// `track=operation[:option...]`, e.g. `A1=velocity,A1=displacement:offset=200:highpass=0.5`.
+    /// Operations are `integrate` (`velocity`), `double-integrate` (`displacement`) and
+    /// `differentiate`; options are `name=TRACK`, `offset=N|mean` and `highpass=HZ`.
+    /// Acceleration is converted to m/s and m.
+    #[serde(default)]
+    derived: Option<String>,
+    /// Optional list of channels computed sample by sample from other tracks, each as
+    /// `name[units]=expression`, e.g. `["HeadR[g]=sqrt(HX^2+HY^2+HZ^2)", "Fsum=LC1+LC2"]`.
+    /// Supports `+ - * / ^`, parentheses, `sqrt`, `abs`, `exp`, `ln`, `log10`, `sin`, `cos`,
+    /// `min` and `max`; track names with other characters go in braces (`{Head X}`). Units
+    /// default to those of the first track used; inputs at another rate are resampled.
+    #[serde(default)]
+    expressions: Option<Vec<String>>,
+    /// Optional comma-separated `role=track` assignments for crash injury criteria, e.g.
+    /// `head-x=A1,head-y=A2,head-z=A3,neck-fz=F1,neck-my=M1`. Roles are `head-x`, `head-y`,
+    /// `head-z` (HIC15, HIC36, head 3 ms clip), `chest-x`, `chest-y`, `chest-z` (chest 3 ms
+    /// clip), `chest-deflection`, and `neck-fz`, `neck-my` and optionally `neck-fx` (Nij). Use
+    /// `cfc`/`channel_cfc` to filter the tracks first.
+    #[serde(default)]
+    injury_roles: Option<String>,
+    /// Optional Nij intercepts as `tension,compression,flexion,extension[,condyle_offset]` in N,
+    /// N·m and m. Defaults to the Hybrid III 50th percentile male.
+    #[serde(default)]
+    nij_intercepts: Option<String>,
+    /// Optional absolute path of a JSON file receiving the injury criteria report.
+    #[serde(default)]
+    injury_json: Option<String>,
+    /// Optional absolute path of a CSV file receiving the injury criteria report.
+    #[serde(default)]
+    injury_csv: Option<String>,
+    /// Optional boolean; when `false`, skips the clipping, flat-channel and noise checks run on
+    /// the raw ADC data of every track. Defaults to `true`.
+    #[serde(default)]
+    quality_checks: Option<bool>,
+    /// Optional boolean; when `true`, fails the conversion if a track is clipped, flat or noisy
+    /// instead of reporting warnings.
+    #[serde(default)]
+    strict_quality: Option<bool>,
+    /// Optional standard deviation, in ADC counts, below which a track is reported as flat.
+    /// Defaults to 0.5.
+    #[serde(default)]
+    flat_threshold: Option<f64>,
+    /// Optional multiple of the pre-test noise in the channel header above which a track is
+    /// reported as noisy. Defaults to 10.
+    #[serde(default)]
+    noise_ratio: Option<f64>,
+    /// Optional comma-separated `track=code` ISO/TS 13499 channel codes for the `mme` format,
+    /// e.g. `A1=11HEAD0000H3ACX?,A2=11HEAD0000H3ACY?`. A `?` as the 16th character is replaced
+    /// by the filter class of the track (`A`-`D` for CFC 1000-60, `0` unfiltered, `X` other).
+    /// Tracks without a code are written with `NOVALUE`.
+    #[serde(default)]
+    mme_channel_codes: Option<String>,
+    /// Optional field delimiter for the `csv` format: a single character or `comma`,
+    /// `semicolon`, `tab`, `space` or `pipe`. Defaults to a tab for `.tsv` files and a comma
+    /// otherwise.
+    #[serde(default)]
+    csv_delimiter: Option<String>,
+    /// Optional decimal separator for the `csv` format, `.` or `,`. Defaults to `.`.
+    #[serde(default)]
+    csv_decimal: Option<String>,
+    /// Optional number of digits after the decimal point for the `csv` format. Defaults to every
+    /// significant digit.
+    #[serde(default)]
+    csv_precision: Option<u64>,
+    /// When true, add units, sensitivity and serial number rows below the column names of the
+    /// `csv` format. Defaults to false.
+    #[serde(default)]
+    csv_metadata: Option<bool>,
+    /// Optional handling of tracks sampled at different rates in the `csv` format: `split`
+    /// writes one file per rate (`<name>_<rate>Hz.csv`), `align` interpolates every track onto
+    /// the highest rate. Defaults to `split`.
+    #[serde(default)]
+    csv_rates: Option<String>,
+    /// When true, scale every channel of the `wav` format to a peak of 1.0; the scale factors
+    /// are stored in the file. Defaults to false.
+    #[serde(default)]
+    wav_normalize: Option<bool>,
+    /// Optional absolute path of a JSON sidecar receiving the provenance of the conversion: the
+    /// source folder and `.dts` SHA-256, the channel plan, every channel's `.dts` metadata, the
+    /// applied slice and processing, the output format and the converter version.
+    #[serde(default)]
+    metadata_json: Option<String>,
 }
 
 impl ConvertDtsToUff {
+    fn cfc_filtering(&self) -> Result<CfcFiltering, String> {
+        let mut filtering = CfcFiltering::default();
+        filtering.set_default(self.cfc.as_deref().map(CfcClass::from_str).transpose()?);
+        for entry in split_list(self.channel_cfc.as_deref()) {
+            let target = ChannelCfcTarget::from_str(entry)?;
+            filtering.set_channel(target.track_name, target.class);
+        }
+        Ok(filtering)
+    }
+
+    fn filter_chain(&self) -> Result<FilterChain, String> {
+        let mut chain = FilterChain::default();
+        if let Some(path) = self.filter_config.as_deref() {
+            chain
+                .extend_from_file(Path::new(path))
+                .map_err(|err| format!("{err:#}"))?;
+        }
+        for entry in split_list(self.filters.as_deref()) {
+            chain.push(FilterSpec::from_str(entry)?, None);
+        }
+        for entry in split_list(self.channel_filters.as_deref()) {
+            let target = ChannelFilterTarget::from_str(entry)?;
+            chain.push(target.spec, Some(vec![target.track_name]));
+        }
+        Ok(chain)
+    }
+
+    fn unit_conversion(&self) -> Result<UnitConversion, String> {
+        let mut conversion = if self.si_units.unwrap_or(false) {
+            UnitConversion::si()
+        } else {
+            UnitConversion::default()
+        };
+
+        for entry in split_list(self.units.as_deref()) {
+            let target = QuantityTarget::from_str(entry)?;
+            conversion.set_quantity_target(target.quantity, target.unit);
+        }
+        for entry in split_list(self.channel_units.as_deref()) {
+            let target = ChannelUnitTarget::from_str(entry)?;
+            conversion.set_channel_target(target.track_name, target.unit);
+        }
+
+        Ok(conversion)
+    }
+
+    fn id_template(&self) -> Result<Option<IdTemplate>, String> {
+        let preset = self
+            .id_preset
+            .as_deref()
+            .map(IdPreset::from_str)
+            .transpose()?
+            .unwrap_or_default();
+        let overrides = self
+            .id_lines
+            .iter()
+            .flatten()
+            .map(|line| IdLineOverride::from_str(line))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(preset.template(overrides))
+    }
+
+    fn spectra(&self) -> Result<(Vec<SpectrumKind>, SpectralSettings), String> {
+        let kinds = split_list(self.spectra.as_deref())
+            .map(SpectrumKind::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let defaults = SpectralSettings::default();
+        let settings = SpectralSettings {
+            window: self
+                .window
+                .as_deref()
+                .map(Window::from_str)
+                .transpose()?
+                .unwrap_or(defaults.window),
+            block_size: self
+                .block_size
+                .map_or(defaults.block_size, |size| size as usize),
+            overlap_percent: self.overlap.unwrap_or(defaults.overlap_percent),
+            averaging: self
+                .averaging
+                .as_deref()
+                .map(Averaging::from_str)
+                .transpose()?
+                .unwrap_or(defaults.averaging),
+        };
+        if !kinds.is_empty() || self.frf_reference.is_some() {
+            settings.validate().map_err(|err| err.to_string())?;
+        }
+
+        Ok((kinds, settings))
+    }
+
+    fn quality_settings(&self) -> QualitySettings {
+        let defaults = QualitySettings::default();
+        QualitySettings {
+            enabled: self.quality_checks.unwrap_or(true),
+            strict: self.strict_quality.unwrap_or(false),
+            flat_threshold: self.flat_threshold.unwrap_or(defaults.flat_threshold),
+            noise_ratio: self.noise_ratio.unwrap_or(defaults.noise_ratio),
+            ..defaults
+        }
+    }
+
+    fn mme_codes(&self) -> Result<MmeChannelCodes, String> {
+        let mut codes = MmeChannelCodes::default();
+        for entry in split_list(self.mme_channel_codes.as_deref()) {
+            codes.insert(MmeChannelCode::from_str(entry)?);
+        }
+        Ok(codes)
+    }
+
+    fn csv_settings(&self) -> Result<CsvSettings, String> {
+        let decimal_separator = match self.csv_decimal.as_deref().map(str::trim) {
+            None => '.',
+            Some(".") => '.',
+            Some(",") => ',',
+            Some(other) => {
+                return Err(format!(
+                    "Invalid decimal separator '{other}': expected '.' or ','."
+                ))
+            }
+        };
+        Ok(CsvSettings {
+            delimiter: self
+                .csv_delimiter
+                .as_deref()
+                .map(parse_delimiter)
+                .transpose()?,
+            decimal_separator,
+            precision: self.csv_precision.map(|digits| digits as usize),
+            metadata: self.csv_metadata.unwrap_or(false),
+            rates: self
+                .csv_rates
+                .as_deref()
+                .map(CsvRateHandling::from_str)
+                .transpose()?
+                .unwrap_or_default(),
+        })
+    }
+
+    fn injury_options(&self) -> Result<Option<InjuryOptions>, String> {
+        let roles = split_list(self.injury_roles.as_deref())
+            .map(RoleAssignment::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        let path = |value: Option<&str>| {
+            value
+                .map(str::trim)
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from)
+        };
+        let json_output = path(self.injury_json.as_deref());
+        let csv_output = path(self.injury_csv.as_deref());
+        if roles.is_empty() {
+            if json_output.is_some() || csv_output.is_some() || self.nij_intercepts.is_some() {
+                return Err("Injury criteria options require `injury_roles`".to_string());
+            }
+            return Ok(None);
+        }
+
+        Ok(Some(InjuryOptions {
+            roles,
+            intercepts: self
+                .nij_intercepts
+                .as_deref()
+                .map(NijIntercepts::from_str)
+                .transpose()?
+                .unwrap_or_default(),
+            json_output,
+            csv_output,
+        }))
+    }
+
+    fn srs_options(&self) -> Result<Option<SrsOptions>, String> {
+        let kinds = split_list(self.srs.as_deref())
+            .map(SrsKind::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        let csv_output = self
+            .srs_csv
+            .as_deref()
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from);
+        if kinds.is_empty() {
+            if csv_output.is_some() {
+                return Err("`srs_csv` requires at least one `srs` curve".to_string());
+            }
+            return Ok(None);
+        }
+
+        let defaults = SrsSettings::default();
+        let settings = SrsSettings {
+            damping: self.srs_damping.unwrap_or(defaults.damping),
+            min_frequency: self.srs_min_frequency.unwrap_or(defaults.min_frequency),
+            max_frequency: self.srs_max_frequency.unwrap_or(defaults.max_frequency),
+            points_per_octave: self
+                .srs_points_per_octave
+                .unwrap_or(defaults.points_per_octave),
+        };
+        settings.validate().map_err(|err| err.to_string())?;
+
+        Ok(Some(SrsOptions {
+            kinds,
+            settings,
+            csv_output,
+        }))
+    }
+
+    fn frf_options(&self) -> Result<Option<FrfOptions>, String> {
+        let Some(reference) = self
+            .frf_reference
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        else {
+            if self.frf_responses.is_some() || self.coherence.unwrap_or(false) {
+                return Err("`frf_responses` and `coherence` require `frf_reference`".to_string());
+            }
+            return Ok(None);
+        };
+
+        let responses = self
+            .frf_responses
+            .as_deref()
+            .map(parse_track_selection)
+            .transpose()?;
+        let estimator = self
+            .frf_estimator
+            .as_deref()
+            .map(FrfEstimator::from_str)
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(Some(FrfOptions {
+            reference: reference.to_string(),
+            responses,
+            estimator,
+            coherence: self.coherence.unwrap_or(false),
+        }))
+    }
+
     pub async fn call_tool(&self) -> Result<CallToolResult, CallToolError> {
         if self.input_dir.trim().is_empty() {
             return Err(CallToolError::invalid_arguments(
@@ -89,6 +557,67 @@ impl ConvertDtsToUff {
             .transpose()
             .map_err(|err| CallToolError::invalid_arguments("convert_dts_to_uff", Some(err)))?;
 
+        let cfc = self
+            .cfc_filtering()
+            .map_err(|err| CallToolError::invalid_arguments("convert_dts_to_uff", Some(err)))?;
+
+        let filters = self
+            .filter_chain()
+            .map_err(|err| CallToolError::invalid_arguments("convert_dts_to_uff", Some(err)))?;
+
+        let units = self
+            .unit_conversion()
+            .map_err(|err| CallToolError::invalid_arguments("convert_dts_to_uff", Some(err)))?;
+
+        let id_template = self
+            .id_template()
+            .map_err(|err| CallToolError::invalid_arguments("convert_dts_to_uff", Some(err)))?;
+
+        let (spectra, spectral) = self
+            .spectra()
+            .map_err(|err| CallToolError::invalid_arguments("convert_dts_to_uff", Some(err)))?;
+
+        let frf = self
+            .frf_options()
+            .map_err(|err| CallToolError::invalid_arguments("convert_dts_to_uff", Some(err)))?;
+
+        let srs = self
+            .srs_options()
+            .map_err(|err| CallToolError::invalid_arguments("convert_dts_to_uff", Some(err)))?;
+        let srs_csv = srs.as_ref().and_then(|options| options.csv_output.clone());
+
+        let derived = split_list(self.derived.as_deref())
+            .map(DerivedChannelSpec::from_str)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| CallToolError::invalid_arguments("convert_dts_to_uff", Some(err)))?;
+
+        let injury = self
+            .injury_options()
+            .map_err(|err| CallToolError::invalid_arguments("convert_dts_to_uff", Some(err)))?;
+
+        let mme_codes = self
+            .mme_codes()
+            .map_err(|err| CallToolError::invalid_arguments("convert_dts_to_uff", Some(err)))?;
+
+        let csv = self
+            .csv_settings()
+            .map_err(|err| CallToolError::invalid_arguments("convert_dts_to_uff", Some(err)))?;
+
+        let expressions = self
+            .expressions
+            .iter()
+            .flatten()
+            .map(|expression| ExpressionChannel::from_str(expression))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| CallToolError::invalid_arguments("convert_dts_to_uff", Some(err)))?;
+
+        let metadata_json = self
+            .metadata_json
+            .as_deref()
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from);
+
         let input_dir = PathBuf::from(&self.input_dir);
         let tracks_file = PathBuf::from(&self.tracks_file);
         let output_path = PathBuf::from(output_path_str);
@@ -109,16 +638,36 @@ impl ConvertDtsToUff {
             let input_dir = input_dir.clone();
             let tracks_file = tracks_file.clone();
             let output_path = output_path.clone();
-            let track_selection = track_selection.clone();
+            let options = ConversionOptions {
+                slice,
+                track_list_filter: track_selection.clone(),
+                cfc,
+                filters,
+                resample_rate: self.resample_rate,
+                units,
+                id_template,
+                write_1858: self.write_1858.unwrap_or(false),
+                spectra,
+                spectral,
+                frf,
+                srs,
+                derived,
+                expressions,
+                injury,
+                quality: self.quality_settings(),
+                mme_codes,
+                csv,
+                wav_normalize: self.wav_normalize.unwrap_or(false),
+                metadata_json: metadata_json.clone(),
+            };
             move || {
-                let track_filter = track_selection.as_deref();
-                conversion::convert_with_options(
+                conversion::convert_with_progress_and_options(
                     &input_dir,
                     &tracks_file,
                     &output_path,
                     format,
-                    slice,
-                    track_filter,
+                    &options,
+                    |_| {},
                 )
             }
         })
@@ -133,7 +682,12 @@ impl ConvertDtsToUff {
         let _ = writeln!(&mut summary);
         let _ = writeln!(&mut summary, "- **Input directory:** `{}`", input_display);
         let _ = writeln!(&mut summary, "- **Track names file:** `{}`", tracks_display);
-        let _ = writeln!(&mut summary, "- **Output file:** `{}`", output_display);
+        let output_label = if matches!(format, OutputFormat::Mme | OutputFormat::Raw) {
+            "Output directory"
+        } else {
+            "Output file"
+        };
+        let _ = writeln!(&mut summary, "- **{}:** `{}`", output_label, output_display);
         let _ = writeln!(&mut summary, "- **Format:** `{}`", format_display);
         let _ = writeln!(
             &mut summary,
@@ -165,6 +719,139 @@ impl ConvertDtsToUff {
             let _ = writeln!(&mut summary, "- **Sample slice:** full range");
         }
 
+        if self.write_1858.unwrap_or(false) {
+            let _ = writeln!(&mut summary, "- **Dataset 1858 qualifiers:** written");
+        }
+
+        if report.spectrum_count > 0 {
+            let _ = writeln!(
+                &mut summary,
+                "- **Spectrum records:** {}",
+                report.spectrum_count
+            );
+        }
+
+        if let Some(reference) = self.frf_reference.as_deref() {
+            let _ = writeln!(
+                &mut summary,
+                "- **FRF records:** {} (reference `{}`)",
+                report.frf_count,
+                reference.trim()
+            );
+        }
+
+        if report.srs_count > 0 {
+            let _ = writeln!(&mut summary, "- **SRS records:** {}", report.srs_count);
+        }
+        if let Some(path) = srs_csv {
+            let _ = writeln!(&mut summary, "- **SRS CSV:** `{}`", path.display());
+        }
+        if let Some(path) = metadata_json {
+            let _ = writeln!(&mut summary, "- **Metadata sidecar:** `{}`", path.display());
+        }
+
+        if !report.derived_channels.is_empty() {
+            let _ = writeln!(&mut summary, "\n**Derived channels:**");
+            for (track_name, derivation) in &report.derived_channels {
+                let _ = writeln!(
+                    &mut summary,
+                    "- {} (`{}`): {} of {}",
+                    track_name, derivation.units, derivation.operation, derivation.source
+                );
+            }
+        }
+
+        if !report.expression_channels.is_empty() {
+            let _ = writeln!(&mut summary, "\n**Expression channels:**");
+            for channel in &report.expression_channels {
+                let _ = writeln!(&mut summary, "- `{}`", channel);
+            }
+        }
+
+        if let Some(injury) = report.injury.as_ref() {
+            let _ = writeln!(&mut summary, "\n**Injury criteria:**");
+            for result in &injury.criteria {
+                let _ = writeln!(
+                    &mut summary,
+                    "- {}: {:.3}{}{} ({:.5} s to {:.5} s; {})",
+                    result.criterion,
+                    result.value,
+                    if result.units.is_empty() { "" } else { " " },
+                    result.units,
+                    result.window_start,
+                    result.window_end,
+                    result.tracks.join(", ")
+                );
+            }
+        }
+
+        if !report.statistics.is_empty() {
+            let _ = writeln!(&mut summary, "\n**Channel statistics:**\n");
+            let _ = writeln!(
+                &mut summary,
+                "| Track | Units | Min | Max | Mean | RMS | Peak-peak | Crest | Peak time s |"
+            );
+            let _ = writeln!(
+                &mut summary,
+                "| --- | --- | --- | --- | --- | --- | --- | --- | --- |"
+            );
+            for stats in &report.statistics {
+                let crest = stats
+                    .crest_factor
+                    .map_or_else(|| "-".to_string(), |crest| format!("{crest:.3}"));
+                let _ = writeln!(
+                    &mut summary,
+                    "| {} | {} | {:.5} | {:.5} | {:.5} | {:.5} | {:.5} | {} | {:.6} |",
+                    stats.track_name.replace('|', "\\|"),
+                    stats.units.replace('|', "\\|"),
+                    stats.min,
+                    stats.max,
+                    stats.mean,
+                    stats.rms,
+                    stats.peak_to_peak,
+                    crest,
+                    stats.peak_time
+                );
+            }
+        }
+
+        if !report.cfc_classes.is_empty() {
+            let _ = writeln!(&mut summary, "\n**CFC filtering:**");
+            for (track_name, class) in &report.cfc_classes {
+                let _ = writeln!(&mut summary, "- {}: {}", track_name, class);
+            }
+        }
+
+        if !report.filters.is_empty() {
+            let _ = writeln!(&mut summary, "\n**Filters:**");
+            for (track_name, filters) in &report.filters {
+                let specs: Vec<String> = filters.iter().map(ToString::to_string).collect();
+                let _ = writeln!(&mut summary, "- {}: {}", track_name, specs.join(", "));
+            }
+        }
+
+        if !report.resampling.is_empty() {
+            let _ = writeln!(&mut summary, "\n**Resampling:**");
+            for (track_name, resampling) in &report.resampling {
+                let _ = writeln!(
+                    &mut summary,
+                    "- {}: {} Hz → {} Hz (×{}/{})",
+                    track_name, resampling.from, resampling.to, resampling.up, resampling.down
+                );
+            }
+        }
+
+        if !report.unit_changes.is_empty() {
+            let _ = writeln!(&mut summary, "\n**Unit conversions:**");
+            for (track_name, change) in &report.unit_changes {
+                let _ = writeln!(
+                    &mut summary,
+                    "- {}: `{}` → `{}` (×{})",
+                    track_name, change.from, change.to, change.factor
+                );
+            }
+        }
+
         if report.track_name_count != report.channel_count {
             let _ = writeln!(
                 &mut summary,
@@ -191,9 +878,22 @@ impl ConvertDtsToUff {
             }
         }
 
-        Ok(CallToolResult::text_content(vec![TextContent::from(
-            summary,
-        )]))
+        let structured = TableStructuredContent {
+            kind: "table".to_string(),
+            title: format!("Channel statistics for {}", output_display),
+            columns: statistics_columns(),
+            rows: report.statistics.iter().map(statistics_row).collect(),
+        };
+        let structured_map = structured_object(&structured)?;
+
+        Ok(
+            CallToolResult::text_content(vec![TextContent::from(summary)])
+                .with_structured_content(structured_map),
+        )
+    }
+
+    pub fn output_schema() -> Option<ToolOutputSchema> {
+        table_output_schema()
     }
 }
 
@@ -516,24 +1216,195 @@ impl ListDtsTracks {
             })
             .collect();
 
-        let structured = ListDtsTracksStructuredContent {
+        let structured = TableStructuredContent {
             kind: "table".to_string(),
             title: table_title,
             columns,
             rows,
         };
 
-        let structured_value = serde_json::to_value(&structured).map_err(|err| {
-            CallToolError::from_message(format!(
-                "Failed to serialize track metadata response: {err}"
-            ))
-        })?;
+        let structured_map = structured_object(&structured)?;
 
-        let structured_map = structured_value.as_object().cloned().ok_or_else(|| {
-            CallToolError::from_message(
-                "Structured content was not serialized as an object".to_string(),
-            )
-        })?;
+        Ok(
+            CallToolResult::text_content(vec![TextContent::from(summary)])
+                .with_structured_content(structured_map),
+        )
+    }
+
+    pub fn output_schema() -> Option<ToolOutputSchema> {
+        table_output_schema()
+    }
+}
+
+#[mcp_tool(
+    name = "sensor_health",
+    description = "Report the zero shift, shunt/calibration diagnostics levels and noise floor of every sensor in a DTS export directory.",
+    title = "Check DTS sensor health",
+    idempotent_hint = true,
+    destructive_hint = false,
+    open_world_hint = false,
+    read_only_hint = true,
+    meta = r#"{"version": "0.1.0"}"#
+)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CheckSensorHealth {
+    /// Absolute path to the DTS export directory containing `.dts`/`.chn` files. Pass a
+    /// directory path, not an individual file.
+    input_dir: String,
+    /// Optional absolute path to the text file with track names used to label the channels.
+    /// Defaults to the channel names in the `.dts` file.
+    #[serde(default)]
+    tracks_file: Option<String>,
+    /// Optional largest accepted shift between the pre- and post-test zero, in percent of full
+    /// scale. Defaults to 1.
+    #[serde(default)]
+    zero_shift_tolerance: Option<f64>,
+    /// Optional largest accepted deviation of the shunt or calibration signal level from the
+    /// value configured in the `.dts` file, in percent. Defaults to 5.
+    #[serde(default)]
+    diagnostics_tolerance: Option<f64>,
+}
+
+impl CheckSensorHealth {
+    pub async fn call_tool(&self) -> Result<CallToolResult, CallToolError> {
+        if self.input_dir.trim().is_empty() {
+            return Err(CallToolError::invalid_arguments(
+                "sensor_health",
+                Some("`input_dir` cannot be empty".to_string()),
+            ));
+        }
+
+        let input_dir = PathBuf::from(self.input_dir.trim());
+        let input_display = input_dir.to_string_lossy().into_owned();
+        let tracks_path = self
+            .tracks_file
+            .as_deref()
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from);
+        let defaults = HealthSettings::default();
+        let settings = HealthSettings {
+            zero_shift_tolerance: self
+                .zero_shift_tolerance
+                .unwrap_or(defaults.zero_shift_tolerance),
+            diagnostics_tolerance: self
+                .diagnostics_tolerance
+                .unwrap_or(defaults.diagnostics_tolerance),
+        };
+
+        let report = tokio::task::spawn_blocking({
+            let input_dir = input_dir.clone();
+            move || -> anyhow::Result<Vec<SensorHealth>> {
+                let reader = dts::DtsReader::new(&input_dir)?;
+                let track_names = match tracks_path {
+                    Some(ref path) => load_track_names(path)?,
+                    None => Vec::new(),
+                };
+                health::sensor_health(&reader, &track_names, &settings)
+            }
+        })
+        .await
+        .map_err(|err| CallToolError::from_message(format!("Background task failed: {err}")))?
+        .map_err(|err| CallToolError::from_message(err.to_string()))?;
+
+        let table_title = format!("Sensor health for {}", input_display);
+        let failed = report.iter().filter(|health| !health.is_healthy()).count();
+        let mut summary = String::new();
+        let _ = writeln!(
+            &mut summary,
+            "**{table_title}** — {} sensor{} checked, {} with issues.",
+            report.len(),
+            if report.len() == 1 { "" } else { "s" },
+            failed
+        );
+        let _ = writeln!(
+            &mut summary,
+            "\n| Track | Units | Zero shift | Shift % FS | Noise | Noise % FS | Diagnostics | Deviation % | Status |"
+        );
+        let _ = writeln!(
+            &mut summary,
+            "| --- | --- | --- | --- | --- | --- | --- | --- | --- |"
+        );
+        for health in &report {
+            let (kind, deviation) = match &health.diagnostics {
+                Some(check) => (
+                    check.kind.to_string(),
+                    format!("{:.2}", check.deviation_percent),
+                ),
+                None => ("-".to_string(), "-".to_string()),
+            };
+            let _ = writeln!(
+                &mut summary,
+                "| {} | {} | {:.5} | {:.4} | {:.5} | {:.5} | {} | {} | {} |",
+                health.track_name.replace('|', "\\|"),
+                health.units.replace('|', "\\|"),
+                health.zero_shift,
+                health.zero_shift_percent,
+                health.noise,
+                health.noise_percent,
+                kind,
+                deviation,
+                if health.is_healthy() { "ok" } else { "⚠️" }
+            );
+        }
+        if failed > 0 {
+            let _ = writeln!(&mut summary, "\n**Issues:**");
+            for health in &report {
+                for issue in &health.issues {
+                    let _ = writeln!(&mut summary, "- {}: {}", health.track_name, issue);
+                }
+            }
+        }
+
+        let structured = TableStructuredContent {
+            kind: "table".to_string(),
+            title: table_title,
+            columns: vec![
+                TableColumn::text("track", "Track"),
+                TableColumn::text("units", "Units"),
+                TableColumn::text("serial", "Serial"),
+                TableColumn::number("preTestZero", "Pre-test Zero"),
+                TableColumn::number("postTestZero", "Post-test Zero"),
+                TableColumn::number("zeroShift", "Zero Shift"),
+                TableColumn::number("zeroShiftPercentFs", "Zero Shift % FS"),
+                TableColumn::number("noise", "Noise"),
+                TableColumn::number("noisePercentFs", "Noise % FS"),
+                TableColumn::text("diagnostics", "Diagnostics"),
+                TableColumn::number("diagnosticsDeviationPercent", "Diagnostics Deviation %"),
+                TableColumn::text("issues", "Issues"),
+            ],
+            rows: report
+                .iter()
+                .map(|health| TableRow {
+                    kind: "row".to_string(),
+                    cells: vec![
+                        TableCell::text(health.track_name.clone()),
+                        TableCell::text(health.units.clone()),
+                        TableCell::text(health.serial_number.clone()),
+                        TableCell::number(health.pre_test_zero),
+                        TableCell::number(health.post_test_zero),
+                        TableCell::number(health.zero_shift),
+                        TableCell::number(health.zero_shift_percent),
+                        TableCell::number(health.noise),
+                        TableCell::number(health.noise_percent),
+                        TableCell::text(
+                            health
+                                .diagnostics
+                                .as_ref()
+                                .map_or_else(String::new, |check| check.kind.to_string()),
+                        ),
+                        TableCell::number(
+                            health
+                                .diagnostics
+                                .as_ref()
+                                .map_or(f64::NAN, |check| check.deviation_percent),
+                        ),
+                        TableCell::text(health.issues.join("; ")),
+                    ],
+                })
+                .collect(),
+        };
+        let structured_map = structured_object(&structured)?;
 
         Ok(
             CallToolResult::text_content(vec![TextContent::from(summary)])
@@ -542,67 +1413,284 @@ impl ListDtsTracks {
     }
 
     pub fn output_schema() -> Option<ToolOutputSchema> {
-        let schema_value = ListDtsTracksStructuredContent::json_schema();
+        table_output_schema()
+    }
+}
 
-        let required: Vec<String> = schema_value
-            .get("required")
-            .and_then(|value| value.as_array())
-            .map(|items| {
-                items
-                    .iter()
-                    .filter_map(|item| item.as_str().map(ToOwned::to_owned))
-                    .collect()
-            })
-            .unwrap_or_default();
+#[mcp_tool(
+    name = "plot_dts_tracks",
+    description = "Plot the time histories of the tracks in a DTS export directory and return the images, one per track or a grid of tracks in a single image.",
+    title = "Plot DTS tracks",
+    idempotent_hint = true,
+    destructive_hint = false,
+    open_world_hint = false,
+    read_only_hint = true,
+    meta = r#"{"version": "0.1.0"}"#
+)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PlotDtsTracks {
+    /// Absolute path to the DTS export directory containing `.dts`/`.chn` files. Pass a
+    /// directory path, not an individual file.
+    input_dir: String,
+    /// Optional absolute path to the text file with track names used to label the channels.
+    /// Defaults to the channel names in the `.dts` file.
+    #[serde(default)]
+    tracks_file: Option<String>,
+    /// Optional comma-separated list of track names to plot. Defaults to every track.
+    #[serde(default)]
+    tracks: Option<String>,
+    /// Optional slice of samples to plot for each track, written as `start:end` with zero-based
+    /// indices. Defaults to the full range.
+    #[serde(default)]
+    slice: Option<String>,
+    /// Optional image format, `png` or `svg`. Defaults to `png`.
+    #[serde(default)]
+    format: Option<String>,
+    /// When true, draw every track into a single image as a grid of `columns` panels per row
+    /// instead of returning one image per track. Defaults to false.
+    #[serde(default)]
+    grid: Option<bool>,
+    /// Optional number of panels per row of the grid. Defaults to 1.
+    #[serde(default)]
+    columns: Option<u64>,
+    /// When true, mark and label the minimum and maximum of every track. Defaults to false.
+    #[serde(default)]
+    markers: Option<bool>,
+    /// Optional width of the panel of one track, in pixels. Defaults to 800.
+    #[serde(default)]
+    width: Option<u32>,
+    /// Optional height of the panel of one track, in pixels. Defaults to 300.
+    #[serde(default)]
+    height: Option<u32>,
+}
 
-        let mut properties: Option<HashMap<String, JsonMap<String, JsonValue>>> = schema_value
-            .get("properties")
-            .and_then(|value| value.as_object())
-            .map(|props| {
-                props
+impl PlotDtsTracks {
+    pub async fn call_tool(&self) -> Result<CallToolResult, CallToolError> {
+        if self.input_dir.trim().is_empty() {
+            return Err(CallToolError::invalid_arguments(
+                "plot_dts_tracks",
+                Some("`input_dir` cannot be empty".to_string()),
+            ));
+        }
+
+        let input_dir = PathBuf::from(self.input_dir.trim());
+        let input_display = input_dir.to_string_lossy().into_owned();
+        let tracks_path = self
+            .tracks_file
+            .as_deref()
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from);
+        let selection = self
+            .tracks
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(parse_track_selection)
+            .transpose()
+            .map_err(|err| CallToolError::invalid_arguments("plot_dts_tracks", Some(err)))?;
+        let slice = self
+            .slice
+            .as_deref()
+            .map(SampleSlice::from_str)
+            .transpose()
+            .map_err(|err| CallToolError::invalid_arguments("plot_dts_tracks", Some(err)))?;
+        let format = self
+            .format
+            .as_deref()
+            .map(PlotFormat::from_str)
+            .transpose()
+            .map_err(|err| CallToolError::invalid_arguments("plot_dts_tracks", Some(err)))?
+            .unwrap_or(PlotFormat::Png);
+        let grid = self.grid.unwrap_or(false);
+        let defaults = PlotSettings::default();
+        let settings = PlotSettings {
+            width: self.width.unwrap_or(defaults.width),
+            height: self.height.unwrap_or(defaults.height),
+            columns: self
+                .columns
+                .map_or(defaults.columns, |columns| columns as usize),
+            markers: self.markers.unwrap_or(defaults.markers),
+        };
+        settings.validate().map_err(|err| {
+            CallToolError::invalid_arguments("plot_dts_tracks", Some(err.to_string()))
+        })?;
+
+        let images = tokio::task::spawn_blocking({
+            let input_dir = input_dir.clone();
+            move || -> anyhow::Result<Vec<(Vec<String>, Vec<u8>)>> {
+                let reader = dts::DtsReader::new(&input_dir)?;
+                let track_names = match tracks_path {
+                    Some(ref path) => load_track_names(path)?,
+                    None => Vec::new(),
+                };
+                let tracks = plot::read_tracks(&reader, &track_names, selection.as_deref(), slice)?;
+                if grid {
+                    let names = tracks.iter().map(|track| track.name.clone()).collect();
+                    return Ok(vec![(names, plot::render(&tracks, format, &settings)?)]);
+                }
+                tracks
                     .iter()
-                    .filter_map(|(key, value)| {
-                        value
-                            .as_object()
-                            .cloned()
-                            .map(|object| (key.clone(), object))
+                    .map(|track| {
+                        let image = plot::render(std::slice::from_ref(track), format, &settings)?;
+                        Ok((vec![track.name.clone()], image))
                     })
                     .collect()
-            });
-
-        // Normalize non-standard schemas emitted for flexible fields.
-        // Some validators reject "type": "unknown". Treat extras as a free-form object.
-        if let Some(props) = properties.as_mut() {
-            if let Some(columns_schema) = props.get_mut("columns") {
-                if let Some(items_obj) = columns_schema
-                    .get_mut("items")
-                    .and_then(|value| value.as_object_mut())
-                {
-                    items_obj.insert("type".to_string(), JsonValue::String("object".to_string()));
-                }
             }
+        })
+        .await
+        .map_err(|err| CallToolError::from_message(format!("Background task failed: {err}")))?
+        .map_err(|err| CallToolError::from_message(err.to_string()))?;
+
+        let track_count: usize = images.iter().map(|(names, _)| names.len()).sum();
+        let mut summary = String::new();
+        let _ = writeln!(
+            &mut summary,
+            "**Plots for {input_display}** — {track_count} track{} in {} {format} image{}.",
+            if track_count == 1 { "" } else { "s" },
+            images.len(),
+            if images.len() == 1 { "" } else { "s" },
+        );
+        if let Some(range) = slice.map(|value| format!("{}:{}", value.start, value.end)) {
+            let _ = writeln!(&mut summary, "- **Sample slice:** `{}`", range);
+        }
+        for (index, (names, _)) in images.iter().enumerate() {
+            let _ = writeln!(
+                &mut summary,
+                "- **Image {}:** {}",
+                index + 1,
+                names.join(", ")
+            );
+        }
+
+        let mut content = vec![ContentBlock::text_content(summary)];
+        content.extend(images.iter().map(|(_, image)| {
+            ContentBlock::image_content(
+                base64::engine::general_purpose::STANDARD.encode(image),
+                format.mime_type().to_string(),
+            )
+        }));
+        Ok(CallToolResult {
+            content,
+            is_error: None,
+            meta: None,
+            structured_content: None,
+        })
+    }
+}
 
-            if let Some(rows_schema) = props.get_mut("rows") {
-                if let Some(items_obj) = rows_schema
-                    .get_mut("items")
+fn statistics_columns() -> Vec<TableColumn> {
+    vec![
+        TableColumn::text("track", "Track"),
+        TableColumn::text("units", "Units"),
+        TableColumn::number("sampleCount", "Samples"),
+        TableColumn::number("sampleRateHz", "Sample Rate Hz"),
+        TableColumn::number("min", "Min"),
+        TableColumn::number("max", "Max"),
+        TableColumn::number("mean", "Mean"),
+        TableColumn::number("rms", "RMS"),
+        TableColumn::number("peakToPeak", "Peak-to-peak"),
+        TableColumn::number("crestFactor", "Crest Factor"),
+        TableColumn::number("peak", "Peak"),
+        TableColumn::number("peakTimeS", "Peak Time s"),
+    ]
+}
+
+fn statistics_row(stats: &ChannelStatistics) -> TableRow {
+    TableRow {
+        kind: "row".to_string(),
+        cells: vec![
+            TableCell::text(stats.track_name.clone()),
+            TableCell::text(stats.units.clone()),
+            TableCell::number(stats.sample_count as f64),
+            TableCell::number(stats.sample_rate),
+            TableCell::number(stats.min),
+            TableCell::number(stats.max),
+            TableCell::number(stats.mean),
+            TableCell::number(stats.rms),
+            TableCell::number(stats.peak_to_peak),
+            TableCell::number(stats.crest_factor.unwrap_or(f64::NAN)),
+            TableCell::number(stats.peak),
+            TableCell::number(stats.peak_time),
+        ],
+    }
+}
+
+fn structured_object(
+    structured: &TableStructuredContent,
+) -> Result<JsonMap<String, JsonValue>, CallToolError> {
+    let structured_value = serde_json::to_value(structured).map_err(|err| {
+        CallToolError::from_message(format!("Failed to serialize {}: {err}", structured.title))
+    })?;
+
+    structured_value.as_object().cloned().ok_or_else(|| {
+        CallToolError::from_message(
+            "Structured content was not serialized as an object".to_string(),
+        )
+    })
+}
+
+fn table_output_schema() -> Option<ToolOutputSchema> {
+    let schema_value = TableStructuredContent::json_schema();
+
+    let required: Vec<String> = schema_value
+        .get("required")
+        .and_then(|value| value.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(ToOwned::to_owned))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut properties: Option<HashMap<String, JsonMap<String, JsonValue>>> = schema_value
+        .get("properties")
+        .and_then(|value| value.as_object())
+        .map(|props| {
+            props
+                .iter()
+                .filter_map(|(key, value)| {
+                    value
+                        .as_object()
+                        .cloned()
+                        .map(|object| (key.clone(), object))
+                })
+                .collect()
+        });
+
+    // Normalize non-standard schemas emitted for flexible fields.
+    // Some validators reject "type": "unknown". Treat extras as a free-form object.
+    if let Some(props) = properties.as_mut() {
+        if let Some(columns_schema) = props.get_mut("columns") {
+            if let Some(items_obj) = columns_schema
+                .get_mut("items")
+                .and_then(|value| value.as_object_mut())
+            {
+                items_obj.insert("type".to_string(), JsonValue::String("object".to_string()));
+            }
+        }
+
+        if let Some(rows_schema) = props.get_mut("rows") {
+            if let Some(items_obj) = rows_schema
+                .get_mut("items")
+                .and_then(|value| value.as_object_mut())
+            {
+                items_obj.insert("type".to_string(), JsonValue::String("object".to_string()));
+
+                if let Some(row_props) = items_obj
+                    .get_mut("properties")
                     .and_then(|value| value.as_object_mut())
                 {
-                    items_obj.insert("type".to_string(), JsonValue::String("object".to_string()));
-
-                    if let Some(row_props) = items_obj
-                        .get_mut("properties")
-                        .and_then(|value| value.as_object_mut())
-                    {
-                        if let Some(cells_schema) = row_props.get_mut("cells") {
-                            normalize_cells_schema(cells_schema);
-                        }
+                    if let Some(cells_schema) = row_props.get_mut("cells") {
+                        normalize_cells_schema(cells_schema);
                     }
                 }
             }
         }
-
-        Some(ToolOutputSchema::new(required, properties))
     }
+
+    Some(ToolOutputSchema::new(required, properties))
 }
 
 fn normalize_cells_schema(cells_schema: &mut JsonValue) {
@@ -660,6 +1748,14 @@ fn parse_track_selection(value: &str) -> Result<Vec<String>, String> {
     }
 }
 
+fn split_list(value: Option<&str>) -> impl Iterator<Item = &str> {
+    value
+        .unwrap_or("")
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
 fn load_track_names(path: &Path) -> anyhow::Result<Vec<String>> {
     let contents = std::fs::read_to_string(path)
         .with_context(|| format!("Failed to read track names from {}", path.display()))?;
@@ -682,7 +1778,7 @@ fn load_track_names(path: &Path) -> anyhow::Result<Vec<String>> {
 }
 
 #[derive(Debug, Serialize, JsonSchema)]
-struct ListDtsTracksStructuredContent {
+struct TableStructuredContent {
     #[serde(rename = "type")]
     kind: String,
     title: String,
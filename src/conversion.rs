@@ -1,19 +1,62 @@
+use crate::csv::{CsvSettings, CsvWriter};
+use crate::derived::{Derivation, DerivedChannel, DerivedChannelSpec};
+use crate::export::{ChannelWriter, ExportChannel};
+use crate::expression::ExpressionChannel;
+use crate::filter::{CfcClass, CfcFiltering, FilterChain, FilterSpec};
+use crate::id_template::{IdContext, IdTemplate};
+use crate::injury::{self, InjuryReport, NijIntercepts, RoleAssignment, RoleChannel};
+use crate::mat::MatWriter;
+use crate::mdf::MdfWriter;
+use crate::mme::{MmeChannelCodes, MmeWriter};
+use crate::npz::NpzWriter;
+use crate::quality::{self, QualityIssue, QualitySettings};
+use crate::raw::RawWriter;
+use crate::resample::{self, Resampling};
+use crate::sidecar::{self, ConversionRecord, Sidecar};
+use crate::spectral::{
+    self, FrequencyResponse, FrfEstimator, FrfReference, SpectralSettings, Spectrum, SpectrumKind,
+};
+use crate::srs::{self, ShockResponseSpectrum, SrsKind, SrsSettings};
+use crate::statistics::ChannelStatistics;
+use crate::tdms::TdmsWriter;
+use crate::units::{UnitChange, UnitConversion};
+use crate::wav::WavWriter;
 use crate::{dts, uff};
 use anyhow::{anyhow, Context, Result};
 use clap::ValueEnum;
 use rayon::prelude::*;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::fs::{self, OpenOptions};
 use std::io::{BufWriter, Write};
 use std::ops::Range;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-/// Output format options for generating the UFF file.
+/// Output format options for generating the UFF file or another export.
 #[derive(ValueEnum, Clone, Copy, Debug, Eq, PartialEq)]
 pub enum OutputFormat {
     /// Generate an ASCII UFF file.
     Ascii,
     /// Generate a binary UFF file.
     Binary,
+    /// Generate an ISO/TS 13499 (MME) test directory.
+    Mme,
+    /// Generate a MATLAB MAT-file (level 5) with the fields returned by `DTS.read`, except `ADC`.
+    Mat,
+    /// Generate delimited text with a time column and one column per track.
+    Csv,
+    /// Generate a multichannel WAVE file with 32-bit float samples.
+    Wav,
+    /// Generate a NumPy `.npz` archive with one array per track.
+    Npz,
+    /// Generate an ASAM MDF 4 (`.mf4`) file with one data group per time base.
+    Mdf,
+    /// Generate an NI TDMS file with one group per recorder module.
+    Tdms,
+    /// Generate a directory with the unscaled ADC counts of every track and a JSON manifest of
+    /// their exact scaling.
+    Raw,
 }
 
 impl OutputFormat {
@@ -22,8 +65,21 @@ impl OutputFormat {
         match self {
             OutputFormat::Ascii => "ascii",
             OutputFormat::Binary => "binary",
+            OutputFormat::Mme => "mme",
+            OutputFormat::Mat => "mat",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Wav => "wav",
+            OutputFormat::Npz => "npz",
+            OutputFormat::Mdf => "mdf",
+            OutputFormat::Tdms => "tdms",
+            OutputFormat::Raw => "raw",
         }
     }
+
+    /// Returns `true` for the UFF formats, which carry spectra, FRF, SRS and 1858 records.
+    pub fn is_uff(&self) -> bool {
+        matches!(self, OutputFormat::Ascii | OutputFormat::Binary)
+    }
 }
 
 impl std::fmt::Display for OutputFormat {
@@ -39,8 +95,17 @@ impl std::str::FromStr for OutputFormat {
         match s.trim().to_ascii_lowercase().as_str() {
             "ascii" => Ok(OutputFormat::Ascii),
             "binary" => Ok(OutputFormat::Binary),
+            "mme" => Ok(OutputFormat::Mme),
+            "mat" => Ok(OutputFormat::Mat),
+            "csv" => Ok(OutputFormat::Csv),
+            "wav" => Ok(OutputFormat::Wav),
+            "npz" => Ok(OutputFormat::Npz),
+            "mdf" | "mf4" => Ok(OutputFormat::Mdf),
+            "tdms" => Ok(OutputFormat::Tdms),
+            "raw" => Ok(OutputFormat::Raw),
             other => Err(format!(
-                "Unsupported output format '{other}'. Expected 'ascii' or 'binary'."
+                "Unsupported output format '{other}'. Expected 'ascii', 'binary', 'mme', 'mat', \
+                 'csv', 'wav', 'npz', 'mdf', 'tdms' or 'raw'."
             )),
         }
     }
@@ -80,11 +145,160 @@ pub struct ConversionReport {
     pub processed_track_names: Vec<String>,
     /// Any warnings generated during conversion.
     pub warnings: Vec<String>,
+    /// Unit rescaling applied to each track, keyed by track name.
+    pub unit_changes: Vec<(String, UnitChange)>,
+    /// Number of frequency-domain 58 records written after the time histories.
+    pub spectrum_count: usize,
+    /// Number of FRF records written (coherence records are not counted).
+    pub frf_count: usize,
+    /// Number of SRS records written.
+    pub srs_count: usize,
+    /// CFC class applied to each filtered track, keyed by track name.
+    pub cfc_classes: Vec<(String, CfcClass)>,
+    /// Generic IIR filters applied to each filtered track, keyed by track name.
+    pub filters: Vec<(String, Vec<FilterSpec>)>,
+    /// Sample rate change applied to each resampled track, keyed by track name.
+    pub resampling: Vec<(String, Resampling)>,
+    /// Derived channels appended after the exported tracks, keyed by derived track name.
+    pub derived_channels: Vec<(String, Derivation)>,
+    /// Expression channels appended after the derived channels, with their units resolved.
+    pub expression_channels: Vec<ExpressionChannel>,
+    /// Injury criteria of the mapped channels.
+    pub injury: Option<InjuryReport>,
+    /// Statistics of every time history written, in output order.
+    pub statistics: Vec<ChannelStatistics>,
+    /// Data-quality issues found in the raw ADC data, keyed by track name. Each issue is also
+    /// reported as a warning.
+    pub quality_issues: Vec<(String, Vec<QualityIssue>)>,
+}
+
+/// Optional processing settings applied by [`convert_with_progress_and_options`].
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ConversionOptions {
+    /// Sample range to export for every track. `None` exports every sample.
+    pub slice: Option<SampleSlice>,
+    /// Track names to write into the output file, in order. `None` writes every channel.
+    pub track_list_filter: Option<Vec<String>>,
+    /// SAE J211 / ISO 6487 CFC filtering applied to the exported channels.
+    pub cfc: CfcFiltering,
+    /// Low-, high-, band-pass, band-stop and notch filters applied after the CFC filter.
+    pub filters: FilterChain,
+    /// Sample rate, in Hz, every channel is resampled to after filtering. `None` keeps the
+    /// native rates.
+    pub resample_rate: Option<f64>,
+    /// Target units for the exported channels.
+    pub units: UnitConversion,
+    /// Template used to fill UFF ID records 1-5. `None` keeps the MATLAB-compatible layout.
+    pub id_template: Option<IdTemplate>,
+    /// Write a dataset 1858 block describing the applied processing before every 58 record.
+    pub write_1858: bool,
+    /// Frequency-domain functions written after every time history.
+    pub spectra: Vec<SpectrumKind>,
+    /// Window, block size, overlap and averaging used for `spectra` and `frf`.
+    pub spectral: SpectralSettings,
+    /// FRF and coherence estimation relative to a reference track.
+    pub frf: Option<FrfOptions>,
+    /// Shock response spectra written after every time history.
+    pub srs: Option<SrsOptions>,
+    /// Integrated or differentiated channels appended as time histories after every track.
+    pub derived: Vec<DerivedChannelSpec>,
+    /// Channels computed sample by sample from exported, derived or earlier expression channels,
+    /// appended after the derived channels.
+    pub expressions: Vec<ExpressionChannel>,
+    /// Crash injury criteria computed from the exported channels.
+    pub injury: Option<InjuryOptions>,
+    /// Clipping, dead-channel and noise checks run on the raw ADC data of every track.
+    pub quality: QualitySettings,
+    /// ISO/TS 13499 channel codes written to the MME export, keyed by track name.
+    pub mme_codes: MmeChannelCodes,
+    /// Delimiter, number format, metadata rows and rate handling of the CSV export.
+    pub csv: CsvSettings,
+    /// Scale every channel of the WAV export to a peak of 1.0.
+    pub wav_normalize: bool,
+    /// Optional JSON file receiving the provenance of the conversion: source folder and `.dts`
+    /// hash, channel plan, channel metadata, applied processing, format and converter version.
+    pub metadata_json: Option<PathBuf>,
+}
+
+/// Channel roles and outputs of the injury criteria report.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct InjuryOptions {
+    /// Exported, derived or expression tracks assigned to each dummy channel role.
+    pub roles: Vec<RoleAssignment>,
+    pub intercepts: NijIntercepts,
+    /// Optional JSON file receiving the criteria report.
+    pub json_output: Option<PathBuf>,
+    /// Optional CSV file receiving the criteria report.
+    pub csv_output: Option<PathBuf>,
+}
+
+/// Shock response spectrum curves to export for every channel.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SrsOptions {
+    /// Curves written as UFF 58 records, in order.
+    pub kinds: Vec<SrsKind>,
+    pub settings: SrsSettings,
+    /// Optional CSV file receiving the same curves for every exported track.
+    pub csv_output: Option<PathBuf>,
+}
+
+/// Reference and response tracks for FRF estimation.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FrfOptions {
+    /// Track name of the reference (excitation) channel.
+    pub reference: String,
+    /// Response track names. `None` uses every other exported track.
+    pub responses: Option<Vec<String>>,
+    pub estimator: FrfEstimator,
+    /// Also write the coherence of every FRF.
+    pub coherence: bool,
+}
+
+impl FrfOptions {
+    fn includes(&self, track_name: &str) -> bool {
+        track_name != self.reference
+            && self
+                .responses
+                .as_ref()
+                .is_none_or(|responses| responses.iter().any(|name| name == track_name))
+    }
+}
+
+/// A channel read from the DTS export and ready to be written.
+struct ProcessedChannel {
+    order: usize,
+    channel_index: usize,
+    track_name: String,
+    data: dts::ChannelData,
+    cfc: Option<CfcClass>,
+    filters: Vec<FilterSpec>,
+    quality: Vec<QualityIssue>,
+    resampling: Option<Resampling>,
+    /// Time of the first exported sample, in seconds.
+    start_time: f64,
+    /// Time of the first recorded sample relative to the trigger, in seconds.
+    trigger_offset: f64,
+    unit_change: Option<UnitChange>,
+    spectra: Vec<Spectrum>,
+    frf: Option<FrequencyResponse>,
+    srs: Option<ShockResponseSpectrum>,
+    derived: Vec<DerivedChannel>,
+}
+
+impl ProcessedChannel {
+    /// Whether the samples are the scaled ADC values, without filtering, resampling or unit
+    /// conversion.
+    fn is_unprocessed(&self) -> bool {
+        self.cfc.is_none()
+            && self.filters.is_empty()
+            && self.resampling.is_none()
+            && self.unit_change.is_none()
+    }
 }
 
 /// A slice of samples to export for every processed track.
 /// Indices are zero-based and expressed in the native sample units of each track.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
 pub struct SampleSlice {
     /// Inclusive starting index of the slice.
     pub start: usize,
@@ -136,6 +350,19 @@ impl std::str::FromStr for SampleSlice {
     }
 }
 
+/// Read track names from a text file, one per line or comma-separated.
+pub fn read_track_names(tracks_path: &Path) -> Result<Vec<String>> {
+    let track_names_raw = fs::read_to_string(tracks_path)
+        .with_context(|| format!("Failed to read track names from {}", tracks_path.display()))?;
+
+    Ok(track_names_raw
+        .split([',', '\n', '\r'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(ToOwned::to_owned)
+        .collect())
+}
+
 /// Convert a DTS directory to a UFF Type 58 file while reporting progress.
 pub fn convert_with_progress<F>(
     input_dir: &Path,
@@ -144,35 +371,140 @@ pub fn convert_with_progress<F>(
     format: OutputFormat,
     slice: Option<SampleSlice>,
     track_list_filter: Option<&[String]>,
+    progress: F,
+) -> Result<ConversionReport>
+where
+    F: FnMut(ConversionProgress<'_>),
+{
+    let options = ConversionOptions {
+        slice,
+        track_list_filter: track_list_filter.map(<[String]>::to_vec),
+        ..ConversionOptions::default()
+    };
+    convert_with_progress_and_options(
+        input_dir,
+        tracks_path,
+        output_path,
+        format,
+        &options,
+        progress,
+    )
+}
+
+/// Convert a DTS directory to a UFF Type 58 file, or another output format, with the given
+/// processing options while reporting progress.
+pub fn convert_with_progress_and_options<F>(
+    input_dir: &Path,
+    tracks_path: &Path,
+    output_path: &Path,
+    format: OutputFormat,
+    options: &ConversionOptions,
     mut progress: F,
 ) -> Result<ConversionReport>
 where
     F: FnMut(ConversionProgress<'_>),
 {
-    // 1. Read track names
-    let track_names_raw = fs::read_to_string(tracks_path)
-        .with_context(|| format!("Failed to read track names from {}", tracks_path.display()))?;
+    validate_options(options)?;
 
-    let track_names: Vec<String> = track_names_raw
-        .split([',', '\n', '\r'])
-        .map(str::trim)
-        .filter(|s| !s.is_empty())
-        .map(ToOwned::to_owned)
-        .collect();
+    // 1. Read track names
+    let track_names = read_track_names(tracks_path)?;
 
     // 2. Parse DTS metadata
     let dts_reader = dts::DtsReader::new(input_dir)
         .with_context(|| format!("Failed to read DTS metadata from {}", input_dir.display()))?;
-    let num_channels = dts_reader.channel_count();
 
+    let conversion = Conversion {
+        input_dir,
+        tracks_path,
+        output_path,
+        format,
+        options,
+        dts_reader,
+        track_names,
+    };
     let mut warnings = Vec::new();
+    let channel_plan = conversion.plan_channels(&mut warnings);
+
+    progress(ConversionProgress::Started {
+        track_name_count: conversion.track_names.len(),
+        channel_count: channel_plan.len(),
+    });
+
+    // 3. Read, filter and analyse channel data in parallel
+    let mut processed_channels = channel_plan
+        .into_par_iter()
+        .map(|(order, channel_index)| conversion.process_channel(order, channel_index))
+        .collect::<Result<Vec<_>>>()?;
+
+    processed_channels.sort_by_key(|channel| channel.order);
+
+    let quality_issues = take_quality_issues(&mut processed_channels, options, &mut warnings)?;
+    let analyses = run_analyses(&mut processed_channels, options, &mut warnings)?;
+
+    // 4. Stream channel data into the output
+    let record_counts = conversion.write_output(
+        &processed_channels,
+        &analyses.expressions,
+        &mut warnings,
+        &mut progress,
+    )?;
+
+    conversion.report(
+        processed_channels,
+        analyses,
+        quality_issues,
+        record_counts,
+        warnings,
+        &mut progress,
+    )
+}
+
+/// Check the analysis settings before any channel is read.
+fn validate_options(options: &ConversionOptions) -> Result<()> {
+    if !options.spectra.is_empty() || options.frf.is_some() {
+        options.spectral.validate()?;
+    }
+    if let Some(srs_options) = options.srs.as_ref() {
+        srs_options.settings.validate()?;
+    }
+    if options.quality.enabled {
+        options.quality.validate()?;
+    }
+    Ok(())
+}
+
+/// Inputs shared by the stages of a conversion.
+struct Conversion<'a> {
+    input_dir: &'a Path,
+    tracks_path: &'a Path,
+    output_path: &'a Path,
+    format: OutputFormat,
+    options: &'a ConversionOptions,
+    dts_reader: dts::DtsReader,
+    track_names: Vec<String>,
+}
+
+/// Results of the analyses that combine several channels.
+struct Analyses {
+    expressions: Vec<ComputedExpression>,
+    injury: Option<InjuryReport>,
+}
+
+impl Conversion<'_> {
+    /// `(order, channel index)` of every channel to export.
+    fn plan_channels(&self, warnings: &mut Vec<String>) -> Vec<(usize, usize)> {
+        let Some(filter) = self.options.track_list_filter.as_deref() else {
+            return (0..self.dts_reader.channel_count())
+                .map(|index| (index, index))
+                .collect();
+        };
 
-    let channel_plan: Vec<(usize, usize)> = if let Some(filter) = track_list_filter {
         let mut plan = Vec::new();
-        let mut used = vec![false; track_names.len()];
+        let mut used = vec![false; self.track_names.len()];
 
         for (order, requested_name) in filter.iter().enumerate() {
-            if let Some((channel_index, _)) = track_names
+            if let Some((channel_index, _)) = self
+                .track_names
                 .iter()
                 .enumerate()
                 .find(|(idx, name)| !used[*idx] && *name == requested_name)
@@ -187,45 +519,802 @@ where
         }
 
         plan
-    } else {
-        (0..num_channels).map(|index| (index, index)).collect()
-    };
+    }
 
-    progress(ConversionProgress::Started {
-        track_name_count: track_names.len(),
-        channel_count: channel_plan.len(),
-    });
+    /// Read one channel, then filter, resample, slice, rescale and analyse it.
+    fn process_channel(&self, order: usize, channel_index: usize) -> Result<ProcessedChannel> {
+        let mut channel = self.read_channel(order, channel_index)?;
+        condition_channel(&mut channel, self.options)?;
+        analyse_channel(&mut channel, self.options)?;
+        Ok(channel)
+    }
 
-    // 3. Read channel data in parallel
-    let track_names_ref = &track_names;
-    let dts_reader_ref = &dts_reader;
-    let mut processed_channels = channel_plan
-        .into_par_iter()
-        .map(
-            |(order, channel_index)| -> Result<(usize, String, dts::ChannelData)> {
-                let track_name = track_names_ref
-                    .get(channel_index)
-                    .cloned()
-                    .unwrap_or_else(|| format!("Channel_{}", channel_index + 1));
-
-                let mut channel_data = dts_reader_ref
-                    .read_track(channel_index)
-                    .with_context(|| format!("Failed to read channel {}", channel_index + 1))?;
-
-                if let Some(slice) = slice {
-                    let len = channel_data.time_series.len();
-                    let range = slice.as_range(len)?;
-                    channel_data.time_series = channel_data.time_series[range].to_vec();
+    /// Read and scale one channel, running the data-quality checks on its ADC counts.
+    fn read_channel(&self, order: usize, channel_index: usize) -> Result<ProcessedChannel> {
+        let track_name = self
+            .track_names
+            .get(channel_index)
+            .cloned()
+            .unwrap_or_else(|| format!("Channel_{}", channel_index + 1));
+
+        let raw = self
+            .dts_reader
+            .read_track_raw(channel_index)
+            .with_context(|| format!("Failed to read channel {}", channel_index + 1))?;
+        let quality = if self.options.quality.enabled {
+            quality::check_track(&raw, &self.options.quality)
+        } else {
+            Vec::new()
+        };
+        let data = self.dts_reader.scale_track(channel_index, raw);
+
+        Ok(ProcessedChannel {
+            order,
+            channel_index,
+            track_name,
+            data,
+            cfc: None,
+            filters: Vec::new(),
+            quality,
+            resampling: None,
+            start_time: 0.0,
+            trigger_offset: self
+                .dts_reader
+                .time_of_first_sample(channel_index)
+                .unwrap_or_default(),
+            unit_change: None,
+            spectra: Vec::new(),
+            frf: None,
+            srs: None,
+            derived: Vec::new(),
+        })
+    }
+
+    /// Write every channel in the output format, warning about options the format ignores.
+    fn write_output<F>(
+        &self,
+        processed_channels: &[ProcessedChannel],
+        expression_channels: &[ComputedExpression],
+        warnings: &mut Vec<String>,
+        progress: &mut F,
+    ) -> Result<UffRecordCounts>
+    where
+        F: FnMut(ConversionProgress<'_>),
+    {
+        let Self {
+            input_dir,
+            output_path,
+            format,
+            options,
+            ref dts_reader,
+            ..
+        } = *self;
+
+        output_warnings(
+            format,
+            options,
+            processed_channels,
+            expression_channels,
+            warnings,
+        );
+
+        let record_counts = match format {
+            OutputFormat::Ascii => write_uff(
+                output_path,
+                uff::Uff58Format::Ascii,
+                options,
+                dts_reader,
+                processed_channels,
+                expression_channels,
+                progress,
+            )?,
+            OutputFormat::Binary => write_uff(
+                output_path,
+                uff::Uff58Format::Binary58b,
+                options,
+                dts_reader,
+                processed_channels,
+                expression_channels,
+                progress,
+            )?,
+            OutputFormat::Mme => {
+                let mut writer =
+                    MmeWriter::create(output_path, dts_reader.test_info(), &options.mme_codes)?;
+                write_channels(
+                    &mut writer,
+                    dts_reader,
+                    processed_channels,
+                    expression_channels,
+                    progress,
+                )
+                .with_context(|| {
+                    format!("Failed to write MME export to {}", output_path.display())
+                })?;
+                UffRecordCounts::default()
+            }
+            OutputFormat::Mat => {
+                let mut writer = MatWriter::create(output_path, input_dir)?;
+                write_channels(
+                    &mut writer,
+                    dts_reader,
+                    processed_channels,
+                    expression_channels,
+                    progress,
+                )
+                .with_context(|| format!("Failed to write MAT-file {}", output_path.display()))?;
+                UffRecordCounts::default()
+            }
+            OutputFormat::Csv => {
+                let mut writer = CsvWriter::create(output_path, &options.csv)?;
+                write_channels(
+                    &mut writer,
+                    dts_reader,
+                    processed_channels,
+                    expression_channels,
+                    progress,
+                )
+                .with_context(|| {
+                    format!("Failed to write CSV export to {}", output_path.display())
+                })?;
+                UffRecordCounts::default()
+            }
+            OutputFormat::Wav => {
+                let mut writer =
+                    WavWriter::create(output_path, dts_reader.test_info(), options.wav_normalize)?;
+                write_channels(
+                    &mut writer,
+                    dts_reader,
+                    processed_channels,
+                    expression_channels,
+                    progress,
+                )
+                .with_context(|| format!("Failed to write WAV file {}", output_path.display()))?;
+                UffRecordCounts::default()
+            }
+            OutputFormat::Npz => {
+                let mut writer = NpzWriter::create(output_path)?;
+                write_channels(
+                    &mut writer,
+                    dts_reader,
+                    processed_channels,
+                    expression_channels,
+                    progress,
+                )
+                .with_context(|| {
+                    format!("Failed to write NPZ archive {}", output_path.display())
+                })?;
+                UffRecordCounts::default()
+            }
+            OutputFormat::Mdf => {
+                let mut writer = MdfWriter::create(output_path, dts_reader.test_info())?;
+                write_channels(
+                    &mut writer,
+                    dts_reader,
+                    processed_channels,
+                    expression_channels,
+                    progress,
+                )
+                .with_context(|| format!("Failed to write MDF file {}", output_path.display()))?;
+                UffRecordCounts::default()
+            }
+            OutputFormat::Tdms => {
+                let mut writer = TdmsWriter::create(output_path, dts_reader.test_info())?;
+                write_channels(
+                    &mut writer,
+                    dts_reader,
+                    processed_channels,
+                    expression_channels,
+                    progress,
+                )
+                .with_context(|| format!("Failed to write TDMS file {}", output_path.display()))?;
+                UffRecordCounts::default()
+            }
+            OutputFormat::Raw => {
+                let mut writer = RawWriter::create(output_path, dts_reader.test_info())?;
+                for (position, channel) in processed_channels.iter().enumerate() {
+                    writer
+                        .write_track(
+                            dts_reader,
+                            channel.channel_index,
+                            &channel.track_name,
+                            options.slice,
+                        )
+                        .with_context(|| {
+                            format!("Failed to write raw export to {}", output_path.display())
+                        })?;
+                    progress(ConversionProgress::Advanced {
+                        completed: position + 1,
+                        total: processed_channels.len(),
+                        track_name: &channel.track_name,
+                    });
                 }
+                writer.finish().with_context(|| {
+                    format!("Failed to write raw export to {}", output_path.display())
+                })?;
+                UffRecordCounts::default()
+            }
+        };
+        Ok(record_counts)
+    }
 
-                Ok((order, track_name, channel_data))
-            },
+    /// Summarise the conversion, write the SRS, injury and metadata side files and report
+    /// options that did not match any exported track.
+    fn report<F>(
+        &self,
+        processed_channels: Vec<ProcessedChannel>,
+        analyses: Analyses,
+        quality_issues: Vec<(String, Vec<QualityIssue>)>,
+        record_counts: UffRecordCounts,
+        mut warnings: Vec<String>,
+        progress: &mut F,
+    ) -> Result<ConversionReport>
+    where
+        F: FnMut(ConversionProgress<'_>),
+    {
+        let options = self.options;
+        let channel_plan: Vec<_> = processed_channels
+            .iter()
+            .enumerate()
+            .map(|(order, channel)| {
+                sidecar::planned_channel(
+                    &self.dts_reader,
+                    order,
+                    channel.channel_index,
+                    &channel.track_name,
+                )
+            })
+            .collect();
+
+        let mut processed_names = Vec::with_capacity(processed_channels.len());
+        let mut unit_changes = Vec::new();
+        let mut cfc_classes = Vec::new();
+        let mut applied_filters = Vec::new();
+        let mut resampled = Vec::new();
+        let mut srs_results = Vec::new();
+        let mut statistics = Vec::new();
+        let mut derived_statistics = Vec::new();
+        let mut derived_channels = Vec::new();
+        for channel in processed_channels {
+            let ProcessedChannel {
+                track_name,
+                data,
+                cfc,
+                filters,
+                resampling,
+                start_time,
+                unit_change,
+                srs,
+                derived,
+                ..
+            } = channel;
+
+            statistics.push(ChannelStatistics::compute(&track_name, &data, start_time));
+            for derived in derived {
+                derived_statistics.push(ChannelStatistics::compute(
+                    &derived.name,
+                    &derived.data,
+                    start_time,
+                ));
+                derived_channels.push((derived.name, derived.derivation));
+            }
+            if let Some(srs) = srs {
+                srs_results.push((track_name.clone(), srs));
+            }
+            if let Some(change) = unit_change {
+                unit_changes.push((track_name.clone(), change));
+            }
+            if let Some(class) = cfc {
+                cfc_classes.push((track_name.clone(), class));
+            }
+            if !filters.is_empty() {
+                applied_filters.push((track_name.clone(), filters));
+            }
+            if let Some(resampling) = resampling {
+                resampled.push((track_name.clone(), resampling));
+            }
+            processed_names.push(track_name);
+        }
+        statistics.append(&mut derived_statistics);
+
+        let Analyses {
+            expressions,
+            injury,
+        } = analyses;
+        let mut computed_expressions = Vec::with_capacity(expressions.len());
+        for computed in expressions {
+            statistics.push(ChannelStatistics::compute(
+                &computed.channel.name,
+                &computed.data,
+                computed.start_time,
+            ));
+            computed_expressions.push(computed.channel);
+        }
+
+        if self.format == OutputFormat::Mme {
+            let output_names: Vec<&str> = processed_names
+                .iter()
+                .chain(derived_channels.iter().map(|(name, _)| name))
+                .chain(
+                    computed_expressions
+                        .iter()
+                        .map(|expression| &expression.name),
+                )
+                .map(String::as_str)
+                .collect();
+            mme_code_warnings(&options.mme_codes, &output_names, &mut warnings);
+        }
+
+        if let Some(srs_options) = options.srs.as_ref() {
+            write_srs_csv(srs_options, &srs_results)?;
+        }
+        if let (Some(report), Some(injury_options)) = (injury.as_ref(), options.injury.as_ref()) {
+            write_injury_files(report, injury_options)?;
+        }
+
+        progress(ConversionProgress::Finished);
+
+        let processed_channel_count = processed_names.len();
+        let num_channels = self.dts_reader.channel_count();
+
+        if self.track_names.len() != num_channels {
+            warnings.push(format!(
+                "Number of track names ({}) does not match number of channels ({})",
+                self.track_names.len(),
+                num_channels
+            ));
+        }
+
+        if options.track_list_filter.is_none() && processed_channel_count != num_channels {
+            warnings.push(format!(
+                "Channel count ({num_channels}) did not match processed channel count ({processed_channel_count})."
+            ));
+        }
+
+        unmatched_target_warnings(options, &processed_names, &mut warnings);
+
+        let report = ConversionReport {
+            channel_count: processed_channel_count,
+            track_name_count: self.track_names.len(),
+            processed_track_names: processed_names,
+            warnings,
+            unit_changes,
+            spectrum_count: record_counts.spectra,
+            frf_count: record_counts.frf,
+            srs_count: record_counts.srs,
+            cfc_classes,
+            filters: applied_filters,
+            resampling: resampled,
+            derived_channels,
+            expression_channels: computed_expressions,
+            injury,
+            statistics,
+            quality_issues,
+        };
+
+        if let Some(json_path) = options.metadata_json.as_ref() {
+            Sidecar::new(ConversionRecord {
+                input_dir: self.input_dir,
+                tracks_path: self.tracks_path,
+                output_path: self.output_path,
+                format: self.format,
+                options,
+                reader: &self.dts_reader,
+                channel_plan,
+                report: &report,
+            })?
+            .write_file(json_path)?;
+        }
+
+        Ok(report)
+    }
+}
+
+/// Filter, resample, slice and rescale a channel read at its native rate.
+fn condition_channel(channel: &mut ProcessedChannel, options: &ConversionOptions) -> Result<()> {
+    let track_name = &channel.track_name;
+    let data = &mut channel.data;
+
+    // Filter the full record so that slicing does not introduce start-up transients.
+    channel.cfc = options
+        .cfc
+        .apply(track_name, data)
+        .with_context(|| format!("Failed to filter '{track_name}'"))?;
+    channel.filters = options
+        .filters
+        .apply(track_name, data)
+        .with_context(|| format!("Failed to filter '{track_name}'"))?;
+
+    // Slices are given in native samples and mapped onto the resampled record.
+    let range = options
+        .slice
+        .map(|slice| slice.as_range(data.time_series.len()))
+        .transpose()?;
+
+    channel.resampling = options
+        .resample_rate
+        .map(|rate| resample::resample(data, rate))
+        .transpose()
+        .with_context(|| format!("Failed to resample '{track_name}'"))?
+        .flatten();
+
+    if let Some(range) = range {
+        let range = match &channel.resampling {
+            Some(resampling) => resampling.output_range(range),
+            None => range,
+        };
+        channel.start_time = range.start as f64 / data.sample_rate;
+        data.time_series = data.time_series[range].to_vec();
+    }
+
+    channel.unit_change = options.units.apply(track_name, data)?;
+    Ok(())
+}
+
+/// Compute the spectra, SRS and derived channels of a conditioned channel.
+fn analyse_channel(channel: &mut ProcessedChannel, options: &ConversionOptions) -> Result<()> {
+    let track_name = &channel.track_name;
+    let data = &channel.data;
+
+    channel.spectra = spectral::compute_spectra(data, &options.spectra, &options.spectral)
+        .with_context(|| format!("Failed to compute spectra for '{track_name}'"))?;
+
+    channel.srs = options
+        .srs
+        .as_ref()
+        .map(|srs_options| srs::compute_srs(data, &srs_options.settings))
+        .transpose()
+        .with_context(|| format!("Failed to compute the SRS of '{track_name}'"))?;
+
+    channel.derived = options
+        .derived
+        .iter()
+        .filter(|spec| spec.source == *track_name)
+        .map(|spec| spec.derive(data))
+        .collect::<Result<Vec<_>>>()
+        .with_context(|| format!("Failed to derive channels from '{track_name}'"))?;
+    Ok(())
+}
+
+/// Move the data-quality findings out of the channels and turn them into warnings, or into an
+/// error in strict mode.
+fn take_quality_issues(
+    processed_channels: &mut [ProcessedChannel],
+    options: &ConversionOptions,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<(String, Vec<QualityIssue>)>> {
+    let quality_issues: Vec<(String, Vec<QualityIssue>)> = processed_channels
+        .iter_mut()
+        .filter(|channel| !channel.quality.is_empty())
+        .map(|channel| {
+            (
+                channel.track_name.clone(),
+                std::mem::take(&mut channel.quality),
+            )
+        })
+        .collect();
+    let quality_messages: Vec<String> = quality_issues
+        .iter()
+        .flat_map(|(track_name, issues)| {
+            issues
+                .iter()
+                .map(move |issue| format!("Track '{track_name}': {issue}."))
+        })
+        .collect();
+    if options.quality.strict && !quality_messages.is_empty() {
+        return Err(anyhow!(
+            "Data-quality checks failed:\n{}",
+            quality_messages.join("\n")
+        ));
+    }
+    warnings.extend(quality_messages);
+    Ok(quality_issues)
+}
+
+/// Evaluate the expression channels, injury criteria and FRFs, which combine several channels.
+fn run_analyses(
+    processed_channels: &mut [ProcessedChannel],
+    options: &ConversionOptions,
+    warnings: &mut Vec<String>,
+) -> Result<Analyses> {
+    check_output_names(processed_channels, &options.expressions)?;
+
+    let expressions = evaluate_expressions(processed_channels, &options.expressions, warnings)?;
+
+    let injury = options
+        .injury
+        .as_ref()
+        .map(|injury_options| {
+            compute_injury_criteria(processed_channels, &expressions, injury_options, warnings)
+        })
+        .transpose()
+        .context("Failed to compute injury criteria")?;
+
+    if let Some(frf_options) = options.frf.as_ref() {
+        compute_frfs(processed_channels, frf_options, &options.spectral, warnings)?;
+    }
+
+    Ok(Analyses {
+        expressions,
+        injury,
+    })
+}
+
+/// Reject derived and expression channels named like another exported track.
+fn check_output_names(
+    processed_channels: &[ProcessedChannel],
+    expressions: &[ExpressionChannel],
+) -> Result<()> {
+    let mut track_names_in_output: Vec<&str> = processed_channels
+        .iter()
+        .map(|channel| channel.track_name.as_str())
+        .collect();
+    for derived in processed_channels
+        .iter()
+        .flat_map(|channel| &channel.derived)
+    {
+        if track_names_in_output.contains(&derived.name.as_str()) {
+            return Err(anyhow!(
+                "Derived channel '{}' has the same name as another exported track.",
+                derived.name
+            ));
+        }
+        track_names_in_output.push(&derived.name);
+    }
+    for expression in expressions {
+        if track_names_in_output.contains(&expression.name.as_str()) {
+            return Err(anyhow!(
+                "Expression channel '{}' has the same name as another exported track.",
+                expression.name
+            ));
+        }
+        track_names_in_output.push(&expression.name);
+    }
+    Ok(())
+}
+
+/// Compute the FRF of every response channel relative to the reference channel.
+fn compute_frfs(
+    processed_channels: &mut [ProcessedChannel],
+    frf_options: &FrfOptions,
+    spectral: &SpectralSettings,
+    warnings: &mut Vec<String>,
+) -> Result<()> {
+    let reference = processed_channels
+        .iter()
+        .find(|channel| channel.track_name == frf_options.reference)
+        .ok_or_else(|| {
+            anyhow!(
+                "FRF reference track '{}' is not among the exported tracks.",
+                frf_options.reference
+            )
+        })?;
+    let reference = FrfReference::new(&reference.data, spectral).with_context(|| {
+        format!(
+            "Failed to compute the spectra of FRF reference '{}'",
+            frf_options.reference
         )
+    })?;
+
+    let responses = processed_channels
+        .par_iter()
+        .map(|channel| -> Result<Option<FrequencyResponse>> {
+            if !frf_options.includes(&channel.track_name) {
+                return Ok(None);
+            }
+            reference
+                .frf(&channel.data, frf_options.estimator)
+                .map(Some)
+                .with_context(|| {
+                    format!(
+                        "Failed to compute FRF of '{}' relative to '{}'",
+                        channel.track_name, frf_options.reference
+                    )
+                })
+        })
         .collect::<Result<Vec<_>>>()?;
 
-    processed_channels.sort_by_key(|(order, _, _)| *order);
+    for (channel, frf) in processed_channels.iter_mut().zip(responses) {
+        channel.frf = frf;
+    }
+
+    for requested in frf_options.responses.iter().flatten() {
+        if !processed_channels
+            .iter()
+            .any(|channel| &channel.track_name == requested)
+        {
+            warnings.push(format!(
+                "FRF response track '{requested}' did not match any exported track."
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Warn about requested records and channels that `format` does not store.
+fn output_warnings(
+    format: OutputFormat,
+    options: &ConversionOptions,
+    processed_channels: &[ProcessedChannel],
+    expression_channels: &[ComputedExpression],
+    warnings: &mut Vec<String>,
+) {
+    if !format.is_uff() {
+        for (requested, records) in [
+            (!options.spectra.is_empty(), "Spectra"),
+            (options.frf.is_some(), "FRF records"),
+            (options.srs.is_some(), "SRS records"),
+            (options.write_1858, "Dataset 1858 blocks"),
+            (options.id_template.is_some(), "ID templates"),
+        ] {
+            if requested {
+                warnings.push(format!(
+                    "{records} only apply to UFF output and are not part of the {format} export."
+                ));
+            }
+        }
+    }
+
+    if format == OutputFormat::Raw {
+        for channel in processed_channels
+            .iter()
+            .filter(|channel| !channel.is_unprocessed())
+        {
+            warnings.push(format!(
+                "Track '{}' is stored as unprocessed ADC counts; filtering, resampling and unit \
+                 conversion are not part of the raw export.",
+                channel.track_name
+            ));
+        }
+        for (requested, channels) in [
+            (
+                processed_channels
+                    .iter()
+                    .any(|channel| !channel.derived.is_empty()),
+                "Derived channels",
+            ),
+            (!expression_channels.is_empty(), "Expression channels"),
+        ] {
+            if requested {
+                warnings.push(format!("{channels} are not part of the raw export."));
+            }
+        }
+    }
+}
 
-    // 4. Stream channel data into the output file
+/// Warn about MME output channels without a channel code, and codes without a channel.
+fn mme_code_warnings(
+    mme_codes: &MmeChannelCodes,
+    output_names: &[&str],
+    warnings: &mut Vec<String>,
+) {
+    for name in output_names {
+        if !mme_codes.track_names().any(|track| track == *name) {
+            warnings.push(format!(
+                "Track '{name}' has no MME channel code; its channel code is NOVALUE."
+            ));
+        }
+    }
+    for track in mme_codes.track_names() {
+        if !output_names.contains(&track) {
+            warnings.push(format!(
+                "MME channel code for track '{track}' did not match any exported track."
+            ));
+        }
+    }
+}
+
+/// Write the SRS table when `srs_options` names a CSV file.
+fn write_srs_csv(
+    srs_options: &SrsOptions,
+    srs_results: &[(String, ShockResponseSpectrum)],
+) -> Result<()> {
+    let Some(csv_path) = srs_options.csv_output.as_ref() else {
+        return Ok(());
+    };
+    let spectra: Vec<(&str, &ShockResponseSpectrum)> = srs_results
+        .iter()
+        .map(|(track_name, srs)| (track_name.as_str(), srs))
+        .collect();
+    let file = fs::File::create(csv_path)
+        .with_context(|| format!("Failed to create {}", csv_path.display()))?;
+    let mut csv_writer = BufWriter::new(file);
+    srs::write_srs_csv(&mut csv_writer, &spectra, &srs_options.kinds)
+        .and_then(|()| csv_writer.flush().map_err(Into::into))
+        .with_context(|| format!("Failed to write SRS CSV to {}", csv_path.display()))
+}
+
+/// Write the injury criteria to the JSON and CSV files named in `injury_options`.
+fn write_injury_files(report: &InjuryReport, injury_options: &InjuryOptions) -> Result<()> {
+    if let Some(json_path) = injury_options.json_output.as_ref() {
+        let file = fs::File::create(json_path)
+            .with_context(|| format!("Failed to create {}", json_path.display()))?;
+        let mut json_writer = BufWriter::new(file);
+        report
+            .write_json(&mut json_writer)
+            .and_then(|()| json_writer.flush().map_err(Into::into))
+            .with_context(|| {
+                format!("Failed to write injury criteria to {}", json_path.display())
+            })?;
+    }
+    if let Some(csv_path) = injury_options.csv_output.as_ref() {
+        let file = fs::File::create(csv_path)
+            .with_context(|| format!("Failed to create {}", csv_path.display()))?;
+        let mut csv_writer = BufWriter::new(file);
+        report
+            .write_csv(&mut csv_writer)
+            .and_then(|()| csv_writer.flush().map_err(Into::into))
+            .with_context(|| {
+                format!("Failed to write injury criteria to {}", csv_path.display())
+            })?;
+    }
+    Ok(())
+}
+
+/// Warn about per-track options naming a track that was not exported.
+fn unmatched_target_warnings(
+    options: &ConversionOptions,
+    processed_names: &[String],
+    warnings: &mut Vec<String>,
+) {
+    for (target_track, _) in options.cfc.channel_classes() {
+        if !processed_names.iter().any(|name| name == target_track) {
+            warnings.push(format!(
+                "CFC class for track '{target_track}' did not match any exported track."
+            ));
+        }
+    }
+
+    for stage in options.filters.stages() {
+        for target_track in stage.tracks.iter().flatten() {
+            if !processed_names.iter().any(|name| name == target_track) {
+                warnings.push(format!(
+                    "Filter '{}' for track '{target_track}' did not match any exported track.",
+                    stage.spec
+                ));
+            }
+        }
+    }
+
+    for spec in &options.derived {
+        if !processed_names.iter().any(|name| name == &spec.source) {
+            warnings.push(format!(
+                "Derived channel source '{}' did not match any exported track.",
+                spec.source
+            ));
+        }
+    }
+
+    for (target_track, _) in options.units.channel_targets() {
+        if !processed_names.iter().any(|name| name == target_track) {
+            warnings.push(format!(
+                "Unit target for track '{target_track}' did not match any exported track."
+            ));
+        }
+    }
+}
+
+/// Number of frequency-domain records written to a UFF file.
+#[derive(Debug, Default)]
+struct UffRecordCounts {
+    spectra: usize,
+    frf: usize,
+    srs: usize,
+}
+
+/// Write every channel as UFF 58 records: the time histories with their spectra, FRF and SRS
+/// records, then the derived and expression channels.
+fn write_uff<F>(
+    output_path: &Path,
+    format: uff::Uff58Format,
+    options: &ConversionOptions,
+    dts_reader: &dts::DtsReader,
+    processed_channels: &[ProcessedChannel],
+    expression_channels: &[ComputedExpression],
+    progress: &mut F,
+) -> Result<UffRecordCounts>
+where
+    F: FnMut(ConversionProgress<'_>),
+{
     let file = OpenOptions::new()
         .write(true)
         .create(true)
@@ -235,64 +1324,472 @@ where
 
     let mut writer = BufWriter::with_capacity(8 * 1024 * 1024, file);
     let total_channels = processed_channels.len();
-    let mut processed_names = Vec::with_capacity(total_channels);
+    let mut pending_derived = Vec::new();
+    let mut counts = UffRecordCounts::default();
+    let mut record_number = 0;
+
+    for (position, channel) in processed_channels.iter().enumerate() {
+        let ProcessedChannel {
+            channel_index,
+            track_name,
+            data: channel_data,
+            cfc,
+            filters,
+            start_time,
+            unit_change,
+            spectra,
+            frf,
+            srs,
+            derived,
+            ..
+        } = channel;
+        let start_time = *start_time;
+
+        let filter_label = cfc
+            .map(|class| class.to_string())
+            .into_iter()
+            .chain(filters.iter().map(FilterSpec::to_string))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        // Filtered channels fall back to the MATLAB template so that the filters are recorded.
+        let template = match options.id_template.as_ref() {
+            Some(template) => Some(Cow::Borrowed(template)),
+            None if !filter_label.is_empty() => Some(Cow::Owned(IdTemplate::matlab())),
+            None => None,
+        };
+        let ids = match template.as_ref() {
+            Some(template) => template.render(&IdContext {
+                track_name,
+                test: dts_reader.test_info(),
+                channel: dts_reader.channel_info(*channel_index),
+                data: channel_data,
+                filter: (!filter_label.is_empty()).then_some(filter_label.as_str()),
+            }),
+            None => uff::IdLines::matlab(track_name),
+        };
+
+        // Processing shared by every record written for this channel.
+        let unit_factor = unit_change.as_ref().map_or(1.0, |change| change.factor);
+        let low_pass = cfc
+            .map(|class| class.cutoff_frequency())
+            .into_iter()
+            .chain(filters.iter().filter_map(FilterSpec::low_pass_cutoff))
+            .reduce(f64::min)
+            .unwrap_or(0.0);
+        let high_pass = filters
+            .iter()
+            .filter_map(FilterSpec::high_pass_cutoff)
+            .reduce(f64::max)
+            .unwrap_or(0.0);
+        let annotate = |qualifiers: &mut uff::Uff1858| {
+            qualifiers.z_time = start_time;
+            qualifiers.user_values[1] = low_pass;
+            qualifiers.user_values[2] = high_pass;
+            qualifiers.user_values[3] = unit_factor;
+        };
+
+        record_number += 1;
+        if options.write_1858 {
+            let mut qualifiers =
+                uff::Uff1858::time_history(record_number, channel_data.time_series.len());
+            annotate(&mut qualifiers);
+            uff::write_uff1858(&mut writer, &qualifiers).with_context(|| {
+                format!("Failed to write UFF 1858 qualifiers for channel '{track_name}'")
+            })?;
+        }
 
-    for (position, (_order, track_name, channel_data)) in processed_channels.into_iter().enumerate()
-    {
         match format {
-            OutputFormat::Ascii => uff::write_uff58_ascii(&mut writer, &channel_data, &track_name)
-                .with_context(|| {
-                    format!(
-                        "Failed to write ASCII UFF data for channel '{}'",
-                        track_name
-                    )
-                })?,
-            OutputFormat::Binary => uff::write_uff58b(&mut writer, &channel_data, &track_name)
-                .with_context(|| {
-                    format!(
-                        "Failed to write binary UFF data for channel '{}'",
-                        track_name
-                    )
-                })?,
+            uff::Uff58Format::Ascii => {
+                uff::write_uff58_ascii_with_ids(&mut writer, channel_data, track_name, &ids)
+                    .with_context(|| {
+                        format!(
+                            "Failed to write ASCII UFF data for channel '{}'",
+                            track_name
+                        )
+                    })?
+            }
+            uff::Uff58Format::Binary58b => {
+                uff::write_uff58b_with_ids(&mut writer, channel_data, track_name, &ids)
+                    .with_context(|| {
+                        format!(
+                            "Failed to write binary UFF data for channel '{}'",
+                            track_name
+                        )
+                    })?
+            }
         };
 
-        let completed = position + 1;
+        for spectrum in spectra {
+            record_number += 1;
+            if options.write_1858 {
+                let mut qualifiers = spectrum.uff1858_qualifiers(record_number);
+                annotate(&mut qualifiers);
+                uff::write_uff1858(&mut writer, &qualifiers).with_context(|| {
+                    format!("Failed to write UFF 1858 qualifiers for channel '{track_name}'")
+                })?;
+            }
+            let record = spectrum.uff58_record(track_name, ids.clone());
+            uff::write_uff58_record(&mut writer, &record, format).with_context(|| {
+                format!(
+                    "Failed to write {} spectrum for channel '{}'",
+                    spectrum.kind, track_name
+                )
+            })?;
+            counts.spectra += 1;
+        }
+
+        if let (Some(frf), Some(frf_options)) = (frf.as_ref(), options.frf.as_ref()) {
+            let reference_track = frf_options.reference.as_str();
+            let mut records = vec![frf.frf_record(track_name, reference_track, ids.clone())];
+            if frf_options.coherence {
+                records.push(frf.coherence_record(track_name, reference_track, ids.clone()));
+            }
+            for record in &records {
+                record_number += 1;
+                if options.write_1858 {
+                    let mut qualifiers = frf.uff1858_qualifiers(record_number);
+                    annotate(&mut qualifiers);
+                    uff::write_uff1858(&mut writer, &qualifiers).with_context(|| {
+                        format!("Failed to write UFF 1858 qualifiers for channel '{track_name}'")
+                    })?;
+                }
+                uff::write_uff58_record(&mut writer, record, format).with_context(|| {
+                    format!("Failed to write FRF data for channel '{track_name}'")
+                })?;
+            }
+            counts.frf += 1;
+        }
+
+        if let (Some(srs), Some(srs_options)) = (srs.as_ref(), options.srs.as_ref()) {
+            for &kind in &srs_options.kinds {
+                record_number += 1;
+                if options.write_1858 {
+                    let mut qualifiers = srs.uff1858_qualifiers(record_number);
+                    annotate(&mut qualifiers);
+                    uff::write_uff1858(&mut writer, &qualifiers).with_context(|| {
+                        format!("Failed to write UFF 1858 qualifiers for channel '{track_name}'")
+                    })?;
+                }
+                let record = srs.uff58_record(kind, track_name, ids.clone());
+                uff::write_uff58_record(&mut writer, &record, format).with_context(|| {
+                    format!("Failed to write {kind} SRS for channel '{track_name}'")
+                })?;
+                counts.srs += 1;
+            }
+        }
+
+        // Derived channels are appended after every exported track, with the IDs and
+        // processing of their source.
+        for derived in derived {
+            let ids = match template.as_ref() {
+                Some(template) => template.render(&IdContext {
+                    track_name: &derived.name,
+                    test: dts_reader.test_info(),
+                    channel: dts_reader.channel_info(*channel_index),
+                    data: &derived.data,
+                    filter: (!filter_label.is_empty()).then_some(filter_label.as_str()),
+                }),
+                None => uff::IdLines::matlab(&derived.name),
+            };
+            let mut qualifiers = uff::Uff1858::time_history(0, derived.data.time_series.len());
+            annotate(&mut qualifiers);
+            qualifiers.user_values[3] = 1.0;
+            pending_derived.push((derived, ids, qualifiers));
+        }
+
         progress(ConversionProgress::Advanced {
-            completed,
+            completed: position + 1,
             total: total_channels,
-            track_name: &track_name,
+            track_name,
         });
-        processed_names.push(track_name);
+    }
+
+    for (derived, ids, mut qualifiers) in pending_derived {
+        record_number += 1;
+        if options.write_1858 {
+            qualifiers.set_record_number = record_number;
+            uff::write_uff1858(&mut writer, &qualifiers).with_context(|| {
+                format!(
+                    "Failed to write UFF 1858 qualifiers for channel '{}'",
+                    derived.name
+                )
+            })?;
+        }
+        let record = uff::Uff58Record {
+            ordinate_axis: derived.ordinate_axis.clone(),
+            ..uff::Uff58Record::time_history(&derived.data, &derived.name, ids)
+        };
+        uff::write_uff58_record(&mut writer, &record, format)
+            .with_context(|| format!("Failed to write derived channel '{}'", derived.name))?;
+    }
+
+    for computed in expression_channels {
+        let name = computed.channel.name.as_str();
+        let ids = match options.id_template.as_ref() {
+            Some(template) => template.render(&IdContext {
+                track_name: name,
+                test: dts_reader.test_info(),
+                channel: None,
+                data: &computed.data,
+                filter: None,
+            }),
+            None => uff::IdLines::matlab(name),
+        };
+        record_number += 1;
+        if options.write_1858 {
+            let mut qualifiers =
+                uff::Uff1858::time_history(record_number, computed.data.time_series.len());
+            qualifiers.z_time = computed.start_time;
+            uff::write_uff1858(&mut writer, &qualifiers).with_context(|| {
+                format!("Failed to write UFF 1858 qualifiers for channel '{name}'")
+            })?;
+        }
+        let record = uff::Uff58Record::time_history(&computed.data, name, ids);
+        uff::write_uff58_record(&mut writer, &record, format)
+            .with_context(|| format!("Failed to write expression channel '{name}'"))?;
     }
 
     writer
         .flush()
         .with_context(|| format!("Failed to flush writer for {}", output_path.display()))?;
+    Ok(counts)
+}
+
+/// Hand every time history to `writer`, in the same order as the UFF output.
+fn write_channels<'a, W, F>(
+    writer: &mut W,
+    dts_reader: &'a dts::DtsReader,
+    processed_channels: &'a [ProcessedChannel],
+    expression_channels: &'a [ComputedExpression],
+    progress: &mut F,
+) -> Result<()>
+where
+    W: ChannelWriter<'a>,
+    F: FnMut(ConversionProgress<'_>),
+{
+    for (position, channel) in processed_channels.iter().enumerate() {
+        writer.write_channel(&ExportChannel {
+            name: &channel.track_name,
+            data: &channel.data,
+            start_time: channel.start_time,
+            trigger_time: channel.trigger_offset + channel.start_time,
+            channel: dts_reader.channel_info(channel.channel_index),
+            cfc: channel.cfc,
+            filters: &channel.filters,
+            computed: false,
+            adc_scaling: channel
+                .is_unprocessed()
+                .then(|| dts_reader.adc_scaling(channel.channel_index)),
+        })?;
+        progress(ConversionProgress::Advanced {
+            completed: position + 1,
+            total: processed_channels.len(),
+            track_name: &channel.track_name,
+        });
+    }
+    for channel in processed_channels {
+        for derived in &channel.derived {
+            writer.write_channel(&ExportChannel {
+                name: &derived.name,
+                data: &derived.data,
+                start_time: channel.start_time,
+                trigger_time: channel.trigger_offset + channel.start_time,
+                channel: dts_reader.channel_info(channel.channel_index),
+                cfc: channel.cfc,
+                filters: &channel.filters,
+                computed: true,
+                adc_scaling: None,
+            })?;
+        }
+    }
+    for computed in expression_channels {
+        writer.write_channel(&ExportChannel {
+            name: &computed.channel.name,
+            data: &computed.data,
+            start_time: computed.start_time,
+            trigger_time: computed.trigger_time,
+            channel: None,
+            cfc: None,
+            filters: &[],
+            computed: true,
+            adc_scaling: None,
+        })?;
+    }
+    writer.finish()
+}
 
-    progress(ConversionProgress::Finished);
+/// A channel computed from an [`ExpressionChannel`], ready to be written.
+struct ComputedExpression {
+    /// The expression with its units resolved.
+    channel: ExpressionChannel,
+    data: dts::ChannelData,
+    start_time: f64,
+    /// Time of the first sample relative to the trigger, taken from the first input.
+    trigger_time: f64,
+}
 
-    let processed_channel_count = processed_names.len();
+/// Exported, derived or expression channel named `track`, with the time of its first sample
+/// and that time relative to the trigger.
+fn find_output_channel<'a>(
+    processed_channels: &'a [ProcessedChannel],
+    expressions: &'a [ComputedExpression],
+    track: &str,
+) -> Option<(&'a dts::ChannelData, f64, f64)> {
+    processed_channels
+        .iter()
+        .find_map(|channel| {
+            let trigger_time = channel.trigger_offset + channel.start_time;
+            if channel.track_name == track {
+                return Some((&channel.data, channel.start_time, trigger_time));
+            }
+            channel
+                .derived
+                .iter()
+                .find(|derived| derived.name == track)
+                .map(|derived| (&derived.data, channel.start_time, trigger_time))
+        })
+        .or_else(|| {
+            expressions
+                .iter()
+                .find(|expression| expression.channel.name == track)
+                .map(|expression| {
+                    (
+                        &expression.data,
+                        expression.start_time,
+                        expression.trigger_time,
+                    )
+                })
+        })
+}
 
-    if track_names.len() != num_channels {
-        warnings.push(format!(
-            "Number of track names ({}) does not match number of channels ({})",
-            track_names.len(),
-            num_channels
-        ));
+/// Compute the injury criteria of the channels mapped in `injury_options`.
+fn compute_injury_criteria(
+    processed_channels: &[ProcessedChannel],
+    expressions: &[ComputedExpression],
+    injury_options: &InjuryOptions,
+    warnings: &mut Vec<String>,
+) -> Result<InjuryReport> {
+    let mut channels = BTreeMap::new();
+    for assignment in &injury_options.roles {
+        let track = assignment.track_name.as_str();
+        let (data, start_time, _) = find_output_channel(processed_channels, expressions, track)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Track '{track}' mapped to {} is not among the exported tracks.",
+                    assignment.role
+                )
+            })?;
+        if processed_channels
+            .iter()
+            .any(|channel| channel.track_name == track && channel.cfc.is_none())
+        {
+            warnings.push(format!(
+                "Track '{track}' ({}) is not CFC filtered; SAE J211 recommends {}.",
+                assignment.role,
+                assignment.role.recommended_cfc()
+            ));
+        }
+        channels.insert(
+            assignment.role,
+            RoleChannel {
+                track_name: track,
+                data,
+                start_time,
+            },
+        );
     }
+    injury::compute_criteria(&channels, &injury_options.intercepts)
+}
 
-    if track_list_filter.is_none() && processed_channel_count != num_channels {
-        warnings.push(format!(
-            "Channel count ({num_channels}) did not match processed channel count ({processed_channel_count})."
-        ));
-    }
+/// Evaluate `expressions` in order. Each expression may use the exported tracks, the derived
+/// channels and the expressions before it. Inputs sampled at another rate than the first track
+/// of the expression are resampled to that rate.
+fn evaluate_expressions(
+    processed_channels: &[ProcessedChannel],
+    expressions: &[ExpressionChannel],
+    warnings: &mut Vec<String>,
+) -> Result<Vec<ComputedExpression>> {
+    let mut computed: Vec<ComputedExpression> = Vec::with_capacity(expressions.len());
+    for expression in expressions {
+        let name = &expression.name;
+        let lookup = |track: &str| {
+            find_output_channel(processed_channels, &computed, track).ok_or_else(|| {
+                anyhow!("Expression channel '{name}' uses track '{track}', which is not exported.")
+            })
+        };
 
-    Ok(ConversionReport {
-        channel_count: processed_channel_count,
-        track_name_count: track_names.len(),
-        processed_track_names: processed_names,
-        warnings,
-    })
+        let tracks = expression.expression.tracks();
+        let inputs = tracks
+            .iter()
+            .map(|track| lookup(track))
+            .collect::<Result<Vec<_>>>()?;
+        let (first, start_time, trigger_time) = inputs[0];
+        let sample_rate = first.sample_rate;
+        // Samples are combined by index, so inputs must start within half a sample.
+        for (track, (_, input_start, _)) in tracks.iter().zip(&inputs).skip(1) {
+            if (input_start - start_time).abs() > 0.5 / sample_rate {
+                warnings.push(format!(
+                    "Input '{track}' of expression channel '{name}' starts at {input_start} s, \
+                     not {start_time} s like '{}'; samples are combined out of alignment.",
+                    tracks[0]
+                ));
+            }
+        }
+
+        let mut resampled = Vec::new();
+        for (track, (data, ..)) in tracks.iter().zip(&inputs) {
+            if let Some(resampling) = Resampling::new(data.sample_rate, sample_rate)? {
+                warnings.push(format!(
+                    "Resampled '{track}' from {} Hz to {} Hz for expression channel '{name}'.",
+                    resampling.from, resampling.to
+                ));
+                resampled.push((track.as_str(), resampling.apply(&data.time_series)));
+            }
+        }
+        let mut columns: BTreeMap<&str, &[f64]> = tracks
+            .iter()
+            .zip(&inputs)
+            .map(|(track, (data, ..))| (track.as_str(), data.time_series.as_slice()))
+            .collect();
+        for (track, samples) in &resampled {
+            columns.insert(track, samples);
+        }
+        if columns
+            .values()
+            .any(|samples| samples.len() != first.time_series.len())
+        {
+            warnings.push(format!(
+                "Inputs of expression channel '{name}' differ in length; the shortest is used."
+            ));
+        }
+
+        let time_series = expression.expression.evaluate(&columns)?;
+        if let Some(index) = time_series.iter().position(|value| !value.is_finite()) {
+            return Err(anyhow!(
+                "Expression channel '{name}' is not finite at sample {index}."
+            ));
+        }
+        let units = expression
+            .units
+            .clone()
+            .unwrap_or_else(|| first.units.clone());
+        let data = dts::ChannelData {
+            time_series,
+            sample_rate,
+            units: units.clone(),
+        };
+        computed.push(ComputedExpression {
+            channel: ExpressionChannel {
+                units: Some(units),
+                ..expression.clone()
+            },
+            data,
+            start_time,
+            trigger_time,
+        });
+    }
+    Ok(computed)
 }
 
 /// Convert a DTS directory to a UFF file without reporting progress.
@@ -0,0 +1,342 @@
+use crate::export::{ChannelWriter, ExportChannel};
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// How channels sampled at different rates are written.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CsvRateHandling {
+    /// Write one file per sample rate, named `<stem>_<rate>Hz.<extension>`.
+    #[default]
+    Split,
+    /// Write a single file at the highest rate, interpolating the slower channels linearly.
+    Align,
+}
+
+impl CsvRateHandling {
+    /// Returns the name used on the command line.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CsvRateHandling::Split => "split",
+            CsvRateHandling::Align => "align",
+        }
+    }
+}
+
+impl std::fmt::Display for CsvRateHandling {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for CsvRateHandling {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "split" => Ok(CsvRateHandling::Split),
+            "align" => Ok(CsvRateHandling::Align),
+            other => Err(format!(
+                "Unsupported CSV rate handling '{other}'. Expected 'split' or 'align'."
+            )),
+        }
+    }
+}
+
+/// Layout of the delimited text export.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CsvSettings {
+    /// Field delimiter. `None` uses a tab for `.tsv` files and a comma otherwise.
+    pub delimiter: Option<char>,
+    pub decimal_separator: char,
+    /// Digits after the decimal point. `None` writes the shortest exact representation. The time
+    /// column keeps enough digits to resolve the sample interval.
+    pub precision: Option<usize>,
+    /// Write units, sensitivity and serial number rows below the column names.
+    pub metadata: bool,
+    pub rates: CsvRateHandling,
+}
+
+impl Default for CsvSettings {
+    fn default() -> Self {
+        Self {
+            delimiter: None,
+            decimal_separator: '.',
+            precision: None,
+            metadata: false,
+            rates: CsvRateHandling::default(),
+        }
+    }
+}
+
+impl CsvSettings {
+    /// Delimiter used for the file at `path`.
+    pub fn delimiter_for(&self, path: &Path) -> char {
+        self.delimiter.unwrap_or_else(|| {
+            let tsv = path
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("tsv"));
+            if tsv {
+                '\t'
+            } else {
+                ','
+            }
+        })
+    }
+
+    /// Ensure the delimiter and decimal separator can be told apart.
+    pub fn validate(&self, path: &Path) -> Result<()> {
+        let delimiter = self.delimiter_for(path);
+        if delimiter == self.decimal_separator || matches!(delimiter, '"' | '\n' | '\r') {
+            return Err(anyhow!(
+                "Invalid CSV delimiter {delimiter:?} with decimal separator {:?}; use a \
+                 different delimiter, e.g. ';' with a decimal comma.",
+                self.decimal_separator
+            ));
+        }
+        if !matches!(self.decimal_separator, '.' | ',') {
+            return Err(anyhow!(
+                "Invalid decimal separator {:?}: expected '.' or ','.",
+                self.decimal_separator
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Parse a delimiter given as a single character or as `comma`, `semicolon`, `tab`, `space`
+/// or `pipe`.
+pub fn parse_delimiter(value: &str) -> Result<char, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "comma" => Ok(','),
+        "semicolon" => Ok(';'),
+        "tab" | "\\t" => Ok('\t'),
+        "space" => Ok(' '),
+        "pipe" => Ok('|'),
+        _ => {
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(delimiter), None) => Ok(delimiter),
+                _ => Err(format!(
+                    "Invalid delimiter '{value}': expected a single character, 'comma', \
+                     'semicolon', 'tab', 'space' or 'pipe'."
+                )),
+            }
+        }
+    }
+}
+
+/// Writes delimited text with a time column followed by one column per channel.
+///
+/// Channels are kept until [`ChannelWriter::finish`], which lays them out row by row. Cells
+/// outside the record of a channel are left empty.
+pub struct CsvWriter<'a> {
+    path: PathBuf,
+    settings: CsvSettings,
+    delimiter: char,
+    channels: Vec<ExportChannel<'a>>,
+}
+
+impl<'a> CsvWriter<'a> {
+    /// Prepare a delimited text export to `path`.
+    pub fn create(path: &Path, settings: &CsvSettings) -> Result<Self> {
+        settings.validate(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            settings: settings.clone(),
+            delimiter: settings.delimiter_for(path),
+            channels: Vec::new(),
+        })
+    }
+
+    /// Path of the file holding the channels sampled at `rate`, in split mode.
+    fn rate_path(&self, rate: f64) -> PathBuf {
+        let stem = self
+            .path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let name = match self.path.extension() {
+            Some(extension) => format!("{stem}_{rate}Hz.{}", extension.to_string_lossy()),
+            None => format!("{stem}_{rate}Hz"),
+        };
+        self.path.with_file_name(name)
+    }
+
+    fn number(&self, value: f64, precision: Option<usize>, text: &mut String) {
+        let start = text.len();
+        let _ = match precision {
+            Some(precision) => write!(text, "{value:.precision$}"),
+            None => write!(text, "{value}"),
+        };
+        if self.settings.decimal_separator != '.' {
+            if let Some(dot) = text[start..].find('.') {
+                text.replace_range(start + dot..start + dot + 1, ",");
+            }
+        }
+    }
+
+    fn field(&self, value: &str, text: &mut String) {
+        let quote = value
+            .chars()
+            .any(|c| c == self.delimiter || matches!(c, '"' | '\n' | '\r'));
+        if quote {
+            text.push('"');
+            text.push_str(&value.replace('"', "\"\""));
+            text.push('"');
+        } else {
+            text.push_str(value);
+        }
+    }
+
+    /// Write `channels` on a common time grid sampled at `rate`.
+    fn write_table(&self, path: &Path, rate: f64, channels: &[ExportChannel<'a>]) -> Result<()> {
+        let file =
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut writer = BufWriter::with_capacity(8 * 1024 * 1024, file);
+        let mut line = String::new();
+
+        let header_row = |label: &str, values: &mut dyn Iterator<Item = String>| {
+            let mut line = String::new();
+            self.field(label, &mut line);
+            for value in values {
+                line.push(self.delimiter);
+                self.field(&value, &mut line);
+            }
+            line.push('\n');
+            line
+        };
+        let mut names = channels.iter().map(|channel| channel.name.to_string());
+        writer.write_all(header_row("Time (s)", &mut names).as_bytes())?;
+        if self.settings.metadata {
+            let mut units = channels.iter().map(|channel| channel.data.units.clone());
+            writer.write_all(header_row("Units", &mut units).as_bytes())?;
+            let mut sensitivities = channels.iter().map(|channel| {
+                let mut text = String::new();
+                if let Some(info) = channel.channel.filter(|_| !channel.computed) {
+                    self.number(info.sensitivity, self.settings.precision, &mut text);
+                }
+                text
+            });
+            writer.write_all(header_row("Sensitivity", &mut sensitivities).as_bytes())?;
+            let mut serials = channels.iter().map(|channel| {
+                channel
+                    .channel
+                    .map(|info| info.serial_number.clone())
+                    .unwrap_or_default()
+            });
+            writer.write_all(header_row("Serial number", &mut serials).as_bytes())?;
+        }
+
+        let populated = channels
+            .iter()
+            .filter(|channel| !channel.data.time_series.is_empty());
+        let start = populated
+            .clone()
+            .map(|channel| channel.start_time)
+            .reduce(f64::min);
+        let end = populated
+            .map(|channel| {
+                channel.start_time
+                    + (channel.data.time_series.len() - 1) as f64 / channel.data.sample_rate
+            })
+            .reduce(f64::max);
+        let rows = match (start, end) {
+            (Some(start), Some(end)) => ((end - start) * rate + 1e-6).floor() as usize + 1,
+            _ => 0,
+        };
+        let start = start.unwrap_or_default();
+        // Keep enough digits to tell consecutive rows apart.
+        let time_precision = self
+            .settings
+            .precision
+            .map(|precision| precision.max(rate.log10().ceil().max(0.0) as usize + 2));
+
+        for row in 0..rows {
+            let time = start + row as f64 / rate;
+            line.clear();
+            self.number(time, time_precision, &mut line);
+            for channel in channels {
+                line.push(self.delimiter);
+                if let Some(value) = sample_at(channel, time) {
+                    self.number(value, self.settings.precision, &mut line);
+                }
+            }
+            line.push('\n');
+            writer.write_all(line.as_bytes())?;
+        }
+        writer
+            .flush()
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+impl<'a> ChannelWriter<'a> for CsvWriter<'a> {
+    fn write_channel(&mut self, channel: &ExportChannel<'a>) -> Result<()> {
+        self.channels.push(*channel);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let mut groups: Vec<(f64, Vec<ExportChannel<'a>>)> = Vec::new();
+        for channel in &self.channels {
+            let rate = channel.data.sample_rate;
+            match self.settings.rates {
+                CsvRateHandling::Align => match groups.first_mut() {
+                    Some((group_rate, members)) => {
+                        *group_rate = group_rate.max(rate);
+                        members.push(*channel);
+                    }
+                    None => groups.push((rate, vec![*channel])),
+                },
+                CsvRateHandling::Split => {
+                    match groups
+                        .iter_mut()
+                        .find(|(group_rate, _)| same_rate(*group_rate, rate))
+                    {
+                        Some((_, members)) => members.push(*channel),
+                        None => groups.push((rate, vec![*channel])),
+                    }
+                }
+            }
+        }
+
+        match groups.as_slice() {
+            [] => self.write_table(&self.path, 1.0, &[]),
+            [(rate, channels)] => self.write_table(&self.path, *rate, channels),
+            groups => {
+                for (rate, channels) in groups {
+                    self.write_table(&self.rate_path(*rate), *rate, channels)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn same_rate(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-9 * a.abs().max(b.abs())
+}
+
+/// Value of `channel` at `time`, linearly interpolated between samples. `None` outside the
+/// record.
+fn sample_at(channel: &ExportChannel<'_>, time: f64) -> Option<f64> {
+    let samples = &channel.data.time_series;
+    let position = (time - channel.start_time) * channel.data.sample_rate;
+    let last = samples.len().checked_sub(1)? as f64;
+    if position < -1e-6 || position > last + 1e-6 {
+        return None;
+    }
+    let nearest = position.round().clamp(0.0, last);
+    if (position - nearest).abs() < 1e-6 {
+        return Some(samples[nearest as usize]);
+    }
+    let below = position.floor() as usize;
+    let fraction = position - below as f64;
+    Some(samples[below] + (samples[below + 1] - samples[below]) * fraction)
+}
@@ -0,0 +1,336 @@
+use crate::dts::ChannelData;
+use crate::filter::{FilterResponse, FilterSpec};
+use crate::uff::AxisSpec;
+use crate::units::{lookup_unit, Quantity};
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+
+/// UFF specific data type codes of derived channels.
+const DATA_TYPE_GENERAL: i32 = 1;
+const DATA_TYPE_DISPLACEMENT: i32 = 8;
+const DATA_TYPE_VELOCITY: i32 = 11;
+const DATA_TYPE_ACCELERATION: i32 = 12;
+
+/// Calculus applied to a source track to derive a new channel.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DerivedOperation {
+    /// Cumulative trapezoidal integral, e.g. acceleration to velocity.
+    Integrate,
+    /// Two cumulative integrals, e.g. acceleration to displacement.
+    DoubleIntegrate,
+    /// Central difference derivative, e.g. velocity to acceleration.
+    Differentiate,
+}
+
+impl DerivedOperation {
+    /// Returns the human readable name of the operation.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DerivedOperation::Integrate => "integrate",
+            DerivedOperation::DoubleIntegrate => "double-integrate",
+            DerivedOperation::Differentiate => "differentiate",
+        }
+    }
+
+    /// Suffix of the default derived track name.
+    fn suffix(&self, acceleration: bool) -> &'static str {
+        match (self, acceleration) {
+            (DerivedOperation::Integrate, true) => "velocity",
+            (DerivedOperation::DoubleIntegrate, true) => "displacement",
+            (DerivedOperation::Integrate, false) => "integral",
+            (DerivedOperation::DoubleIntegrate, false) => "double_integral",
+            (DerivedOperation::Differentiate, _) => "derivative",
+        }
+    }
+}
+
+impl std::fmt::Display for DerivedOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for DerivedOperation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "integrate" | "velocity" => Ok(DerivedOperation::Integrate),
+            "double-integrate" | "displacement" => Ok(DerivedOperation::DoubleIntegrate),
+            "differentiate" | "derivative" => Ok(DerivedOperation::Differentiate),
+            other => Err(format!(
+                "Unsupported derived operation '{other}'. Expected 'integrate' (or 'velocity'), \
+                 'double-integrate' (or 'displacement') or 'differentiate'."
+            )),
+        }
+    }
+}
+
+/// Offset subtracted from the source track before the operation.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OffsetRemoval {
+    /// Mean of the first `n` samples, e.g. the pre-trigger part of the record.
+    Leading(usize),
+    /// Mean of the whole record.
+    Mean,
+}
+
+/// A channel derived from an exported track, as `track=operation[:option...]`.
+///
+/// Examples: `A1=velocity`, `A1=displacement:offset=200:highpass=0.5:name=A1_disp`,
+/// `A2=differentiate`. Options are `name=TRACK` (defaults to `<track>_<velocity|...>`),
+/// `offset=N` or `offset=mean` (offset removal before integrating) and `highpass=HZ` (a
+/// second-order zero-phase Butterworth high-pass applied after every integration or
+/// differentiation to control drift).
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DerivedChannelSpec {
+    pub source: String,
+    pub operation: DerivedOperation,
+    pub name: Option<String>,
+    pub offset: Option<OffsetRemoval>,
+    /// Cutoff frequency of the drift control high-pass, in Hz.
+    pub high_pass: Option<f64>,
+}
+
+impl std::str::FromStr for DerivedChannelSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (source, rest) = s.split_once('=').ok_or_else(|| {
+            "Derived channel must be provided in the format track=operation".to_string()
+        })?;
+        let source = source.trim();
+        if source.is_empty() {
+            return Err("Derived channel is missing a source track name".to_string());
+        }
+
+        let mut parts = rest.split(':').map(str::trim);
+        let operation = parts.next().unwrap_or("").parse()?;
+        let mut spec = DerivedChannelSpec {
+            source: source.to_string(),
+            operation,
+            name: None,
+            offset: None,
+            high_pass: None,
+        };
+
+        for option in parts {
+            let (key, value) = option.split_once('=').ok_or_else(|| {
+                format!("Derived channel option '{option}' must be provided as key=value")
+            })?;
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "name" if !value.is_empty() => spec.name = Some(value.to_string()),
+                "offset" if value.eq_ignore_ascii_case("mean") => {
+                    spec.offset = Some(OffsetRemoval::Mean)
+                }
+                "offset" => {
+                    let samples = value.parse::<usize>().map_err(|err| {
+                        format!("Failed to parse offset samples '{value}': {err}")
+                    })?;
+                    if samples == 0 {
+                        return Err("Offset removal needs at least one sample".to_string());
+                    }
+                    spec.offset = Some(OffsetRemoval::Leading(samples));
+                }
+                "highpass" => {
+                    let cutoff = value.parse::<f64>().map_err(|err| {
+                        format!("Failed to parse high-pass cutoff '{value}': {err}")
+                    })?;
+                    if cutoff <= 0.0 || !cutoff.is_finite() {
+                        return Err(format!("Invalid high-pass cutoff ({cutoff} Hz)"));
+                    }
+                    spec.high_pass = Some(cutoff);
+                }
+                _ => {
+                    return Err(format!(
+                        "Unknown derived channel option '{option}'. Expected name=TRACK, \
+                         offset=N|mean or highpass=HZ."
+                    ))
+                }
+            }
+        }
+        Ok(spec)
+    }
+}
+
+/// Describes how a derived channel was computed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Derivation {
+    /// Track the channel was computed from.
+    pub source: String,
+    pub operation: DerivedOperation,
+    /// Unit label of the derived channel.
+    pub units: String,
+}
+
+/// A channel computed from an exported track.
+pub struct DerivedChannel {
+    pub name: String,
+    pub derivation: Derivation,
+    pub data: ChannelData,
+    /// Ordinate axis with the UFF data type of the derived quantity.
+    pub ordinate_axis: AxisSpec,
+}
+
+impl DerivedChannelSpec {
+    /// Name of the derived track for a source channel in `source_units`.
+    pub fn track_name(&self, source_units: &str) -> String {
+        self.name.clone().unwrap_or_else(|| {
+            let acceleration = is_acceleration(source_units);
+            format!("{}_{}", self.source, self.operation.suffix(acceleration))
+        })
+    }
+
+    /// Derive the channel from `source`, the exported data of the source track.
+    ///
+    /// Acceleration in a known unit (`g`, `m/s^2`, `mm/s^2`) is converted to m/s^2 first, so
+    /// that integrals come out in m/s and m. Displacement in `m` or `mm` is converted to m, so
+    /// that its derivative is a velocity in m/s. Other units are labelled with `*s` or `/s`.
+    pub fn derive(&self, source: &ChannelData) -> Result<DerivedChannel> {
+        let name = self.track_name(&source.units);
+        let unit = lookup_unit(&source.units);
+        let acceleration = unit.filter(|unit| unit.quantity == Quantity::Acceleration);
+        let displacement = displacement_to_m(&source.units);
+
+        let factor = match (acceleration, displacement, self.operation) {
+            (Some(unit), _, _) => unit.to_si,
+            (None, Some(to_m), DerivedOperation::Differentiate) => to_m,
+            _ => 1.0,
+        };
+        let mut samples: Vec<f64> = source.time_series.iter().map(|x| x * factor).collect();
+
+        match self.offset {
+            Some(OffsetRemoval::Leading(count)) => remove_offset(&mut samples, count),
+            Some(OffsetRemoval::Mean) => remove_offset(&mut samples, source.time_series.len()),
+            None => {}
+        }
+
+        let dt = 1.0 / source.sample_rate;
+        let drift_control = self.high_pass.map(|cutoff| FilterSpec {
+            order: 2,
+            ..FilterSpec::new(FilterResponse::HighPass { cutoff })
+        });
+        let step = |samples: &mut Vec<f64>, operation: fn(&mut [f64], f64)| -> Result<()> {
+            operation(samples, dt);
+            if let Some(filter) = &drift_control {
+                filter
+                    .apply(samples, source.sample_rate)
+                    .with_context(|| format!("Failed to apply drift control to '{name}'"))?;
+            }
+            Ok(())
+        };
+        match self.operation {
+            DerivedOperation::Integrate => step(&mut samples, integrate)?,
+            DerivedOperation::DoubleIntegrate => {
+                step(&mut samples, integrate)?;
+                step(&mut samples, integrate)?;
+            }
+            DerivedOperation::Differentiate => step(&mut samples, differentiate)?,
+        }
+
+        let (units, spec_data_type) = match (acceleration, self.operation) {
+            (Some(_), DerivedOperation::Integrate) => ("m/s".to_string(), DATA_TYPE_VELOCITY),
+            (Some(_), DerivedOperation::DoubleIntegrate) => {
+                ("m".to_string(), DATA_TYPE_DISPLACEMENT)
+            }
+            (Some(_), DerivedOperation::Differentiate) => ("m/s^3".to_string(), DATA_TYPE_GENERAL),
+            (None, DerivedOperation::Integrate) => {
+                (format!("{}*s", source.units), DATA_TYPE_GENERAL)
+            }
+            (None, DerivedOperation::DoubleIntegrate) => {
+                (format!("{}*s^2", source.units), DATA_TYPE_GENERAL)
+            }
+            (None, DerivedOperation::Differentiate) if source.units == "m/s" => {
+                ("m/s^2".to_string(), DATA_TYPE_ACCELERATION)
+            }
+            (None, DerivedOperation::Differentiate) if displacement.is_some() => {
+                ("m/s".to_string(), DATA_TYPE_VELOCITY)
+            }
+            (None, DerivedOperation::Differentiate) => {
+                (format!("{}/s", source.units), DATA_TYPE_GENERAL)
+            }
+        };
+
+        if samples.iter().any(|value| !value.is_finite()) {
+            return Err(anyhow!(
+                "Derived channel '{name}' contains non-finite values."
+            ));
+        }
+
+        Ok(DerivedChannel {
+            ordinate_axis: AxisSpec {
+                spec_data_type,
+                label: name.clone(),
+                units: units.clone(),
+            },
+            name,
+            derivation: Derivation {
+                source: self.source.clone(),
+                operation: self.operation,
+                units: units.clone(),
+            },
+            data: ChannelData {
+                time_series: samples,
+                sample_rate: source.sample_rate,
+                units,
+            },
+        })
+    }
+}
+
+fn is_acceleration(units: &str) -> bool {
+    lookup_unit(units).is_some_and(|unit| unit.quantity == Quantity::Acceleration)
+}
+
+/// Factor converting a displacement in `units` to m.
+fn displacement_to_m(units: &str) -> Option<f64> {
+    match units.trim() {
+        "m" => Some(1.0),
+        "mm" => Some(1e-3),
+        _ => None,
+    }
+}
+
+fn remove_offset(samples: &mut [f64], count: usize) {
+    let count = count.min(samples.len());
+    if count == 0 {
+        return;
+    }
+    let offset = samples[..count].iter().sum::<f64>() / count as f64;
+    for value in samples {
+        *value -= offset;
+    }
+}
+
+/// Cumulative trapezoidal integral starting at zero.
+fn integrate(samples: &mut [f64], dt: f64) {
+    let mut previous = match samples.first_mut() {
+        Some(first) => std::mem::replace(first, 0.0),
+        None => return,
+    };
+    let mut sum = 0.0;
+    for value in samples.iter_mut().skip(1) {
+        let current = *value;
+        sum += 0.5 * (previous + current) * dt;
+        previous = current;
+        *value = sum;
+    }
+}
+
+/// Central differences, with one-sided differences at the ends.
+fn differentiate(samples: &mut [f64], dt: f64) {
+    let len = samples.len();
+    if len < 2 {
+        samples.iter_mut().for_each(|value| *value = 0.0);
+        return;
+    }
+    let original = samples.to_vec();
+    samples[0] = (original[1] - original[0]) / dt;
+    samples[len - 1] = (original[len - 1] - original[len - 2]) / dt;
+    for index in 1..len - 1 {
+        samples[index] = (original[index + 1] - original[index - 1]) / (2.0 * dt);
+    }
+}
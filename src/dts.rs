@@ -4,11 +4,12 @@ use encoding_rs::{Encoding, UTF_16BE, UTF_16LE};
 use natord::compare;
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
+use serde::Serialize;
 use std::fs::{self, File};
 use std::io::{BufReader, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AnalogInputChannel {
     pub name: String,
     pub description: String,
@@ -22,27 +23,125 @@ pub struct AnalogInputChannel {
     pub zero_method: ZeroMethod,
     pub eu: String,
     pub display_order: u32,
+    pub shunt_enabled: bool,
+    pub target_shunt_deflection_mv: f64,
+    pub measured_shunt_deflection_mv: f64,
+    pub cal_signal_enabled: bool,
+    pub target_cal_signal_mv: f64,
+    pub measured_cal_signal_mv: f64,
+    /// Serial number of the recorder module holding the channel, or `Module <number>` when the
+    /// module has no serial number. Empty for channels outside a module.
+    pub module: String,
+}
+
+/// Test-level information taken from the root element of the `.dts` file.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TestInfo {
+    /// Test identifier (`Id` attribute).
+    pub id: String,
+    /// Free-form test description (`Description` attribute).
+    pub description: String,
+    /// Test date as written by the DTS software. Empty when the export carries no usable date.
+    pub date: String,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum ZeroMethod {
     UsePreCalZero,
     AverageOverTime,
     None,
 }
 
+impl ZeroMethod {
+    /// Value of the `ZeroMethod` attribute in the `.dts` file.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ZeroMethod::UsePreCalZero => "UsePreCalZero",
+            ZeroMethod::AverageOverTime => "AverageOverTime",
+            ZeroMethod::None => "None",
+        }
+    }
+}
+
 // --- Structs for data from .chn files ---
 #[derive(Debug, Clone)]
 struct ChnHeader {
     channel_start: u64,
     npts: u64,
+    bits_per_sample: u32,
     sample_rate: f64,
+    trigger_sample_number: i64,
     pre_test_zero_level_adc: i32,
+    pre_test_diagnostics_level_adc: i32,
+    pre_test_noise_percent: f64,
+    post_test_zero_level_adc: i32,
+    post_test_diagnostics_level_adc: i32,
     data_zero_level_adc: i32,
     scale_factor_mv: f64,
     scale_factor_eu: f64,
 }
 
+/// Unscaled ADC samples of a single channel, as stored in its `.chn` file.
+pub struct RawTrack {
+    pub adc: Vec<i16>,
+    /// Resolution of the recorder, in bits. The ADC limits are `±2^(bits - 1)`.
+    pub bits_per_sample: u32,
+    /// Pre-test noise measured by the recorder, in percent of full scale.
+    pub pre_test_noise_percent: f64,
+}
+
+impl RawTrack {
+    /// Lowest and highest ADC values the recorder can produce.
+    pub fn adc_limits(&self) -> (i16, i16) {
+        let bits = self.bits_per_sample.clamp(2, 16);
+        let half = 1i32 << (bits - 1);
+        (-half as i16, (half - 1) as i16)
+    }
+}
+
+/// Zero and diagnostics levels recorded in the `.chn` header of a channel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelLevels {
+    pub pre_test_zero_adc: i32,
+    /// Shunt or calibration signal level measured before the test.
+    pub pre_test_diagnostics_adc: i32,
+    pub post_test_zero_adc: i32,
+    /// Shunt or calibration signal level measured after the test. Zero when it was not measured.
+    pub post_test_diagnostics_adc: i32,
+    /// Pre-test noise, in percent of full scale.
+    pub pre_test_noise_percent: f64,
+    pub bits_per_sample: u32,
+    /// Sensor output per ADC count, in mV.
+    pub mv_per_count: f64,
+    /// Engineering units per ADC count, including inversion and excitation.
+    pub eu_per_count: f64,
+}
+
+/// Conversion from ADC counts to engineering units: `counts * eu_per_count + offset`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdcScaling {
+    /// Engineering units per ADC count, including inversion and excitation.
+    pub eu_per_count: f64,
+    /// Engineering value of zero counts, from the zero method and the initial EU.
+    pub offset: f64,
+}
+
+/// Values [`DtsReader::adc_scaling`] derives the [`AdcScaling`] of a track from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScalingParameters {
+    /// Sensor output per ADC count, in mV, before inversion.
+    pub scale_factor_mv: f64,
+    /// Sensor output per engineering unit, in mV (per volt of excitation).
+    pub scale_factor_eu: f64,
+    /// Excitation the sensitivity is proportional to, or 1 for self-generating sensors.
+    pub excitation_voltage: f64,
+    pub inverted: bool,
+    pub zero_method: ZeroMethod,
+    /// ADC level the zero method subtracts. Zero for [`ZeroMethod::None`].
+    pub zero_level_adc: i32,
+    pub initial_eu: f64,
+}
+
 /// Holds all processed data for a single channel, ready for writing.
 pub struct ChannelData {
     pub time_series: Vec<f64>,
@@ -64,8 +163,10 @@ pub struct TrackMetadata {
 /// It opens all files in a test folder and parses their metadata.
 pub struct DtsReader {
     // Metadata is stored per-channel, in the correct, sorted order.
+    dts_file: PathBuf,
     chn_files: Vec<PathBuf>,
     xml_metadata: Vec<(AnalogInputChannel, f64)>, // (ChannelInfo, StartRecordSampleNumber)
+    test_info: TestInfo,
     chn_headers: Vec<ChnHeader>,
     min_npts: u64,
 }
@@ -78,7 +179,7 @@ impl DtsReader {
 
         // 1. Find and parse the .dts XML file
         let dts_file_path = find_file_by_extension(base_path, "dts")?;
-        let mut all_channels = parse_dts_metadata(&dts_file_path)?;
+        let (test_info, mut all_channels) = parse_dts_metadata(&dts_file_path)?;
 
         // Sort channels by their absolute display order
         all_channels.sort_by_key(|(ch, _)| ch.display_order);
@@ -120,8 +221,10 @@ impl DtsReader {
         }
 
         Ok(DtsReader {
+            dts_file: dts_file_path,
             chn_files,
             xml_metadata: all_channels,
+            test_info,
             chn_headers,
             min_npts,
         })
@@ -141,14 +244,22 @@ impl DtsReader {
         reader.seek(SeekFrom::Start(8))?;
         let channel_start = reader.read_u64::<LittleEndian>()?;
         let npts = reader.read_u64::<LittleEndian>()?;
+        let bits_per_sample = reader.read_u32::<LittleEndian>()?;
         reader.seek(SeekFrom::Start(32))?;
         let sample_rate = reader.read_f64::<LittleEndian>()?;
         let num_triggers = reader.read_u16::<LittleEndian>()?;
-        let _trigger_sample_number = reader.read_i64::<LittleEndian>()?;
+        let trigger_sample_number = reader.read_i64::<LittleEndian>()?;
 
         let n = num_triggers as u64 * 8;
         reader.seek(SeekFrom::Start(n + 42))?;
         let pre_test_zero_level_adc = reader.read_i32::<LittleEndian>()?;
+        // `n + 46` holds the removed ADC offset, which is not used.
+        reader.seek(SeekFrom::Start(n + 50))?;
+        let pre_test_diagnostics_level_adc = reader.read_i32::<LittleEndian>()?;
+        reader.seek(SeekFrom::Start(n + 54))?;
+        let pre_test_noise_percent = reader.read_f64::<LittleEndian>()?;
+        let post_test_zero_level_adc = reader.read_i32::<LittleEndian>()?;
+        let post_test_diagnostics_level_adc = reader.read_i32::<LittleEndian>()?;
         reader.seek(SeekFrom::Start(n + 70))?;
         let data_zero_level_adc = reader.read_i32::<LittleEndian>()?;
         let scale_factor_mv = reader.read_f64::<LittleEndian>()?;
@@ -157,8 +268,14 @@ impl DtsReader {
         Ok(ChnHeader {
             channel_start,
             npts,
+            bits_per_sample,
             sample_rate,
+            trigger_sample_number,
             pre_test_zero_level_adc,
+            pre_test_diagnostics_level_adc,
+            pre_test_noise_percent,
+            post_test_zero_level_adc,
+            post_test_diagnostics_level_adc,
             data_zero_level_adc,
             scale_factor_mv,
             scale_factor_eu,
@@ -167,11 +284,16 @@ impl DtsReader {
 
     /// Reads and processes the data for a single track.
     pub fn read_track(&self, track_index: usize) -> Result<ChannelData> {
+        let raw = self.read_track_raw(track_index)?;
+        Ok(self.scale_track(track_index, raw))
+    }
+
+    /// Reads the unscaled ADC samples of a single track.
+    pub fn read_track_raw(&self, track_index: usize) -> Result<RawTrack> {
         if track_index >= self.channel_count() {
             return Err(anyhow!("Track index {} is out of bounds.", track_index));
         }
 
-        let (xml_meta, _start_rec_sample) = &self.xml_metadata[track_index];
         let chn_header = &self.chn_headers[track_index];
         let chn_path = &self.chn_files[track_index];
 
@@ -184,19 +306,48 @@ impl DtsReader {
         let mut reader = BufReader::with_capacity(Self::READ_BUFFER_CAPACITY, file);
         reader.read_i16_into::<LittleEndian>(&mut adc_data)?;
 
+        Ok(RawTrack {
+            adc: adc_data,
+            bits_per_sample: chn_header.bits_per_sample,
+            pre_test_noise_percent: chn_header.pre_test_noise_percent,
+        })
+    }
+
+    /// Converts the raw ADC samples of a track, read with [`Self::read_track_raw`], into
+    /// engineering units.
+    pub fn scale_track(&self, track_index: usize, raw: RawTrack) -> ChannelData {
+        let (xml_meta, _start_rec_sample) = &self.xml_metadata[track_index];
+        let chn_header = &self.chn_headers[track_index];
+        let AdcScaling {
+            eu_per_count: scale,
+            offset,
+        } = self.adc_scaling(track_index);
+
+        let time_series: Vec<f64> = raw
+            .adc
+            .into_iter()
+            .map(|adc_val| (f64::from(adc_val) * scale) + offset)
+            .collect();
+
+        ChannelData {
+            time_series,
+            sample_rate: chn_header.sample_rate,
+            units: xml_meta.eu.clone(),
+        }
+    }
+
+    /// Linear mapping applied by [`Self::scale_track`] to the ADC counts of a track.
+    pub fn adc_scaling(&self, track_index: usize) -> AdcScaling {
+        let (xml_meta, _start_rec_sample) = &self.xml_metadata[track_index];
+        let chn_header = &self.chn_headers[track_index];
+
         // --- Perform scaling and offset calculations ---
         let mut scale_factor_mv = chn_header.scale_factor_mv;
         if xml_meta.is_inverted {
             scale_factor_mv = -scale_factor_mv;
         }
 
-        let excitation = if !xml_meta.proportional_to_excitation {
-            1.0
-        } else if xml_meta.factory_excitation_voltage.is_nan() {
-            xml_meta.measured_excitation_voltage
-        } else {
-            xml_meta.factory_excitation_voltage
-        };
+        let excitation = excitation_voltage(xml_meta);
 
         let offset = match xml_meta.zero_method {
             ZeroMethod::UsePreCalZero => {
@@ -214,24 +365,89 @@ impl DtsReader {
             ZeroMethod::None => xml_meta.initial_eu,
         };
 
-        let scale = scale_factor_mv / chn_header.scale_factor_eu / excitation;
-
-        let time_series: Vec<f64> = adc_data
-            .into_iter()
-            .map(|adc_val| (f64::from(adc_val) * scale) + offset)
-            .collect();
+        AdcScaling {
+            eu_per_count: eu_per_count(xml_meta, chn_header),
+            offset,
+        }
+    }
 
-        Ok(ChannelData {
-            time_series,
-            sample_rate: chn_header.sample_rate,
-            units: xml_meta.eu.clone(),
+    /// Header values and settings behind the [`AdcScaling`] of a track.
+    pub fn scaling_parameters(&self, track_index: usize) -> Option<ScalingParameters> {
+        let (xml_meta, _start_rec_sample) = self.xml_metadata.get(track_index)?;
+        let chn_header = self.chn_headers.get(track_index)?;
+        let zero_level_adc = match xml_meta.zero_method {
+            ZeroMethod::UsePreCalZero => chn_header.pre_test_zero_level_adc,
+            ZeroMethod::AverageOverTime => chn_header.data_zero_level_adc,
+            ZeroMethod::None => 0,
+        };
+        Some(ScalingParameters {
+            scale_factor_mv: chn_header.scale_factor_mv,
+            scale_factor_eu: chn_header.scale_factor_eu,
+            excitation_voltage: excitation_voltage(xml_meta),
+            inverted: xml_meta.is_inverted,
+            zero_method: xml_meta.zero_method,
+            zero_level_adc,
+            initial_eu: xml_meta.initial_eu,
         })
     }
 
+    /// Sample rate of a track, in Hz.
+    pub fn sample_rate(&self, track_index: usize) -> Option<f64> {
+        self.chn_headers
+            .get(track_index)
+            .map(|header| header.sample_rate)
+    }
+
+    /// Time of the first recorded sample of a track relative to the trigger, in seconds:
+    /// `(StartRecordSampleNumber - trigger sample number) / sample rate`, as in `DTS.read`.
+    pub fn time_of_first_sample(&self, track_index: usize) -> Option<f64> {
+        let (_, start_rec_sample) = self.xml_metadata.get(track_index)?;
+        let header = self.chn_headers.get(track_index)?;
+        Some((start_rec_sample - header.trigger_sample_number as f64) / header.sample_rate)
+    }
+
     pub fn channel_count(&self) -> usize {
         self.chn_files.len()
     }
 
+    /// Path of the `.dts` file the metadata was read from.
+    pub fn dts_path(&self) -> &Path {
+        &self.dts_file
+    }
+
+    /// Path of the `.chn` file of a track.
+    pub fn chn_path(&self, track_index: usize) -> Option<&Path> {
+        self.chn_files.get(track_index).map(PathBuf::as_path)
+    }
+
+    /// Test-level information from the `.dts` file.
+    pub fn test_info(&self) -> &TestInfo {
+        &self.test_info
+    }
+
+    /// Full XML metadata of a channel, in track order.
+    pub fn channel_info(&self, track_index: usize) -> Option<&AnalogInputChannel> {
+        self.xml_metadata
+            .get(track_index)
+            .map(|(channel, _)| channel)
+    }
+
+    /// Zero and diagnostics levels of a channel, in track order.
+    pub fn channel_levels(&self, track_index: usize) -> Option<ChannelLevels> {
+        let (xml_meta, _start_rec_sample) = self.xml_metadata.get(track_index)?;
+        let chn_header = self.chn_headers.get(track_index)?;
+        Some(ChannelLevels {
+            pre_test_zero_adc: chn_header.pre_test_zero_level_adc,
+            pre_test_diagnostics_adc: chn_header.pre_test_diagnostics_level_adc,
+            post_test_zero_adc: chn_header.post_test_zero_level_adc,
+            post_test_diagnostics_adc: chn_header.post_test_diagnostics_level_adc,
+            pre_test_noise_percent: chn_header.pre_test_noise_percent,
+            bits_per_sample: chn_header.bits_per_sample,
+            mv_per_count: chn_header.scale_factor_mv,
+            eu_per_count: eu_per_count(xml_meta, chn_header),
+        })
+    }
+
     pub fn track_metadata(&self) -> Vec<TrackMetadata> {
         self.xml_metadata
             .iter()
@@ -248,6 +464,25 @@ impl DtsReader {
     }
 }
 
+/// Excitation the sensitivity is proportional to, or 1 for self-generating sensors.
+pub(crate) fn excitation_voltage(channel: &AnalogInputChannel) -> f64 {
+    if !channel.proportional_to_excitation {
+        1.0
+    } else if channel.factory_excitation_voltage.is_nan() {
+        channel.measured_excitation_voltage
+    } else {
+        channel.factory_excitation_voltage
+    }
+}
+
+fn eu_per_count(channel: &AnalogInputChannel, header: &ChnHeader) -> f64 {
+    let mut scale_factor_mv = header.scale_factor_mv;
+    if channel.is_inverted {
+        scale_factor_mv = -scale_factor_mv;
+    }
+    scale_factor_mv / header.scale_factor_eu / excitation_voltage(channel)
+}
+
 /// Helper to find the first file with a given extension in a directory.
 fn find_file_by_extension(dir: &Path, extension: &str) -> Result<PathBuf> {
     fs::read_dir(dir)?
@@ -257,7 +492,7 @@ fn find_file_by_extension(dir: &Path, extension: &str) -> Result<PathBuf> {
         .ok_or_else(|| anyhow!("No '.{}' file found in directory {:?}", extension, dir))
 }
 
-fn parse_dts_metadata(path: &Path) -> Result<Vec<(AnalogInputChannel, f64)>> {
+fn parse_dts_metadata(path: &Path) -> Result<(TestInfo, Vec<(AnalogInputChannel, f64)>)> {
     let mut xml = read_dts_xml(path)?;
     sanitize_duplicate_xml_headers(&mut xml);
 
@@ -265,14 +500,22 @@ fn parse_dts_metadata(path: &Path) -> Result<Vec<(AnalogInputChannel, f64)>> {
     reader.trim_text(true);
 
     let mut buf = Vec::new();
-    let mut module_stack: Vec<f64> = Vec::new();
+    // Start sample and name of every open module.
+    let mut module_stack: Vec<(f64, String)> = Vec::new();
     let mut channels = Vec::new();
+    let mut test_info: Option<TestInfo> = None;
+    let mut first_channel_start: Option<String> = None;
 
     loop {
         match reader.read_event_into(&mut buf)? {
+            Event::Start(ref e) if test_info.is_none() => {
+                test_info = Some(collect_test_info(e)?);
+            }
             Event::Start(ref e) => match e.name().as_ref() {
                 b"Module" => {
                     let mut start_sample = 0.0;
+                    let mut serial_number = String::new();
+                    let mut number = String::new();
                     for attr in e.attributes().with_checks(false) {
                         let attr = attr?;
                         let key = attr.key.as_ref();
@@ -281,26 +524,43 @@ fn parse_dts_metadata(path: &Path) -> Result<Vec<(AnalogInputChannel, f64)>> {
                                 "Failed to decode StartRecordSampleNumber attribute"
                             })?;
                             start_sample = parse_f64(value.as_ref());
+                        } else if key == b"SerialNumber" {
+                            serial_number = attr
+                                .unescape_value()
+                                .with_context(|| "Failed to decode module SerialNumber attribute")?
+                                .trim()
+                                .to_string();
+                        } else if key == b"Number" {
+                            number = attr
+                                .unescape_value()
+                                .with_context(|| "Failed to decode module Number attribute")?
+                                .trim()
+                                .to_string();
                         }
                     }
-                    module_stack.push(start_sample);
+                    let module = match (serial_number.is_empty(), number.is_empty()) {
+                        (false, _) => serial_number,
+                        (true, false) => format!("Module {number}"),
+                        (true, true) => String::new(),
+                    };
+                    module_stack.push((start_sample, module));
                 }
                 b"AnalogInputChanel" => {
-                    let start_sample = *module_stack.last().unwrap_or(&0.0);
-                    collect_channel(e, start_sample, &mut channels)?;
+                    collect_channel(e, module_stack.last(), &mut channels)?;
+                    if first_channel_start.is_none() {
+                        first_channel_start = channel_start_time(e)?;
+                    }
                 }
                 _ => {}
             },
-            Event::Empty(ref e) => {
-                if e.name().as_ref() == b"AnalogInputChanel" {
-                    let start_sample = *module_stack.last().unwrap_or(&0.0);
-                    collect_channel(e, start_sample, &mut channels)?;
+            Event::Empty(ref e) if e.name().as_ref() == b"AnalogInputChanel" => {
+                collect_channel(e, module_stack.last(), &mut channels)?;
+                if first_channel_start.is_none() {
+                    first_channel_start = channel_start_time(e)?;
                 }
             }
-            Event::End(ref e) => {
-                if e.name().as_ref() == b"Module" {
-                    module_stack.pop();
-                }
+            Event::End(ref e) if e.name().as_ref() == b"Module" => {
+                module_stack.pop();
             }
             Event::Eof => break,
             _ => {}
@@ -309,14 +569,55 @@ fn parse_dts_metadata(path: &Path) -> Result<Vec<(AnalogInputChannel, f64)>> {
         buf.clear();
     }
 
-    Ok(channels)
+    let mut test_info = test_info.unwrap_or_default();
+    if test_info.date.is_empty() {
+        test_info.date = first_channel_start.unwrap_or_default();
+    }
+
+    Ok((test_info, channels))
+}
+
+fn collect_test_info(event: &BytesStart) -> Result<TestInfo> {
+    let mut info = TestInfo::default();
+    for attr in event.attributes().with_checks(false) {
+        let attr = attr?;
+        let value = attr
+            .unescape_value()
+            .with_context(|| "Failed to decode test attribute")?;
+        match attr.key.as_ref() {
+            b"Id" => info.id = value.into_owned(),
+            b"Description" => info.description = value.into_owned(),
+            b"Date" | b"TestDate" => info.date = value.into_owned(),
+            _ => {}
+        }
+    }
+    Ok(info)
+}
+
+/// Returns the channel `Start` timestamp, ignoring the `01/01/0001` placeholder written when the
+/// recorder clock was not set.
+fn channel_start_time(event: &BytesStart) -> Result<Option<String>> {
+    for attr in event.attributes().with_checks(false) {
+        let attr = attr?;
+        if attr.key.as_ref() == b"Start" {
+            let value = attr
+                .unescape_value()
+                .with_context(|| "Failed to decode Start attribute")?;
+            let value = value.trim();
+            if !value.is_empty() && !value.starts_with("01/01/0001") {
+                return Ok(Some(value.to_string()));
+            }
+        }
+    }
+    Ok(None)
 }
 
 fn collect_channel(
     event: &BytesStart,
-    start_sample: f64,
+    module: Option<&(f64, String)>,
     channels: &mut Vec<(AnalogInputChannel, f64)>,
 ) -> Result<()> {
+    let (start_sample, module) = module.cloned().unwrap_or_default();
     let mut name = String::new();
     let mut description = String::new();
     let mut sensitivity = f64::NAN;
@@ -329,6 +630,12 @@ fn collect_channel(
     let mut zero_method = ZeroMethod::None;
     let mut eu = String::new();
     let mut display_order = 0u32;
+    let mut shunt_enabled = false;
+    let mut target_shunt_deflection_mv = f64::NAN;
+    let mut measured_shunt_deflection_mv = f64::NAN;
+    let mut cal_signal_enabled = false;
+    let mut target_cal_signal_mv = f64::NAN;
+    let mut measured_cal_signal_mv = f64::NAN;
 
     for attr in event.attributes().with_checks(false) {
         let attr = attr?;
@@ -379,6 +686,24 @@ fn collect_channel(
             b"AbsoluteDisplayOrder" => {
                 display_order = value.as_ref().parse::<u32>().unwrap_or(0);
             }
+            b"ShuntEnabled" => {
+                shunt_enabled = value.as_ref().eq_ignore_ascii_case("True");
+            }
+            b"TargetShuntDeflectionMv" => {
+                target_shunt_deflection_mv = parse_f64(value.as_ref());
+            }
+            b"MeasuredShuntDeflectionMv" => {
+                measured_shunt_deflection_mv = parse_f64(value.as_ref());
+            }
+            b"CalSignalEnabled" => {
+                cal_signal_enabled = value.as_ref().eq_ignore_ascii_case("True");
+            }
+            b"TargetCalSignalMv" => {
+                target_cal_signal_mv = parse_f64(value.as_ref());
+            }
+            b"MeasuredCalSignalMv" => {
+                measured_cal_signal_mv = parse_f64(value.as_ref());
+            }
             _ => {}
         }
     }
@@ -401,6 +726,13 @@ fn collect_channel(
             zero_method,
             eu,
             display_order,
+            shunt_enabled,
+            target_shunt_deflection_mv,
+            measured_shunt_deflection_mv,
+            cal_signal_enabled,
+            target_cal_signal_mv,
+            measured_cal_signal_mv,
+            module,
         },
         start_sample,
     ));
@@ -0,0 +1,39 @@
+use crate::dts::{AdcScaling, AnalogInputChannel, ChannelData};
+use crate::filter::{CfcClass, FilterSpec};
+use anyhow::Result;
+
+/// A time history handed to a [`ChannelWriter`].
+#[derive(Clone, Copy)]
+pub struct ExportChannel<'a> {
+    pub name: &'a str,
+    pub data: &'a ChannelData,
+    /// Time of the first sample, in seconds.
+    pub start_time: f64,
+    /// Time of the first sample relative to the trigger, in seconds, like `timeOfFirstSamples`
+    /// of `DTS.read`.
+    pub trigger_time: f64,
+    /// Metadata of the source channel in the `.dts` file. `None` for expression channels.
+    pub channel: Option<&'a AnalogInputChannel>,
+    /// CFC class applied to the channel or, for derived channels, to their source.
+    pub cfc: Option<CfcClass>,
+    /// Generic IIR filters applied after the CFC filter.
+    pub filters: &'a [FilterSpec],
+    /// Whether the channel was computed from other channels (derived and expression channels).
+    pub computed: bool,
+    /// Scaling of the ADC counts when the samples are the unprocessed, scaled ADC values: no
+    /// filter, resampling or unit conversion was applied. `None` otherwise.
+    pub adc_scaling: Option<AdcScaling>,
+}
+
+/// Writes time histories, one at a time, to an output target other than UFF.
+///
+/// Channels are handed over in output order: exported tracks, then derived channels, then
+/// expression channels. The channel data outlives the writer, so writers that lay out several
+/// channels side by side can keep the channels until [`ChannelWriter::finish`].
+pub trait ChannelWriter<'a> {
+    /// Write one time history.
+    fn write_channel(&mut self, channel: &ExportChannel<'a>) -> Result<()>;
+
+    /// Complete the output once every channel was written.
+    fn finish(&mut self) -> Result<()>;
+}
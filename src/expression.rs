@@ -0,0 +1,439 @@
+use anyhow::{anyhow, Result};
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// Functions available in channel expressions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Function {
+    Sqrt,
+    Abs,
+    Exp,
+    Ln,
+    Log10,
+    Sin,
+    Cos,
+    Min,
+    Max,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "sqrt" => Function::Sqrt,
+            "abs" => Function::Abs,
+            "exp" => Function::Exp,
+            "ln" => Function::Ln,
+            "log10" => Function::Log10,
+            "sin" => Function::Sin,
+            "cos" => Function::Cos,
+            "min" => Function::Min,
+            "max" => Function::Max,
+            _ => return None,
+        })
+    }
+
+    fn is_variadic(&self) -> bool {
+        matches!(self, Function::Min | Function::Max)
+    }
+
+    fn call(&self, arguments: &[f64]) -> f64 {
+        match self {
+            Function::Sqrt => arguments[0].sqrt(),
+            Function::Abs => arguments[0].abs(),
+            Function::Exp => arguments[0].exp(),
+            Function::Ln => arguments[0].ln(),
+            Function::Log10 => arguments[0].log10(),
+            Function::Sin => arguments[0].sin(),
+            Function::Cos => arguments[0].cos(),
+            Function::Min => arguments.iter().copied().fold(f64::INFINITY, f64::min),
+            Function::Max => arguments.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Number(f64),
+    /// Index into [`Expression::tracks`].
+    Track(usize),
+    Negate(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+    Call(Function, Vec<Node>),
+}
+
+impl Node {
+    fn evaluate(&self, values: &[f64]) -> f64 {
+        match self {
+            Node::Number(value) => *value,
+            Node::Track(index) => values[*index],
+            Node::Negate(node) => -node.evaluate(values),
+            Node::Binary(operator, left, right) => {
+                let (left, right) = (left.evaluate(values), right.evaluate(values));
+                match operator {
+                    Operator::Add => left + right,
+                    Operator::Subtract => left - right,
+                    Operator::Multiply => left * right,
+                    Operator::Divide => left / right,
+                    Operator::Power => left.powf(right),
+                }
+            }
+            Node::Call(function, arguments) => {
+                let arguments: Vec<f64> =
+                    arguments.iter().map(|node| node.evaluate(values)).collect();
+                function.call(&arguments)
+            }
+        }
+    }
+}
+
+/// An arithmetic expression over track names, evaluated sample by sample.
+///
+/// Supports numbers, `+ - * / ^`, parentheses and the functions `sqrt`, `abs`, `exp`, `ln`,
+/// `log10`, `sin`, `cos`, `min` and `max` (the last two take any number of arguments). Track
+/// names made of letters, digits, `_` and `.` can be written as is; other names are wrapped in
+/// braces, e.g. `{Head Accel X}`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expression {
+    source: String,
+    tracks: Vec<String>,
+    root: Node,
+}
+
+impl Expression {
+    /// Track names referenced by the expression, in order of first use.
+    pub fn tracks(&self) -> &[String] {
+        &self.tracks
+    }
+
+    /// Evaluate the expression for every sample. `inputs` maps each referenced track to its
+    /// samples; the result has the length of the shortest input.
+    pub fn evaluate(&self, inputs: &BTreeMap<&str, &[f64]>) -> Result<Vec<f64>> {
+        let columns = self
+            .tracks
+            .iter()
+            .map(|track| {
+                inputs
+                    .get(track.as_str())
+                    .copied()
+                    .ok_or_else(|| anyhow!("Expression track '{track}' is not available."))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let len = columns.iter().map(|column| column.len()).min().unwrap_or(0);
+
+        let mut values = vec![0.0; columns.len()];
+        Ok((0..len)
+            .map(|index| {
+                for (value, column) in values.iter_mut().zip(&columns) {
+                    *value = column[index];
+                }
+                self.root.evaluate(&values)
+            })
+            .collect())
+    }
+}
+
+impl std::fmt::Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl std::str::FromStr for Expression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            position: 0,
+            tracks: Vec::new(),
+        };
+        let root = parser.expression()?;
+        if let Some(token) = parser.tokens.get(parser.position) {
+            return Err(format!("Unexpected {token} in expression '{s}'."));
+        }
+        if parser.tracks.is_empty() {
+            return Err(format!("Expression '{s}' does not reference any track."));
+        }
+        Ok(Expression {
+            source: s.trim().to_string(),
+            tracks: parser.tracks,
+            root,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    /// A braced track name, never treated as a function.
+    Track(String),
+    Symbol(char),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(value) => write!(f, "number {value}"),
+            Token::Name(name) => write!(f, "name '{name}'"),
+            Token::Track(name) => write!(f, "track '{{{name}}}'"),
+            Token::Symbol(symbol) => write!(f, "'{symbol}'"),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut end = start;
+            while let Some(&(index, c)) = chars.peek() {
+                let exponent_sign =
+                    (c == '+' || c == '-') && matches!(s[..index].chars().last(), Some('e' | 'E'));
+                if c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || exponent_sign {
+                    end = index + c.len_utf8();
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            let text = &s[start..end];
+            let value = text
+                .parse::<f64>()
+                .map_err(|err| format!("Failed to parse number '{text}': {err}"))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(index, c)) = chars.peek() {
+                if c.is_alphanumeric() || c == '_' || c == '.' {
+                    end = index + c.len_utf8();
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push(Token::Name(s[start..end].to_string()));
+        } else if c == '{' {
+            chars.next();
+            let mut name = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '}')) => break,
+                    Some((_, c)) => name.push(c),
+                    None => return Err(format!("Unclosed '{{' in expression '{s}'.")),
+                }
+            }
+            if name.trim().is_empty() {
+                return Err(format!("Empty track name in expression '{s}'."));
+            }
+            tokens.push(Token::Track(name.trim().to_string()));
+        } else if "+-*/^(),".contains(c) {
+            tokens.push(Token::Symbol(c));
+            chars.next();
+        } else {
+            return Err(format!("Unexpected character '{c}' in expression '{s}'."));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    tracks: Vec<String>,
+}
+
+impl Parser {
+    fn peek_symbol(&self, symbol: char) -> bool {
+        self.tokens.get(self.position) == Some(&Token::Symbol(symbol))
+    }
+
+    fn expect_symbol(&mut self, symbol: char) -> Result<(), String> {
+        if self.peek_symbol(symbol) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(match self.tokens.get(self.position) {
+                Some(token) => format!("Expected '{symbol}' but found {token}."),
+                None => format!("Expected '{symbol}' at the end of the expression."),
+            })
+        }
+    }
+
+    fn track(&mut self, name: String) -> Node {
+        let index = match self.tracks.iter().position(|track| *track == name) {
+            Some(index) => index,
+            None => {
+                self.tracks.push(name);
+                self.tracks.len() - 1
+            }
+        };
+        Node::Track(index)
+    }
+
+    /// expression := term (('+' | '-') term)*
+    fn expression(&mut self) -> Result<Node, String> {
+        let mut node = self.term()?;
+        loop {
+            let operator = if self.peek_symbol('+') {
+                Operator::Add
+            } else if self.peek_symbol('-') {
+                Operator::Subtract
+            } else {
+                return Ok(node);
+            };
+            self.position += 1;
+            node = Node::Binary(operator, Box::new(node), Box::new(self.term()?));
+        }
+    }
+
+    /// term := unary (('*' | '/') unary)*
+    fn term(&mut self) -> Result<Node, String> {
+        let mut node = self.unary()?;
+        loop {
+            let operator = if self.peek_symbol('*') {
+                Operator::Multiply
+            } else if self.peek_symbol('/') {
+                Operator::Divide
+            } else {
+                return Ok(node);
+            };
+            self.position += 1;
+            node = Node::Binary(operator, Box::new(node), Box::new(self.unary()?));
+        }
+    }
+
+    /// unary := '-' unary | '+' unary | power
+    fn unary(&mut self) -> Result<Node, String> {
+        if self.peek_symbol('-') {
+            self.position += 1;
+            return Ok(Node::Negate(Box::new(self.unary()?)));
+        }
+        if self.peek_symbol('+') {
+            self.position += 1;
+            return self.unary();
+        }
+        self.power()
+    }
+
+    /// power := primary ('^' unary)?, right associative so that `-x^2` is `-(x^2)`.
+    fn power(&mut self) -> Result<Node, String> {
+        let base = self.primary()?;
+        if self.peek_symbol('^') {
+            self.position += 1;
+            let exponent = self.unary()?;
+            return Ok(Node::Binary(
+                Operator::Power,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    /// primary := number | track | function '(' arguments ')' | '(' expression ')'
+    fn primary(&mut self) -> Result<Node, String> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or_else(|| "Unexpected end of the expression.".to_string())?;
+        self.position += 1;
+        match token {
+            Token::Number(value) => Ok(Node::Number(value)),
+            Token::Track(name) => Ok(self.track(name)),
+            Token::Name(name) if self.peek_symbol('(') => {
+                let function = Function::from_name(&name)
+                    .ok_or_else(|| format!("Unknown function '{name}'."))?;
+                self.position += 1;
+                let mut arguments = vec![self.expression()?];
+                while self.peek_symbol(',') {
+                    self.position += 1;
+                    arguments.push(self.expression()?);
+                }
+                self.expect_symbol(')')?;
+                if !function.is_variadic() && arguments.len() != 1 {
+                    return Err(format!(
+                        "Function '{name}' takes one argument, got {}.",
+                        arguments.len()
+                    ));
+                }
+                Ok(Node::Call(function, arguments))
+            }
+            Token::Name(name) => Ok(self.track(name)),
+            Token::Symbol('(') => {
+                let node = self.expression()?;
+                self.expect_symbol(')')?;
+                Ok(node)
+            }
+            token => Err(format!("Unexpected {token} in the expression.")),
+        }
+    }
+}
+
+/// A channel computed from an expression, as `name[units]=expression`.
+///
+/// Examples: `HeadR[g]=sqrt(HX^2+HY^2+HZ^2)`, `Fsum=LC1+LC2+LC3`, `A1kN[kN]=A1*0.001`. The units
+/// default to those of the first referenced track.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExpressionChannel {
+    pub name: String,
+    pub units: Option<String>,
+    pub expression: Expression,
+}
+
+impl std::fmt::Display for ExpressionChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)?;
+        if let Some(units) = &self.units {
+            write!(f, "[{units}]")?;
+        }
+        write!(f, "={}", self.expression)
+    }
+}
+
+impl Serialize for ExpressionChannel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl std::str::FromStr for ExpressionChannel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (target, expression) = s.split_once('=').ok_or_else(|| {
+            "Expression channel must be provided in the format name[units]=expression".to_string()
+        })?;
+        let target = target.trim();
+        let (name, units) = match target.strip_suffix(']') {
+            Some(rest) => {
+                let (name, units) = rest
+                    .split_once('[')
+                    .ok_or_else(|| format!("Missing '[' in expression channel name '{target}'"))?;
+                (name.trim(), Some(units.trim().to_string()))
+            }
+            None => (target, None),
+        };
+        if name.is_empty() {
+            return Err("Expression channel is missing a name".to_string());
+        }
+        Ok(ExpressionChannel {
+            name: name.to_string(),
+            units: units.filter(|units| !units.is_empty()),
+            expression: expression.parse()?,
+        })
+    }
+}
@@ -0,0 +1,904 @@
+use crate::dts::ChannelData;
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use num_complex::Complex64;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::f64::consts::{PI, SQRT_2};
+use std::fs;
+use std::path::Path;
+
+/// Channel frequency classes defined by SAE J211-1 / ISO 6487.
+#[derive(ValueEnum, Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum CfcClass {
+    #[value(name = "cfc60", alias = "60")]
+    Cfc60,
+    #[value(name = "cfc180", alias = "180")]
+    Cfc180,
+    #[value(name = "cfc600", alias = "600")]
+    Cfc600,
+    #[value(name = "cfc1000", alias = "1000")]
+    Cfc1000,
+}
+
+impl CfcClass {
+    /// Returns the class as written in reports, e.g. `CFC180`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CfcClass::Cfc60 => "CFC60",
+            CfcClass::Cfc180 => "CFC180",
+            CfcClass::Cfc600 => "CFC600",
+            CfcClass::Cfc1000 => "CFC1000",
+        }
+    }
+
+    /// Channel frequency class value used in the J211 filter equations.
+    pub fn value(&self) -> f64 {
+        match self {
+            CfcClass::Cfc60 => 60.0,
+            CfcClass::Cfc180 => 180.0,
+            CfcClass::Cfc600 => 600.0,
+            CfcClass::Cfc1000 => 1000.0,
+        }
+    }
+
+    /// Nominal -3 dB frequency of the class, in Hz.
+    pub fn cutoff_frequency(&self) -> f64 {
+        match self {
+            CfcClass::Cfc60 => 100.0,
+            CfcClass::Cfc180 => 300.0,
+            CfcClass::Cfc600 => 1000.0,
+            CfcClass::Cfc1000 => 1650.0,
+        }
+    }
+
+    /// Filter `samples` in place with the phaseless four-pole Butterworth filter of SAE J211-1
+    /// Appendix C (a two-pole section run forward, then backward).
+    pub fn apply(&self, samples: &mut [f64], sample_rate: f64) -> Result<()> {
+        let wd = 2.0 * PI * self.value() * 2.0775;
+        let half_angle = wd / sample_rate / 2.0;
+        if half_angle >= PI / 2.0 {
+            return Err(anyhow!(
+                "{} needs a sample rate above {:.0} Hz, got {} Hz.",
+                self,
+                self.value() * 2.0775 * 2.0,
+                sample_rate
+            ));
+        }
+
+        let wa = half_angle.tan();
+        let norm = 1.0 + SQRT_2 * wa + wa * wa;
+        let a0 = wa * wa / norm;
+        let coefficients = SecondOrderSection {
+            b: [a0, 2.0 * a0, a0],
+            a: [
+                -2.0 * (wa * wa - 1.0) / norm,
+                (-1.0 + SQRT_2 * wa - wa * wa) / norm,
+            ],
+        };
+
+        coefficients.run(samples.iter_mut());
+        coefficients.run(samples.iter_mut().rev());
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for CfcClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for CfcClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.trim().to_ascii_lowercase();
+        match value.strip_prefix("cfc").unwrap_or(&value).trim() {
+            "60" => Ok(CfcClass::Cfc60),
+            "180" => Ok(CfcClass::Cfc180),
+            "600" => Ok(CfcClass::Cfc600),
+            "1000" => Ok(CfcClass::Cfc1000),
+            _ => Err(format!(
+                "Unsupported CFC class '{}'. Expected 60, 180, 600 or 1000.",
+                s.trim()
+            )),
+        }
+    }
+}
+
+/// Direct form I biquad: `y[n] = b0 x[n] + b1 x[n-1] + b2 x[n-2] + a1 y[n-1] + a2 y[n-2]`.
+#[derive(Clone, Debug, PartialEq)]
+struct SecondOrderSection {
+    b: [f64; 3],
+    a: [f64; 2],
+}
+
+impl SecondOrderSection {
+    /// Gain of the section for a constant input.
+    fn dc_gain(&self) -> f64 {
+        let denominator = 1.0 - self.a[0] - self.a[1];
+        if denominator.abs() < f64::EPSILON {
+            1.0
+        } else {
+            self.b.iter().sum::<f64>() / denominator
+        }
+    }
+
+    /// Complex response of the section at the normalised angular frequency `omega`.
+    fn response(&self, omega: f64) -> Complex64 {
+        let z1 = Complex64::from_polar(1.0, -omega);
+        let z2 = z1 * z1;
+        (self.b[0] + self.b[1] * z1 + self.b[2] * z2) / (1.0 - self.a[0] * z1 - self.a[1] * z2)
+    }
+
+    /// Filter the samples yielded by `samples` in place. The filter state starts in the steady
+    /// state of the first sample so that a DC offset does not produce a start-up transient.
+    fn run<'a>(&self, samples: impl Iterator<Item = &'a mut f64>) {
+        let mut samples = samples.peekable();
+        let Some(first) = samples.peek().map(|value| **value) else {
+            return;
+        };
+        let settled = first * self.dc_gain();
+        let (mut x1, mut x2, mut y1, mut y2) = (first, first, settled, settled);
+        for sample in samples {
+            let x = *sample;
+            let y =
+                self.b[0] * x + self.b[1] * x1 + self.b[2] * x2 + self.a[0] * y1 + self.a[1] * y2;
+            (x2, x1, y2, y1) = (x1, x, y1, y);
+            *sample = y;
+        }
+    }
+}
+
+/// A `track=class` CFC override, e.g. `A1=180`. Use `none` to leave a track unfiltered.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelCfcTarget {
+    pub track_name: String,
+    pub class: Option<CfcClass>,
+}
+
+impl std::str::FromStr for ChannelCfcTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (track_name, class) = s
+            .rsplit_once('=')
+            .ok_or_else(|| "Channel CFC must be provided in the format track=class".to_string())?;
+        let track_name = track_name.trim();
+        if track_name.is_empty() {
+            return Err("Channel CFC target is missing a track name".to_string());
+        }
+        let class = match class.trim().to_ascii_lowercase().as_str() {
+            "none" | "off" => None,
+            _ => Some(class.parse()?),
+        };
+        Ok(ChannelCfcTarget {
+            track_name: track_name.to_string(),
+            class,
+        })
+    }
+}
+
+/// CFC classes requested for the exported channels.
+///
+/// Per-channel classes take precedence over the class applied to every channel.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct CfcFiltering {
+    default_class: Option<CfcClass>,
+    channel_classes: BTreeMap<String, Option<CfcClass>>,
+}
+
+impl CfcFiltering {
+    /// Filter every channel with `class` unless a per-channel class is set.
+    pub fn set_default(&mut self, class: Option<CfcClass>) {
+        self.default_class = class;
+    }
+
+    /// Filter the channel exported as `track_name` with `class` (`None` leaves it unfiltered).
+    pub fn set_channel(&mut self, track_name: impl Into<String>, class: Option<CfcClass>) {
+        self.channel_classes.insert(track_name.into(), class);
+    }
+
+    /// Returns `true` when no channel is filtered.
+    pub fn is_empty(&self) -> bool {
+        self.default_class.is_none() && self.channel_classes.values().all(Option::is_none)
+    }
+
+    /// Iterates over the per-channel classes.
+    pub fn channel_classes(&self) -> impl Iterator<Item = (&str, Option<CfcClass>)> {
+        self.channel_classes
+            .iter()
+            .map(|(name, class)| (name.as_str(), *class))
+    }
+
+    /// Class applied to `track_name`, if any.
+    pub fn class_for(&self, track_name: &str) -> Option<CfcClass> {
+        match self.channel_classes.get(track_name) {
+            Some(class) => *class,
+            None => self.default_class,
+        }
+    }
+
+    /// Filter `data` with the class requested for `track_name`.
+    ///
+    /// Returns the applied class, or `None` when the channel was left as is.
+    pub fn apply(&self, track_name: &str, data: &mut ChannelData) -> Result<Option<CfcClass>> {
+        let Some(class) = self.class_for(track_name) else {
+            return Ok(None);
+        };
+        class.apply(&mut data.time_series, data.sample_rate)?;
+        Ok(Some(class))
+    }
+}
+
+/// Analog prototype family of an IIR filter.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FilterDesign {
+    /// Maximally flat pass band.
+    #[default]
+    Butterworth,
+    /// Maximally flat group delay, normalised to -3 dB at the cutoff.
+    Bessel,
+}
+
+impl FilterDesign {
+    /// Returns the human readable name of the design.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilterDesign::Butterworth => "butterworth",
+            FilterDesign::Bessel => "bessel",
+        }
+    }
+
+    /// Poles of the low-pass prototype with a -3 dB cutoff of 1 rad/s.
+    fn prototype_poles(&self, order: usize) -> Vec<Complex64> {
+        match self {
+            FilterDesign::Butterworth => (0..order)
+                .map(|k| {
+                    let angle = PI * (2 * k + order + 1) as f64 / (2 * order) as f64;
+                    Complex64::from_polar(1.0, angle)
+                })
+                .collect(),
+            FilterDesign::Bessel => bessel_poles(order),
+        }
+    }
+}
+
+impl std::fmt::Display for FilterDesign {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for FilterDesign {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "butterworth" | "butter" => Ok(FilterDesign::Butterworth),
+            "bessel" => Ok(FilterDesign::Bessel),
+            other => Err(format!(
+                "Unsupported filter design '{other}'. Expected 'butterworth' or 'bessel'."
+            )),
+        }
+    }
+}
+
+/// Roots of the reverse Bessel polynomial of `order`, scaled to a -3 dB cutoff of 1 rad/s.
+fn bessel_poles(order: usize) -> Vec<Complex64> {
+    // Coefficients a_k = (2n - k)! / (2^(n - k) k! (n - k)!), lowest power first.
+    let coefficients: Vec<f64> = (0..=order)
+        .map(|k| {
+            let mut value = 1.0;
+            for factor in (order - k + 1)..=(2 * order - k) {
+                value *= factor as f64;
+            }
+            for factor in 1..=k {
+                value /= factor as f64;
+            }
+            value / 2f64.powi((order - k) as i32)
+        })
+        .collect();
+
+    let evaluate = |s: Complex64| {
+        coefficients
+            .iter()
+            .rev()
+            .fold(Complex64::new(0.0, 0.0), |acc, &c| acc * s + c)
+    };
+
+    // Durand-Kerner iteration on the monic polynomial.
+    let leading = coefficients[order];
+    let mut roots: Vec<Complex64> = (0..order)
+        .map(|k| Complex64::from_polar(1.0, 0.4 + 2.0 * PI * k as f64 / order as f64) * 1.5)
+        .collect();
+    for _ in 0..500 {
+        let mut largest_step: f64 = 0.0;
+        for index in 0..order {
+            let root = roots[index];
+            let mut denominator = Complex64::new(leading, 0.0);
+            for (other_index, other) in roots.iter().enumerate() {
+                if other_index != index {
+                    denominator *= root - other;
+                }
+            }
+            let step = evaluate(root) / denominator;
+            roots[index] = root - step;
+            largest_step = largest_step.max(step.norm());
+        }
+        if largest_step < 1e-14 {
+            break;
+        }
+    }
+
+    // Find the -3 dB frequency of H(s) = a_0 / theta(s) and move it to 1 rad/s.
+    let magnitude = |omega: f64| coefficients[0] / evaluate(Complex64::new(0.0, omega)).norm();
+    let (mut low, mut high) = (1e-3_f64, 1e3_f64);
+    for _ in 0..200 {
+        let mid = (low * high).sqrt();
+        if magnitude(mid) > std::f64::consts::FRAC_1_SQRT_2 {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    let cutoff = (low * high).sqrt();
+    roots.into_iter().map(|root| root / cutoff).collect()
+}
+
+/// Frequency response shape of an IIR filter. Frequencies are in Hz.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FilterResponse {
+    LowPass {
+        cutoff: f64,
+    },
+    HighPass {
+        cutoff: f64,
+    },
+    BandPass {
+        low: f64,
+        high: f64,
+    },
+    BandStop {
+        low: f64,
+        high: f64,
+    },
+    /// Second-order notch with quality factor `q` (centre frequency over -3 dB bandwidth).
+    Notch {
+        frequency: f64,
+        q: f64,
+    },
+}
+
+impl FilterResponse {
+    fn name(&self) -> &'static str {
+        match self {
+            FilterResponse::LowPass { .. } => "lowpass",
+            FilterResponse::HighPass { .. } => "highpass",
+            FilterResponse::BandPass { .. } => "bandpass",
+            FilterResponse::BandStop { .. } => "bandstop",
+            FilterResponse::Notch { .. } => "notch",
+        }
+    }
+
+    fn highest_frequency(&self) -> f64 {
+        match *self {
+            FilterResponse::LowPass { cutoff } | FilterResponse::HighPass { cutoff } => cutoff,
+            FilterResponse::BandPass { high, .. } | FilterResponse::BandStop { high, .. } => high,
+            FilterResponse::Notch { frequency, .. } => frequency,
+        }
+    }
+}
+
+/// Default notch quality factor.
+pub const DEFAULT_NOTCH_Q: f64 = 30.0;
+
+/// A configurable IIR filter.
+///
+/// The spec string form is `type:frequency[:option...]`, e.g. `lowpass:1000:order=4`,
+/// `highpass:0.5:order=2:design=bessel`, `bandpass:10-2000`, `bandstop:45-55`, `notch:50:q=35`.
+/// Options are `order=N` (1 to 8, the prototype order; band filters have twice as many poles),
+/// `design=butterworth|bessel`, `q=Q` (notch only) and `causal` for a single forward pass.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FilterSpec {
+    pub response: FilterResponse,
+    pub design: FilterDesign,
+    pub order: usize,
+    /// Run the filter forward and backward (zero phase, squared magnitude response).
+    pub zero_phase: bool,
+}
+
+impl FilterSpec {
+    /// Largest supported prototype order.
+    pub const MAX_ORDER: usize = 8;
+
+    /// Butterworth filter of order 4, applied with zero phase.
+    pub fn new(response: FilterResponse) -> Self {
+        Self {
+            response,
+            design: FilterDesign::Butterworth,
+            order: 4,
+            zero_phase: true,
+        }
+    }
+
+    /// Check the parameters that do not depend on the sample rate.
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=Self::MAX_ORDER).contains(&self.order) {
+            return Err(anyhow!(
+                "Invalid filter order {} in '{}': expected 1 to {}.",
+                self.order,
+                self,
+                Self::MAX_ORDER
+            ));
+        }
+        let valid = match self.response {
+            FilterResponse::LowPass { cutoff } | FilterResponse::HighPass { cutoff } => {
+                cutoff > 0.0
+            }
+            FilterResponse::BandPass { low, high } | FilterResponse::BandStop { low, high } => {
+                low > 0.0 && low < high
+            }
+            FilterResponse::Notch { frequency, q } => frequency > 0.0 && q > 0.0,
+        };
+        if !valid {
+            return Err(anyhow!("Invalid filter frequencies in '{}'.", self));
+        }
+        Ok(())
+    }
+
+    /// Low-pass corner of the filter, if it attenuates high frequencies.
+    pub fn low_pass_cutoff(&self) -> Option<f64> {
+        match self.response {
+            FilterResponse::LowPass { cutoff } => Some(cutoff),
+            FilterResponse::BandPass { high, .. } => Some(high),
+            _ => None,
+        }
+    }
+
+    /// High-pass corner of the filter, if it attenuates low frequencies.
+    pub fn high_pass_cutoff(&self) -> Option<f64> {
+        match self.response {
+            FilterResponse::HighPass { cutoff } => Some(cutoff),
+            FilterResponse::BandPass { low, .. } => Some(low),
+            _ => None,
+        }
+    }
+
+    /// Filter `samples` in place.
+    pub fn apply(&self, samples: &mut [f64], sample_rate: f64) -> Result<()> {
+        let sections = self.sections(sample_rate)?;
+        for section in &sections {
+            section.run(samples.iter_mut());
+        }
+        if self.zero_phase {
+            for section in &sections {
+                section.run(samples.iter_mut().rev());
+            }
+        }
+        Ok(())
+    }
+
+    fn sections(&self, sample_rate: f64) -> Result<Vec<SecondOrderSection>> {
+        self.validate()?;
+        let nyquist = sample_rate / 2.0;
+        if self.response.highest_frequency() >= nyquist {
+            return Err(anyhow!(
+                "Filter '{}' needs frequencies below the Nyquist frequency ({} Hz).",
+                self,
+                nyquist
+            ));
+        }
+
+        if let FilterResponse::Notch { frequency, q } = self.response {
+            let omega = 2.0 * PI * frequency / sample_rate;
+            let alpha = omega.sin() / (2.0 * q);
+            let norm = 1.0 + alpha;
+            return Ok(vec![SecondOrderSection {
+                b: [1.0 / norm, -2.0 * omega.cos() / norm, 1.0 / norm],
+                a: [2.0 * omega.cos() / norm, -(1.0 - alpha) / norm],
+            }]);
+        }
+
+        // Analog zeros and poles after the frequency transformation, with pre-warped corners.
+        let fs2 = 2.0 * sample_rate;
+        let warp = |frequency: f64| fs2 * (PI * frequency / sample_rate).tan();
+        let prototype = self.design.prototype_poles(self.order);
+        let zero = Complex64::new(0.0, 0.0);
+        let (poles, zeros, reference_omega): (Vec<Complex64>, Vec<Complex64>, f64) =
+            match self.response {
+                FilterResponse::LowPass { cutoff } => {
+                    let wc = warp(cutoff);
+                    (prototype.iter().map(|p| p * wc).collect(), Vec::new(), 0.0)
+                }
+                FilterResponse::HighPass { cutoff } => {
+                    let wc = warp(cutoff);
+                    (
+                        prototype.iter().map(|p| wc / p).collect(),
+                        vec![zero; self.order],
+                        PI,
+                    )
+                }
+                FilterResponse::BandPass { low, high } => {
+                    let (wl, wh) = (warp(low), warp(high));
+                    let (w0, bw) = ((wl * wh).sqrt(), wh - wl);
+                    (
+                        prototype
+                            .iter()
+                            .flat_map(|p| quadratic_roots(p * bw, w0 * w0))
+                            .collect(),
+                        vec![zero; self.order],
+                        2.0 * (w0 / fs2).atan(),
+                    )
+                }
+                FilterResponse::BandStop { low, high } => {
+                    let (wl, wh) = (warp(low), warp(high));
+                    let (w0, bw) = ((wl * wh).sqrt(), wh - wl);
+                    let notch = Complex64::new(0.0, w0);
+                    (
+                        prototype
+                            .iter()
+                            .flat_map(|p| quadratic_roots(bw / p, w0 * w0))
+                            .collect(),
+                        (0..self.order)
+                            .flat_map(|_| [notch, notch.conj()])
+                            .collect(),
+                        0.0,
+                    )
+                }
+                FilterResponse::Notch { .. } => unreachable!("handled above"),
+            };
+
+        // Bilinear transform; zeros at infinity map to z = -1.
+        let bilinear = |s: &Complex64| (fs2 + s) / (fs2 - s);
+        let digital_poles: Vec<Complex64> = poles.iter().map(bilinear).collect();
+        let mut digital_zeros: Vec<Complex64> = zeros.iter().map(bilinear).collect();
+        digital_zeros.resize(digital_poles.len(), Complex64::new(-1.0, 0.0));
+
+        let denominators = polynomial_factors(&digital_poles);
+        let numerators = polynomial_factors(&digital_zeros);
+        let mut sections: Vec<SecondOrderSection> = numerators
+            .iter()
+            .zip(&denominators)
+            .map(|(numerator, denominator)| SecondOrderSection {
+                b: *numerator,
+                a: [-denominator[1], -denominator[2]],
+            })
+            .collect();
+
+        // Unity gain in the pass band.
+        let gain: Complex64 = sections
+            .iter()
+            .map(|section| section.response(reference_omega))
+            .product();
+        if let Some(first) = sections.first_mut() {
+            for value in &mut first.b {
+                *value /= gain.norm();
+            }
+        }
+        Ok(sections)
+    }
+}
+
+/// Roots of `s^2 - a s + b`.
+fn quadratic_roots(a: Complex64, b: f64) -> [Complex64; 2] {
+    let discriminant = (a * a - 4.0 * b).sqrt();
+    [(a + discriminant) / 2.0, (a - discriminant) / 2.0]
+}
+
+/// Groups `roots` into real polynomials `[1, c1, c2]` of at most second order: complex conjugate
+/// pairs first, then real roots two at a time, with a first-order factor last for an odd count.
+fn polynomial_factors(roots: &[Complex64]) -> Vec<[f64; 3]> {
+    const TOLERANCE: f64 = 1e-9;
+    let mut factors = Vec::new();
+    let mut reals = Vec::new();
+    for root in roots {
+        if root.im.abs() <= TOLERANCE * root.norm().max(1.0) {
+            reals.push(root.re);
+        } else if root.im > 0.0 {
+            factors.push([1.0, -2.0 * root.re, root.norm_sqr()]);
+        }
+    }
+    reals.sort_by(f64::total_cmp);
+    for pair in reals.chunks(2) {
+        match pair {
+            [r1, r2] => factors.push([1.0, -(r1 + r2), r1 * r2]),
+            [r] => factors.push([1.0, -r, 0.0]),
+            _ => unreachable!("chunks of two"),
+        }
+    }
+    factors
+}
+
+impl std::fmt::Display for FilterSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:", self.response.name())?;
+        match self.response {
+            FilterResponse::LowPass { cutoff } | FilterResponse::HighPass { cutoff } => {
+                write!(f, "{cutoff}")?
+            }
+            FilterResponse::BandPass { low, high } | FilterResponse::BandStop { low, high } => {
+                write!(f, "{low}-{high}")?
+            }
+            FilterResponse::Notch { frequency, q } => write!(f, "{frequency}:q={q}")?,
+        }
+        if !matches!(self.response, FilterResponse::Notch { .. }) {
+            write!(f, ":order={}", self.order)?;
+            if self.design != FilterDesign::Butterworth {
+                write!(f, ":design={}", self.design)?;
+            }
+        }
+        if !self.zero_phase {
+            f.write_str(":causal")?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for FilterSpec {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl std::str::FromStr for FilterSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':').map(str::trim);
+        let kind = parts.next().unwrap_or("").to_ascii_lowercase();
+        let frequencies = parts
+            .next()
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| format!("Filter '{s}' is missing its frequency (e.g. lowpass:1000)."))?;
+
+        let parse_frequency = |value: &str| {
+            value
+                .trim()
+                .parse::<f64>()
+                .map_err(|err| format!("Failed to parse filter frequency '{value}': {err}"))
+        };
+        let parse_band = || {
+            let (low, high) = frequencies
+                .split_once('-')
+                .ok_or_else(|| format!("Band filter '{s}' needs a range such as 10-2000."))?;
+            Ok::<_, String>((parse_frequency(low)?, parse_frequency(high)?))
+        };
+
+        let response = match kind.as_str() {
+            "lowpass" | "low-pass" | "lp" => FilterResponse::LowPass {
+                cutoff: parse_frequency(frequencies)?,
+            },
+            "highpass" | "high-pass" | "hp" => FilterResponse::HighPass {
+                cutoff: parse_frequency(frequencies)?,
+            },
+            "bandpass" | "band-pass" | "bp" => {
+                let (low, high) = parse_band()?;
+                FilterResponse::BandPass { low, high }
+            }
+            "bandstop" | "band-stop" | "bs" => {
+                let (low, high) = parse_band()?;
+                FilterResponse::BandStop { low, high }
+            }
+            "notch" => FilterResponse::Notch {
+                frequency: parse_frequency(frequencies)?,
+                q: DEFAULT_NOTCH_Q,
+            },
+            other => {
+                return Err(format!(
+                    "Unsupported filter type '{other}'. Expected 'lowpass', 'highpass', \
+                     'bandpass', 'bandstop' or 'notch'."
+                ))
+            }
+        };
+
+        let mut spec = FilterSpec::new(response);
+        for option in parts {
+            let (key, value) = option.split_once('=').unwrap_or((option, ""));
+            match (key.trim().to_ascii_lowercase().as_str(), value.trim()) {
+                ("order", value) => {
+                    spec.order = value
+                        .parse()
+                        .map_err(|err| format!("Failed to parse filter order '{value}': {err}"))?
+                }
+                ("design", value) => spec.design = value.parse()?,
+                ("q", value) => match &mut spec.response {
+                    FilterResponse::Notch { q, .. } => {
+                        *q = value
+                            .parse()
+                            .map_err(|err| format!("Failed to parse notch Q '{value}': {err}"))?
+                    }
+                    _ => return Err(format!("Option 'q' only applies to notch filters ('{s}').")),
+                },
+                ("causal", "") => spec.zero_phase = false,
+                ("zero-phase", "") => spec.zero_phase = true,
+                _ => {
+                    return Err(format!(
+                        "Unknown filter option '{option}'. Expected order=N, design=..., q=Q, \
+                         causal or zero-phase."
+                    ))
+                }
+            }
+        }
+        spec.validate().map_err(|err| err.to_string())?;
+        Ok(spec)
+    }
+}
+
+/// A `track=spec` filter for a single track, e.g. `A1=highpass:0.5`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelFilterTarget {
+    pub track_name: String,
+    pub spec: FilterSpec,
+}
+
+impl std::str::FromStr for ChannelFilterTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (track_name, spec) = s.split_once('=').ok_or_else(|| {
+            "Channel filter must be provided in the format track=spec".to_string()
+        })?;
+        let track_name = track_name.trim();
+        if track_name.is_empty() {
+            return Err("Channel filter is missing a track name".to_string());
+        }
+        Ok(ChannelFilterTarget {
+            track_name: track_name.to_string(),
+            spec: spec.parse()?,
+        })
+    }
+}
+
+/// One filter of a [`FilterChain`] and the tracks it applies to.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FilterStage {
+    pub spec: FilterSpec,
+    /// Tracks filtered by this stage. `None` filters every track.
+    pub tracks: Option<Vec<String>>,
+}
+
+impl FilterStage {
+    fn includes(&self, track_name: &str) -> bool {
+        self.tracks
+            .as_ref()
+            .is_none_or(|tracks| tracks.iter().any(|name| name == track_name))
+    }
+}
+
+/// Filters applied to the exported channels, in order.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct FilterChain {
+    stages: Vec<FilterStage>,
+}
+
+/// One entry of the `filters` array of a filter configuration file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FilterConfigEntry {
+    #[serde(rename = "type")]
+    kind: String,
+    cutoff: Option<f64>,
+    low: Option<f64>,
+    high: Option<f64>,
+    frequency: Option<f64>,
+    q: Option<f64>,
+    order: Option<usize>,
+    design: Option<String>,
+    zero_phase: Option<bool>,
+    tracks: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FilterConfig {
+    filters: Vec<FilterConfigEntry>,
+}
+
+impl FilterConfigEntry {
+    fn into_stage(self) -> Result<FilterStage, String> {
+        let require = |value: Option<f64>, field: &str| {
+            value.ok_or_else(|| format!("Filter of type '{}' requires '{field}'.", self.kind))
+        };
+        let response = match self.kind.trim().to_ascii_lowercase().as_str() {
+            "lowpass" => FilterResponse::LowPass {
+                cutoff: require(self.cutoff, "cutoff")?,
+            },
+            "highpass" => FilterResponse::HighPass {
+                cutoff: require(self.cutoff, "cutoff")?,
+            },
+            "bandpass" => FilterResponse::BandPass {
+                low: require(self.low, "low")?,
+                high: require(self.high, "high")?,
+            },
+            "bandstop" => FilterResponse::BandStop {
+                low: require(self.low, "low")?,
+                high: require(self.high, "high")?,
+            },
+            "notch" => FilterResponse::Notch {
+                frequency: require(self.frequency, "frequency")?,
+                q: self.q.unwrap_or(DEFAULT_NOTCH_Q),
+            },
+            other => {
+                return Err(format!(
+                    "Unsupported filter type '{other}'. Expected 'lowpass', 'highpass', \
+                     'bandpass', 'bandstop' or 'notch'."
+                ))
+            }
+        };
+
+        let mut spec = FilterSpec::new(response);
+        if let Some(order) = self.order {
+            spec.order = order;
+        }
+        if let Some(design) = self.design.as_deref() {
+            spec.design = design.parse()?;
+        }
+        if let Some(zero_phase) = self.zero_phase {
+            spec.zero_phase = zero_phase;
+        }
+        spec.validate().map_err(|err| err.to_string())?;
+        Ok(FilterStage {
+            spec,
+            tracks: self.tracks,
+        })
+    }
+}
+
+impl FilterChain {
+    /// Append a filter for `tracks` (`None` for every track).
+    pub fn push(&mut self, spec: FilterSpec, tracks: Option<Vec<String>>) {
+        self.stages.push(FilterStage { spec, tracks });
+    }
+
+    /// Append the filters of a JSON configuration, e.g.
+    /// `{"filters": [{"type": "highpass", "cutoff": 0.5, "order": 2, "tracks": ["A1"]}]}`.
+    pub fn extend_from_json(&mut self, json: &str) -> Result<()> {
+        let config: FilterConfig =
+            serde_json::from_str(json).context("Invalid filter configuration")?;
+        for entry in config.filters {
+            self.stages
+                .push(entry.into_stage().map_err(anyhow::Error::msg)?);
+        }
+        Ok(())
+    }
+
+    /// Append the filters of a JSON configuration file.
+    pub fn extend_from_file(&mut self, path: &Path) -> Result<()> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read filter configuration {}", path.display()))?;
+        self.extend_from_json(&json)
+            .with_context(|| format!("Failed to load filters from {}", path.display()))
+    }
+
+    /// Returns `true` when no filter was configured.
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// The configured filters, in application order.
+    pub fn stages(&self) -> &[FilterStage] {
+        &self.stages
+    }
+
+    /// Apply every stage selecting `track_name` to `data`, in order.
+    ///
+    /// Returns the applied filters.
+    pub fn apply(&self, track_name: &str, data: &mut ChannelData) -> Result<Vec<FilterSpec>> {
+        let mut applied = Vec::new();
+        for stage in self
+            .stages
+            .iter()
+            .filter(|stage| stage.includes(track_name))
+        {
+            stage
+                .spec
+                .apply(&mut data.time_series, data.sample_rate)
+                .with_context(|| format!("Failed to apply filter '{}'", stage.spec))?;
+            applied.push(stage.spec.clone());
+        }
+        Ok(applied)
+    }
+}
@@ -0,0 +1,261 @@
+use crate::dts::DtsReader;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::io::Write;
+
+/// Tolerances of the sensor-health report.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HealthSettings {
+    /// Largest accepted zero shift between the pre- and post-test zero, in percent of full scale.
+    pub zero_shift_tolerance: f64,
+    /// Largest accepted deviation of a diagnostics level from its expected value, in percent.
+    pub diagnostics_tolerance: f64,
+}
+
+impl Default for HealthSettings {
+    fn default() -> Self {
+        Self {
+            zero_shift_tolerance: 1.0,
+            diagnostics_tolerance: 5.0,
+        }
+    }
+}
+
+impl HealthSettings {
+    /// Ensure the tolerances are usable.
+    pub fn validate(&self) -> Result<()> {
+        for (name, value) in [
+            ("zero shift", self.zero_shift_tolerance),
+            ("diagnostics", self.diagnostics_tolerance),
+        ] {
+            if value < 0.0 || !value.is_finite() {
+                return Err(anyhow!(
+                    "Invalid {name} tolerance ({value}%): expected a non-negative percentage."
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Diagnostics signal applied to the sensor before and after the test.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiagnosticsKind {
+    /// Shunt resistor across a bridge arm.
+    Shunt,
+    /// Calibration voltage injected at the amplifier input.
+    CalSignal,
+}
+
+impl DiagnosticsKind {
+    /// Returns the human readable name of the diagnostics signal.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagnosticsKind::Shunt => "shunt",
+            DiagnosticsKind::CalSignal => "cal-signal",
+        }
+    }
+}
+
+impl std::fmt::Display for DiagnosticsKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Diagnostics levels measured against the value configured in the `.dts` file.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DiagnosticsCheck {
+    pub kind: DiagnosticsKind,
+    /// Expected deflection above the zero level, in mV.
+    pub expected_mv: f64,
+    /// Pre-test deflection above the pre-test zero, in mV.
+    pub pre_test_mv: f64,
+    /// Post-test deflection above the post-test zero, in mV. `None` when it was not measured.
+    pub post_test_mv: Option<f64>,
+    /// Largest deviation from the expected deflection, in percent.
+    pub deviation_percent: f64,
+    pub passed: bool,
+}
+
+/// Health of one sensor, from the levels recorded in its `.chn` header.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SensorHealth {
+    pub track_name: String,
+    pub units: String,
+    pub serial_number: String,
+    /// Pre-test zero level, in engineering units.
+    pub pre_test_zero: f64,
+    /// Post-test zero level, in engineering units.
+    pub post_test_zero: f64,
+    /// Post-test minus pre-test zero, in engineering units.
+    pub zero_shift: f64,
+    /// Post-test minus pre-test zero, in percent of full scale.
+    pub zero_shift_percent: f64,
+    /// Pre-test noise, in engineering units.
+    pub noise: f64,
+    /// Pre-test noise, in percent of full scale.
+    pub noise_percent: f64,
+    /// `None` when neither a shunt nor a calibration signal is enabled for the channel.
+    pub diagnostics: Option<DiagnosticsCheck>,
+    /// Checks that failed, in words.
+    pub issues: Vec<String>,
+}
+
+impl SensorHealth {
+    /// Whether every check passed.
+    pub fn is_healthy(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Sensor-health report of every channel in `reader`.
+///
+/// Channels are named from `track_names` by index, falling back to the channel name in the
+/// `.dts` file.
+pub fn sensor_health(
+    reader: &DtsReader,
+    track_names: &[String],
+    settings: &HealthSettings,
+) -> Result<Vec<SensorHealth>> {
+    settings.validate()?;
+    (0..reader.channel_count())
+        .map(|index| {
+            let channel = reader
+                .channel_info(index)
+                .ok_or_else(|| anyhow!("Missing metadata for channel {}", index + 1))?;
+            let levels = reader
+                .channel_levels(index)
+                .ok_or_else(|| anyhow!("Missing header levels for channel {}", index + 1))?;
+            let track_name = track_names
+                .get(index)
+                .cloned()
+                .unwrap_or_else(|| channel.name.clone());
+
+            let full_scale = f64::from(1u32 << (levels.bits_per_sample.clamp(2, 16) - 1));
+            let shift_counts = f64::from(levels.post_test_zero_adc - levels.pre_test_zero_adc);
+            let zero_shift_percent = 100.0 * shift_counts / full_scale;
+            let noise_percent = levels.pre_test_noise_percent;
+
+            let expected = if channel.shunt_enabled {
+                Some((DiagnosticsKind::Shunt, channel.target_shunt_deflection_mv))
+            } else if channel.cal_signal_enabled {
+                Some((DiagnosticsKind::CalSignal, channel.target_cal_signal_mv))
+            } else {
+                None
+            };
+            let diagnostics = expected
+                .filter(|(_, expected_mv)| expected_mv.is_finite() && *expected_mv != 0.0)
+                .map(|(kind, expected_mv)| {
+                    let deflection =
+                        |level: i32, zero: i32| f64::from(level - zero) * levels.mv_per_count;
+                    let pre_test_mv =
+                        deflection(levels.pre_test_diagnostics_adc, levels.pre_test_zero_adc);
+                    let post_test_mv = (levels.post_test_diagnostics_adc != 0).then(|| {
+                        deflection(levels.post_test_diagnostics_adc, levels.post_test_zero_adc)
+                    });
+                    let deviation_percent = std::iter::once(pre_test_mv)
+                        .chain(post_test_mv)
+                        .map(|measured| 100.0 * (measured - expected_mv).abs() / expected_mv.abs())
+                        .fold(0.0, f64::max);
+                    DiagnosticsCheck {
+                        kind,
+                        expected_mv,
+                        pre_test_mv,
+                        post_test_mv,
+                        deviation_percent,
+                        passed: deviation_percent <= settings.diagnostics_tolerance,
+                    }
+                });
+
+            let mut issues = Vec::new();
+            if zero_shift_percent.abs() > settings.zero_shift_tolerance {
+                issues.push(format!(
+                    "zero shifted by {zero_shift_percent:.3}% FS (tolerance {}%)",
+                    settings.zero_shift_tolerance
+                ));
+            }
+            if let Some(check) = diagnostics.as_ref().filter(|check| !check.passed) {
+                issues.push(format!(
+                    "{} level deviates {:.2}% from the expected {} mV (tolerance {}%)",
+                    check.kind,
+                    check.deviation_percent,
+                    check.expected_mv,
+                    settings.diagnostics_tolerance
+                ));
+            }
+
+            Ok(SensorHealth {
+                track_name,
+                units: channel.eu.clone(),
+                serial_number: channel.serial_number.clone(),
+                pre_test_zero: f64::from(levels.pre_test_zero_adc) * levels.eu_per_count,
+                post_test_zero: f64::from(levels.post_test_zero_adc) * levels.eu_per_count,
+                zero_shift: shift_counts * levels.eu_per_count,
+                zero_shift_percent,
+                noise: noise_percent / 100.0 * full_scale * levels.eu_per_count.abs(),
+                noise_percent,
+                diagnostics,
+                issues,
+            })
+        })
+        .collect()
+}
+
+/// Write the report as pretty-printed JSON.
+pub fn write_json<W: Write>(writer: &mut W, report: &[SensorHealth]) -> Result<()> {
+    serde_json::to_writer_pretty(&mut *writer, report)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Write the report as an aligned plain text table, followed by the failed checks.
+pub fn write_table<W: Write>(writer: &mut W, report: &[SensorHealth]) -> Result<()> {
+    let name_width = report
+        .iter()
+        .map(|health| health.track_name.chars().count())
+        .chain(std::iter::once("Track".len()))
+        .max()
+        .unwrap_or_default();
+    let units_width = report
+        .iter()
+        .map(|health| health.units.chars().count())
+        .chain(std::iter::once("Units".len()))
+        .max()
+        .unwrap_or_default();
+
+    writeln!(
+        writer,
+        "{:<name_width$}  {:<units_width$}  {:>12}  {:>12}  {:>10}  {:>12}  {:>10}  {:>12}  Status",
+        "Track", "Units", "Zero shift", "Shift % FS", "Noise", "Noise % FS", "Diag", "Diag dev %"
+    )?;
+    for health in report {
+        let (kind, deviation) = match &health.diagnostics {
+            Some(check) => (
+                check.kind.to_string(),
+                format!("{:.2}", check.deviation_percent),
+            ),
+            None => ("-".to_string(), "-".to_string()),
+        };
+        writeln!(
+            writer,
+            "{:<name_width$}  {:<units_width$}  {:>12.5}  {:>12.4}  {:>10.5}  {:>12.5}  {:>10}  {:>12}  {}",
+            health.track_name,
+            health.units,
+            health.zero_shift,
+            health.zero_shift_percent,
+            health.noise,
+            health.noise_percent,
+            kind,
+            deviation,
+            if health.is_healthy() { "ok" } else { "CHECK" }
+        )?;
+    }
+    for health in report {
+        for issue in &health.issues {
+            writeln!(writer, "{}: {}", health.track_name, issue)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,392 @@
+use crate::dts::{excitation_voltage, AnalogInputChannel, ChannelData, TestInfo};
+use crate::uff::IdLines;
+use clap::ValueEnum;
+use serde::{Serialize, Serializer};
+
+/// Starting layout for the UFF ID records.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum IdPreset {
+    /// Layout written by MATLAB `writeuff58DX` (blank, `Pt=<track>;`, blank, `NONE`, `NONE`).
+    #[default]
+    Matlab,
+    /// Test, channel description, date, serial number, sensitivity, sample rate and excitation.
+    Metadata,
+}
+
+impl IdPreset {
+    /// Returns the human readable name of the preset.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IdPreset::Matlab => "matlab",
+            IdPreset::Metadata => "metadata",
+        }
+    }
+
+    /// Build the template for this preset with `overrides` applied.
+    ///
+    /// Returns `None` for the untouched MATLAB preset so callers can keep the byte-exact
+    /// MATLAB output.
+    pub fn template<I>(self, overrides: I) -> Option<IdTemplate>
+    where
+        I: IntoIterator<Item = IdLineOverride>,
+    {
+        let mut overrides = overrides.into_iter().peekable();
+        if self == IdPreset::Matlab && overrides.peek().is_none() {
+            return None;
+        }
+
+        let mut template = match self {
+            IdPreset::Matlab => IdTemplate::matlab(),
+            IdPreset::Metadata => IdTemplate::metadata(),
+        };
+        for line_override in overrides {
+            template.set_line(line_override);
+        }
+        Some(template)
+    }
+}
+
+impl std::fmt::Display for IdPreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for IdPreset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "matlab" => Ok(IdPreset::Matlab),
+            "metadata" => Ok(IdPreset::Metadata),
+            other => Err(format!(
+                "Unsupported ID preset '{other}'. Expected 'matlab' or 'metadata'."
+            )),
+        }
+    }
+}
+
+/// Values available to an ID line template for one exported channel.
+pub struct IdContext<'a> {
+    /// Track name written to the UFF file.
+    pub track_name: &'a str,
+    /// Test-level information from the `.dts` file.
+    pub test: &'a TestInfo,
+    /// XML metadata of the source channel, when the channel exists in the DTS export.
+    pub channel: Option<&'a AnalogInputChannel>,
+    /// Channel data as it will be written (after any unit conversion).
+    pub data: &'a ChannelData,
+    /// Filter applied to the channel during conversion, e.g. `CFC180`.
+    pub filter: Option<&'a str>,
+}
+
+/// Placeholders understood by [`IdTemplate`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Field {
+    Track,
+    Name,
+    Description,
+    Serial,
+    Sensitivity,
+    Eu,
+    Units,
+    Excitation,
+    SampleRate,
+    Test,
+    TestDescription,
+    Date,
+    Filter,
+}
+
+impl Field {
+    const ALL: [(&'static str, Field); 13] = [
+        ("track", Field::Track),
+        ("name", Field::Name),
+        ("description", Field::Description),
+        ("serial", Field::Serial),
+        ("sensitivity", Field::Sensitivity),
+        ("eu", Field::Eu),
+        ("units", Field::Units),
+        ("excitation", Field::Excitation),
+        ("sample_rate", Field::SampleRate),
+        ("test", Field::Test),
+        ("test_description", Field::TestDescription),
+        ("date", Field::Date),
+        ("filter", Field::Filter),
+    ];
+
+    /// Placeholder name of the field, without braces.
+    fn name(&self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(_, field)| field == self)
+            .map_or("", |(key, _)| key)
+    }
+
+    fn parse(name: &str) -> Result<Self, String> {
+        Self::ALL
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, field)| *field)
+            .ok_or_else(|| {
+                let known: Vec<String> = Self::ALL
+                    .iter()
+                    .map(|(key, _)| format!("{{{key}}}"))
+                    .collect();
+                format!(
+                    "Unknown ID template placeholder '{{{name}}}'. Expected one of: {}.",
+                    known.join(", ")
+                )
+            })
+    }
+
+    fn render(&self, context: &IdContext<'_>, out: &mut String) {
+        let channel = context.channel;
+        match self {
+            Field::Track => out.push_str(context.track_name),
+            Field::Name => out.push_str(channel.map_or("", |c| c.name.as_str())),
+            Field::Description => out.push_str(channel.map_or("", |c| c.description.as_str())),
+            Field::Serial => out.push_str(channel.map_or("", |c| c.serial_number.as_str())),
+            Field::Sensitivity => {
+                if let Some(value) = channel.map(|c| c.sensitivity).filter(|v| v.is_finite()) {
+                    out.push_str(&value.to_string());
+                }
+            }
+            Field::Eu => out.push_str(channel.map_or("", |c| c.eu.as_str())),
+            Field::Units => out.push_str(&context.data.units),
+            Field::Excitation => {
+                // Same excitation the ADC scaling divides by; self-generating sensors have none.
+                if let Some(value) = channel
+                    .filter(|c| c.proportional_to_excitation)
+                    .map(excitation_voltage)
+                    .filter(|v| v.is_finite())
+                {
+                    out.push_str(&format!("{value:.3}"));
+                }
+            }
+            Field::SampleRate => out.push_str(&context.data.sample_rate.to_string()),
+            Field::Test => out.push_str(&context.test.id),
+            Field::TestDescription => out.push_str(&context.test.description),
+            Field::Date => out.push_str(&context.test.date),
+            Field::Filter => out.push_str(context.filter.unwrap_or("")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    Field(Field),
+    /// Written only when every placeholder in it renders to a non-empty value. Used by the
+    /// built-in presets; templates given by the user have no syntax for it.
+    Clause(Vec<Segment>),
+}
+
+impl Segment {
+    /// Append the rendered segment to `out`, returning `false` if a placeholder was empty.
+    fn render(&self, context: &IdContext<'_>, out: &mut String) -> bool {
+        match self {
+            Segment::Literal(text) => {
+                out.push_str(text);
+                true
+            }
+            Segment::Field(field) => {
+                let len = out.len();
+                field.render(context, out);
+                out.len() > len
+            }
+            Segment::Clause(segments) => {
+                let mut clause = String::new();
+                if segments
+                    .iter()
+                    .all(|segment| segment.render(context, &mut clause))
+                {
+                    out.push_str(&clause);
+                }
+                true
+            }
+        }
+    }
+
+    fn write(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Segment::Literal(text) => f.write_str(&text.replace('{', "{{").replace('}', "}}")),
+            Segment::Field(field) => write!(f, "{{{}}}", field.name()),
+            Segment::Clause(segments) => segments.iter().try_for_each(|segment| segment.write(f)),
+        }
+    }
+}
+
+/// A parsed template for a single ID line. Placeholders are written as `{name}`; use `{{` and
+/// `}}` for literal braces.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IdLineTemplate {
+    segments: Vec<Segment>,
+}
+
+impl IdLineTemplate {
+    fn render(&self, context: &IdContext<'_>) -> String {
+        let mut out = String::new();
+        for segment in &self.segments {
+            segment.render(context, &mut out);
+        }
+        out.trim().to_string()
+    }
+}
+
+impl std::fmt::Display for IdLineTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.segments
+            .iter()
+            .try_for_each(|segment| segment.write(f))
+    }
+}
+
+impl Serialize for IdLineTemplate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl std::str::FromStr for IdLineTemplate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => {
+                                return Err(format!("Unclosed placeholder in ID template '{s}'."))
+                            }
+                        }
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Field(Field::parse(name.trim())?));
+                }
+                '}' => return Err(format!("Unmatched '}}' in ID template '{s}'.")),
+                other => literal.push(other),
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(IdLineTemplate { segments })
+    }
+}
+
+/// A `line=template` override for one of the five ID records, e.g. `4=SN:{serial}`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IdLineOverride {
+    /// One-based ID record number (1 to 5).
+    pub line: usize,
+    pub template: IdLineTemplate,
+}
+
+impl std::str::FromStr for IdLineOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (line, template) = s
+            .split_once('=')
+            .ok_or_else(|| "ID line must be provided in the format line=template".to_string())?;
+        let line = line
+            .trim()
+            .parse::<usize>()
+            .map_err(|err| format!("Failed to parse ID line number '{line}': {err}"))?;
+        if !(1..=5).contains(&line) {
+            return Err(format!(
+                "ID line number must be between 1 and 5, got {line}."
+            ));
+        }
+        Ok(IdLineOverride {
+            line,
+            template: template.parse()?,
+        })
+    }
+}
+
+/// Templates for the five ID records of every UFF 58 block.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct IdTemplate {
+    lines: [IdLineTemplate; 5],
+}
+
+impl IdTemplate {
+    /// Template equivalent of the MATLAB layout, used as a base for per-line overrides. Record 3
+    /// holds the filter class and stays blank for unfiltered channels.
+    pub fn matlab() -> Self {
+        Self::from_lines(["", "Pt={track};", "{filter}", "NONE", "NONE"])
+    }
+
+    /// Default metadata layout:
+    ///
+    /// 1. `{test} {test_description}`
+    /// 2. `Pt={track}; {description}`
+    /// 3. `{date} {filter}`
+    /// 4. `SN:{serial} {sensitivity} mV/{eu}`
+    /// 5. `Excitation {excitation} V, {sample_rate} Hz`, without the excitation clause for
+    ///    self-generating sensors
+    pub fn metadata() -> Self {
+        let mut template = Self::from_lines([
+            "{test} {test_description}",
+            "Pt={track}; {description}",
+            "{date} {filter}",
+            "SN:{serial} {sensitivity} mV/{eu}",
+            "{sample_rate} Hz",
+        ]);
+        let excitation = Self::parse_line("Excitation {excitation} V, ");
+        template.lines[4]
+            .segments
+            .insert(0, Segment::Clause(excitation.segments));
+        template
+    }
+
+    fn from_lines(lines: [&str; 5]) -> Self {
+        Self {
+            lines: lines.map(Self::parse_line),
+        }
+    }
+
+    fn parse_line(line: &str) -> IdLineTemplate {
+        line.parse().expect("built-in ID templates are valid")
+    }
+
+    /// Replace the template of one ID record.
+    pub fn set_line(&mut self, line_override: IdLineOverride) {
+        self.lines[line_override.line - 1] = line_override.template;
+    }
+
+    /// Render the five ID records for one channel.
+    pub fn render(&self, context: &IdContext<'_>) -> IdLines {
+        IdLines {
+            lines: std::array::from_fn(|index| self.lines[index].render(context)),
+        }
+    }
+}
+
+impl Default for IdTemplate {
+    fn default() -> Self {
+        Self::metadata()
+    }
+}
@@ -0,0 +1,659 @@
+use crate::dts::ChannelData;
+use crate::units::{lookup_unit, Quantity};
+use anyhow::{anyhow, Result};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::io::Write;
+
+const STANDARD_GRAVITY: f64 = 9.80665;
+
+/// Dummy channel used by the injury criteria.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChannelRole {
+    HeadX,
+    HeadY,
+    HeadZ,
+    ChestX,
+    ChestY,
+    ChestZ,
+    ChestDeflection,
+    NeckFx,
+    NeckFz,
+    NeckMy,
+}
+
+impl ChannelRole {
+    /// All supported roles, in display order.
+    pub const ALL: [ChannelRole; 10] = [
+        ChannelRole::HeadX,
+        ChannelRole::HeadY,
+        ChannelRole::HeadZ,
+        ChannelRole::ChestX,
+        ChannelRole::ChestY,
+        ChannelRole::ChestZ,
+        ChannelRole::ChestDeflection,
+        ChannelRole::NeckFx,
+        ChannelRole::NeckFz,
+        ChannelRole::NeckMy,
+    ];
+
+    /// Returns the human readable name of the role.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChannelRole::HeadX => "head-x",
+            ChannelRole::HeadY => "head-y",
+            ChannelRole::HeadZ => "head-z",
+            ChannelRole::ChestX => "chest-x",
+            ChannelRole::ChestY => "chest-y",
+            ChannelRole::ChestZ => "chest-z",
+            ChannelRole::ChestDeflection => "chest-deflection",
+            ChannelRole::NeckFx => "neck-fx",
+            ChannelRole::NeckFz => "neck-fz",
+            ChannelRole::NeckMy => "neck-my",
+        }
+    }
+
+    /// Channel frequency class recommended by SAE J211 for the role.
+    pub fn recommended_cfc(&self) -> &'static str {
+        match self {
+            ChannelRole::HeadX | ChannelRole::HeadY | ChannelRole::HeadZ => "CFC1000",
+            ChannelRole::ChestX | ChannelRole::ChestY | ChannelRole::ChestZ => "CFC180",
+            ChannelRole::ChestDeflection
+            | ChannelRole::NeckFx
+            | ChannelRole::NeckFz
+            | ChannelRole::NeckMy => "CFC600",
+        }
+    }
+}
+
+impl std::fmt::Display for ChannelRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ChannelRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().to_ascii_lowercase().replace('_', "-");
+        ChannelRole::ALL
+            .into_iter()
+            .find(|role| role.as_str() == normalized)
+            .ok_or_else(|| {
+                let names: Vec<&str> = ChannelRole::ALL.iter().map(|role| role.as_str()).collect();
+                format!(
+                    "Unsupported channel role '{}'. Expected one of {}.",
+                    s.trim(),
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// A `role=track` assignment, e.g. `head-x=A1`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RoleAssignment {
+    pub role: ChannelRole,
+    pub track_name: String,
+}
+
+impl std::str::FromStr for RoleAssignment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (role, track_name) = s
+            .split_once('=')
+            .ok_or_else(|| "Channel role must be provided in the format role=track".to_string())?;
+        let track_name = track_name.trim();
+        if track_name.is_empty() {
+            return Err(format!(
+                "Channel role '{}' is missing a track name",
+                role.trim()
+            ));
+        }
+        Ok(RoleAssignment {
+            role: role.parse()?,
+            track_name: track_name.to_string(),
+        })
+    }
+}
+
+/// Neck load intercepts used to normalise Nij.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct NijIntercepts {
+    /// Axial tension intercept, in N.
+    pub tension: f64,
+    /// Axial compression intercept, in N.
+    pub compression: f64,
+    /// Flexion moment intercept, in N·m.
+    pub flexion: f64,
+    /// Extension moment intercept, in N·m.
+    pub extension: f64,
+    /// Distance from the load cell to the occipital condyle, in m. Used to correct the moment
+    /// when a `neck-fx` channel is mapped.
+    pub condyle_offset: f64,
+}
+
+impl Default for NijIntercepts {
+    /// Hybrid III 50th percentile male (FMVSS 208).
+    fn default() -> Self {
+        Self {
+            tension: 6806.0,
+            compression: 6160.0,
+            flexion: 310.0,
+            extension: 135.0,
+            condyle_offset: 0.01778,
+        }
+    }
+}
+
+impl std::str::FromStr for NijIntercepts {
+    type Err = String;
+
+    /// Parses `tension,compression,flexion,extension[,condyle_offset]`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = s
+            .split(',')
+            .map(|value| {
+                value.trim().parse::<f64>().map_err(|err| {
+                    format!("Failed to parse Nij intercept '{}': {err}", value.trim())
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let intercepts = match values[..] {
+            [tension, compression, flexion, extension] => NijIntercepts {
+                tension,
+                compression,
+                flexion,
+                extension,
+                ..NijIntercepts::default()
+            },
+            [tension, compression, flexion, extension, condyle_offset] => NijIntercepts {
+                tension,
+                compression,
+                flexion,
+                extension,
+                condyle_offset,
+            },
+            _ => return Err(
+                "Nij intercepts must be given as tension,compression,flexion,extension[,offset]"
+                    .to_string(),
+            ),
+        };
+        if [
+            intercepts.tension,
+            intercepts.compression,
+            intercepts.flexion,
+            intercepts.extension,
+        ]
+        .iter()
+        .any(|value| *value <= 0.0)
+        {
+            return Err("Nij intercepts must be positive".to_string());
+        }
+        Ok(intercepts)
+    }
+}
+
+/// A channel assigned to a role, as written to the output.
+pub struct RoleChannel<'a> {
+    pub track_name: &'a str,
+    pub data: &'a ChannelData,
+    /// Time of the first sample, in seconds.
+    pub start_time: f64,
+}
+
+/// One evaluated criterion and the time window it was taken from.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CriterionResult {
+    /// Criterion name, e.g. `HIC15`, `Chest 3ms clip (cumulative)` or `Nij NTE`.
+    pub criterion: String,
+    pub value: f64,
+    pub units: String,
+    /// Start of the time window, in seconds. Peak values use the time of the peak.
+    pub window_start: f64,
+    /// End of the time window, in seconds.
+    pub window_end: f64,
+    /// Tracks the criterion was computed from.
+    pub tracks: Vec<String>,
+}
+
+/// Injury criteria computed from the mapped channels.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct InjuryReport {
+    pub criteria: Vec<CriterionResult>,
+}
+
+impl InjuryReport {
+    /// Look up a criterion by name.
+    pub fn get(&self, criterion: &str) -> Option<&CriterionResult> {
+        self.criteria
+            .iter()
+            .find(|result| result.criterion == criterion)
+    }
+
+    /// Write the report as pretty-printed JSON.
+    pub fn write_json<W: Write>(&self, writer: &mut W) -> Result<()> {
+        serde_json::to_writer_pretty(&mut *writer, self)?;
+        writeln!(writer)?;
+        Ok(())
+    }
+
+    /// Write the report as CSV with one row per criterion.
+    pub fn write_csv<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writeln!(
+            writer,
+            "criterion,value,units,window_start_s,window_end_s,tracks"
+        )?;
+        for result in &self.criteria {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{}",
+                csv_field(&result.criterion),
+                result.value,
+                csv_field(&result.units),
+                result.window_start,
+                result.window_end,
+                csv_field(&result.tracks.join(" "))
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    value.replace([',', '"', '\n', '\r'], "_")
+}
+
+/// Compute every criterion whose channels are mapped:
+///
+/// - `HIC15`, `HIC36`, `Head 3ms clip (cumulative)` and `Head 3ms clip (continuous)` from the
+///   head acceleration resultant,
+/// - `Chest 3ms clip (cumulative)` and `Chest 3ms clip (continuous)` from the chest
+///   acceleration resultant,
+/// - `Chest deflection` (largest absolute value),
+/// - `Nij NTE`, `Nij NTF`, `Nij NCE`, `Nij NCF` and `Nij` from the upper neck Fz and My.
+///
+/// The channels should already be CFC filtered (see [`ChannelRole::recommended_cfc`]).
+/// Acceleration is converted to g and forces to N; moments must be in N·m.
+pub fn compute_criteria(
+    channels: &BTreeMap<ChannelRole, RoleChannel<'_>>,
+    intercepts: &NijIntercepts,
+) -> Result<InjuryReport> {
+    let mut criteria = Vec::new();
+
+    let head = [ChannelRole::HeadX, ChannelRole::HeadY, ChannelRole::HeadZ];
+    if let Some(resultant) = acceleration_resultant(channels, &head)? {
+        for window in [0.015, 0.036] {
+            let (value, start, end) = hic(&resultant.values, resultant.sample_rate, window);
+            criteria.push(resultant.result(
+                format!("HIC{}", (window * 1000.0).round()),
+                value,
+                "",
+                start,
+                end,
+            ));
+        }
+        criteria.extend(resultant.three_ms_clips("Head"));
+    }
+
+    let chest = [
+        ChannelRole::ChestX,
+        ChannelRole::ChestY,
+        ChannelRole::ChestZ,
+    ];
+    if let Some(resultant) = acceleration_resultant(channels, &chest)? {
+        criteria.extend(resultant.three_ms_clips("Chest"));
+    }
+
+    if let Some(channel) = channels.get(&ChannelRole::ChestDeflection) {
+        let (index, value) = peak(channel.data.time_series.iter().map(|value| value.abs()));
+        let time = channel.start_time + index as f64 / channel.data.sample_rate;
+        criteria.push(CriterionResult {
+            criterion: "Chest deflection".to_string(),
+            value,
+            units: channel.data.units.clone(),
+            window_start: time,
+            window_end: time,
+            tracks: vec![channel.track_name.to_string()],
+        });
+    }
+
+    if let (Some(fz), Some(my)) = (
+        channels.get(&ChannelRole::NeckFz),
+        channels.get(&ChannelRole::NeckMy),
+    ) {
+        criteria.extend(nij(fz, my, channels.get(&ChannelRole::NeckFx), intercepts)?);
+    }
+
+    Ok(InjuryReport { criteria })
+}
+
+/// Resultant of the mapped axes of one sensor, in g.
+struct Resultant {
+    values: Vec<f64>,
+    sample_rate: f64,
+    start_time: f64,
+    tracks: Vec<String>,
+}
+
+impl Resultant {
+    fn result(
+        &self,
+        criterion: String,
+        value: f64,
+        units: &str,
+        start: usize,
+        end: usize,
+    ) -> CriterionResult {
+        CriterionResult {
+            criterion,
+            value,
+            units: units.to_string(),
+            window_start: self.start_time + start as f64 / self.sample_rate,
+            window_end: self.start_time + end as f64 / self.sample_rate,
+            tracks: self.tracks.clone(),
+        }
+    }
+
+    /// The cumulative 3 ms clip of FMVSS 208 and ECE R94, followed by the continuous one, which
+    /// is never higher.
+    fn three_ms_clips(&self, body: &str) -> [CriterionResult; 2] {
+        let (value, start, end) = cumulative_three_ms_clip(&self.values, self.sample_rate);
+        let cumulative = self.result(
+            format!("{body} 3ms clip (cumulative)"),
+            value,
+            "g",
+            start,
+            end,
+        );
+        let (value, start, end) = continuous_three_ms_clip(&self.values, self.sample_rate);
+        let continuous = self.result(
+            format!("{body} 3ms clip (continuous)"),
+            value,
+            "g",
+            start,
+            end,
+        );
+        [cumulative, continuous]
+    }
+}
+
+fn acceleration_resultant(
+    channels: &BTreeMap<ChannelRole, RoleChannel<'_>>,
+    roles: &[ChannelRole],
+) -> Result<Option<Resultant>> {
+    let axes: Vec<(ChannelRole, &RoleChannel)> = roles
+        .iter()
+        .filter_map(|role| channels.get(role).map(|channel| (*role, channel)))
+        .collect();
+    let Some((_, first)) = axes.first() else {
+        return Ok(None);
+    };
+
+    let sample_rate = first.data.sample_rate;
+    let len = axes
+        .iter()
+        .map(|(_, channel)| channel.data.time_series.len())
+        .min()
+        .unwrap_or(0);
+    let mut values = vec![0.0; len];
+    for (role, channel) in &axes {
+        check_rate(*role, channel, sample_rate)?;
+        let to_g = scale_to_si(*role, channel, Quantity::Acceleration)? / STANDARD_GRAVITY;
+        for (sum, value) in values.iter_mut().zip(&channel.data.time_series) {
+            *sum += (value * to_g).powi(2);
+        }
+    }
+    for value in &mut values {
+        *value = value.sqrt();
+    }
+
+    Ok(Some(Resultant {
+        values,
+        sample_rate,
+        start_time: first.start_time,
+        tracks: axes
+            .iter()
+            .map(|(_, channel)| channel.track_name.to_string())
+            .collect(),
+    }))
+}
+
+fn check_rate(role: ChannelRole, channel: &RoleChannel, sample_rate: f64) -> Result<()> {
+    if channel.data.sample_rate != sample_rate {
+        return Err(anyhow!(
+            "Track '{}' ({role}) is sampled at {} Hz instead of {} Hz; resample the channels first.",
+            channel.track_name,
+            channel.data.sample_rate,
+            sample_rate
+        ));
+    }
+    Ok(())
+}
+
+/// Factor converting the channel to the SI unit of `quantity`.
+fn scale_to_si(role: ChannelRole, channel: &RoleChannel, quantity: Quantity) -> Result<f64> {
+    match lookup_unit(&channel.data.units) {
+        Some(unit) if unit.quantity == quantity => Ok(unit.to_si),
+        _ => Err(anyhow!(
+            "Track '{}' ({role}) is in '{}', which is not a known {quantity} unit.",
+            channel.track_name,
+            channel.data.units
+        )),
+    }
+}
+
+/// Index and value of the largest value.
+fn peak(values: impl Iterator<Item = f64>) -> (usize, f64) {
+    values
+        .enumerate()
+        .fold((0, f64::NEG_INFINITY), |best, (index, value)| {
+            if value > best.1 {
+                (index, value)
+            } else {
+                best
+            }
+        })
+}
+
+/// Head injury criterion over windows up to `max_window` seconds. Returns the value and the
+/// first and last sample of the window.
+fn hic(acceleration: &[f64], sample_rate: f64, max_window: f64) -> (f64, usize, usize) {
+    let dt = 1.0 / sample_rate;
+    let mut integral = Vec::with_capacity(acceleration.len());
+    let mut sum = 0.0;
+    integral.push(0.0);
+    for pair in acceleration.windows(2) {
+        sum += 0.5 * (pair[0] + pair[1]) * dt;
+        integral.push(sum);
+    }
+    let max_span = (max_window * sample_rate).round() as usize;
+
+    (0..acceleration.len())
+        .into_par_iter()
+        .map(|start| {
+            let last = (start + max_span).min(acceleration.len().saturating_sub(1));
+            (start + 1..=last)
+                .map(|end| {
+                    let duration = (end - start) as f64 * dt;
+                    let mean = (integral[end] - integral[start]) / duration;
+                    (duration * mean.max(0.0).powf(2.5), start, end)
+                })
+                .fold((0.0, start, start), |best, candidate| {
+                    if candidate.0 > best.0 {
+                        candidate
+                    } else {
+                        best
+                    }
+                })
+        })
+        .reduce(
+            || (0.0, 0, 0),
+            |best, candidate| {
+                if candidate.0 > best.0 {
+                    candidate
+                } else {
+                    best
+                }
+            },
+        )
+}
+
+/// Samples covering 3 ms.
+fn three_ms_span(sample_rate: f64) -> usize {
+    ((0.003 * sample_rate).round() as usize).max(1)
+}
+
+/// Highest level exceeded for a cumulative 3 ms, not necessarily in one interval. Returns the
+/// value and the first and last sample at or above it.
+fn cumulative_three_ms_clip(values: &[f64], sample_rate: f64) -> (f64, usize, usize) {
+    if values.is_empty() {
+        return (f64::NAN, 0, 0);
+    }
+    let span = three_ms_span(sample_rate).min(values.len());
+    let mut sorted = values.to_vec();
+    let (_, &mut value, _) = sorted.select_nth_unstable_by(span - 1, |a, b| b.total_cmp(a));
+    let start = values.iter().position(|&v| v >= value).unwrap_or(0);
+    let end = values.iter().rposition(|&v| v >= value).unwrap_or(0);
+    (value, start, end)
+}
+
+/// Highest level exceeded continuously for 3 ms: the largest minimum over any 3 ms window.
+/// Returns the value and the first and last sample of the window.
+fn continuous_three_ms_clip(values: &[f64], sample_rate: f64) -> (f64, usize, usize) {
+    let span = three_ms_span(sample_rate);
+    if values.len() <= span {
+        let value = values.iter().copied().fold(f64::INFINITY, f64::min);
+        return (value, 0, values.len().saturating_sub(1));
+    }
+
+    // Sliding window minimum over windows of `span + 1` samples (3 ms long).
+    let mut best = (f64::NEG_INFINITY, 0, span);
+    let mut window: VecDeque<usize> = VecDeque::new();
+    for (index, &value) in values.iter().enumerate() {
+        while window.back().is_some_and(|&last| values[last] >= value) {
+            window.pop_back();
+        }
+        window.push_back(index);
+        if window.front().is_some_and(|&first| first + span < index) {
+            window.pop_front();
+        }
+        if index >= span {
+            let minimum = values[window[0]];
+            if minimum > best.0 {
+                best = (minimum, index - span, index);
+            }
+        }
+    }
+    best
+}
+
+fn nij(
+    fz: &RoleChannel,
+    my: &RoleChannel,
+    fx: Option<&RoleChannel>,
+    intercepts: &NijIntercepts,
+) -> Result<Vec<CriterionResult>> {
+    let sample_rate = fz.data.sample_rate;
+    check_rate(ChannelRole::NeckMy, my, sample_rate)?;
+    let fz_scale = scale_to_si(ChannelRole::NeckFz, fz, Quantity::Force)?;
+    check_moment_units(my)?;
+    let fx_scale = fx
+        .map(|fx| {
+            check_rate(ChannelRole::NeckFx, fx, sample_rate)?;
+            scale_to_si(ChannelRole::NeckFx, fx, Quantity::Force)
+        })
+        .transpose()?;
+
+    let mut tracks = vec![fz.track_name.to_string(), my.track_name.to_string()];
+    if let Some(fx) = fx {
+        tracks.push(fx.track_name.to_string());
+    }
+
+    // Best (value, sample) for NTE, NTF, NCE and NCF.
+    let mut best = [(0.0, 0usize); 4];
+    let len = fz.data.time_series.len().min(my.data.time_series.len());
+    for index in 0..len {
+        let force = fz.data.time_series[index] * fz_scale;
+        let mut moment = my.data.time_series[index];
+        if let (Some(fx), Some(scale)) = (fx, fx_scale) {
+            if let Some(shear) = fx.data.time_series.get(index) {
+                moment -= intercepts.condyle_offset * shear * scale;
+            }
+        }
+        let tension = force >= 0.0;
+        let flexion = moment >= 0.0;
+        let value = force.abs()
+            / if tension {
+                intercepts.tension
+            } else {
+                intercepts.compression
+            }
+            + moment.abs()
+                / if flexion {
+                    intercepts.flexion
+                } else {
+                    intercepts.extension
+                };
+        let category = match (tension, flexion) {
+            (true, false) => 0,
+            (true, true) => 1,
+            (false, false) => 2,
+            (false, true) => 3,
+        };
+        if value > best[category].0 {
+            best[category] = (value, index);
+        }
+    }
+
+    let time = |index: usize| fz.start_time + index as f64 / sample_rate;
+    let mut results: Vec<CriterionResult> = ["NTE", "NTF", "NCE", "NCF"]
+        .iter()
+        .zip(best)
+        .map(|(name, (value, index))| CriterionResult {
+            criterion: format!("Nij {name}"),
+            value,
+            units: String::new(),
+            window_start: time(index),
+            window_end: time(index),
+            tracks: tracks.clone(),
+        })
+        .collect();
+    let overall =
+        results
+            .iter()
+            .cloned()
+            .fold(None::<CriterionResult>, |best, result| match best {
+                Some(best) if best.value >= result.value => Some(best),
+                _ => Some(result),
+            });
+    if let Some(overall) = overall {
+        results.push(CriterionResult {
+            criterion: "Nij".to_string(),
+            ..overall
+        });
+    }
+    Ok(results)
+}
+
+fn check_moment_units(channel: &RoleChannel) -> Result<()> {
+    let units = channel
+        .data
+        .units
+        .trim()
+        .to_ascii_lowercase()
+        .replace(' ', "");
+    if ["nm", "n.m", "n*m", "n-m", "n·m"].contains(&units.as_str()) {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Track '{}' (neck-my) is in '{}'; Nij needs the moment in N·m.",
+            channel.track_name,
+            channel.data.units
+        ))
+    }
+}
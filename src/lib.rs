@@ -1,3 +1,27 @@
 pub mod conversion;
+pub mod csv;
+pub mod derived;
 pub mod dts;
+pub mod export;
+pub mod expression;
+pub mod filter;
+pub mod health;
+pub mod id_template;
+pub mod injury;
+pub mod mat;
+pub mod mdf;
+pub mod mme;
+pub mod npz;
+pub mod plot;
+pub mod png;
+pub mod quality;
+pub mod raw;
+pub mod resample;
+pub mod sidecar;
+pub mod spectral;
+pub mod srs;
+pub mod statistics;
+pub mod tdms;
 pub mod uff;
+pub mod units;
+pub mod wav;
@@ -1,29 +1,57 @@
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use dts_to_uff_converter::conversion::{
-    convert_with_progress, ConversionProgress, OutputFormat, SampleSlice,
+    convert_with_progress_and_options, read_track_names, ConversionOptions, ConversionProgress,
+    FrfOptions, InjuryOptions, OutputFormat, SampleSlice, SrsOptions,
 };
+use dts_to_uff_converter::csv::{parse_delimiter, CsvRateHandling, CsvSettings};
+use dts_to_uff_converter::derived::DerivedChannelSpec;
+use dts_to_uff_converter::dts::DtsReader;
+use dts_to_uff_converter::expression::ExpressionChannel;
+use dts_to_uff_converter::filter::{
+    CfcClass, CfcFiltering, ChannelCfcTarget, ChannelFilterTarget, FilterChain, FilterSpec,
+};
+use dts_to_uff_converter::health::{self, HealthSettings};
+use dts_to_uff_converter::id_template::{IdLineOverride, IdPreset};
+use dts_to_uff_converter::injury::{NijIntercepts, RoleAssignment};
+use dts_to_uff_converter::mme::{MmeChannelCode, MmeChannelCodes};
+use dts_to_uff_converter::plot::{self, PlotFormat, PlotSettings};
+use dts_to_uff_converter::quality::QualitySettings;
+use dts_to_uff_converter::spectral::{
+    Averaging, FrfEstimator, SpectralSettings, SpectrumKind, Window,
+};
+use dts_to_uff_converter::srs::{SrsKind, SrsSettings};
+use dts_to_uff_converter::statistics::{self, StatisticsFormat};
+use dts_to_uff_converter::units::{ChannelUnitTarget, QuantityTarget, UnitConversion};
 use indicatif::{ProgressBar, ProgressStyle};
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Path to the input directory containing DTS files (.dts, .chn)
-    #[arg(short, long)]
-    input_dir: PathBuf,
+    #[arg(short, long, required = true)]
+    input_dir: Option<PathBuf>,
 
-    /// Output format for the generated UFF file (`ascii` or `binary`)
+    /// Output format: an `ascii` or `binary` UFF file, an ISO/TS 13499 (`mme`) test directory, a
+    /// MATLAB (`mat`) file with the fields returned by `DTS.read` except `ADC`, delimited text
+    /// (`csv`), a 32-bit float `wav` file, a NumPy `npz` archive, an ASAM MDF 4 (`mdf`) file, an
+    /// NI `tdms` file or a `raw` directory with the ADC counts and their exact scaling
     #[arg(short, long, value_enum, default_value_t = OutputFormat::Ascii)]
     format: OutputFormat,
 
     /// Path to the .txt file containing track names, one per line or comma-separated
-    #[arg(short, long)]
-    tracks: PathBuf,
+    #[arg(short, long, required = true)]
+    tracks: Option<PathBuf>,
 
-    /// Output UFF file path
-    #[arg(short, long)]
-    output: PathBuf,
+    /// Output UFF file path, or the MME test directory for `--format mme`. A `.tsv` extension
+    /// selects tab-separated text for `--format csv`
+    #[arg(short, long, required = true)]
+    output: Option<PathBuf>,
 
     /// Sample range to export for every track, in the form `start:end` using zero-based indices.
     /// The start index is inclusive, the end index is exclusive, and values must be non-negative.
@@ -34,6 +62,453 @@ struct Args {
     /// Comma-separated list of track names to write into the output file.
     #[arg(long = "track-list-output", value_parser = parse_track_selection)]
     track_list_output: Option<Vec<String>>,
+
+    /// Filter every channel with a SAE J211 / ISO 6487 channel frequency class (60, 180, 600 or
+    /// 1000) before export. The class is recorded in ID record 3.
+    #[arg(long, value_enum)]
+    cfc: Option<CfcClass>,
+
+    /// Filter a single track with a CFC class, as `track=class` (e.g. `A1=180`). Use
+    /// `track=none` to leave a track unfiltered. Overrides `--cfc` for that track. Can be repeated.
+    #[arg(long = "channel-cfc", value_name = "TRACK=CLASS")]
+    channel_cfc: Vec<ChannelCfcTarget>,
+
+    /// Apply an IIR filter to every channel after the CFC filter, as `type:frequency[:option...]`
+    /// (e.g. `lowpass:1000:order=4`, `highpass:0.5:order=2`, `bandpass:10-2000:design=bessel`,
+    /// `notch:50:q=30`). Filters run forward and backward (zero phase) unless `causal` is given.
+    /// Can be repeated; filters are applied in order.
+    #[arg(long = "filter", value_name = "SPEC")]
+    filters: Vec<FilterSpec>,
+
+    /// Apply an IIR filter to a single track, as `track=spec` (e.g. `A1=highpass:0.5`). Can be
+    /// repeated.
+    #[arg(long = "channel-filter", value_name = "TRACK=SPEC")]
+    channel_filters: Vec<ChannelFilterTarget>,
+
+    /// JSON file listing filters to apply before those given with `--filter`, e.g.
+    /// `{"filters": [{"type": "notch", "frequency": 50, "q": 30, "tracks": ["A1"]}]}`
+    #[arg(long = "filter-config", value_name = "PATH")]
+    filter_config: Option<PathBuf>,
+
+    /// Resample every channel to this rate, in Hz, after filtering (e.g. `--resample 20000`).
+    /// Uses an anti-aliased polyphase filter; the effective rate of each track is reported.
+    #[arg(long = "resample", value_name = "HZ")]
+    resample_rate: Option<f64>,
+
+    /// Append a channel integrated or differentiated from an exported track, as
+    /// `track=operation[:option...]`. Operations are `integrate` (or `velocity`),
+    /// `double-integrate` (or `displacement`) and `differentiate`. Options are `name=TRACK`,
+    /// `offset=N` or `offset=mean` (remove the mean of the first N samples or of the whole record
+    /// first) and `highpass=HZ` (drift control after every step), e.g.
+    /// `A1=displacement:offset=200:highpass=0.5`. Acceleration is converted to m/s and m. Can be
+    /// repeated.
+    #[arg(long = "derive", value_name = "TRACK=OPERATION")]
+    derived: Vec<DerivedChannelSpec>,
+
+    /// Append a channel computed sample by sample from other tracks, as
+    /// `name[units]=expression`, e.g. `HeadR[g]=sqrt(HX^2+HY^2+HZ^2)` or `Fsum[kN]=LC1+LC2`.
+    /// Supports `+ - * / ^`, parentheses, `sqrt`, `abs`, `exp`, `ln`, `log10`, `sin`, `cos`,
+    /// `min` and `max`; wrap track names with other characters in braces (`{Head X}`). Units
+    /// default to those of the first track used. Inputs at another rate are resampled. Can be
+    /// repeated.
+    #[arg(long = "expression", value_name = "NAME[UNITS]=EXPR")]
+    expressions: Vec<ExpressionChannel>,
+
+    /// Convert every channel of a quantity to the given unit, as `quantity=unit`
+    /// (e.g. `acceleration=m/s^2`, `force=kN`, `pressure=kPa`). Can be repeated.
+    #[arg(long = "unit", value_name = "QUANTITY=UNIT")]
+    units: Vec<QuantityTarget>,
+
+    /// Convert a single track to the given unit, as `track=unit`. Overrides `--unit` and `--si`
+    /// for that track. Can be repeated.
+    #[arg(long = "channel-unit", value_name = "TRACK=UNIT")]
+    channel_units: Vec<ChannelUnitTarget>,
+
+    /// Convert acceleration, force and pressure channels to SI units (m/s^2, N, Pa).
+    /// `--unit` entries override the preset for their quantity.
+    #[arg(long)]
+    si: bool,
+
+    /// Layout of the UFF ID records 1-5. `matlab` reproduces the MATLAB reference output;
+    /// `metadata` fills them with test, description, date, serial, sensitivity, sample rate and
+    /// the excitation of sensors proportional to it.
+    #[arg(long, value_enum, default_value_t = IdPreset::Matlab)]
+    id_preset: IdPreset,
+
+    /// Override one ID record with a template, as `line=template` (line 1 to 5), e.g.
+    /// `1={test} {description} SN:{serial} {sensitivity} mV/{eu}`. Placeholders: {track}, {name},
+    /// {description}, {serial}, {sensitivity}, {eu}, {units}, {excitation}, {sample_rate},
+    /// {test}, {test_description}, {date}, {filter}. Can be repeated.
+    #[arg(long = "id-line", value_name = "LINE=TEMPLATE")]
+    id_lines: Vec<IdLineOverride>,
+
+    /// Write a dataset 1858 block before every 58 record, describing the windowing, averaging,
+    /// filtering, weighting and scaling applied during conversion.
+    #[arg(long = "uff1858")]
+    uff1858: bool,
+
+    /// Frequency-domain records to write after every time history: `auto-power` (function
+    /// type 2), `psd` (9) or `linear` (spectrum, 12). Comma-separated or repeated.
+    #[arg(long = "spectrum", value_enum, value_delimiter = ',')]
+    spectra: Vec<SpectrumKind>,
+
+    /// Reference (excitation) track for FRF and coherence estimation. Enables complex FRF
+    /// records (function type 4) after every response track.
+    #[arg(long, value_name = "TRACK")]
+    frf_reference: Option<String>,
+
+    /// Comma-separated response tracks for `--frf-reference`. Defaults to every other exported
+    /// track.
+    #[arg(long, value_parser = parse_track_selection, requires = "frf_reference")]
+    frf_responses: Option<Vec<String>>,
+
+    /// FRF estimator: `h1` (noise on the response) or `h2` (noise on the reference).
+    #[arg(long, value_enum, default_value_t = FrfEstimator::H1)]
+    frf_estimator: FrfEstimator,
+
+    /// Also write the coherence (function type 6) of every FRF.
+    #[arg(long, requires = "frf_reference")]
+    coherence: bool,
+
+    /// Window applied to every FFT block.
+    #[arg(long, value_enum, default_value_t = Window::Hann)]
+    window: Window,
+
+    /// Number of samples per FFT block. The frequency resolution is the sample rate divided by
+    /// the block size.
+    #[arg(long, default_value_t = 4096)]
+    block_size: usize,
+
+    /// Overlap between consecutive FFT blocks, in percent (0 to <100).
+    #[arg(long, default_value_t = 50.0)]
+    overlap: f64,
+
+    /// How the block spectra are combined.
+    #[arg(long, value_enum, default_value_t = Averaging::Linear)]
+    averaging: Averaging,
+
+    /// Shock response spectrum curves to write after every time history: `maximax`, `primary`
+    /// and/or `residual`. Uses the Smallwood ramp-invariant filter and an uneven, log-spaced
+    /// frequency abscissa. Comma-separated or repeated.
+    #[arg(long = "srs", value_enum, value_delimiter = ',')]
+    srs: Vec<SrsKind>,
+
+    /// SRS damping as a fraction of critical damping (0.05 corresponds to Q = 10).
+    #[arg(long, default_value_t = 0.05)]
+    srs_damping: f64,
+
+    /// Lowest SRS natural frequency in Hz.
+    #[arg(long, default_value_t = 10.0)]
+    srs_min_frequency: f64,
+
+    /// Highest SRS natural frequency in Hz. Must be below the Nyquist frequency.
+    #[arg(long, default_value_t = 10_000.0)]
+    srs_max_frequency: f64,
+
+    /// Number of SRS natural frequencies per octave.
+    #[arg(long, default_value_t = 12)]
+    srs_points_per_octave: u32,
+
+    /// Also write the SRS curves of every exported track to this CSV file.
+    #[arg(long, requires = "srs")]
+    srs_csv: Option<PathBuf>,
+
+    /// Assign exported tracks to dummy channel roles for the injury criteria, as `role=track`
+    /// (e.g. `head-x=A1,head-y=A2,head-z=A3`). Roles are `head-x`, `head-y`, `head-z`
+    /// (HIC15, HIC36, head 3 ms clip), `chest-x`, `chest-y`, `chest-z` (chest 3 ms clip),
+    /// `chest-deflection`, and `neck-fz`, `neck-my` and optionally `neck-fx` (Nij). Filter the
+    /// tracks with `--cfc`/`--channel-cfc` first. Can be repeated.
+    #[arg(long = "injury-role", value_name = "ROLE=TRACK", value_delimiter = ',')]
+    injury_roles: Vec<RoleAssignment>,
+
+    /// Nij intercepts as `tension,compression,flexion,extension[,condyle_offset]` in N, N·m and m.
+    /// Defaults to the Hybrid III 50th percentile male (6806,6160,310,135,0.01778).
+    #[arg(long, value_name = "VALUES", requires = "injury_roles")]
+    nij_intercepts: Option<NijIntercepts>,
+
+    /// Write the injury criteria, with the time window of each, to this JSON file.
+    #[arg(long, value_name = "PATH", requires = "injury_roles")]
+    injury_json: Option<PathBuf>,
+
+    /// Write the injury criteria, with the time window of each, to this CSV file.
+    #[arg(long, value_name = "PATH", requires = "injury_roles")]
+    injury_csv: Option<PathBuf>,
+
+    /// Print min, max, mean, RMS, peak-to-peak, crest factor and time of the absolute peak of
+    /// every exported time history, as a `table` or `json`.
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    statistics: Option<StatisticsFormat>,
+
+    /// Fail the conversion when a track is clipped, flat or noisy instead of reporting warnings.
+    #[arg(long, conflicts_with = "no_quality_checks")]
+    strict_quality: bool,
+
+    /// Skip the clipping, flat-channel and noise checks on the raw ADC data.
+    #[arg(long)]
+    no_quality_checks: bool,
+
+    /// Standard deviation, in ADC counts, below which a track is reported as flat.
+    #[arg(long, value_name = "COUNTS", default_value_t = 0.5)]
+    flat_threshold: f64,
+
+    /// Report a track as noisy when its noise exceeds this multiple of the pre-test noise
+    /// recorded in the channel header.
+    #[arg(long, value_name = "RATIO", default_value_t = 10.0)]
+    noise_ratio: f64,
+
+    /// Assign an ISO/TS 13499 channel code to an exported, derived or expression track for
+    /// `--format mme`, as `track=code` (e.g. `A1=11HEAD0000H3ACXA`). A `?` as the last character
+    /// is replaced by the filter class of the track. Can be repeated.
+    #[arg(
+        long = "mme-channel-code",
+        value_name = "TRACK=CODE",
+        value_delimiter = ','
+    )]
+    mme_channel_codes: Vec<MmeChannelCode>,
+
+    /// Read `track=code` MME channel codes from a text file, one per line. Codes given with
+    /// `--mme-channel-code` take precedence.
+    #[arg(long, value_name = "PATH")]
+    mme_code_file: Option<PathBuf>,
+
+    /// Field delimiter of `--format csv`: a single character or `comma`, `semicolon`, `tab`,
+    /// `space` or `pipe`. Defaults to a tab for `.tsv` files and a comma otherwise.
+    #[arg(long, value_name = "DELIMITER", value_parser = parse_delimiter)]
+    csv_delimiter: Option<char>,
+
+    /// Decimal separator of `--format csv`, `.` or `,`.
+    #[arg(long, value_name = "CHAR", default_value_t = '.')]
+    csv_decimal: char,
+
+    /// Digits after the decimal point for `--format csv`. Omit to write every significant digit.
+    #[arg(long, value_name = "DIGITS")]
+    csv_precision: Option<usize>,
+
+    /// Add units, sensitivity and serial number rows below the column names of `--format csv`.
+    #[arg(long)]
+    csv_metadata: bool,
+
+    /// How `--format csv` handles tracks sampled at different rates: `split` writes one file per
+    /// rate (`<name>_<rate>Hz.csv`), `align` interpolates every track onto the highest rate.
+    #[arg(long, value_enum, default_value_t = CsvRateHandling::Split)]
+    csv_rates: CsvRateHandling,
+
+    /// Scale every channel of `--format wav` to a peak of 1.0. The scale factors are stored in
+    /// the file so that engineering values can be recovered.
+    #[arg(long)]
+    wav_normalize: bool,
+
+    /// Write a JSON sidecar with the provenance of the conversion to this file: the source
+    /// folder and `.dts` SHA-256, the channel plan, every channel's `.dts` metadata, the applied
+    /// slice and processing, the output format and the converter version.
+    #[arg(long, value_name = "PATH")]
+    metadata_json: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Report the zero shift, diagnostics levels and noise floor of every sensor from the
+    /// pre- and post-test levels in the `.chn` headers.
+    SensorHealth(SensorHealthArgs),
+    /// Plot the time history of tracks to SVG or PNG images, one image per track or a grid of
+    /// tracks in a single image.
+    Plot(PlotArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct SensorHealthArgs {
+    /// Path to the input directory containing DTS files (.dts, .chn)
+    #[arg(short, long)]
+    input_dir: PathBuf,
+
+    /// Optional .txt file with track names used to label the channels. Defaults to the channel
+    /// names in the .dts file.
+    #[arg(short, long)]
+    tracks: Option<PathBuf>,
+
+    /// Largest accepted shift between the pre- and post-test zero, in percent of full scale.
+    #[arg(long, value_name = "PERCENT", default_value_t = 1.0)]
+    zero_shift_tolerance: f64,
+
+    /// Largest accepted deviation of the shunt or calibration signal level from the value
+    /// configured in the .dts file, in percent.
+    #[arg(long, value_name = "PERCENT", default_value_t = 5.0)]
+    diagnostics_tolerance: f64,
+
+    /// Print the report as JSON instead of a table.
+    #[arg(long)]
+    json: bool,
+
+    /// Exit with an error when a sensor fails a check.
+    #[arg(long)]
+    strict: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct PlotArgs {
+    /// Path to the input directory containing DTS files (.dts, .chn)
+    #[arg(short, long)]
+    input_dir: PathBuf,
+
+    /// Optional .txt file with track names used to label the channels. Defaults to the channel
+    /// names in the .dts file.
+    #[arg(short, long)]
+    tracks: Option<PathBuf>,
+
+    /// Output directory receiving one `<track>.<format>` image per track, or the image file with
+    /// `--grid`. Characters other than letters, digits, `-`, `_` and `.` in track names become
+    /// `_`, and repeated names are numbered (`<track>_2.<format>`).
+    #[arg(short, long)]
+    output: PathBuf,
+
+    /// Image format.
+    #[arg(short, long, value_enum, default_value_t = PlotFormat::Svg)]
+    format: PlotFormat,
+
+    /// Comma-separated list of track names to plot. Defaults to every track.
+    #[arg(long = "track", value_name = "TRACKS", value_delimiter = ',')]
+    selection: Vec<String>,
+
+    /// Sample range to plot for every track, in the form `start:end` using zero-based indices.
+    #[arg(long, value_parser = parse_sample_slice)]
+    slice: Option<SampleSlice>,
+
+    /// Draw every track into a single image, as a grid of `--columns` panels per row.
+    #[arg(long)]
+    grid: bool,
+
+    /// Panels per row of `--grid`.
+    #[arg(long, default_value_t = 1, requires = "grid")]
+    columns: usize,
+
+    /// Mark and label the minimum and maximum of every track.
+    #[arg(long)]
+    markers: bool,
+
+    /// Width of the panel of one track, in pixels.
+    #[arg(long, default_value_t = 800)]
+    width: u32,
+
+    /// Height of the panel of one track, in pixels.
+    #[arg(long, default_value_t = 300)]
+    height: u32,
+}
+
+impl Args {
+    fn unit_conversion(&self) -> UnitConversion {
+        let mut conversion = if self.si {
+            UnitConversion::si()
+        } else {
+            UnitConversion::default()
+        };
+        for target in &self.units {
+            conversion.set_quantity_target(target.quantity, target.unit);
+        }
+        for target in &self.channel_units {
+            conversion.set_channel_target(target.track_name.clone(), target.unit);
+        }
+        conversion
+    }
+
+    fn cfc_filtering(&self) -> CfcFiltering {
+        let mut filtering = CfcFiltering::default();
+        filtering.set_default(self.cfc);
+        for target in &self.channel_cfc {
+            filtering.set_channel(target.track_name.clone(), target.class);
+        }
+        filtering
+    }
+
+    fn filter_chain(&self) -> Result<FilterChain> {
+        let mut chain = FilterChain::default();
+        if let Some(path) = &self.filter_config {
+            chain.extend_from_file(path)?;
+        }
+        for spec in &self.filters {
+            chain.push(spec.clone(), None);
+        }
+        for target in &self.channel_filters {
+            chain.push(target.spec.clone(), Some(vec![target.track_name.clone()]));
+        }
+        Ok(chain)
+    }
+
+    fn spectral_settings(&self) -> SpectralSettings {
+        SpectralSettings {
+            window: self.window,
+            block_size: self.block_size,
+            overlap_percent: self.overlap,
+            averaging: self.averaging,
+        }
+    }
+
+    fn injury_options(&self) -> Option<InjuryOptions> {
+        if self.injury_roles.is_empty() {
+            return None;
+        }
+        Some(InjuryOptions {
+            roles: self.injury_roles.clone(),
+            intercepts: self.nij_intercepts.clone().unwrap_or_default(),
+            json_output: self.injury_json.clone(),
+            csv_output: self.injury_csv.clone(),
+        })
+    }
+
+    fn quality_settings(&self) -> QualitySettings {
+        QualitySettings {
+            enabled: !self.no_quality_checks,
+            strict: self.strict_quality,
+            flat_threshold: self.flat_threshold,
+            noise_ratio: self.noise_ratio,
+            ..QualitySettings::default()
+        }
+    }
+
+    fn mme_codes(&self) -> Result<MmeChannelCodes> {
+        let mut codes = MmeChannelCodes::default();
+        if let Some(path) = &self.mme_code_file {
+            codes.extend_from_file(path)?;
+        }
+        for code in &self.mme_channel_codes {
+            codes.insert(code.clone());
+        }
+        Ok(codes)
+    }
+
+    fn csv_settings(&self) -> CsvSettings {
+        CsvSettings {
+            delimiter: self.csv_delimiter,
+            decimal_separator: self.csv_decimal,
+            precision: self.csv_precision,
+            metadata: self.csv_metadata,
+            rates: self.csv_rates,
+        }
+    }
+
+    fn srs_options(&self) -> Option<SrsOptions> {
+        if self.srs.is_empty() {
+            return None;
+        }
+        Some(SrsOptions {
+            kinds: self.srs.clone(),
+            settings: SrsSettings {
+                damping: self.srs_damping,
+                min_frequency: self.srs_min_frequency,
+                max_frequency: self.srs_max_frequency,
+                points_per_octave: self.srs_points_per_octave,
+            },
+            csv_output: self.srs_csv.clone(),
+        })
+    }
+
+    fn frf_options(&self) -> Option<FrfOptions> {
+        self.frf_reference.as_ref().map(|reference| FrfOptions {
+            reference: reference.clone(),
+            responses: self.frf_responses.clone(),
+            estimator: self.frf_estimator,
+            coherence: self.coherence,
+        })
+    }
 }
 
 fn parse_sample_slice(value: &str) -> Result<SampleSlice, String> {
@@ -55,8 +530,80 @@ fn parse_track_selection(value: &str) -> Result<Vec<String>, String> {
     }
 }
 
+fn sensor_health(args: &SensorHealthArgs) -> Result<()> {
+    let reader = DtsReader::new(&args.input_dir)?;
+    let track_names = args
+        .tracks
+        .as_deref()
+        .map(read_track_names)
+        .transpose()?
+        .unwrap_or_default();
+    let settings = HealthSettings {
+        zero_shift_tolerance: args.zero_shift_tolerance,
+        diagnostics_tolerance: args.diagnostics_tolerance,
+    };
+    let report = health::sensor_health(&reader, &track_names, &settings)?;
+
+    let mut stdout = std::io::stdout().lock();
+    if args.json {
+        health::write_json(&mut stdout, &report)?;
+    } else {
+        health::write_table(&mut stdout, &report)?;
+    }
+
+    let failed = report.iter().filter(|health| !health.is_healthy()).count();
+    if args.strict && failed > 0 {
+        anyhow::bail!("{failed} sensor(s) failed the health checks.");
+    }
+    Ok(())
+}
+
+fn plot_tracks(args: &PlotArgs) -> Result<()> {
+    let reader = DtsReader::new(&args.input_dir)?;
+    let track_names = args
+        .tracks
+        .as_deref()
+        .map(read_track_names)
+        .transpose()?
+        .unwrap_or_default();
+    let settings = PlotSettings {
+        width: args.width,
+        height: args.height,
+        columns: args.columns,
+        markers: args.markers,
+    };
+    settings.validate()?;
+    let tracks = plot::read_tracks(
+        &reader,
+        &track_names,
+        (!args.selection.is_empty()).then_some(args.selection.as_slice()),
+        args.slice,
+    )?;
+
+    let written = if args.grid {
+        std::fs::write(&args.output, plot::render(&tracks, args.format, &settings)?)?;
+        vec![args.output.clone()]
+    } else {
+        plot::write_track_images(&args.output, &tracks, args.format, &settings)?
+    };
+    for path in written {
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
 fn main() -> Result<()> {
     let args = Args::parse();
+    match &args.command {
+        Some(Command::SensorHealth(health_args)) => return sensor_health(health_args),
+        Some(Command::Plot(plot_args)) => return plot_tracks(plot_args),
+        None => {}
+    }
+    let (Some(input_dir), Some(tracks), Some(output)) =
+        (&args.input_dir, &args.tracks, &args.output)
+    else {
+        unreachable!("clap requires the input directory, tracks and output without a subcommand");
+    };
 
     let bar = ProgressBar::new(0);
     bar.set_style(
@@ -65,20 +612,42 @@ fn main() -> Result<()> {
             .progress_chars("#>-"),
     );
 
-    let _report = convert_with_progress(
-        &args.input_dir,
-        &args.tracks,
-        &args.output,
+    let options = ConversionOptions {
+        slice: args.slice,
+        track_list_filter: args.track_list_output.clone(),
+        cfc: args.cfc_filtering(),
+        filters: args.filter_chain()?,
+        resample_rate: args.resample_rate,
+        units: args.unit_conversion(),
+        id_template: args.id_preset.template(args.id_lines.iter().cloned()),
+        write_1858: args.uff1858,
+        spectra: args.spectra.clone(),
+        spectral: args.spectral_settings(),
+        frf: args.frf_options(),
+        srs: args.srs_options(),
+        derived: args.derived.clone(),
+        expressions: args.expressions.clone(),
+        injury: args.injury_options(),
+        quality: args.quality_settings(),
+        mme_codes: args.mme_codes()?,
+        csv: args.csv_settings(),
+        wav_normalize: args.wav_normalize,
+        metadata_json: args.metadata_json.clone(),
+    };
+
+    let report = convert_with_progress_and_options(
+        input_dir,
+        tracks,
+        output,
         args.format,
-        args.slice,
-        args.track_list_output.as_deref(),
+        &options,
         |update| match update {
             ConversionProgress::Started {
                 track_name_count,
                 channel_count,
             } => {
                 println!("Found {} track names.", track_name_count);
-                println!("Reading metadata from folder: {:?}", &args.input_dir);
+                println!("Reading metadata from folder: {:?}", input_dir);
                 println!("Will export {} channel(s).", channel_count);
                 if args.track_list_output.is_none() && track_name_count != channel_count {
                     println!(
@@ -105,5 +674,81 @@ fn main() -> Result<()> {
         },
     )?;
 
+    for (track_name, class) in &report.cfc_classes {
+        println!("Filtered '{}' with {}.", track_name, class);
+    }
+    for (track_name, filters) in &report.filters {
+        let specs: Vec<String> = filters.iter().map(ToString::to_string).collect();
+        println!("Filtered '{}' with {}.", track_name, specs.join(", "));
+    }
+    for (track_name, resampling) in &report.resampling {
+        println!(
+            "Resampled '{}' from {} Hz to {} Hz (x{}/{}).",
+            track_name, resampling.from, resampling.to, resampling.up, resampling.down
+        );
+    }
+    for (track_name, change) in &report.unit_changes {
+        println!(
+            "Converted '{}' from {} to {} (x{}).",
+            track_name, change.from, change.to, change.factor
+        );
+    }
+    for (track_name, derivation) in &report.derived_channels {
+        println!(
+            "Derived '{}' ({}) from '{}' ({}).",
+            track_name, derivation.units, derivation.source, derivation.operation
+        );
+    }
+    for channel in &report.expression_channels {
+        println!("Computed {}.", channel);
+    }
+    if report.spectrum_count > 0 {
+        println!("Wrote {} spectrum record(s).", report.spectrum_count);
+    }
+    if report.frf_count > 0 {
+        println!("Wrote {} FRF record(s).", report.frf_count);
+    }
+    if report.srs_count > 0 {
+        println!("Wrote {} SRS record(s).", report.srs_count);
+    }
+    if let Some(path) = args.srs_csv.as_ref() {
+        println!("Wrote SRS CSV to {:?}.", path);
+    }
+    if let Some(injury) = report.injury.as_ref() {
+        for result in &injury.criteria {
+            println!(
+                "{}: {:.3}{}{} ({:.5} s to {:.5} s)",
+                result.criterion,
+                result.value,
+                if result.units.is_empty() { "" } else { " " },
+                result.units,
+                result.window_start,
+                result.window_end
+            );
+        }
+    }
+    if let Some(path) = args.injury_json.as_ref() {
+        println!("Wrote injury criteria JSON to {:?}.", path);
+    }
+    if let Some(path) = args.injury_csv.as_ref() {
+        println!("Wrote injury criteria CSV to {:?}.", path);
+    }
+    if let Some(path) = args.metadata_json.as_ref() {
+        println!("Wrote metadata sidecar to {:?}.", path);
+    }
+    if args.format == OutputFormat::Mme {
+        println!(
+            "Wrote {} MME channel(s) to {:?}.",
+            report.statistics.len(),
+            output
+        );
+    }
+    for warning in &report.warnings {
+        println!("Warning: {}", warning);
+    }
+    if let Some(format) = args.statistics {
+        statistics::write_statistics(&mut std::io::stdout().lock(), &report.statistics, format)?;
+    }
+
     Ok(())
 }
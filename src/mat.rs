@@ -0,0 +1,310 @@
+use crate::export::{ChannelWriter, ExportChannel};
+use anyhow::{anyhow, Context, Result};
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const MI_INT8: u32 = 1;
+const MI_UINT16: u32 = 4;
+const MI_INT32: u32 = 5;
+const MI_UINT32: u32 = 6;
+const MI_DOUBLE: u32 = 9;
+const MI_MATRIX: u32 = 14;
+
+const MX_CELL_CLASS: u32 = 1;
+const MX_STRUCT_CLASS: u32 = 2;
+const MX_CHAR_CLASS: u32 = 4;
+const MX_DOUBLE_CLASS: u32 = 6;
+
+/// Name of the struct variable written to the file.
+pub const VARIABLE_NAME: &str = "dts";
+
+/// Fields of the struct: the outputs of `DTS.read` except the raw `ADC` matrix, followed by the
+/// exported track names. `data` comes first because it is streamed as channels arrive.
+const FIELD_NAMES: [&str; 6] = [
+    "data",
+    "sampleRate",
+    "pn",
+    "channelInfoMetadata",
+    "timeOfFirstSamples",
+    "trackNames",
+];
+/// Space reserved for every field name, including the terminating NUL.
+const FIELD_NAME_LENGTH: usize = 32;
+/// Rows of `channelInfoMetadata`: serial number, description, EU, display order and an empty
+/// row, as in `DTS.m`.
+const METADATA_ROWS: usize = 5;
+
+/// Writes a MATLAB MAT-file (level 5) holding the same values as `DTS.read`.
+///
+/// The file contains one struct, `dts`, with the fields `data` (samples by channels),
+/// `sampleRate`, `pn` (the input directory), `channelInfoMetadata` (a 5-by-channels cell array),
+/// `timeOfFirstSamples` (relative to the trigger) and `trackNames`. The sixth `DTS.read` output,
+/// `ADC`, is not written. Every channel must share the sample rate and length of the first one.
+pub struct MatWriter {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    /// Bytes written so far.
+    position: u64,
+    pn: String,
+    layout: Option<DataLayout>,
+    sample_rate: f64,
+    metadata: Vec<[String; 4]>,
+    start_times: Vec<f64>,
+    track_names: Vec<String>,
+}
+
+/// Offsets of the size fields completed by [`MatWriter::finish`].
+struct DataLayout {
+    rows: usize,
+    struct_size_offset: u64,
+    data_size_offset: u64,
+    columns_offset: u64,
+    real_size_offset: u64,
+}
+
+impl MatWriter {
+    /// Create the MAT-file at `path`. `input_dir` is stored as `pn`.
+    pub fn create(path: &Path, input_dir: &Path) -> Result<Self> {
+        let file =
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut writer = Self {
+            path: path.to_path_buf(),
+            writer: BufWriter::with_capacity(8 * 1024 * 1024, file),
+            position: 0,
+            pn: input_dir.display().to_string(),
+            layout: None,
+            sample_rate: 0.0,
+            metadata: Vec::new(),
+            start_times: Vec::new(),
+            track_names: Vec::new(),
+        };
+
+        let mut header = format!(
+            "MATLAB 5.0 MAT-file, written by {} {}",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION")
+        )
+        .into_bytes();
+        header.resize(116, b' ');
+        header.extend([0; 8]);
+        header.extend(0x0100u16.to_le_bytes());
+        header.extend(b"IM");
+        writer.write(&header)?;
+        Ok(writer)
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.writer.write_all(bytes)?;
+        self.position += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Write the struct header and the header of the `data` matrix, whose columns follow.
+    fn begin(&mut self, rows: usize) -> Result<()> {
+        let struct_size_offset = self.position + 4;
+        let mut names = Vec::with_capacity(FIELD_NAMES.len() * FIELD_NAME_LENGTH);
+        for name in FIELD_NAMES {
+            let mut field = name.as_bytes().to_vec();
+            field.resize(FIELD_NAME_LENGTH, 0);
+            names.extend(field);
+        }
+        let mut head = tag(MI_MATRIX, 0);
+        head.extend(element(MI_UINT32, &array_flags(MX_STRUCT_CLASS)));
+        head.extend(element(MI_INT32, &dimensions(1, 1)));
+        head.extend(element(MI_INT8, VARIABLE_NAME.as_bytes()));
+        // Field name length, as a small data element.
+        head.extend((4u32 << 16 | MI_INT32).to_le_bytes());
+        head.extend((FIELD_NAME_LENGTH as i32).to_le_bytes());
+        head.extend(element(MI_INT8, &names));
+
+        let data_size_offset = self.position + head.len() as u64 + 4;
+        head.extend(tag(MI_MATRIX, 0));
+        head.extend(element(MI_UINT32, &array_flags(MX_DOUBLE_CLASS)));
+        let columns_offset = self.position + head.len() as u64 + 12;
+        head.extend(element(MI_INT32, &dimensions(rows, 0)));
+        head.extend(element(MI_INT8, &[]));
+        let real_size_offset = self.position + head.len() as u64 + 4;
+        head.extend(tag(MI_DOUBLE, 0));
+        self.write(&head)?;
+
+        self.layout = Some(DataLayout {
+            rows,
+            struct_size_offset,
+            data_size_offset,
+            columns_offset,
+            real_size_offset,
+        });
+        Ok(())
+    }
+
+    fn patch(&mut self, offset: u64, value: u64) -> Result<()> {
+        let value = u32::try_from(value).map_err(|_| {
+            anyhow!(
+                "{} exceeds the 4 GiB limit of MAT v5 files; export fewer samples or channels.",
+                self.path.display()
+            )
+        })?;
+        self.writer.seek(SeekFrom::Start(offset))?;
+        self.writer.write_all(&value.to_le_bytes())?;
+        Ok(())
+    }
+}
+
+impl<'a> ChannelWriter<'a> for MatWriter {
+    fn write_channel(&mut self, channel: &ExportChannel<'a>) -> Result<()> {
+        let data = channel.data;
+        let rows = data.time_series.len();
+        match self.layout.as_ref() {
+            None => {
+                self.sample_rate = data.sample_rate;
+                self.begin(rows)?;
+            }
+            Some(layout) => {
+                if (data.sample_rate - self.sample_rate).abs() > 1e-9 * self.sample_rate.abs() {
+                    return Err(anyhow!(
+                        "Track '{}' is sampled at {} Hz, but the MAT export holds one sample \
+                         rate ({} Hz); resample the channels to a common rate.",
+                        channel.name,
+                        data.sample_rate,
+                        self.sample_rate
+                    ));
+                }
+                if rows != layout.rows {
+                    return Err(anyhow!(
+                        "Track '{}' has {} samples, but the MAT export holds {} per channel.",
+                        channel.name,
+                        rows,
+                        layout.rows
+                    ));
+                }
+            }
+        }
+
+        let mut column = Vec::with_capacity(rows * 8);
+        for value in &data.time_series {
+            column.extend(value.to_le_bytes());
+        }
+        self.write(&column)?;
+
+        let info = channel.channel;
+        self.metadata.push([
+            info.map(|info| info.serial_number.clone())
+                .unwrap_or_default(),
+            info.map(|info| info.description.clone())
+                .unwrap_or_default(),
+            data.units.clone(),
+            info.map(|info| info.display_order.to_string())
+                .unwrap_or_default(),
+        ]);
+        self.start_times.push(channel.trigger_time);
+        self.track_names.push(channel.name.to_string());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if self.layout.is_none() {
+            self.begin(0)?;
+        }
+        let columns = self.track_names.len();
+        let data_end = self.position;
+
+        let mut metadata_cells = Vec::with_capacity(columns * METADATA_ROWS);
+        for fields in &self.metadata {
+            metadata_cells.extend(fields.iter().map(|field| char_array(field)));
+            metadata_cells.push(double_array(0, 0, &[]));
+        }
+        let track_cells = self
+            .track_names
+            .iter()
+            .map(|name| char_array(name))
+            .collect();
+        let mut fields = double_array(1, 1, &[self.sample_rate]);
+        fields.extend(char_array(&self.pn));
+        fields.extend(cell_array(METADATA_ROWS, columns, metadata_cells));
+        fields.extend(double_array(1, columns, &self.start_times));
+        fields.extend(cell_array(1, columns, track_cells));
+        self.write(&fields)?;
+        let end = self.position;
+
+        let Some(layout) = self.layout.take() else {
+            unreachable!("the data matrix header is written above");
+        };
+        self.patch(
+            layout.struct_size_offset,
+            end - layout.struct_size_offset - 4,
+        )?;
+        self.patch(
+            layout.data_size_offset,
+            data_end - layout.data_size_offset - 4,
+        )?;
+        self.patch(layout.columns_offset, columns as u64)?;
+        self.patch(
+            layout.real_size_offset,
+            data_end - layout.real_size_offset - 4,
+        )?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer
+            .flush()
+            .with_context(|| format!("Failed to flush writer for {}", self.path.display()))
+    }
+}
+
+fn tag(data_type: u32, size: u32) -> Vec<u8> {
+    let mut bytes = data_type.to_le_bytes().to_vec();
+    bytes.extend(size.to_le_bytes());
+    bytes
+}
+
+/// A data element padded to a multiple of 8 bytes.
+fn element(data_type: u32, data: &[u8]) -> Vec<u8> {
+    let mut bytes = tag(data_type, data.len() as u32);
+    bytes.extend(data);
+    bytes.resize(bytes.len().next_multiple_of(8), 0);
+    bytes
+}
+
+fn array_flags(class: u32) -> [u8; 8] {
+    let mut flags = [0; 8];
+    flags[..4].copy_from_slice(&class.to_le_bytes());
+    flags
+}
+
+fn dimensions(rows: usize, columns: usize) -> [u8; 8] {
+    let mut dims = [0; 8];
+    dims[..4].copy_from_slice(&(rows as i32).to_le_bytes());
+    dims[4..].copy_from_slice(&(columns as i32).to_le_bytes());
+    dims
+}
+
+/// An unnamed array, as used for struct fields and cells.
+fn matrix(class: u32, rows: usize, columns: usize, body: &[u8]) -> Vec<u8> {
+    let mut content = element(MI_UINT32, &array_flags(class));
+    content.extend(element(MI_INT32, &dimensions(rows, columns)));
+    content.extend(element(MI_INT8, &[]));
+    content.extend(body);
+    element(MI_MATRIX, &content)
+}
+
+fn double_array(rows: usize, columns: usize, values: &[f64]) -> Vec<u8> {
+    let bytes: Vec<u8> = values
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect();
+    matrix(MX_DOUBLE_CLASS, rows, columns, &element(MI_DOUBLE, &bytes))
+}
+
+/// A character row vector stored as UTF-16 code units. Empty text gives a 0-by-0 array.
+fn char_array(text: &str) -> Vec<u8> {
+    let bytes: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    let (rows, columns) = match bytes.len() / 2 {
+        0 => (0, 0),
+        length => (1, length),
+    };
+    matrix(MX_CHAR_CLASS, rows, columns, &element(MI_UINT16, &bytes))
+}
+
+/// A cell array of `cells` in column-major order.
+fn cell_array(rows: usize, columns: usize, cells: Vec<Vec<u8>>) -> Vec<u8> {
+    matrix(MX_CELL_CLASS, rows, columns, &cells.concat())
+}
@@ -0,0 +1,390 @@
+use crate::dts::{AdcScaling, TestInfo};
+use crate::export::{ChannelWriter, ExportChannel};
+use anyhow::{Context, Result};
+use quick_xml::escape::escape;
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Size of the identification block at the start of the file.
+const ID_BLOCK_SIZE: u64 = 64;
+/// Size of the header block, written directly after the identification block.
+const HD_BLOCK_SIZE: u64 = 104;
+/// Version written to the identification block.
+const MDF_VERSION: u16 = 410;
+
+const CN_TYPE_FIXED_LENGTH: u8 = 0;
+const CN_TYPE_VIRTUAL_MASTER: u8 = 3;
+const CN_SYNC_NONE: u8 = 0;
+const CN_SYNC_TIME: u8 = 1;
+const CN_DATA_UNSIGNED: u8 = 0;
+const CN_DATA_SIGNED: u8 = 2;
+const CN_DATA_FLOAT: u8 = 4;
+const CC_TYPE_LINEAR: u8 = 1;
+
+/// Writes an ASAM MDF 4.1 file.
+///
+/// Channels sharing a time base (sample rate, start time and length) form one data group with a
+/// single channel group. Each group has a virtual master channel `time`, computed from the
+/// record index with a linear conversion, so the records only hold the channel values.
+/// Unprocessed tracks (see [`ExportChannel::adc_scaling`]) are stored as their 16-bit ADC
+/// counts with a linear conversion to engineering units, which is lossless; every other channel
+/// is stored as 64-bit floating point values.
+pub struct MdfWriter<'a> {
+    path: PathBuf,
+    test: TestInfo,
+    channels: Vec<ExportChannel<'a>>,
+}
+
+/// Channels sharing a time base.
+struct TimeBase<'a> {
+    sample_rate: f64,
+    start_time: f64,
+    records: usize,
+    channels: Vec<StoredChannel<'a>>,
+}
+
+struct StoredChannel<'a> {
+    channel: ExportChannel<'a>,
+    /// Scaling of the stored ADC counts; `None` stores the values as 64-bit floats.
+    counts: Option<AdcScaling>,
+}
+
+impl StoredChannel<'_> {
+    fn byte_count(&self) -> u32 {
+        if self.counts.is_some() {
+            2
+        } else {
+            8
+        }
+    }
+}
+
+impl<'a> MdfWriter<'a> {
+    /// Prepare an MDF export to `path`.
+    pub fn create(path: &Path, test: &TestInfo) -> Result<Self> {
+        Ok(Self {
+            path: path.to_path_buf(),
+            test: test.clone(),
+            channels: Vec::new(),
+        })
+    }
+
+    /// Group the channels by time base, in output order.
+    fn time_bases(&self) -> Vec<TimeBase<'a>> {
+        let mut time_bases: Vec<TimeBase<'a>> = Vec::new();
+        for channel in &self.channels {
+            let data = channel.data;
+            let stored = StoredChannel {
+                channel: *channel,
+                counts: channel
+                    .adc_scaling
+                    .filter(|scaling| is_lossless(&data.time_series, scaling)),
+            };
+            let existing = time_bases.iter_mut().find(|time_base| {
+                (time_base.sample_rate - data.sample_rate).abs()
+                    <= 1e-9 * time_base.sample_rate.abs()
+                    && time_base.start_time == channel.start_time
+                    && time_base.records == data.time_series.len()
+            });
+            match existing {
+                Some(time_base) => time_base.channels.push(stored),
+                None => time_bases.push(TimeBase {
+                    sample_rate: data.sample_rate,
+                    start_time: channel.start_time,
+                    records: data.time_series.len(),
+                    channels: vec![stored],
+                }),
+            }
+        }
+        time_bases
+    }
+}
+
+impl<'a> ChannelWriter<'a> for MdfWriter<'a> {
+    fn write_channel(&mut self, channel: &ExportChannel<'a>) -> Result<()> {
+        self.channels.push(*channel);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let file = File::create(&self.path)
+            .with_context(|| format!("Failed to create {}", self.path.display()))?;
+        let mut file = BlockWriter {
+            writer: BufWriter::with_capacity(8 * 1024 * 1024, file),
+            position: 0,
+        };
+
+        let mut id = Vec::with_capacity(ID_BLOCK_SIZE as usize);
+        id.extend(b"MDF     4.10    DTS2UFF ");
+        id.extend([0; 4]);
+        id.extend(MDF_VERSION.to_le_bytes());
+        id.extend([0; 34]);
+        file.write(&id)?;
+        // The header block is written once the addresses it links to are known.
+        file.write(&[0; HD_BLOCK_SIZE as usize])?;
+
+        // Blocks are written after the blocks they link to, so every link is known when a block
+        // is written; lists are therefore built from their last element.
+        let mut next_group = 0;
+        for time_base in self.time_bases().iter().rev() {
+            next_group = write_data_group(&mut file, time_base, next_group)?;
+        }
+
+        let software = format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+        let history_comment = file.text_block(
+            b"##MD",
+            &format!(
+                "<FHcomment><TX>Converted from DTS</TX><tool_id>{name}</tool_id>\
+                 <tool_vendor>{name}</tool_vendor><tool_version>{version}</tool_version>\
+                 </FHcomment>",
+                name = env!("CARGO_PKG_NAME"),
+                version = env!("CARGO_PKG_VERSION"),
+            ),
+        )?;
+        let history = file.block(b"##FH", &[0, history_comment], &[0; 16])?;
+
+        let title = [&self.test.id, &self.test.description]
+            .into_iter()
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| value.as_str())
+            .collect::<Vec<_>>()
+            .join(" - ");
+        let header_comment = file.text_block(
+            b"##MD",
+            &format!(
+                "<HDcomment><TX>{}</TX><common_properties><e name=\"software\">{}</e>\
+                 </common_properties></HDcomment>",
+                escape(title.as_str()),
+                escape(software.as_str())
+            ),
+        )?;
+
+        // The start time is unknown and left at 0 ns since 1970 (UTC), without angle or distance.
+        let header = block_bytes(
+            b"##HD",
+            &[next_group, history, 0, 0, 0, header_comment],
+            &[0; 32],
+        );
+        file.writer.seek(SeekFrom::Start(ID_BLOCK_SIZE))?;
+        file.writer.write_all(&header)?;
+        file.writer.seek(SeekFrom::End(0))?;
+        file.writer
+            .flush()
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+/// Writes MDF blocks at 8-byte aligned addresses.
+struct BlockWriter {
+    writer: BufWriter<File>,
+    position: u64,
+}
+
+impl BlockWriter {
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.writer.write_all(bytes)?;
+        self.position += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Write a block and return its address.
+    fn block(&mut self, id: &[u8; 4], links: &[u64], data: &[u8]) -> Result<u64> {
+        let address = self.position;
+        self.write(&block_bytes(id, links, data))?;
+        Ok(address)
+    }
+
+    /// Write a `##TX` or `##MD` block holding `text` and return its address.
+    fn text_block(&mut self, id: &[u8; 4], text: &str) -> Result<u64> {
+        let mut data = text.as_bytes().to_vec();
+        data.push(0);
+        data.resize(data.len().next_multiple_of(8), 0);
+        self.block(id, &[], &data)
+    }
+
+    /// Write a `##TX` block, or return a nil link for empty text.
+    fn optional_text(&mut self, text: &str) -> Result<u64> {
+        if text.trim().is_empty() {
+            Ok(0)
+        } else {
+            self.text_block(b"##TX", text)
+        }
+    }
+
+    /// Write a linear conversion `physical = factor * raw + offset` and return its address.
+    fn linear_conversion(&mut self, offset: f64, factor: f64) -> Result<u64> {
+        let mut data = Vec::with_capacity(40);
+        data.push(CC_TYPE_LINEAR);
+        data.push(0);
+        data.extend(0u16.to_le_bytes());
+        data.extend(0u16.to_le_bytes());
+        data.extend(2u16.to_le_bytes());
+        data.extend(0f64.to_le_bytes());
+        data.extend(0f64.to_le_bytes());
+        data.extend(offset.to_le_bytes());
+        data.extend(factor.to_le_bytes());
+        self.block(b"##CC", &[0, 0, 0, 0], &data)
+    }
+
+    /// Write a channel block, with its name and unit, and return its address.
+    fn channel(&mut self, next: u64, channel: &ChannelBlock<'_>) -> Result<u64> {
+        let name = self.text_block(b"##TX", channel.name)?;
+        let unit = self.optional_text(channel.unit)?;
+        let mut data = Vec::with_capacity(72);
+        data.extend([
+            channel.channel_type,
+            channel.sync_type,
+            channel.data_type,
+            0,
+        ]);
+        data.extend(channel.byte_offset.to_le_bytes());
+        data.extend(channel.bit_count.to_le_bytes());
+        // Flags, invalidation bit, precision, reserved byte and attachment count.
+        data.extend([0; 12]);
+        data.extend([0; 48]);
+        self.block(
+            b"##CN",
+            &[next, 0, name, 0, channel.conversion, 0, unit, 0],
+            &data,
+        )
+    }
+}
+
+/// Fields of a channel block.
+struct ChannelBlock<'a> {
+    name: &'a str,
+    unit: &'a str,
+    /// Address of the conversion block, or 0 for none.
+    conversion: u64,
+    channel_type: u8,
+    sync_type: u8,
+    data_type: u8,
+    byte_offset: u32,
+    bit_count: u32,
+}
+
+/// Write the data, channels and channel group of `time_base`, followed by its data group block.
+/// Returns the address of the data group block.
+fn write_data_group(file: &mut BlockWriter, time_base: &TimeBase<'_>, next: u64) -> Result<u64> {
+    let record_size: u32 = time_base
+        .channels
+        .iter()
+        .map(StoredChannel::byte_count)
+        .sum();
+
+    let data_address = file.position;
+    file.write(&block_header(
+        b"##DT",
+        24 + time_base.records as u64 * u64::from(record_size),
+        0,
+    ))?;
+    let mut record = Vec::with_capacity(record_size as usize);
+    for index in 0..time_base.records {
+        record.clear();
+        for stored in &time_base.channels {
+            let value = stored.channel.data.time_series[index];
+            match &stored.counts {
+                Some(scaling) => record.extend(count(value, scaling).to_le_bytes()),
+                None => record.extend(value.to_le_bytes()),
+            }
+        }
+        file.write(&record)?;
+    }
+    let padding = file.position.next_multiple_of(8) - file.position;
+    file.write(&vec![0; padding as usize])?;
+
+    let mut offsets = Vec::with_capacity(time_base.channels.len());
+    let mut byte_offset = 0;
+    for stored in &time_base.channels {
+        offsets.push(byte_offset);
+        byte_offset += stored.byte_count();
+    }
+    let mut next_channel = 0;
+    for (stored, byte_offset) in time_base.channels.iter().zip(offsets).rev() {
+        let channel = &stored.channel;
+        let (conversion, data_type) = match &stored.counts {
+            Some(scaling) => (
+                file.linear_conversion(scaling.offset, scaling.eu_per_count)?,
+                CN_DATA_SIGNED,
+            ),
+            None => (0, CN_DATA_FLOAT),
+        };
+        next_channel = file.channel(
+            next_channel,
+            &ChannelBlock {
+                name: channel.name,
+                unit: &channel.data.units,
+                conversion,
+                channel_type: CN_TYPE_FIXED_LENGTH,
+                sync_type: CN_SYNC_NONE,
+                data_type,
+                byte_offset,
+                bit_count: stored.byte_count() * 8,
+            },
+        )?;
+    }
+    // The master channel is the record index scaled to seconds.
+    let time_conversion =
+        file.linear_conversion(time_base.start_time, 1.0 / time_base.sample_rate)?;
+    let master = file.channel(
+        next_channel,
+        &ChannelBlock {
+            name: "time",
+            unit: "s",
+            conversion: time_conversion,
+            channel_type: CN_TYPE_VIRTUAL_MASTER,
+            sync_type: CN_SYNC_TIME,
+            data_type: CN_DATA_UNSIGNED,
+            byte_offset: 0,
+            bit_count: 0,
+        },
+    )?;
+
+    let acquisition = file.text_block(b"##TX", &format!("{} Hz", time_base.sample_rate))?;
+    let mut group = Vec::with_capacity(32);
+    group.extend(0u64.to_le_bytes());
+    group.extend((time_base.records as u64).to_le_bytes());
+    // Flags, path separator and reserved bytes.
+    group.extend([0; 8]);
+    group.extend(record_size.to_le_bytes());
+    group.extend(0u32.to_le_bytes());
+    let channel_group = file.block(b"##CG", &[0, master, acquisition, 0, 0, 0], &group)?;
+
+    // Records carry no record ID: the data group holds a single channel group.
+    file.block(b"##DG", &[next, channel_group, data_address, 0], &[0; 8])
+}
+
+fn block_header(id: &[u8; 4], length: u64, link_count: usize) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(24);
+    bytes.extend(id);
+    bytes.extend([0; 4]);
+    bytes.extend(length.to_le_bytes());
+    bytes.extend((link_count as u64).to_le_bytes());
+    bytes
+}
+
+fn block_bytes(id: &[u8; 4], links: &[u64], data: &[u8]) -> Vec<u8> {
+    let length = 24 + links.len() * 8 + data.len();
+    let mut bytes = block_header(id, length as u64, links.len());
+    for link in links {
+        bytes.extend(link.to_le_bytes());
+    }
+    bytes.extend(data);
+    bytes
+}
+
+/// ADC count of `value` under `scaling`.
+fn count(value: f64, scaling: &AdcScaling) -> i16 {
+    ((value - scaling.offset) / scaling.eu_per_count).round() as i16
+}
+
+/// Whether every sample is reproduced exactly by its ADC count under `scaling`.
+fn is_lossless(samples: &[f64], scaling: &AdcScaling) -> bool {
+    scaling.eu_per_count != 0.0
+        && samples.iter().all(|&value| {
+            let counts = ((value - scaling.offset) / scaling.eu_per_count).round();
+            (f64::from(i16::MIN)..=f64::from(i16::MAX)).contains(&counts)
+                && f64::from(count(value, scaling)) * scaling.eu_per_count + scaling.offset == value
+        })
+}
@@ -0,0 +1,359 @@
+use crate::dts::{excitation_voltage, TestInfo};
+use crate::export::{ChannelWriter, ExportChannel};
+use crate::filter::{CfcClass, FilterSpec};
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Value written for every header field that is not known.
+const NOVALUE: &str = "NOVALUE";
+/// Data format edition written to the test description file.
+const EDITION: &str = "1.6";
+/// Length of an ISO/TS 13499 channel code.
+const CODE_LENGTH: usize = 16;
+
+/// A `track=code` ISO/TS 13499 channel code assignment, e.g. `A1=11HEAD0000H3ACX?`.
+///
+/// A `?` in the last position (the filter class) is filled from the filters applied to the
+/// track when the channel is written.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MmeChannelCode {
+    pub track_name: String,
+    pub code: String,
+}
+
+impl std::str::FromStr for MmeChannelCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (track_name, code) = s.rsplit_once('=').ok_or_else(|| {
+            "MME channel code must be provided in the format track=code".to_string()
+        })?;
+        let track_name = track_name.trim();
+        if track_name.is_empty() {
+            return Err("MME channel code is missing a track name".to_string());
+        }
+        let code = code.trim().to_ascii_uppercase();
+        if code.chars().count() != CODE_LENGTH {
+            return Err(format!(
+                "Invalid MME channel code '{code}': expected {CODE_LENGTH} characters."
+            ));
+        }
+        let valid = code.chars().enumerate().all(|(position, c)| {
+            c.is_ascii_uppercase()
+                || c.is_ascii_digit()
+                || (c == '?' && position == CODE_LENGTH - 1)
+        });
+        if !valid {
+            return Err(format!(
+                "Invalid MME channel code '{code}': expected letters and digits, with an optional \
+                 '?' filter class."
+            ));
+        }
+        Ok(MmeChannelCode {
+            track_name: track_name.to_string(),
+            code,
+        })
+    }
+}
+
+/// ISO/TS 13499 channel codes of the exported tracks, keyed by track name.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct MmeChannelCodes {
+    codes: BTreeMap<String, String>,
+}
+
+impl MmeChannelCodes {
+    /// Assign `code` to its track, replacing an earlier assignment.
+    pub fn insert(&mut self, code: MmeChannelCode) {
+        self.codes.insert(code.track_name, code.code);
+    }
+
+    /// Read `track=code` assignments from a text file, one per line. Blank lines and lines
+    /// starting with `#` are ignored.
+    pub fn extend_from_file(&mut self, path: &Path) -> Result<()> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read MME channel codes from {}", path.display()))?;
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let code = line
+                .parse()
+                .map_err(|err: String| anyhow!("{} line {}: {err}", path.display(), number + 1))?;
+            self.insert(code);
+        }
+        Ok(())
+    }
+
+    /// Iterates over the tracks with a channel code.
+    pub fn track_names(&self) -> impl Iterator<Item = &str> {
+        self.codes.keys().map(String::as_str)
+    }
+
+    /// Channel code of `track_name`, with a `?` filter class replaced by [`filter_class`].
+    pub fn code_for(
+        &self,
+        track_name: &str,
+        cfc: Option<CfcClass>,
+        filters: &[FilterSpec],
+    ) -> Option<String> {
+        let code = self.codes.get(track_name)?;
+        Some(match code.strip_suffix('?') {
+            Some(prefix) => format!("{prefix}{}", filter_class(cfc, filters)),
+            None => code.clone(),
+        })
+    }
+}
+
+/// Filter class character of a channel code: `A`-`D` for CFC 1000, 600, 180 and 60, `0` for
+/// unfiltered data and `X` for any other filtering.
+pub fn filter_class(cfc: Option<CfcClass>, filters: &[FilterSpec]) -> char {
+    match (cfc, filters.is_empty()) {
+        (None, true) => '0',
+        (Some(CfcClass::Cfc1000), true) => 'A',
+        (Some(CfcClass::Cfc600), true) => 'B',
+        (Some(CfcClass::Cfc180), true) => 'C',
+        (Some(CfcClass::Cfc60), true) => 'D',
+        (_, false) => 'X',
+    }
+}
+
+/// Writes an ISO/TS 13499 (MME) test directory.
+///
+/// The directory receives the test description `<name>.mme` and a `Channel` folder holding the
+/// channel list `<name>.chn` and one `<name>.001`, `<name>.002`, ... file per time history,
+/// where `<name>` is the name of the directory. Files use CRLF line endings.
+pub struct MmeWriter {
+    directory: PathBuf,
+    name: String,
+    test: TestInfo,
+    codes: MmeChannelCodes,
+    /// `(code, name)` of every channel written so far.
+    channels: Vec<(String, String)>,
+    any_cfc: bool,
+}
+
+impl MmeWriter {
+    /// Create `directory` and its `Channel` folder.
+    pub fn create(directory: &Path, test: &TestInfo, codes: &MmeChannelCodes) -> Result<Self> {
+        let name = directory
+            .file_name()
+            .and_then(|name| name.to_str())
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Invalid MME output directory {}: expected a directory name.",
+                    directory.display()
+                )
+            })?
+            .to_string();
+        let channel_dir = directory.join("Channel");
+        fs::create_dir_all(&channel_dir)
+            .with_context(|| format!("Failed to create {}", channel_dir.display()))?;
+        Ok(Self {
+            directory: directory.to_path_buf(),
+            name,
+            test: test.clone(),
+            codes: codes.clone(),
+            channels: Vec::new(),
+            any_cfc: false,
+        })
+    }
+
+    fn create_file(&self, path: &Path) -> Result<BufWriter<File>> {
+        let file =
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        Ok(BufWriter::new(file))
+    }
+
+    fn write_test_description(&self) -> Result<()> {
+        let path = self.directory.join(format!("{}.mme", self.name));
+        let mut writer = self.create_file(&path)?;
+        let test_objects = self
+            .channels
+            .iter()
+            .filter_map(|(code, _)| code.chars().next()?.to_digit(10))
+            .max()
+            .unwrap_or(1)
+            .max(1);
+
+        header(&mut writer, "Data format edition number", EDITION)?;
+        header(&mut writer, "Laboratory name", NOVALUE)?;
+        header(&mut writer, "Customer name", NOVALUE)?;
+        header(
+            &mut writer,
+            "Laboratory test ref. number",
+            or_novalue(&self.test.id),
+        )?;
+        header(&mut writer, "Title", or_novalue(&self.test.description))?;
+        header(&mut writer, "Medium No./number of media", "1/1")?;
+        header(&mut writer, "Date of the test", or_novalue(&self.test.date))?;
+        header(&mut writer, "Number of test objects", test_objects)?;
+        for object in 1..=test_objects {
+            header(
+                &mut writer,
+                &format!("Name of test object {object}"),
+                NOVALUE,
+            )?;
+        }
+        writer
+            .flush()
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    fn write_channel_list(&self) -> Result<()> {
+        let path = self
+            .directory
+            .join("Channel")
+            .join(format!("{}.chn", self.name));
+        let mut writer = self.create_file(&path)?;
+        let standard = if self.any_cfc { "ISO 6487" } else { NOVALUE };
+        header(&mut writer, "Instrumentation standard", standard)?;
+        header(&mut writer, "Number of channels", self.channels.len())?;
+        for (number, (code, name)) in self.channels.iter().enumerate() {
+            header(
+                &mut writer,
+                &format!("Name of channel {:03}", number + 1),
+                format!("{code} / {name}"),
+            )?;
+        }
+        writer
+            .flush()
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+impl<'a> ChannelWriter<'a> for MmeWriter {
+    fn write_channel(&mut self, channel: &ExportChannel<'a>) -> Result<()> {
+        let number = self.channels.len() + 1;
+        let path = self
+            .directory
+            .join("Channel")
+            .join(format!("{}.{number:03}", self.name));
+        let mut writer = self.create_file(&path)?;
+
+        let code = self
+            .codes
+            .code_for(channel.name, channel.cfc, channel.filters)
+            .unwrap_or_else(|| NOVALUE.to_string());
+        let code_part = |range: std::ops::Range<usize>| {
+            code.get(range)
+                .filter(|_| code != NOVALUE)
+                .unwrap_or(NOVALUE)
+                .to_string()
+        };
+        let cut_off = channel
+            .cfc
+            .map(|class| class.cutoff_frequency())
+            .into_iter()
+            .chain(
+                channel
+                    .filters
+                    .iter()
+                    .filter_map(FilterSpec::low_pass_cutoff),
+            )
+            .reduce(f64::min);
+        let data = channel.data;
+
+        header(&mut writer, "Name of the channel", channel.name)?;
+        header(&mut writer, "Channel code", &code)?;
+        header(&mut writer, "Test object number", code_part(0..1))?;
+        header(&mut writer, "Position", code_part(1..2))?;
+        header(&mut writer, "Main location", code_part(2..6))?;
+        header(&mut writer, "Dimension", code_part(12..14))?;
+        header(&mut writer, "Direction", code_part(14..15))?;
+        header(
+            &mut writer,
+            "Channel frequency class",
+            channel
+                .cfc
+                .map_or_else(|| NOVALUE.to_string(), |class| class.value().to_string()),
+        )?;
+        header(&mut writer, "Unit", or_novalue(&data.units))?;
+        header(
+            &mut writer,
+            "Transducer id",
+            channel
+                .channel
+                .map_or(NOVALUE, |info| or_novalue(&info.serial_number)),
+        )?;
+        header(
+            &mut writer,
+            "Cut off frequency",
+            cut_off.map_or_else(|| NOVALUE.to_string(), |frequency| frequency.to_string()),
+        )?;
+        header(
+            &mut writer,
+            "Sampling interval",
+            format!("{:E}", 1.0 / data.sample_rate),
+        )?;
+        header(
+            &mut writer,
+            "Time of first sample",
+            format!("{:E}", channel.trigger_time),
+        )?;
+        header(&mut writer, "Number of samples", data.time_series.len())?;
+        header(
+            &mut writer,
+            "Data source",
+            if channel.computed {
+                "calculated"
+            } else {
+                "test"
+            },
+        )?;
+        if let Some(info) = channel.channel {
+            if !info.description.trim().is_empty() {
+                header(&mut writer, "Comments", &info.description)?;
+            }
+            // The sensor calibration only describes the recorded tracks.
+            if !channel.computed && info.sensitivity.is_finite() {
+                let sensitivity = if info.proportional_to_excitation {
+                    format!(
+                        "Sensitivity {} mV/V/{} at {:.3} V excitation",
+                        info.sensitivity,
+                        info.eu,
+                        excitation_voltage(info)
+                    )
+                } else {
+                    format!("Sensitivity {} mV/{}", info.sensitivity, info.eu)
+                };
+                header(&mut writer, "Comments", sensitivity)?;
+            }
+        }
+        for value in &data.time_series {
+            write!(writer, "{value:E}\r\n")?;
+        }
+        writer
+            .flush()
+            .with_context(|| format!("Failed to write MME channel '{}'", channel.name))?;
+
+        self.any_cfc |= channel.cfc.is_some();
+        self.channels.push((code, channel.name.to_string()));
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.write_channel_list()?;
+        self.write_test_description()
+    }
+}
+
+fn or_novalue(value: &str) -> &str {
+    if value.trim().is_empty() {
+        NOVALUE
+    } else {
+        value
+    }
+}
+
+/// Write a `keyword :value` header line with the keyword padded to 28 characters.
+fn header<W: Write>(writer: &mut W, keyword: &str, value: impl std::fmt::Display) -> Result<()> {
+    write!(writer, "{keyword:<28}:{value}\r\n")?;
+    Ok(())
+}
@@ -0,0 +1,324 @@
+use crate::export::{ChannelWriter, ExportChannel};
+use anyhow::{anyhow, Context, Result};
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Arrays written after the tracks, holding one entry per track in output order.
+pub const METADATA_ARRAYS: [&str; 4] = ["track_names", "sample_rates", "units", "start_times"];
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const ZIP64_END_SIGNATURE: u32 = 0x0606_4b50;
+const ZIP64_LOCATOR_SIGNATURE: u32 = 0x0706_4b50;
+const END_SIGNATURE: u32 = 0x0605_4b50;
+/// ZIP 4.5, the first version with ZIP64 extensions.
+const ZIP_VERSION: u16 = 45;
+/// Bit 11: names are UTF-8.
+const UTF8_NAMES: u16 = 0x0800;
+/// 1980-01-01 in MS-DOS date format; the archive carries no timestamps.
+const DOS_DATE: u16 = 0x0021;
+const ZIP64_EXTRA_ID: u16 = 0x0001;
+/// Field value telling readers to use the ZIP64 extra field or end record instead.
+const ZIP64_PLACEHOLDER: u32 = u32::MAX;
+/// Samples converted per write while streaming a track.
+const BLOCK_SAMPLES: usize = 64 * 1024;
+
+/// Writes a NumPy `.npz` archive: one `float64` array per track, named after the track, and the
+/// [`METADATA_ARRAYS`] `track_names`, `sample_rates`, `units` and `start_times`.
+///
+/// Tracks are streamed into the archive as they are written, so only one block of samples is
+/// held in memory. Entries are stored uncompressed, and ZIP64 records are added when the archive
+/// exceeds 4 GiB. `numpy.load` reads the file without `allow_pickle`.
+pub struct NpzWriter {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    /// Bytes written so far.
+    position: u64,
+    entries: Vec<ZipEntry>,
+    track_names: Vec<String>,
+    sample_rates: Vec<f64>,
+    units: Vec<String>,
+    start_times: Vec<f64>,
+}
+
+struct ZipEntry {
+    name: String,
+    offset: u64,
+    size: u64,
+    crc: u32,
+}
+
+impl NpzWriter {
+    /// Create the archive at `path`.
+    pub fn create(path: &Path) -> Result<Self> {
+        let file =
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer: BufWriter::with_capacity(8 * 1024 * 1024, file),
+            position: 0,
+            entries: Vec::new(),
+            track_names: Vec::new(),
+            sample_rates: Vec::new(),
+            units: Vec::new(),
+            start_times: Vec::new(),
+        })
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.writer.write_all(bytes)?;
+        self.position += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Write the local header of `<key>.npy`. The CRC and sizes are completed by
+    /// [`NpzWriter::end_entry`].
+    fn begin_entry(&mut self, key: &str) -> Result<()> {
+        let name = format!("{key}.npy");
+        let mut header = Vec::with_capacity(50 + name.len());
+        header.extend(LOCAL_HEADER_SIGNATURE.to_le_bytes());
+        header.extend(ZIP_VERSION.to_le_bytes());
+        header.extend(UTF8_NAMES.to_le_bytes());
+        // Stored, without compression.
+        header.extend(0u16.to_le_bytes());
+        header.extend(0u16.to_le_bytes());
+        header.extend(DOS_DATE.to_le_bytes());
+        header.extend(0u32.to_le_bytes());
+        header.extend(ZIP64_PLACEHOLDER.to_le_bytes());
+        header.extend(ZIP64_PLACEHOLDER.to_le_bytes());
+        header.extend((name.len() as u16).to_le_bytes());
+        header.extend(20u16.to_le_bytes());
+        header.extend(name.as_bytes());
+        header.extend(ZIP64_EXTRA_ID.to_le_bytes());
+        header.extend(16u16.to_le_bytes());
+        header.extend([0; 16]);
+
+        self.entries.push(ZipEntry {
+            name,
+            offset: self.position,
+            size: 0,
+            crc: 0,
+        });
+        self.write(&header)
+    }
+
+    /// Append `bytes` to the entry begun last.
+    fn write_entry_data(&mut self, bytes: &[u8]) -> Result<()> {
+        let Some(entry) = self.entries.last_mut() else {
+            unreachable!("entry data is written after begin_entry");
+        };
+        let mut crc = crc32fast::Hasher::new_with_initial(entry.crc);
+        crc.update(bytes);
+        entry.crc = crc.finalize();
+        entry.size += bytes.len() as u64;
+        self.write(bytes)
+    }
+
+    /// Complete the local header of the entry begun last.
+    fn end_entry(&mut self) -> Result<()> {
+        let Some(entry) = self.entries.last() else {
+            unreachable!("end_entry follows begin_entry");
+        };
+        let crc_offset = entry.offset + 14;
+        let sizes_offset = entry.offset + 30 + entry.name.len() as u64 + 4;
+        let mut sizes = entry.size.to_le_bytes().to_vec();
+        sizes.extend(entry.size.to_le_bytes());
+        let crc = entry.crc.to_le_bytes();
+
+        self.writer.seek(SeekFrom::Start(crc_offset))?;
+        self.writer.write_all(&crc)?;
+        self.writer.seek(SeekFrom::Start(sizes_offset))?;
+        self.writer.write_all(&sizes)?;
+        self.writer.seek(SeekFrom::End(0))?;
+        Ok(())
+    }
+
+    /// Write a complete `.npy` array of `header` followed by `data`.
+    fn write_array(&mut self, key: &str, header: &[u8], data: &[u8]) -> Result<()> {
+        self.begin_entry(key)?;
+        self.write_entry_data(header)?;
+        self.write_entry_data(data)?;
+        self.end_entry()
+    }
+
+    fn write_strings(&mut self, key: &str, values: &[String]) -> Result<()> {
+        let width = values
+            .iter()
+            .map(|value| value.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let mut data = Vec::with_capacity(values.len() * width * 4);
+        for value in values {
+            let mut length = 0;
+            for c in value.chars() {
+                data.extend(u32::from(c).to_le_bytes());
+                length += 1;
+            }
+            data.resize(data.len() + (width - length) * 4, 0);
+        }
+        let header = npy_header(&format!("<U{width}"), values.len());
+        self.write_array(key, &header, &data)
+    }
+
+    fn write_doubles(&mut self, key: &str, values: &[f64]) -> Result<()> {
+        let data: Vec<u8> = values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        self.write_array(key, &npy_header("<f8", values.len()), &data)
+    }
+
+    fn write_central_directory(&mut self) -> Result<()> {
+        let directory_offset = self.position;
+        let mut directory = Vec::new();
+        for entry in &self.entries {
+            let mut extra = Vec::new();
+            let size = match u32::try_from(entry.size) {
+                Ok(size) if size != ZIP64_PLACEHOLDER => size,
+                _ => {
+                    extra.extend(entry.size.to_le_bytes());
+                    extra.extend(entry.size.to_le_bytes());
+                    ZIP64_PLACEHOLDER
+                }
+            };
+            let offset = match u32::try_from(entry.offset) {
+                Ok(offset) if offset != ZIP64_PLACEHOLDER => offset,
+                _ => {
+                    extra.extend(entry.offset.to_le_bytes());
+                    ZIP64_PLACEHOLDER
+                }
+            };
+            if !extra.is_empty() {
+                let mut field = ZIP64_EXTRA_ID.to_le_bytes().to_vec();
+                field.extend((extra.len() as u16).to_le_bytes());
+                field.extend(extra);
+                extra = field;
+            }
+
+            directory.extend(CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+            directory.extend(ZIP_VERSION.to_le_bytes());
+            directory.extend(ZIP_VERSION.to_le_bytes());
+            directory.extend(UTF8_NAMES.to_le_bytes());
+            directory.extend(0u16.to_le_bytes());
+            directory.extend(0u16.to_le_bytes());
+            directory.extend(DOS_DATE.to_le_bytes());
+            directory.extend(entry.crc.to_le_bytes());
+            directory.extend(size.to_le_bytes());
+            directory.extend(size.to_le_bytes());
+            directory.extend((entry.name.len() as u16).to_le_bytes());
+            directory.extend((extra.len() as u16).to_le_bytes());
+            // Comment length, disk number, internal and external attributes.
+            directory.extend([0; 10]);
+            directory.extend(offset.to_le_bytes());
+            directory.extend(entry.name.as_bytes());
+            directory.extend(extra);
+        }
+        let directory_size = directory.len() as u64;
+        self.write(&directory)?;
+
+        let entries = self.entries.len() as u64;
+        let small_entries = u16::try_from(entries)
+            .ok()
+            .filter(|&entries| entries != u16::MAX);
+        let small_size = u32::try_from(directory_size)
+            .ok()
+            .filter(|&size| size != ZIP64_PLACEHOLDER);
+        let small_offset = u32::try_from(directory_offset)
+            .ok()
+            .filter(|&offset| offset != ZIP64_PLACEHOLDER);
+
+        let mut end = Vec::new();
+        if small_entries.is_none() || small_size.is_none() || small_offset.is_none() {
+            let zip64_end_offset = self.position;
+            end.extend(ZIP64_END_SIGNATURE.to_le_bytes());
+            end.extend(44u64.to_le_bytes());
+            end.extend(ZIP_VERSION.to_le_bytes());
+            end.extend(ZIP_VERSION.to_le_bytes());
+            end.extend([0; 8]);
+            end.extend(entries.to_le_bytes());
+            end.extend(entries.to_le_bytes());
+            end.extend(directory_size.to_le_bytes());
+            end.extend(directory_offset.to_le_bytes());
+            end.extend(ZIP64_LOCATOR_SIGNATURE.to_le_bytes());
+            end.extend(0u32.to_le_bytes());
+            end.extend(zip64_end_offset.to_le_bytes());
+            end.extend(1u32.to_le_bytes());
+        }
+        let entries = small_entries.unwrap_or(u16::MAX);
+        end.extend(END_SIGNATURE.to_le_bytes());
+        end.extend([0; 4]);
+        end.extend(entries.to_le_bytes());
+        end.extend(entries.to_le_bytes());
+        end.extend(small_size.unwrap_or(ZIP64_PLACEHOLDER).to_le_bytes());
+        end.extend(small_offset.unwrap_or(ZIP64_PLACEHOLDER).to_le_bytes());
+        end.extend(0u16.to_le_bytes());
+        self.write(&end)
+    }
+}
+
+impl<'a> ChannelWriter<'a> for NpzWriter {
+    fn write_channel(&mut self, channel: &ExportChannel<'a>) -> Result<()> {
+        let name = channel.name;
+        if METADATA_ARRAYS.contains(&name) || self.track_names.iter().any(|track| track == name) {
+            return Err(anyhow!(
+                "Track '{name}' clashes with another array of the NPZ export; rename the track."
+            ));
+        }
+        if name.is_empty() || name.contains(['/', '\\']) {
+            return Err(anyhow!(
+                "Track '{name}' cannot be used as an NPZ array name; rename the track."
+            ));
+        }
+
+        let samples = &channel.data.time_series;
+        self.begin_entry(name)?;
+        self.write_entry_data(&npy_header("<f8", samples.len()))?;
+        let mut block = Vec::with_capacity(BLOCK_SAMPLES.min(samples.len()) * 8);
+        for chunk in samples.chunks(BLOCK_SAMPLES) {
+            block.clear();
+            block.extend(chunk.iter().flat_map(|value| value.to_le_bytes()));
+            self.write_entry_data(&block)?;
+        }
+        self.end_entry()?;
+
+        self.track_names.push(name.to_string());
+        self.sample_rates.push(channel.data.sample_rate);
+        self.units.push(channel.data.units.clone());
+        self.start_times.push(channel.start_time);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let [names, rates, units, start_times] = METADATA_ARRAYS;
+        let track_names = std::mem::take(&mut self.track_names);
+        self.write_strings(names, &track_names)?;
+        let sample_rates = std::mem::take(&mut self.sample_rates);
+        self.write_doubles(rates, &sample_rates)?;
+        let track_units = std::mem::take(&mut self.units);
+        self.write_strings(units, &track_units)?;
+        let times = std::mem::take(&mut self.start_times);
+        self.write_doubles(start_times, &times)?;
+        self.write_central_directory()?;
+        self.writer
+            .flush()
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+/// Header of a version 1.0 `.npy` file holding a one-dimensional array of `length` values.
+/// The header is padded so that the data starts at a multiple of 64 bytes.
+fn npy_header(descr: &str, length: usize) -> Vec<u8> {
+    let mut dict =
+        format!("{{'descr': '{descr}', 'fortran_order': False, 'shape': ({length},), }}");
+    // Magic string, version and header length take 10 bytes; the header ends with a newline.
+    let padding = (10 + dict.len() + 1).next_multiple_of(64) - (10 + dict.len() + 1);
+    dict.extend(std::iter::repeat_n(' ', padding));
+    dict.push('\n');
+
+    let mut header = b"\x93NUMPY\x01\x00".to_vec();
+    header.extend((dict.len() as u16).to_le_bytes());
+    header.extend(dict.as_bytes());
+    header
+}
@@ -0,0 +1,933 @@
+use crate::conversion::SampleSlice;
+use crate::dts::{ChannelData, DtsReader};
+use crate::png;
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use quick_xml::escape::escape;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Largest image, in pixels, a plot may cover.
+const MAX_PIXELS: u64 = 64 * 1024 * 1024;
+/// Space around the plot area of a panel, in pixels.
+const MARGIN_LEFT: f64 = 72.0;
+const MARGIN_RIGHT: f64 = 20.0;
+const MARGIN_TOP: f64 = 32.0;
+const MARGIN_BOTTOM: f64 = 40.0;
+/// Number of axis ticks aimed for; the actual count depends on the data range.
+const TARGET_TICKS: f64 = 6.0;
+
+/// Image format of a plot.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PlotFormat {
+    /// Scalable vector graphics.
+    #[default]
+    Svg,
+    /// Palette PNG image.
+    Png,
+}
+
+impl PlotFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlotFormat::Svg => "svg",
+            PlotFormat::Png => "png",
+        }
+    }
+
+    /// MIME type of the image.
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            PlotFormat::Svg => "image/svg+xml",
+            PlotFormat::Png => "image/png",
+        }
+    }
+}
+
+impl std::fmt::Display for PlotFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for PlotFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "svg" => Ok(PlotFormat::Svg),
+            "png" => Ok(PlotFormat::Png),
+            other => Err(format!(
+                "Unsupported plot format '{other}'. Expected 'svg' or 'png'."
+            )),
+        }
+    }
+}
+
+/// Panel size, grid layout and markers of a plot.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlotSettings {
+    /// Width of the panel of one track, in pixels.
+    pub width: u32,
+    /// Height of the panel of one track, in pixels.
+    pub height: u32,
+    /// Panels per row when several tracks share an image.
+    pub columns: usize,
+    /// Mark and label the minimum and maximum of every track.
+    pub markers: bool,
+}
+
+impl Default for PlotSettings {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 300,
+            columns: 1,
+            markers: false,
+        }
+    }
+}
+
+impl PlotSettings {
+    pub fn validate(&self) -> Result<()> {
+        if self.width < 200 || self.height < 120 {
+            return Err(anyhow!(
+                "Invalid plot size {}x{}: panels must be at least 200x120 pixels.",
+                self.width,
+                self.height
+            ));
+        }
+        if self.columns == 0 {
+            return Err(anyhow!("Invalid plot grid: expected at least one column."));
+        }
+        Ok(())
+    }
+}
+
+/// A time history to plot.
+pub struct PlotTrack {
+    pub name: String,
+    pub data: ChannelData,
+    /// Time of the first sample, in seconds.
+    pub start_time: f64,
+}
+
+/// Read tracks of `reader` for plotting.
+///
+/// Tracks are named after `track_names`, falling back to the channel names of the `.dts` file.
+/// `selection` picks tracks by name, in order; `None` plots every track. `slice` limits the
+/// samples of every track.
+pub fn read_tracks(
+    reader: &DtsReader,
+    track_names: &[String],
+    selection: Option<&[String]>,
+    slice: Option<SampleSlice>,
+) -> Result<Vec<PlotTrack>> {
+    let names: Vec<String> = (0..reader.channel_count())
+        .map(|index| {
+            track_names
+                .get(index)
+                .cloned()
+                .or_else(|| reader.channel_info(index).map(|info| info.name.clone()))
+                .unwrap_or_else(|| format!("Channel_{}", index + 1))
+        })
+        .collect();
+    let indices: Vec<usize> = match selection {
+        Some(selection) => selection
+            .iter()
+            .map(|requested| {
+                names
+                    .iter()
+                    .position(|name| name == requested)
+                    .ok_or_else(|| anyhow!("Track '{requested}' was not found in the DTS export."))
+            })
+            .collect::<Result<_>>()?,
+        None => (0..names.len()).collect(),
+    };
+
+    indices
+        .into_iter()
+        .map(|index| {
+            let mut data = reader.read_track(index)?;
+            let mut start_time = 0.0;
+            if let Some(slice) = slice {
+                let range = slice.as_range(data.time_series.len())?;
+                start_time = range.start as f64 / data.sample_rate;
+                data.time_series = data.time_series[range].to_vec();
+            }
+            Ok(PlotTrack {
+                name: names[index].clone(),
+                data,
+                start_time,
+            })
+        })
+        .collect()
+}
+
+/// Render `tracks` into one image, as a grid of `settings.columns` panels per row.
+///
+/// Every panel shows the time history of a track with its name as the title, the time axis in
+/// seconds and the value axis labelled with the track's units. Long tracks are reduced to the
+/// minimum and maximum of every pixel column, so peaks are never lost.
+pub fn render(
+    tracks: &[PlotTrack],
+    format: PlotFormat,
+    settings: &PlotSettings,
+) -> Result<Vec<u8>> {
+    settings.validate()?;
+    if tracks.is_empty() {
+        return Err(anyhow!("No tracks to plot."));
+    }
+    let columns = settings.columns.min(tracks.len());
+    let rows = tracks.len().div_ceil(columns);
+    let width = u64::from(settings.width) * columns as u64;
+    let height = u64::from(settings.height) * rows as u64;
+    if width * height > MAX_PIXELS || width > u64::from(u32::MAX) || height > u64::from(u32::MAX) {
+        return Err(anyhow!(
+            "Plot of {width}x{height} pixels is too large; plot fewer tracks per image or use \
+             smaller panels."
+        ));
+    }
+    let (width, height) = (width as u32, height as u32);
+
+    match format {
+        PlotFormat::Svg => {
+            let mut canvas = SvgCanvas::new(width, height);
+            draw_panels(&mut canvas, tracks, columns, settings);
+            Ok(canvas.finish().into_bytes())
+        }
+        PlotFormat::Png => {
+            let mut canvas = RasterCanvas::new(width, height);
+            draw_panels(&mut canvas, tracks, columns, settings);
+            let palette: Vec<[u8; 3]> = Color::ALL.iter().map(|color| color.rgb()).collect();
+            Ok(png::encode_indexed(width, height, &palette, &canvas.pixels))
+        }
+    }
+}
+
+/// Render every track into its own image in `directory` and return the written paths.
+///
+/// Files are named after the tracks by [`file_names`].
+pub fn write_track_images(
+    directory: &Path,
+    tracks: &[PlotTrack],
+    format: PlotFormat,
+    settings: &PlotSettings,
+) -> Result<Vec<PathBuf>> {
+    settings.validate()?;
+    std::fs::create_dir_all(directory)?;
+    file_names(tracks, format)
+        .into_iter()
+        .zip(tracks)
+        .map(|(name, track)| {
+            let path = directory.join(name);
+            std::fs::write(
+                &path,
+                render(std::slice::from_ref(track), format, settings)?,
+            )?;
+            Ok(path)
+        })
+        .collect()
+}
+
+/// File name of the image of every track: the track name with every character other than ASCII
+/// letters, digits, `-`, `_` and `.` replaced by `_`, and `_2`, `_3`, ... appended to names
+/// already taken (ignoring case).
+pub fn file_names(tracks: &[PlotTrack], format: PlotFormat) -> Vec<String> {
+    let mut taken = HashSet::new();
+    tracks
+        .iter()
+        .map(|track| {
+            let mut stem: String = track
+                .name
+                .trim()
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+            if stem.trim_matches('.').is_empty() || stem.starts_with('.') {
+                stem.insert_str(0, "track");
+            }
+            let mut name = format!("{stem}.{format}");
+            let mut index = 1;
+            while !taken.insert(name.to_ascii_lowercase()) {
+                index += 1;
+                name = format!("{stem}_{index}.{format}");
+            }
+            name
+        })
+        .collect()
+}
+
+fn draw_panels(
+    canvas: &mut dyn Canvas,
+    tracks: &[PlotTrack],
+    columns: usize,
+    settings: &PlotSettings,
+) {
+    for (position, track) in tracks.iter().enumerate() {
+        let origin = (
+            f64::from(settings.width) * (position % columns) as f64,
+            f64::from(settings.height) * (position / columns) as f64,
+        );
+        draw_panel(canvas, track, origin, settings);
+    }
+}
+
+/// Plot area of a panel and the data ranges it maps.
+struct Frame {
+    left: f64,
+    right: f64,
+    top: f64,
+    bottom: f64,
+    time: (f64, f64),
+    value: (f64, f64),
+}
+
+impl Frame {
+    fn x(&self, time: f64) -> f64 {
+        self.left + (time - self.time.0) / (self.time.1 - self.time.0) * (self.right - self.left)
+    }
+
+    fn y(&self, value: f64) -> f64 {
+        self.bottom
+            - (value - self.value.0) / (self.value.1 - self.value.0) * (self.bottom - self.top)
+    }
+}
+
+fn draw_panel(
+    canvas: &mut dyn Canvas,
+    track: &PlotTrack,
+    (x, y): (f64, f64),
+    settings: &PlotSettings,
+) {
+    let (width, height) = (f64::from(settings.width), f64::from(settings.height));
+    let samples = &track.data.time_series;
+    let period = 1.0 / track.data.sample_rate;
+    let time_at = |index: usize| track.start_time + index as f64 * period;
+
+    let extremes = extremes(samples);
+    let (minimum, maximum) = extremes.map_or((-1.0, 1.0), |((_, min), (_, max))| (min, max));
+    let (minimum, maximum) = if minimum == maximum {
+        let pad = if minimum == 0.0 {
+            1.0
+        } else {
+            minimum.abs() * 0.1
+        };
+        (minimum - pad, maximum + pad)
+    } else {
+        (minimum, maximum)
+    };
+    let value_step = nice_step(maximum - minimum);
+    let value = (
+        (minimum / value_step).floor() * value_step,
+        (maximum / value_step).ceil() * value_step,
+    );
+    let last = time_at(samples.len().saturating_sub(1));
+    let time = if samples.len() > 1 && last > time_at(0) {
+        (time_at(0), last)
+    } else {
+        (time_at(0) - period, time_at(0) + period)
+    };
+    let time_step = nice_step(time.1 - time.0);
+
+    let frame = Frame {
+        left: x + MARGIN_LEFT,
+        right: x + width - MARGIN_RIGHT,
+        top: y + MARGIN_TOP,
+        bottom: y + height - MARGIN_BOTTOM,
+        time,
+        value,
+    };
+
+    for tick in ticks(value, value_step) {
+        let tick_y = frame.y(tick);
+        canvas.polyline(
+            &[(frame.left, tick_y), (frame.right, tick_y)],
+            Color::Grid,
+            1.0,
+        );
+        canvas.text(
+            (frame.left - 6.0, tick_y),
+            &format_tick(tick, value_step),
+            TextSize::Label,
+            Anchor::End,
+            Color::Text,
+        );
+    }
+    for tick in ticks(time, time_step) {
+        let tick_x = frame.x(tick);
+        canvas.polyline(
+            &[(tick_x, frame.top), (tick_x, frame.bottom)],
+            Color::Grid,
+            1.0,
+        );
+        canvas.text(
+            (tick_x, frame.bottom + 12.0),
+            &format_tick(tick, time_step),
+            TextSize::Label,
+            Anchor::Middle,
+            Color::Text,
+        );
+    }
+    canvas.polyline(
+        &[
+            (frame.left, frame.top),
+            (frame.right, frame.top),
+            (frame.right, frame.bottom),
+            (frame.left, frame.bottom),
+            (frame.left, frame.top),
+        ],
+        Color::Frame,
+        1.0,
+    );
+
+    canvas.text(
+        (x + width / 2.0, y + 14.0),
+        &track.name,
+        TextSize::Title,
+        Anchor::Middle,
+        Color::Text,
+    );
+    if !track.data.units.trim().is_empty() {
+        canvas.text(
+            (frame.left, frame.top - 9.0),
+            &track.data.units,
+            TextSize::Label,
+            Anchor::Middle,
+            Color::Text,
+        );
+    }
+    canvas.text(
+        ((frame.left + frame.right) / 2.0, y + height - 10.0),
+        "Time (s)",
+        TextSize::Label,
+        Anchor::Middle,
+        Color::Text,
+    );
+
+    let points: Vec<(f64, f64)> = envelope(samples, (frame.right - frame.left) as usize)
+        .into_iter()
+        .map(|index| (frame.x(time_at(index)), frame.y(samples[index])))
+        .collect();
+    canvas.polyline(&points, Color::Trace, 1.5);
+
+    if let (true, Some((min, max))) = (settings.markers, extremes) {
+        for ((index, value), color, label) in [(max, Color::Max, "max"), (min, Color::Min, "min")] {
+            let point = (frame.x(time_at(index)), frame.y(value));
+            canvas.circle(point, 3.5, color);
+            let text = format!(
+                "{label} {}{}{} at {} s",
+                significant(value, 4),
+                if track.data.units.is_empty() { "" } else { " " },
+                track.data.units,
+                significant(time_at(index), 4)
+            );
+            let text_width = canvas.text_width(&text, TextSize::Label);
+            let (text_x, anchor) = if point.0 + 6.0 + text_width <= frame.right {
+                (point.0 + 6.0, Anchor::Start)
+            } else {
+                (point.0 - 6.0, Anchor::End)
+            };
+            // Labels go inside the plot area, below the maximum and above the minimum.
+            let text_y = if color == Color::Max {
+                (point.1 + 10.0).min(frame.bottom - 6.0)
+            } else {
+                (point.1 - 10.0).max(frame.top + 6.0)
+            };
+            let box_left = match anchor {
+                Anchor::End => text_x - text_width,
+                _ => text_x,
+            };
+            canvas.rect(
+                (box_left - 2.0, text_y - 7.0),
+                (text_width + 4.0, 14.0),
+                Color::Background,
+            );
+            canvas.text((text_x, text_y), &text, TextSize::Label, anchor, color);
+        }
+    }
+}
+
+/// Index and value of the minimum and maximum finite samples.
+fn extremes(samples: &[f64]) -> Option<((usize, f64), (usize, f64))> {
+    let mut finite = samples
+        .iter()
+        .copied()
+        .enumerate()
+        .filter(|(_, value)| value.is_finite());
+    let first = finite.next()?;
+    Some(finite.fold((first, first), |(min, max), sample| {
+        (
+            if sample.1 < min.1 { sample } else { min },
+            if sample.1 > max.1 { sample } else { max },
+        )
+    }))
+}
+
+/// Indices of the samples to draw: every finite sample of short tracks, or the minimum and
+/// maximum of each of `columns` buckets, in sample order.
+fn envelope(samples: &[f64], columns: usize) -> Vec<usize> {
+    let columns = columns.max(1);
+    if samples.len() <= 2 * columns {
+        return (0..samples.len())
+            .filter(|&index| samples[index].is_finite())
+            .collect();
+    }
+    let mut indices = Vec::with_capacity(2 * columns);
+    for column in 0..columns {
+        let start = column * samples.len() / columns;
+        let end = (column + 1) * samples.len() / columns;
+        if let Some(((min, _), (max, _))) = extremes(&samples[start..end]) {
+            indices.push(start + min.min(max));
+            if min != max {
+                indices.push(start + min.max(max));
+            }
+        }
+    }
+    indices
+}
+
+/// Tick spacing of 1, 2 or 5 times a power of ten giving about [`TARGET_TICKS`] ticks.
+fn nice_step(range: f64) -> f64 {
+    let raw = range / TARGET_TICKS;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let normalized = raw / magnitude;
+    let factor = if normalized < 1.5 {
+        1.0
+    } else if normalized < 3.0 {
+        2.0
+    } else if normalized < 7.0 {
+        5.0
+    } else {
+        10.0
+    };
+    factor * magnitude
+}
+
+/// Multiples of `step` within `range`.
+fn ticks((start, end): (f64, f64), step: f64) -> Vec<f64> {
+    let first = (start / step - 1e-9).ceil() as i64;
+    let last = (end / step + 1e-9).floor() as i64;
+    (first..=last).map(|tick| tick as f64 * step).collect()
+}
+
+fn format_tick(value: f64, step: f64) -> String {
+    if !(1e-4..1e6).contains(&step) {
+        return format!("{value:.1e}");
+    }
+    let decimals = (-step.log10()).ceil().max(0.0) as usize;
+    let text = format!("{value:.decimals$}");
+    if text
+        .trim_start_matches('-')
+        .chars()
+        .all(|c| c == '0' || c == '.')
+    {
+        text.trim_start_matches('-').to_string()
+    } else {
+        text
+    }
+}
+
+/// Format `value` with `digits` significant digits.
+fn significant(value: f64, digits: i32) -> String {
+    if value == 0.0 || !value.is_finite() {
+        return format!("{value}");
+    }
+    let exponent = value.abs().log10().floor() as i32;
+    if (-4..6).contains(&exponent) {
+        let decimals = (digits - 1 - exponent).max(0) as usize;
+        format!("{value:.decimals$}")
+    } else {
+        format!("{value:.*e}", (digits - 1) as usize)
+    }
+}
+
+/// Colours of a plot, in palette order.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Color {
+    Background,
+    Frame,
+    Grid,
+    Text,
+    Trace,
+    Max,
+    Min,
+}
+
+impl Color {
+    const ALL: [Color; 7] = [
+        Color::Background,
+        Color::Frame,
+        Color::Grid,
+        Color::Text,
+        Color::Trace,
+        Color::Max,
+        Color::Min,
+    ];
+
+    fn rgb(self) -> [u8; 3] {
+        match self {
+            Color::Background => [0xff, 0xff, 0xff],
+            Color::Frame => [0x40, 0x40, 0x40],
+            Color::Grid => [0xdd, 0xdd, 0xdd],
+            Color::Text => [0x20, 0x20, 0x20],
+            Color::Trace => [0x1f, 0x77, 0xb4],
+            Color::Max => [0xd6, 0x27, 0x28],
+            Color::Min => [0x2c, 0xa0, 0x2c],
+        }
+    }
+
+    fn hex(self) -> String {
+        let [red, green, blue] = self.rgb();
+        format!("#{red:02x}{green:02x}{blue:02x}")
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum TextSize {
+    Label,
+    Title,
+}
+
+/// Horizontal alignment of text relative to its position.
+#[derive(Clone, Copy, Debug)]
+enum Anchor {
+    Start,
+    Middle,
+    End,
+}
+
+/// Drawing primitives shared by the SVG and PNG output.
+trait Canvas {
+    fn polyline(&mut self, points: &[(f64, f64)], color: Color, width: f64);
+    fn circle(&mut self, center: (f64, f64), radius: f64, color: Color);
+    fn rect(&mut self, top_left: (f64, f64), size: (f64, f64), color: Color);
+    /// Draw `text` vertically centred on the position.
+    fn text(
+        &mut self,
+        position: (f64, f64),
+        text: &str,
+        size: TextSize,
+        anchor: Anchor,
+        color: Color,
+    );
+    fn text_width(&self, text: &str, size: TextSize) -> f64;
+}
+
+struct SvgCanvas {
+    svg: String,
+}
+
+impl SvgCanvas {
+    fn new(width: u32, height: u32) -> Self {
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+        );
+        let _ = writeln!(
+            svg,
+            r#"<rect width="100%" height="100%" fill="{}"/>"#,
+            Color::Background.hex()
+        );
+        Self { svg }
+    }
+
+    fn finish(mut self) -> String {
+        self.svg.push_str("</svg>\n");
+        self.svg
+    }
+
+    fn font_size(size: TextSize) -> f64 {
+        match size {
+            TextSize::Label => 11.0,
+            TextSize::Title => 14.0,
+        }
+    }
+}
+
+impl Canvas for SvgCanvas {
+    fn polyline(&mut self, points: &[(f64, f64)], color: Color, width: f64) {
+        if points.len() < 2 {
+            return;
+        }
+        let _ = write!(
+            self.svg,
+            r#"<polyline fill="none" stroke="{}" stroke-width="{width}" stroke-linejoin="round" points=""#,
+            color.hex()
+        );
+        for (index, (x, y)) in points.iter().enumerate() {
+            let separator = if index == 0 { "" } else { " " };
+            let _ = write!(self.svg, "{separator}{x:.1},{y:.1}");
+        }
+        self.svg.push_str("\"/>\n");
+    }
+
+    fn circle(&mut self, (x, y): (f64, f64), radius: f64, color: Color) {
+        let _ = writeln!(
+            self.svg,
+            r#"<circle cx="{x:.1}" cy="{y:.1}" r="{radius}" fill="{}"/>"#,
+            color.hex()
+        );
+    }
+
+    fn rect(&mut self, (x, y): (f64, f64), (width, height): (f64, f64), color: Color) {
+        let _ = writeln!(
+            self.svg,
+            r#"<rect x="{x:.1}" y="{y:.1}" width="{width:.1}" height="{height:.1}" fill="{}"/>"#,
+            color.hex()
+        );
+    }
+
+    fn text(
+        &mut self,
+        (x, y): (f64, f64),
+        text: &str,
+        size: TextSize,
+        anchor: Anchor,
+        color: Color,
+    ) {
+        let anchor = match anchor {
+            Anchor::Start => "start",
+            Anchor::Middle => "middle",
+            Anchor::End => "end",
+        };
+        let weight = match size {
+            TextSize::Label => "",
+            TextSize::Title => r#" font-weight="bold""#,
+        };
+        let _ = writeln!(
+            self.svg,
+            r#"<text x="{x:.1}" y="{y:.1}" font-family="sans-serif" font-size="{}"{weight} text-anchor="{anchor}" dominant-baseline="central" fill="{}">{}</text>"#,
+            Self::font_size(size),
+            color.hex(),
+            escape(text)
+        );
+    }
+
+    fn text_width(&self, text: &str, size: TextSize) -> f64 {
+        0.6 * Self::font_size(size) * text.chars().count() as f64
+    }
+}
+
+/// Palette image drawn with aliased lines and the built-in 5x7 pixel font.
+struct RasterCanvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl RasterCanvas {
+    fn new(width: u32, height: u32) -> Self {
+        let (width, height) = (width as usize, height as usize);
+        Self {
+            width,
+            height,
+            pixels: vec![Color::Background as u8; width * height],
+        }
+    }
+
+    fn fill(&mut self, x: i64, y: i64, size: i64, color: Color) {
+        for row in y.max(0)..(y + size).min(self.height as i64) {
+            for column in x.max(0)..(x + size).min(self.width as i64) {
+                self.pixels[row as usize * self.width + column as usize] = color as u8;
+            }
+        }
+    }
+
+    fn scale(size: TextSize) -> i64 {
+        match size {
+            TextSize::Label => 1,
+            TextSize::Title => 2,
+        }
+    }
+}
+
+impl Canvas for RasterCanvas {
+    fn polyline(&mut self, points: &[(f64, f64)], color: Color, width: f64) {
+        let brush = width.round().max(1.0) as i64;
+        let offset = (brush - 1) / 2;
+        for segment in points.windows(2) {
+            let ((x0, y0), (x1, y1)) = (segment[0], segment[1]);
+            let steps = (x1 - x0).abs().max((y1 - y0).abs()).ceil().max(1.0);
+            for step in 0..=steps as i64 {
+                let fraction = step as f64 / steps;
+                let x = (x0 + (x1 - x0) * fraction).round() as i64;
+                let y = (y0 + (y1 - y0) * fraction).round() as i64;
+                self.fill(x - offset, y - offset, brush, color);
+            }
+        }
+    }
+
+    fn circle(&mut self, (x, y): (f64, f64), radius: f64, color: Color) {
+        let reach = radius.ceil() as i64;
+        let (center_x, center_y) = (x.round() as i64, y.round() as i64);
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                if ((dx * dx + dy * dy) as f64) <= radius * radius {
+                    self.fill(center_x + dx, center_y + dy, 1, color);
+                }
+            }
+        }
+    }
+
+    fn rect(&mut self, (x, y): (f64, f64), (width, height): (f64, f64), color: Color) {
+        let (left, top) = (x.round() as i64, y.round() as i64);
+        for row in top..top + height.round() as i64 {
+            for column in left..left + width.round() as i64 {
+                self.fill(column, row, 1, color);
+            }
+        }
+    }
+
+    fn text(
+        &mut self,
+        (x, y): (f64, f64),
+        text: &str,
+        size: TextSize,
+        anchor: Anchor,
+        color: Color,
+    ) {
+        let scale = Self::scale(size);
+        let width = self.text_width(text, size);
+        let left = match anchor {
+            Anchor::Start => x,
+            Anchor::Middle => x - width / 2.0,
+            Anchor::End => x - width,
+        }
+        .round() as i64;
+        let top = (y - 3.5 * scale as f64).round() as i64;
+        for (position, character) in text.chars().enumerate() {
+            let glyph = match character {
+                ' '..='~' => &FONT[character as usize - 32],
+                _ => &FONT['?' as usize - 32],
+            };
+            let glyph_left = left + position as i64 * 6 * scale;
+            for (row, bits) in glyph.iter().enumerate() {
+                for column in 0..5 {
+                    if bits & (0x10 >> column) != 0 {
+                        self.fill(
+                            glyph_left + column * scale,
+                            top + row as i64 * scale,
+                            scale,
+                            color,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn text_width(&self, text: &str, size: TextSize) -> f64 {
+        // Glyphs are 5 pixels wide with one pixel of spacing.
+        let characters = text.chars().count() as f64;
+        (6.0 * characters - 1.0).max(0.0) * Self::scale(size) as f64
+    }
+}
+
+/// 5x7 pixel glyphs of the printable ASCII characters, one row per byte with the leftmost
+/// column in bit 4.
+const FONT: [[u8; 7]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04],
+    [0x0a, 0x0a, 0x0a, 0x00, 0x00, 0x00, 0x00],
+    [0x0a, 0x0a, 0x1f, 0x0a, 0x1f, 0x0a, 0x0a],
+    [0x04, 0x0f, 0x14, 0x0e, 0x05, 0x1e, 0x04],
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03],
+    [0x0c, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0d],
+    [0x04, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00],
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+    [0x00, 0x04, 0x15, 0x0e, 0x15, 0x04, 0x00],
+    [0x00, 0x04, 0x04, 0x1f, 0x04, 0x04, 0x00],
+    [0x00, 0x00, 0x00, 0x00, 0x0c, 0x04, 0x08],
+    [0x00, 0x00, 0x00, 0x1f, 0x00, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x0c],
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+    [0x0e, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0e],
+    [0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e],
+    [0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f],
+    [0x1f, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0e],
+    [0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02],
+    [0x1f, 0x10, 0x1e, 0x01, 0x01, 0x11, 0x0e],
+    [0x06, 0x08, 0x10, 0x1e, 0x11, 0x11, 0x0e],
+    [0x1f, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+    [0x0e, 0x11, 0x11, 0x0e, 0x11, 0x11, 0x0e],
+    [0x0e, 0x11, 0x11, 0x0f, 0x01, 0x02, 0x0c],
+    [0x00, 0x0c, 0x0c, 0x00, 0x0c, 0x0c, 0x00],
+    [0x00, 0x0c, 0x0c, 0x00, 0x0c, 0x04, 0x08],
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02],
+    [0x00, 0x00, 0x1f, 0x00, 0x1f, 0x00, 0x00],
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08],
+    [0x0e, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    [0x0e, 0x11, 0x01, 0x0d, 0x15, 0x15, 0x0e],
+    [0x0e, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11],
+    [0x1e, 0x11, 0x11, 0x1e, 0x11, 0x11, 0x1e],
+    [0x0e, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0e],
+    [0x1c, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1c],
+    [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x1f],
+    [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x10],
+    [0x0e, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0f],
+    [0x11, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11],
+    [0x0e, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0e],
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0c],
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1f],
+    [0x11, 0x1b, 0x15, 0x15, 0x11, 0x11, 0x11],
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+    [0x0e, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e],
+    [0x1e, 0x11, 0x11, 0x1e, 0x10, 0x10, 0x10],
+    [0x0e, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0d],
+    [0x1e, 0x11, 0x11, 0x1e, 0x14, 0x12, 0x11],
+    [0x0f, 0x10, 0x10, 0x0e, 0x01, 0x01, 0x1e],
+    [0x1f, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e],
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0a, 0x04],
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0a],
+    [0x11, 0x11, 0x0a, 0x04, 0x0a, 0x11, 0x11],
+    [0x11, 0x11, 0x11, 0x0a, 0x04, 0x04, 0x04],
+    [0x1f, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1f],
+    [0x0e, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0e],
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00],
+    [0x0e, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0e],
+    [0x04, 0x0a, 0x11, 0x00, 0x00, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1f],
+    [0x08, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00],
+    [0x00, 0x00, 0x0e, 0x01, 0x0f, 0x11, 0x0f],
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x1e],
+    [0x00, 0x00, 0x0e, 0x10, 0x10, 0x11, 0x0e],
+    [0x01, 0x01, 0x0d, 0x13, 0x11, 0x11, 0x0f],
+    [0x00, 0x00, 0x0e, 0x11, 0x1f, 0x10, 0x0e],
+    [0x06, 0x09, 0x08, 0x1c, 0x08, 0x08, 0x08],
+    [0x00, 0x0f, 0x11, 0x11, 0x0f, 0x01, 0x0e],
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x11],
+    [0x04, 0x00, 0x0c, 0x04, 0x04, 0x04, 0x0e],
+    [0x02, 0x00, 0x06, 0x02, 0x02, 0x12, 0x0c],
+    [0x10, 0x10, 0x12, 0x14, 0x18, 0x14, 0x12],
+    [0x0c, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0e],
+    [0x00, 0x00, 0x1a, 0x15, 0x15, 0x11, 0x11],
+    [0x00, 0x00, 0x16, 0x19, 0x11, 0x11, 0x11],
+    [0x00, 0x00, 0x0e, 0x11, 0x11, 0x11, 0x0e],
+    [0x00, 0x00, 0x1e, 0x11, 0x1e, 0x10, 0x10],
+    [0x00, 0x00, 0x0d, 0x13, 0x0f, 0x01, 0x01],
+    [0x00, 0x00, 0x16, 0x19, 0x10, 0x10, 0x10],
+    [0x00, 0x00, 0x0e, 0x10, 0x0e, 0x01, 0x1e],
+    [0x08, 0x08, 0x1c, 0x08, 0x08, 0x09, 0x06],
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x13, 0x0d],
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x0a, 0x04],
+    [0x00, 0x00, 0x11, 0x11, 0x15, 0x15, 0x0a],
+    [0x00, 0x00, 0x11, 0x0a, 0x04, 0x0a, 0x11],
+    [0x00, 0x00, 0x11, 0x11, 0x0f, 0x01, 0x0e],
+    [0x00, 0x00, 0x1f, 0x02, 0x04, 0x08, 0x1f],
+    [0x02, 0x04, 0x04, 0x08, 0x04, 0x04, 0x02],
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+    [0x08, 0x04, 0x04, 0x02, 0x04, 0x04, 0x08],
+    [0x00, 0x00, 0x08, 0x15, 0x02, 0x00, 0x00],
+];
@@ -0,0 +1,42 @@
+use miniz_oxide::deflate::compress_to_vec_zlib;
+
+/// Compression level passed to the deflate encoder (0-10).
+const COMPRESSION_LEVEL: u8 = 6;
+
+/// Encode an 8-bit palette image as a PNG file. `pixels` holds one palette index per pixel, row
+/// by row.
+pub fn encode_indexed(width: u32, height: u32, palette: &[[u8; 3]], pixels: &[u8]) -> Vec<u8> {
+    debug_assert_eq!(pixels.len(), width as usize * height as usize);
+    // Every row starts with filter type 0 (none).
+    let mut scanlines = Vec::with_capacity(pixels.len() + height as usize);
+    for row in pixels.chunks(width as usize) {
+        scanlines.push(0);
+        scanlines.extend_from_slice(row);
+    }
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    let mut header = Vec::with_capacity(13);
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    // Bit depth 8, colour type 3 (palette), deflate, adaptive filtering, no interlace.
+    header.extend([8, 3, 0, 0, 0]);
+    chunk(&mut png, b"IHDR", &header);
+    chunk(&mut png, b"PLTE", &palette.concat());
+    chunk(
+        &mut png,
+        b"IDAT",
+        &compress_to_vec_zlib(&scanlines, COMPRESSION_LEVEL),
+    );
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    png.extend(kind);
+    png.extend(data);
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(kind);
+    crc.update(data);
+    png.extend(crc.finalize().to_be_bytes());
+}
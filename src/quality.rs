@@ -0,0 +1,190 @@
+use crate::dts::RawTrack;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+/// Data-quality checks run on the raw ADC stream of every track.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct QualitySettings {
+    /// Run the checks. Enabled by default.
+    pub enabled: bool,
+    /// Fail the conversion instead of reporting warnings when a check finds an issue.
+    pub strict: bool,
+    /// Smallest number of samples at the ADC limits reported as clipping.
+    pub clipped_samples: usize,
+    /// Standard deviation, in ADC counts, below which a channel is reported as flat.
+    pub flat_threshold: f64,
+    /// Ratio of the record noise to the pre-test noise in the channel header above which a
+    /// channel is reported as noisy.
+    pub noise_ratio: f64,
+}
+
+impl Default for QualitySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            strict: false,
+            clipped_samples: 1,
+            flat_threshold: 0.5,
+            noise_ratio: 10.0,
+        }
+    }
+}
+
+impl QualitySettings {
+    /// Ensure the thresholds are usable.
+    pub fn validate(&self) -> Result<()> {
+        if !(self.flat_threshold >= 0.0 && self.flat_threshold.is_finite()) {
+            return Err(anyhow!(
+                "Invalid flat-channel threshold ({}): expected a non-negative number of ADC counts.",
+                self.flat_threshold
+            ));
+        }
+        if self.noise_ratio <= 0.0 || self.noise_ratio.is_nan() {
+            return Err(anyhow!(
+                "Invalid noise ratio ({}): expected a positive value.",
+                self.noise_ratio
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A data-quality problem found in a track.
+#[derive(Clone, Debug, PartialEq)]
+pub enum QualityIssue {
+    /// Samples at the lowest or highest ADC value.
+    Clipped {
+        low: usize,
+        high: usize,
+        total: usize,
+    },
+    /// Every sample has the same ADC value.
+    Constant { adc: i16 },
+    /// The standard deviation is below [`QualitySettings::flat_threshold`].
+    Flat { std_dev: f64 },
+    /// The noise of the record is well above the pre-test noise.
+    Noisy {
+        noise_percent: f64,
+        pre_test_percent: f64,
+    },
+}
+
+impl QualityIssue {
+    /// Returns the short name of the check that found the issue.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QualityIssue::Clipped { .. } => "clipped",
+            QualityIssue::Constant { .. } => "constant",
+            QualityIssue::Flat { .. } => "flat",
+            QualityIssue::Noisy { .. } => "noisy",
+        }
+    }
+}
+
+impl std::fmt::Display for QualityIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QualityIssue::Clipped { low, high, total } => write!(
+                f,
+                "{} of {} samples ({:.2}%) at the ADC limits ({} low, {} high)",
+                low + high,
+                total,
+                100.0 * (low + high) as f64 / *total as f64,
+                low,
+                high
+            ),
+            QualityIssue::Constant { adc } => {
+                write!(f, "every sample has the same ADC value ({adc})")
+            }
+            QualityIssue::Flat { std_dev } => write!(
+                f,
+                "near-zero variance (standard deviation {std_dev:.3} ADC counts)"
+            ),
+            QualityIssue::Noisy {
+                noise_percent,
+                pre_test_percent,
+            } => write!(
+                f,
+                "noise of {noise_percent:.4}% FS is {:.1}x the pre-test noise ({pre_test_percent:.4}% FS)",
+                noise_percent / pre_test_percent
+            ),
+        }
+    }
+}
+
+/// Check the raw ADC samples of a track for clipping, dead channels and excessive noise.
+pub fn check_track(raw: &RawTrack, settings: &QualitySettings) -> Vec<QualityIssue> {
+    let samples = &raw.adc;
+    let mut issues = Vec::new();
+    if samples.is_empty() {
+        return issues;
+    }
+
+    let (min, max) = raw.adc_limits();
+    let low = samples.iter().filter(|&&adc| adc <= min).count();
+    let high = samples.iter().filter(|&&adc| adc >= max).count();
+    if low + high >= settings.clipped_samples.max(1) {
+        issues.push(QualityIssue::Clipped {
+            low,
+            high,
+            total: samples.len(),
+        });
+    }
+
+    if samples.iter().all(|&adc| adc == samples[0]) {
+        issues.push(QualityIssue::Constant { adc: samples[0] });
+        return issues;
+    }
+    let count = samples.len() as f64;
+    let mean = samples.iter().map(|&adc| f64::from(adc)).sum::<f64>() / count;
+    let variance = samples
+        .iter()
+        .map(|&adc| (f64::from(adc) - mean).powi(2))
+        .sum::<f64>()
+        / count;
+    let std_dev = variance.sqrt();
+    if std_dev < settings.flat_threshold {
+        issues.push(QualityIssue::Flat { std_dev });
+    }
+
+    let pre_test_percent = raw.pre_test_noise_percent;
+    if pre_test_percent.is_finite() && pre_test_percent > 0.0 {
+        let full_scale = f64::from(max) + 1.0;
+        let noise_percent = 100.0 * noise_counts(samples) / full_scale;
+        if noise_percent > settings.noise_ratio * pre_test_percent {
+            issues.push(QualityIssue::Noisy {
+                noise_percent,
+                pre_test_percent,
+            });
+        }
+    }
+
+    issues
+}
+
+/// Standard deviation of the broadband noise, in ADC counts.
+///
+/// Estimated from the median absolute deviation of the first differences, which ignores the
+/// slowly varying signal and short transients such as the impact itself.
+fn noise_counts(samples: &[i16]) -> f64 {
+    let mut differences: Vec<f64> = samples
+        .windows(2)
+        .map(|pair| f64::from(pair[1]) - f64::from(pair[0]))
+        .collect();
+    if differences.is_empty() {
+        return 0.0;
+    }
+    let centre = median(&mut differences);
+    let mut deviations: Vec<f64> = differences
+        .iter()
+        .map(|difference| (difference - centre).abs())
+        .collect();
+    // MAD to standard deviation for Gaussian noise, and the sqrt(2) gain of the difference.
+    median(&mut deviations) / 0.6745 / std::f64::consts::SQRT_2
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    let middle = values.len() / 2;
+    let (_, value, _) = values.select_nth_unstable_by(middle, f64::total_cmp);
+    *value
+}
@@ -0,0 +1,283 @@
+use crate::conversion::SampleSlice;
+use crate::dts::{AdcScaling, ChannelData, DtsReader, TestInfo};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Name of the manifest in a raw export directory.
+pub const MANIFEST_FILE: &str = "manifest.json";
+/// Value of the manifest's `format` field.
+pub const RAW_FORMAT: &str = "dts-raw";
+/// Version of the manifest layout written by [`RawWriter`].
+pub const RAW_VERSION: u32 = 1;
+/// Extension of the sample files.
+const SAMPLE_EXTENSION: &str = "i16";
+/// Samples converted per write while storing a track.
+const BLOCK_SAMPLES: usize = 64 * 1024;
+
+/// Manifest of a raw export, stored as [`MANIFEST_FILE`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawManifest {
+    /// Always [`RAW_FORMAT`].
+    pub format: String,
+    /// Always [`RAW_VERSION`].
+    pub version: u32,
+    pub test: RawTest,
+    /// Tracks in output order.
+    pub tracks: Vec<RawTrackEntry>,
+}
+
+/// Test-level information of a raw export.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawTest {
+    pub id: String,
+    pub description: String,
+    pub date: String,
+}
+
+/// One track of a raw export and the scaling that turns its counts into engineering units.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawTrackEntry {
+    pub name: String,
+    /// File holding the ADC counts as little-endian `i16`, relative to the export directory.
+    pub file: String,
+    pub sample_count: usize,
+    /// Index of the first stored sample in the recorded track, when the export is sliced.
+    pub first_sample: usize,
+    pub sample_rate: f64,
+    /// Time of the first stored sample, in seconds.
+    pub start_time: f64,
+    pub units: String,
+    /// Resolution of the recorder, in bits.
+    pub bits_per_sample: u32,
+    pub scaling: RawScaling,
+    /// Values the scaling was computed from, for reference. [`reconstruct_track`] only uses
+    /// `scaling`.
+    pub parameters: RawScalingParameters,
+}
+
+/// Conversion from counts to engineering units: `counts * eu_per_count + offset`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RawScaling {
+    pub eu_per_count: f64,
+    pub offset: f64,
+}
+
+/// The `.chn` header values and `.dts` settings behind a [`RawScaling`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawScalingParameters {
+    /// Sensor output per ADC count, in mV, before inversion.
+    pub scale_factor_mv: f64,
+    /// Sensor output per engineering unit, in mV (per volt of excitation).
+    pub scale_factor_eu: f64,
+    /// Excitation the sensitivity is proportional to, or 1 for self-generating sensors.
+    pub excitation_voltage: f64,
+    pub inverted: bool,
+    /// `UsePreCalZero`, `AverageOverTime` or `None`.
+    pub zero_method: String,
+    /// ADC level the zero method subtracts.
+    pub zero_level_adc: i32,
+    pub initial_eu: f64,
+}
+
+impl From<AdcScaling> for RawScaling {
+    fn from(scaling: AdcScaling) -> Self {
+        Self {
+            eu_per_count: scaling.eu_per_count,
+            offset: scaling.offset,
+        }
+    }
+}
+
+/// Writes a raw export: a directory with the unscaled ADC counts of every track, one
+/// little-endian `i16` file per track, and a JSON [`MANIFEST_FILE`] with the exact scaling of
+/// each track.
+///
+/// Scaling values are written with their shortest exact decimal representation, so
+/// [`reconstruct`] returns the samples of [`DtsReader::read_track`] bit for bit.
+pub struct RawWriter {
+    directory: PathBuf,
+    manifest: RawManifest,
+}
+
+impl RawWriter {
+    /// Create the export directory at `directory` for `test`.
+    pub fn create(directory: &Path, test: &TestInfo) -> Result<Self> {
+        fs::create_dir_all(directory)
+            .with_context(|| format!("Failed to create {}", directory.display()))?;
+        Ok(Self {
+            directory: directory.to_path_buf(),
+            manifest: RawManifest {
+                format: RAW_FORMAT.to_string(),
+                version: RAW_VERSION,
+                test: RawTest {
+                    id: test.id.clone(),
+                    description: test.description.clone(),
+                    date: test.date.clone(),
+                },
+                tracks: Vec::new(),
+            },
+        })
+    }
+
+    /// Store the counts of track `track_index` of `reader` as `name`, limited to `slice`.
+    pub fn write_track(
+        &mut self,
+        reader: &DtsReader,
+        track_index: usize,
+        name: &str,
+        slice: Option<SampleSlice>,
+    ) -> Result<()> {
+        if name.is_empty() || name.contains(['/', '\\']) {
+            return Err(anyhow!(
+                "Track '{name}' cannot be used as a file name in the raw export."
+            ));
+        }
+        if self.manifest.tracks.iter().any(|track| track.name == name) {
+            return Err(anyhow!(
+                "Track '{name}' is exported twice; the raw export needs unique track names."
+            ));
+        }
+
+        let raw = reader
+            .read_track_raw(track_index)
+            .with_context(|| format!("Failed to read channel {}", track_index + 1))?;
+        let range = match slice {
+            Some(slice) => slice.as_range(raw.adc.len())?,
+            None => 0..raw.adc.len(),
+        };
+        let scaling = reader.adc_scaling(track_index);
+        let (parameters, sample_rate) = reader
+            .scaling_parameters(track_index)
+            .zip(reader.sample_rate(track_index))
+            .ok_or_else(|| anyhow!("Track index {} is out of bounds.", track_index))?;
+        let units = reader
+            .channel_info(track_index)
+            .map(|channel| channel.eu.clone())
+            .unwrap_or_default();
+
+        let values = [
+            scaling.eu_per_count,
+            scaling.offset,
+            parameters.scale_factor_mv,
+            parameters.scale_factor_eu,
+            parameters.excitation_voltage,
+            parameters.initial_eu,
+            sample_rate,
+        ];
+        if values.iter().any(|value| !value.is_finite()) {
+            return Err(anyhow!(
+                "Track '{name}' has a scaling that is not finite and cannot be stored in the raw export."
+            ));
+        }
+
+        let file = format!("{name}.{SAMPLE_EXTENSION}");
+        let path = self.directory.join(&file);
+        let mut writer = BufWriter::new(
+            File::create(&path).with_context(|| format!("Failed to create {}", path.display()))?,
+        );
+        let mut block = Vec::with_capacity(2 * BLOCK_SAMPLES.min(range.len()));
+        for chunk in raw.adc[range.clone()].chunks(BLOCK_SAMPLES) {
+            block.clear();
+            block.extend(chunk.iter().flat_map(|count| count.to_le_bytes()));
+            writer.write_all(&block)?;
+        }
+        writer
+            .flush()
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+
+        self.manifest.tracks.push(RawTrackEntry {
+            name: name.to_string(),
+            file,
+            sample_count: range.len(),
+            first_sample: range.start,
+            sample_rate,
+            start_time: range.start as f64 / sample_rate,
+            units,
+            bits_per_sample: raw.bits_per_sample,
+            scaling: scaling.into(),
+            parameters: RawScalingParameters {
+                scale_factor_mv: parameters.scale_factor_mv,
+                scale_factor_eu: parameters.scale_factor_eu,
+                excitation_voltage: parameters.excitation_voltage,
+                inverted: parameters.inverted,
+                zero_method: parameters.zero_method.as_str().to_string(),
+                zero_level_adc: parameters.zero_level_adc,
+                initial_eu: parameters.initial_eu,
+            },
+        });
+        Ok(())
+    }
+
+    /// Write the manifest.
+    pub fn finish(&mut self) -> Result<()> {
+        let path = self.directory.join(MANIFEST_FILE);
+        let mut writer = BufWriter::new(
+            File::create(&path).with_context(|| format!("Failed to create {}", path.display()))?,
+        );
+        serde_json::to_writer_pretty(&mut writer, &self.manifest)?;
+        writeln!(writer)?;
+        writer
+            .flush()
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Read the manifest of the raw export in `directory`.
+pub fn read_manifest(directory: &Path) -> Result<RawManifest> {
+    let path = directory.join(MANIFEST_FILE);
+    let text =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let manifest: RawManifest = serde_json::from_str(&text)
+        .with_context(|| format!("Invalid raw export manifest {}", path.display()))?;
+    if manifest.format != RAW_FORMAT || manifest.version != RAW_VERSION {
+        return Err(anyhow!(
+            "Unsupported raw export '{}' version {} in {}; expected '{RAW_FORMAT}' version {RAW_VERSION}.",
+            manifest.format,
+            manifest.version,
+            path.display()
+        ));
+    }
+    Ok(manifest)
+}
+
+/// Scale the stored counts of `track` back into engineering units, exactly as
+/// [`DtsReader::scale_track`] does.
+pub fn reconstruct_track(directory: &Path, track: &RawTrackEntry) -> Result<ChannelData> {
+    let path = directory.join(&track.file);
+    let bytes = fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    if bytes.len() != track.sample_count * 2 {
+        return Err(anyhow!(
+            "{} holds {} bytes, but the manifest lists {} samples.",
+            path.display(),
+            bytes.len(),
+            track.sample_count
+        ));
+    }
+
+    let RawScaling {
+        eu_per_count,
+        offset,
+    } = track.scaling;
+    let time_series = bytes
+        .chunks_exact(2)
+        .map(|count| (f64::from(i16::from_le_bytes([count[0], count[1]])) * eu_per_count) + offset)
+        .collect();
+    Ok(ChannelData {
+        time_series,
+        sample_rate: track.sample_rate,
+        units: track.units.clone(),
+    })
+}
+
+/// Reconstruct every track of the raw export in `directory`, in manifest order.
+pub fn reconstruct(directory: &Path) -> Result<Vec<(String, ChannelData)>> {
+    let manifest = read_manifest(directory)?;
+    manifest
+        .tracks
+        .iter()
+        .map(|track| Ok((track.name.clone(), reconstruct_track(directory, track)?)))
+        .collect()
+}
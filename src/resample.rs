@@ -0,0 +1,189 @@
+use crate::dts::ChannelData;
+use anyhow::{anyhow, Result};
+use std::f64::consts::PI;
+use std::ops::Range;
+
+/// Largest interpolation or decimation factor used to approximate a rate ratio.
+pub const MAX_FACTOR: u64 = 10_000;
+
+/// Zero crossings of the anti-aliasing filter on each side of its centre, in output periods.
+const HALF_ZERO_CROSSINGS: usize = 16;
+/// Kaiser window shape of the anti-aliasing filter.
+const KAISER_BETA: f64 = 8.0;
+
+/// Describes a sample rate change applied to one channel.
+///
+/// The channel is upsampled by `up`, low-pass filtered and downsampled by `down`, so the
+/// effective output rate is `from * up / down`. It can differ slightly from the requested rate
+/// when the ratio needs factors larger than [`MAX_FACTOR`]; ratios that exceed [`MAX_FACTOR`]
+/// themselves are rejected.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Resampling {
+    /// Native sample rate, in Hz.
+    pub from: f64,
+    /// Effective output sample rate, in Hz.
+    pub to: f64,
+    pub up: u64,
+    pub down: u64,
+}
+
+impl Resampling {
+    /// Rational approximation of `target_rate / from`. Returns `None` when the rates match.
+    pub fn new(from: f64, target_rate: f64) -> Result<Option<Self>> {
+        if !(target_rate.is_finite() && target_rate > 0.0) {
+            return Err(anyhow!(
+                "Invalid target sample rate ({target_rate} Hz): expected a positive value."
+            ));
+        }
+        if !(from.is_finite() && from > 0.0) {
+            return Err(anyhow!(
+                "Cannot resample a channel with sample rate {from} Hz."
+            ));
+        }
+
+        let Some((up, down)) = rational_approximation(target_rate / from, MAX_FACTOR) else {
+            return Err(anyhow!(
+                "Target sample rate {target_rate} Hz is too high for a channel sampled at {from} Hz: \
+                 the upsampling factor is limited to {MAX_FACTOR}."
+            ));
+        };
+        if up == 0 {
+            return Err(anyhow!(
+                "Target sample rate {target_rate} Hz is too low for a channel sampled at {from} Hz."
+            ));
+        }
+        if up == down {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            from,
+            to: from * up as f64 / down as f64,
+            up,
+            down,
+        }))
+    }
+
+    /// Number of output samples for `len` input samples.
+    pub fn output_len(&self, len: usize) -> usize {
+        self.output_index(len)
+    }
+
+    /// First output sample at or after input sample `index`.
+    pub fn output_index(&self, index: usize) -> usize {
+        (index as u64 * self.up).div_ceil(self.down) as usize
+    }
+
+    /// Output samples covering the input samples in `range`.
+    pub fn output_range(&self, range: Range<usize>) -> Range<usize> {
+        self.output_index(range.start)..self.output_index(range.end)
+    }
+
+    /// Resample `samples` with a polyphase Kaiser-windowed sinc filter.
+    ///
+    /// The record is extended with its first and last values so that an offset does not droop at
+    /// the edges.
+    pub fn apply(&self, samples: &[f64]) -> Vec<f64> {
+        if samples.is_empty() {
+            return Vec::new();
+        }
+        let up = self.up as usize;
+        let down = self.down as usize;
+        let taps = self.filter();
+        let delay = (taps.len() - 1) / 2;
+        let last = samples.len() as i64 - 1;
+
+        (0..self.output_len(samples.len()))
+            .map(|k| {
+                // Position in the upsampled stream, shifted by the filter delay.
+                let position = k * down + delay;
+                (position % up..taps.len())
+                    .step_by(up)
+                    .map(|tap| {
+                        let index = (position as i64 - tap as i64).div_euclid(up as i64);
+                        taps[tap] * samples[index.clamp(0, last) as usize]
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Anti-aliasing low-pass at the lower of the two Nyquist frequencies, scaled by `up`.
+    fn filter(&self) -> Vec<f64> {
+        let factor = self.up.max(self.down) as usize;
+        let half = HALF_ZERO_CROSSINGS * factor;
+        let len = 2 * half + 1;
+        let mut taps: Vec<f64> = (0..len)
+            .map(|n| {
+                let x = (n as f64 - half as f64) / factor as f64;
+                let sinc = if x == 0.0 {
+                    1.0
+                } else {
+                    (PI * x).sin() / (PI * x)
+                };
+                let r = (n as f64 - half as f64) / half as f64;
+                sinc * bessel_i0(KAISER_BETA * (1.0 - r * r).max(0.0).sqrt())
+            })
+            .collect();
+
+        // Unity gain at DC for every polyphase branch on average.
+        let scale = self.up as f64 / taps.iter().sum::<f64>();
+        for tap in &mut taps {
+            *tap *= scale;
+        }
+        taps
+    }
+}
+
+/// Resample `data` in place to `target_rate`.
+///
+/// Returns the applied change, or `None` when the channel is already at the target rate.
+pub fn resample(data: &mut ChannelData, target_rate: f64) -> Result<Option<Resampling>> {
+    let Some(resampling) = Resampling::new(data.sample_rate, target_rate)? else {
+        return Ok(None);
+    };
+    data.time_series = resampling.apply(&data.time_series);
+    data.sample_rate = resampling.to;
+    Ok(Some(resampling))
+}
+
+/// Best rational approximation `p / q` of `value` with `p` and `q` at most `max`.
+///
+/// Returns `None` when `value` exceeds `max`, so that no convergent fits.
+fn rational_approximation(value: f64, max: u64) -> Option<(u64, u64)> {
+    // Continued fraction convergents.
+    let (mut p0, mut q0, mut p1, mut q1) = (0u64, 1u64, 1u64, 0u64);
+    let mut remainder = value;
+    loop {
+        let term = remainder.floor();
+        if term > max as f64 {
+            break;
+        }
+        let a = term as u64;
+        let (p2, q2) = (a * p1 + p0, a * q1 + q0);
+        if p2 > max || q2 > max {
+            break;
+        }
+        (p0, q0, p1, q1) = (p1, q1, p2, q2);
+        let fraction = remainder - term;
+        if fraction < 1e-12 || (p1 as f64 / q1 as f64 - value).abs() <= value * 1e-12 {
+            break;
+        }
+        remainder = 1.0 / fraction;
+    }
+    (q1 != 0).then_some((p1, q1))
+}
+
+/// Modified Bessel function of the first kind, order zero.
+fn bessel_i0(x: f64) -> f64 {
+    let quarter_square = x * x / 4.0;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for k in 1..50 {
+        term *= quarter_square / (k * k) as f64;
+        sum += term;
+        if term < sum * 1e-17 {
+            break;
+        }
+    }
+    sum
+}
@@ -0,0 +1,270 @@
+use crate::conversion::{ConversionOptions, ConversionReport, OutputFormat};
+use crate::dts::{AnalogInputChannel, DtsReader, TestInfo};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Version of the sidecar layout, increased when fields are renamed or removed.
+pub const SIDECAR_VERSION: u32 = 1;
+
+/// Provenance of one conversion: where the data came from, how each channel was mapped and
+/// processed, and which converter wrote it. Serialized as the JSON metadata sidecar.
+#[derive(Debug, Clone, Serialize)]
+pub struct Sidecar {
+    /// Always [`SIDECAR_VERSION`].
+    pub sidecar_version: u32,
+    pub converter: ConverterInfo,
+    pub source: SourceInfo,
+    pub output: OutputInfo,
+    /// Exported tracks in output order, with the channel each was read from.
+    pub channel_plan: Vec<PlannedChannel>,
+    /// Every channel of the `.dts` file, in track order. Values missing from the file are
+    /// `null`.
+    pub channels: Vec<AnalogInputChannel>,
+    pub processing: Processing,
+    /// Every option the conversion was run with. Filters, expressions and ID templates are
+    /// written in the syntax of the corresponding CLI flags.
+    pub options: ConversionOptions,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConverterInfo {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceInfo {
+    pub directory: String,
+    pub dts_file: String,
+    /// SHA-256 of the `.dts` file, as lowercase hex.
+    pub dts_sha256: String,
+    pub tracks_file: String,
+    pub test: TestInfo,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OutputInfo {
+    pub path: String,
+    pub format: String,
+}
+
+/// An exported track and the channel of the DTS export it was read from.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedChannel {
+    /// Position of the track in the output.
+    pub order: usize,
+    /// Index of the channel in track order of the `.dts` file.
+    pub channel_index: usize,
+    pub track_name: String,
+    pub chn_file: String,
+}
+
+/// Settings and processing applied to the exported channels.
+#[derive(Debug, Clone, Serialize)]
+pub struct Processing {
+    /// Zero-based `[start, end)` sample range, in native samples. `null` exports every sample.
+    pub slice: Option<[usize; 2]>,
+    /// Requested output sample rate, in Hz.
+    pub resample_rate: Option<f64>,
+    /// Processing of every exported track, in output order.
+    pub tracks: Vec<TrackProcessing>,
+    pub derived: Vec<DerivedInfo>,
+    /// Expression channels as `name[unit]=expression`.
+    pub expressions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackProcessing {
+    pub track_name: String,
+    pub cfc: Option<String>,
+    /// Filters applied after the CFC filter, in order.
+    pub filters: Vec<String>,
+    pub resampling: Option<ResamplingInfo>,
+    pub unit_change: Option<UnitChangeInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResamplingInfo {
+    pub from: f64,
+    pub to: f64,
+    pub up: u64,
+    pub down: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UnitChangeInfo {
+    pub from: String,
+    pub to: String,
+    pub factor: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DerivedInfo {
+    pub name: String,
+    pub source: String,
+    pub operation: String,
+    pub units: String,
+}
+
+/// Inputs, options and result of a conversion, from which the sidecar is built.
+pub struct ConversionRecord<'a> {
+    pub input_dir: &'a Path,
+    pub tracks_path: &'a Path,
+    pub output_path: &'a Path,
+    pub format: OutputFormat,
+    pub options: &'a ConversionOptions,
+    pub reader: &'a DtsReader,
+    pub channel_plan: Vec<PlannedChannel>,
+    pub report: &'a ConversionReport,
+}
+
+impl Sidecar {
+    /// Describe the conversion in `record`, hashing its `.dts` file.
+    pub fn new(record: ConversionRecord<'_>) -> Result<Self> {
+        let ConversionRecord {
+            input_dir,
+            tracks_path,
+            output_path,
+            format,
+            options,
+            reader,
+            channel_plan,
+            report,
+        } = record;
+
+        let dts_path = reader.dts_path();
+        let dts_sha256 = sha256_file(dts_path)
+            .with_context(|| format!("Failed to hash {}", dts_path.display()))?;
+
+        let tracks = report
+            .processed_track_names
+            .iter()
+            .map(|track_name| TrackProcessing {
+                track_name: track_name.clone(),
+                cfc: find(&report.cfc_classes, track_name).map(ToString::to_string),
+                filters: find(&report.filters, track_name)
+                    .map(|filters| filters.iter().map(ToString::to_string).collect())
+                    .unwrap_or_default(),
+                resampling: find(&report.resampling, track_name).map(|resampling| ResamplingInfo {
+                    from: resampling.from,
+                    to: resampling.to,
+                    up: resampling.up,
+                    down: resampling.down,
+                }),
+                unit_change: find(&report.unit_changes, track_name).map(|change| UnitChangeInfo {
+                    from: change.from.clone(),
+                    to: change.to.to_string(),
+                    factor: change.factor,
+                }),
+            })
+            .collect();
+
+        Ok(Self {
+            sidecar_version: SIDECAR_VERSION,
+            converter: ConverterInfo {
+                name: env!("CARGO_PKG_NAME").to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+            source: SourceInfo {
+                directory: path_string(input_dir),
+                dts_file: path_string(dts_path),
+                dts_sha256,
+                tracks_file: path_string(tracks_path),
+                test: reader.test_info().clone(),
+            },
+            output: OutputInfo {
+                path: path_string(output_path),
+                format: format.as_str().to_string(),
+            },
+            channel_plan,
+            channels: (0..reader.channel_count())
+                .filter_map(|index| reader.channel_info(index).cloned())
+                .collect(),
+            processing: Processing {
+                slice: options.slice.map(|slice| [slice.start, slice.end]),
+                resample_rate: options.resample_rate,
+                tracks,
+                derived: report
+                    .derived_channels
+                    .iter()
+                    .map(|(name, derivation)| DerivedInfo {
+                        name: name.clone(),
+                        source: derivation.source.clone(),
+                        operation: derivation.operation.to_string(),
+                        units: derivation.units.clone(),
+                    })
+                    .collect(),
+                expressions: report
+                    .expression_channels
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+            },
+            options: options.clone(),
+            warnings: report.warnings.clone(),
+        })
+    }
+
+    /// Write the sidecar as pretty-printed JSON.
+    pub fn write_json<W: Write>(&self, writer: &mut W) -> Result<()> {
+        serde_json::to_writer_pretty(&mut *writer, self)?;
+        writeln!(writer)?;
+        Ok(())
+    }
+
+    /// Write the sidecar to the JSON file at `path`.
+    pub fn write_file(&self, path: &Path) -> Result<()> {
+        let file =
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        self.write_json(&mut writer)
+            .and_then(|()| writer.flush().map_err(Into::into))
+            .with_context(|| format!("Failed to write metadata sidecar to {}", path.display()))
+    }
+}
+
+/// Build the [`PlannedChannel`] of an exported track.
+pub fn planned_channel(
+    reader: &DtsReader,
+    order: usize,
+    channel_index: usize,
+    track_name: &str,
+) -> PlannedChannel {
+    PlannedChannel {
+        order,
+        channel_index,
+        track_name: track_name.to_string(),
+        chn_file: reader
+            .chn_path(channel_index)
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    }
+}
+
+fn find<'a, T>(entries: &'a [(String, T)], track_name: &str) -> Option<&'a T> {
+    entries
+        .iter()
+        .find(|(name, _)| name == track_name)
+        .map(|(_, value)| value)
+}
+
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// SHA-256 of the file at `path`, as lowercase hex.
+fn sha256_file(path: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
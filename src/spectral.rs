@@ -0,0 +1,716 @@
+use crate::dts::ChannelData;
+use crate::uff::{
+    Abscissa, AmplitudeUnits, AxisSpec, Dof, FunctionType, IdLines, Normalization, Ordinate,
+    Uff1858, Uff58Record, WindowQualifier,
+};
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use num_complex::Complex64;
+use rustfft::FftPlanner;
+use serde::Serialize;
+use std::f64::consts::PI;
+
+/// Window applied to each block before the FFT.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Window {
+    /// No window (uniform weighting).
+    Rectangular,
+    /// Hann window, suited to random and broadband signals.
+    #[default]
+    Hann,
+    /// Flat top window, for accurate amplitudes of tonal components.
+    FlatTop,
+}
+
+impl Window {
+    /// Returns the human readable name of the window.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Window::Rectangular => "rectangular",
+            Window::Hann => "hann",
+            Window::FlatTop => "flat-top",
+        }
+    }
+
+    /// Periodic (DFT-even) window coefficients for a block of `len` samples.
+    pub fn coefficients(&self, len: usize) -> Vec<f64> {
+        let n = len as f64;
+        (0..len)
+            .map(|index| {
+                let phase = 2.0 * PI * index as f64 / n;
+                match self {
+                    Window::Rectangular => 1.0,
+                    Window::Hann => 0.5 - 0.5 * phase.cos(),
+                    Window::FlatTop => {
+                        0.215_578_95 - 0.416_631_58 * phase.cos()
+                            + 0.277_263_158 * (2.0 * phase).cos()
+                            - 0.083_578_947 * (3.0 * phase).cos()
+                            + 0.006_947_368 * (4.0 * phase).cos()
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Dataset 1858 window code for this window.
+    pub fn uff1858_qualifier(&self) -> WindowQualifier {
+        match self {
+            Window::Rectangular => WindowQualifier::None,
+            Window::Hann => WindowQualifier::HanningNarrow,
+            Window::FlatTop => WindowQualifier::FlatTop,
+        }
+    }
+}
+
+impl std::fmt::Display for Window {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Window {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "rectangular" | "uniform" | "none" => Ok(Window::Rectangular),
+            "hann" | "hanning" => Ok(Window::Hann),
+            "flat-top" | "flattop" => Ok(Window::FlatTop),
+            other => Err(format!(
+                "Unsupported window '{other}'. Expected 'rectangular', 'hann' or 'flat-top'."
+            )),
+        }
+    }
+}
+
+/// How the spectra of the individual blocks are combined.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Averaging {
+    /// Arithmetic mean of all blocks. The linear spectrum averages magnitudes and is written as
+    /// a real record.
+    #[default]
+    Linear,
+    /// Largest value seen in any block, per frequency line. The linear spectrum is a real
+    /// magnitude record.
+    PeakHold,
+    /// Synchronous averaging: the linear spectrum is the complex mean of the blocks, which
+    /// cancels every component whose phase is not the same at each block start. Auto-power and
+    /// PSD are averaged as with `linear`.
+    Vector,
+}
+
+impl Averaging {
+    /// Returns the human readable name of the averaging mode.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Averaging::Linear => "linear",
+            Averaging::PeakHold => "peak-hold",
+            Averaging::Vector => "vector",
+        }
+    }
+}
+
+impl std::fmt::Display for Averaging {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Averaging {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "linear" => Ok(Averaging::Linear),
+            "peak-hold" | "peakhold" | "peak" => Ok(Averaging::PeakHold),
+            "vector" | "synchronous" => Ok(Averaging::Vector),
+            other => Err(format!(
+                "Unsupported averaging '{other}'. Expected 'linear', 'peak-hold' or 'vector'."
+            )),
+        }
+    }
+}
+
+/// Frequency-domain functions that can be exported for every channel.
+#[derive(ValueEnum, Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SpectrumKind {
+    /// One-sided auto-power spectrum in EU² RMS (function type 2).
+    AutoPower,
+    /// One-sided power spectral density in EU²/Hz (function type 9).
+    Psd,
+    /// One-sided linear spectrum in EU peak (function type 12). Real magnitudes, or complex with
+    /// [`Averaging::Vector`].
+    Linear,
+}
+
+impl SpectrumKind {
+    /// Returns the human readable name of the spectrum kind.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpectrumKind::AutoPower => "auto-power",
+            SpectrumKind::Psd => "psd",
+            SpectrumKind::Linear => "linear",
+        }
+    }
+
+    /// UFF 58 function type of the exported record.
+    pub fn function_type(&self) -> FunctionType {
+        match self {
+            SpectrumKind::AutoPower => FunctionType::AutoSpectrum,
+            SpectrumKind::Psd => FunctionType::PowerSpectralDensity,
+            SpectrumKind::Linear => FunctionType::Spectrum,
+        }
+    }
+
+    fn units(&self, eu: &str) -> String {
+        match self {
+            SpectrumKind::AutoPower => format!("{eu}^2"),
+            SpectrumKind::Psd => format!("{eu}^2/Hz"),
+            SpectrumKind::Linear => eu.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for SpectrumKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for SpectrumKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "auto-power" | "autopower" | "auto-spectrum" => Ok(SpectrumKind::AutoPower),
+            "psd" => Ok(SpectrumKind::Psd),
+            "linear" | "spectrum" => Ok(SpectrumKind::Linear),
+            other => Err(format!(
+                "Unsupported spectrum '{other}'. Expected 'auto-power', 'psd' or 'linear'."
+            )),
+        }
+    }
+}
+
+/// Block processing settings shared by every spectral estimate.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SpectralSettings {
+    pub window: Window,
+    /// Number of samples per FFT block.
+    pub block_size: usize,
+    /// Overlap between consecutive blocks, in percent of the block size (0 to <100).
+    pub overlap_percent: f64,
+    pub averaging: Averaging,
+}
+
+impl Default for SpectralSettings {
+    fn default() -> Self {
+        Self {
+            window: Window::Hann,
+            block_size: 4096,
+            overlap_percent: 50.0,
+            averaging: Averaging::Linear,
+        }
+    }
+}
+
+impl SpectralSettings {
+    /// Check the settings before any data is processed.
+    pub fn validate(&self) -> Result<()> {
+        if self.block_size < 2 {
+            return Err(anyhow!(
+                "Invalid block size ({}): at least 2 samples are required.",
+                self.block_size
+            ));
+        }
+        if !(0.0..100.0).contains(&self.overlap_percent) {
+            return Err(anyhow!(
+                "Invalid overlap ({}%): expected a value from 0 up to (but excluding) 100.",
+                self.overlap_percent
+            ));
+        }
+        Ok(())
+    }
+
+    fn hop(&self) -> usize {
+        let overlap = (self.block_size as f64 * self.overlap_percent / 100.0).round() as usize;
+        (self.block_size - overlap).max(1)
+    }
+
+    /// Number of blocks that fit into `len` samples.
+    pub fn block_count(&self, len: usize) -> usize {
+        if len < self.block_size {
+            0
+        } else {
+            (len - self.block_size) / self.hop() + 1
+        }
+    }
+}
+
+/// Ordinate values of a spectrum.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SpectrumValues {
+    Real(Vec<f64>),
+    Complex(Vec<Complex64>),
+}
+
+/// A one-sided spectrum starting at 0 Hz.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spectrum {
+    pub kind: SpectrumKind,
+    /// Spacing of the frequency lines, in Hz.
+    pub frequency_step: f64,
+    pub values: SpectrumValues,
+    /// Unit label of the ordinate.
+    pub units: String,
+    /// Number of blocks combined into the estimate.
+    pub averages: usize,
+    pub settings: SpectralSettings,
+}
+
+impl Spectrum {
+    /// UFF 58 record holding this spectrum for `track_name`.
+    pub fn uff58_record<'a>(&'a self, track_name: &str, ids: IdLines) -> Uff58Record<'a> {
+        Uff58Record {
+            ids,
+            function_type: self.kind.function_type(),
+            response: Dof::response(track_name),
+            reference: Dof::no_reference(),
+            abscissa: Abscissa::Even {
+                start: 0.0,
+                step: self.frequency_step,
+            },
+            abscissa_axis: AxisSpec::frequency(),
+            ordinate_axis: AxisSpec::ordinate(track_name, &self.units),
+            denominator_axis: AxisSpec::none(),
+            ordinate: match &self.values {
+                SpectrumValues::Real(values) => Ordinate::Real(values),
+                SpectrumValues::Complex(values) => Ordinate::Complex(values),
+            },
+        }
+    }
+
+    /// Dataset 1858 qualifiers describing how the spectrum was estimated.
+    pub fn uff1858_qualifiers(&self, set_record_number: i32) -> Uff1858 {
+        let mut qualifiers = Uff1858::time_history(set_record_number, self.settings.block_size);
+        qualifiers.window = self.settings.window.uff1858_qualifier();
+        let (amplitude_units, normalization, squared) = match self.kind {
+            SpectrumKind::AutoPower => (AmplitudeUnits::Rms, Normalization::UnitsSquared, true),
+            SpectrumKind::Psd => (AmplitudeUnits::Rms, Normalization::UnitsSquaredPerHz, true),
+            SpectrumKind::Linear => (AmplitudeUnits::Peak, Normalization::Unknown, false),
+        };
+        qualifiers.amplitude_units = amplitude_units;
+        qualifiers.normalization = normalization;
+        if squared {
+            qualifiers.ordinate_numerator_qualifier = 2;
+        }
+        qualifiers.user_values[0] = self.averages as f64;
+        qualifiers
+    }
+}
+
+/// Calls `visit` with the one-sided FFT of every windowed block of `signal`.
+pub(crate) fn for_each_block<F>(
+    signal: &[f64],
+    settings: &SpectralSettings,
+    window: &[f64],
+    mut visit: F,
+) -> usize
+where
+    F: FnMut(&[Complex64]),
+{
+    let block_size = settings.block_size;
+    let fft = FftPlanner::<f64>::new().plan_fft_forward(block_size);
+    let mut buffer = vec![Complex64::new(0.0, 0.0); block_size];
+    let mut scratch = vec![Complex64::new(0.0, 0.0); fft.get_inplace_scratch_len()];
+    let bins = block_size / 2 + 1;
+
+    let blocks = settings.block_count(signal.len());
+    for block in 0..blocks {
+        let start = block * settings.hop();
+        for ((slot, &sample), &weight) in buffer
+            .iter_mut()
+            .zip(&signal[start..start + block_size])
+            .zip(window)
+        {
+            *slot = Complex64::new(sample * weight, 0.0);
+        }
+        fft.process_with_scratch(&mut buffer, &mut scratch);
+        visit(&buffer[..bins]);
+    }
+    blocks
+}
+
+/// Factor doubling every line except DC and (for even block sizes) Nyquist, folding the negative
+/// frequencies into the one-sided spectrum.
+pub(crate) fn one_sided_factor(bin: usize, block_size: usize) -> f64 {
+    if bin == 0 || (block_size % 2 == 0 && bin == block_size / 2) {
+        1.0
+    } else {
+        2.0
+    }
+}
+
+/// Estimate the requested spectra of `data` with Welch's method.
+pub fn compute_spectra(
+    data: &ChannelData,
+    kinds: &[SpectrumKind],
+    settings: &SpectralSettings,
+) -> Result<Vec<Spectrum>> {
+    if kinds.is_empty() {
+        return Ok(Vec::new());
+    }
+    settings.validate()?;
+    let block_size = settings.block_size;
+    if data.time_series.len() < block_size {
+        return Err(anyhow!(
+            "Cannot compute spectra: {} samples available but the block size is {}.",
+            data.time_series.len(),
+            block_size
+        ));
+    }
+
+    let window = settings.window.coefficients(block_size);
+    let window_sum: f64 = window.iter().sum();
+    let window_power: f64 = window.iter().map(|w| w * w).sum();
+    let bins = block_size / 2 + 1;
+
+    let mut power = vec![0.0f64; bins];
+    let mut magnitude = vec![0.0f64; bins];
+    let mut linear = vec![Complex64::new(0.0, 0.0); bins];
+    let averaging = settings.averaging;
+
+    let averages = for_each_block(&data.time_series, settings, &window, |spectrum| {
+        for (bin, value) in spectrum.iter().enumerate() {
+            let magnitude_squared = value.norm_sqr();
+            match averaging {
+                Averaging::Linear => {
+                    power[bin] += magnitude_squared;
+                    magnitude[bin] += value.norm();
+                }
+                Averaging::PeakHold => {
+                    power[bin] = power[bin].max(magnitude_squared);
+                    magnitude[bin] = magnitude[bin].max(value.norm());
+                }
+                Averaging::Vector => {
+                    power[bin] += magnitude_squared;
+                    linear[bin] += *value;
+                }
+            }
+        }
+    });
+
+    if averaging != Averaging::PeakHold {
+        let count = averages as f64;
+        for value in power.iter_mut().chain(&mut magnitude) {
+            *value /= count;
+        }
+        for value in &mut linear {
+            *value /= count;
+        }
+    }
+
+    let frequency_step = data.sample_rate / block_size as f64;
+    let spectra = kinds
+        .iter()
+        .map(|&kind| {
+            let values = match kind {
+                SpectrumKind::AutoPower => SpectrumValues::Real(
+                    power
+                        .iter()
+                        .enumerate()
+                        .map(|(bin, p)| {
+                            p * one_sided_factor(bin, block_size) / (window_sum * window_sum)
+                        })
+                        .collect(),
+                ),
+                SpectrumKind::Psd => SpectrumValues::Real(
+                    power
+                        .iter()
+                        .enumerate()
+                        .map(|(bin, p)| {
+                            p * one_sided_factor(bin, block_size)
+                                / (data.sample_rate * window_power)
+                        })
+                        .collect(),
+                ),
+                // Magnitude averages carry no phase, so only the vector average is complex.
+                SpectrumKind::Linear if averaging == Averaging::Vector => SpectrumValues::Complex(
+                    linear
+                        .iter()
+                        .enumerate()
+                        .map(|(bin, x)| x * one_sided_factor(bin, block_size) / window_sum)
+                        .collect(),
+                ),
+                SpectrumKind::Linear => SpectrumValues::Real(
+                    magnitude
+                        .iter()
+                        .enumerate()
+                        .map(|(bin, x)| x * one_sided_factor(bin, block_size) / window_sum)
+                        .collect(),
+                ),
+            };
+            Spectrum {
+                kind,
+                frequency_step,
+                values,
+                units: kind.units(&data.units),
+                averages,
+                settings: settings.clone(),
+            }
+        })
+        .collect();
+
+    Ok(spectra)
+}
+
+/// Estimator used for frequency response functions.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FrfEstimator {
+    /// `H1 = Gxy / Gxx`, minimises noise on the response.
+    #[default]
+    H1,
+    /// `H2 = Gyy / Gyx`, minimises noise on the reference.
+    H2,
+}
+
+impl FrfEstimator {
+    /// Returns the human readable name of the estimator.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FrfEstimator::H1 => "h1",
+            FrfEstimator::H2 => "h2",
+        }
+    }
+}
+
+impl std::fmt::Display for FrfEstimator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for FrfEstimator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "h1" => Ok(FrfEstimator::H1),
+            "h2" => Ok(FrfEstimator::H2),
+            other => Err(format!(
+                "Unsupported FRF estimator '{other}'. Expected 'h1' or 'h2'."
+            )),
+        }
+    }
+}
+
+/// Frequency response function and coherence of a response channel relative to a reference.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrequencyResponse {
+    pub estimator: FrfEstimator,
+    /// Spacing of the frequency lines, in Hz.
+    pub frequency_step: f64,
+    pub values: Vec<Complex64>,
+    /// Ordinary coherence between reference and response, 0 to 1.
+    pub coherence: Vec<f64>,
+    pub response_units: String,
+    pub reference_units: String,
+    /// Number of blocks combined into the estimate.
+    pub averages: usize,
+    pub settings: SpectralSettings,
+}
+
+impl FrequencyResponse {
+    /// Complex FRF record (function type 4) with the reference DOF filled in.
+    pub fn frf_record<'a>(
+        &'a self,
+        track_name: &str,
+        reference_track: &str,
+        ids: IdLines,
+    ) -> Uff58Record<'a> {
+        Uff58Record {
+            ids,
+            function_type: FunctionType::FrequencyResponse,
+            response: Dof::response(track_name),
+            reference: Dof::reference(reference_track),
+            abscissa: Abscissa::Even {
+                start: 0.0,
+                step: self.frequency_step,
+            },
+            abscissa_axis: AxisSpec::frequency(),
+            ordinate_axis: AxisSpec::ordinate(track_name, &self.response_units),
+            denominator_axis: AxisSpec::ordinate(reference_track, &self.reference_units),
+            ordinate: Ordinate::Complex(&self.values),
+        }
+    }
+
+    /// Coherence record (function type 6) with the reference DOF filled in.
+    pub fn coherence_record<'a>(
+        &'a self,
+        track_name: &str,
+        reference_track: &str,
+        ids: IdLines,
+    ) -> Uff58Record<'a> {
+        Uff58Record {
+            function_type: FunctionType::Coherence,
+            ordinate_axis: AxisSpec {
+                spec_data_type: 0,
+                label: "Coherence".to_string(),
+                units: "NONE".to_string(),
+            },
+            denominator_axis: AxisSpec::none(),
+            ordinate: Ordinate::Real(&self.coherence),
+            ..self.frf_record(track_name, reference_track, ids)
+        }
+    }
+
+    /// Dataset 1858 qualifiers describing how the FRF and coherence were estimated.
+    pub fn uff1858_qualifiers(&self, set_record_number: i32) -> Uff1858 {
+        let mut qualifiers = Uff1858::time_history(set_record_number, self.settings.block_size);
+        qualifiers.window = self.settings.window.uff1858_qualifier();
+        qualifiers.user_values[0] = self.averages as f64;
+        qualifiers
+    }
+}
+
+/// Block spectra of an FRF reference channel, shared by every response estimated against it.
+#[derive(Clone, Debug)]
+pub struct FrfReference {
+    blocks: Vec<Vec<Complex64>>,
+    window: Vec<f64>,
+    sample_rate: f64,
+    units: String,
+    settings: SpectralSettings,
+}
+
+impl FrfReference {
+    /// Compute the block spectra of `reference`.
+    ///
+    /// FRFs always combine the cross and auto spectra with linear averaging, whatever
+    /// `settings.averaging` asks of the exported spectra.
+    pub fn new(reference: &ChannelData, settings: &SpectralSettings) -> Result<Self> {
+        settings.validate()?;
+        let block_size = settings.block_size;
+        let len = reference.time_series.len();
+        if len < block_size {
+            return Err(anyhow!(
+                "Cannot compute FRF: {len} reference samples available but the block size is \
+                 {block_size}."
+            ));
+        }
+
+        let settings = SpectralSettings {
+            averaging: Averaging::Linear,
+            ..settings.clone()
+        };
+        let window = settings.window.coefficients(block_size);
+        let mut blocks = Vec::with_capacity(settings.block_count(len));
+        for_each_block(&reference.time_series, &settings, &window, |spectrum| {
+            blocks.push(spectrum.to_vec())
+        });
+
+        Ok(Self {
+            blocks,
+            window,
+            sample_rate: reference.sample_rate,
+            units: reference.units.clone(),
+            settings,
+        })
+    }
+
+    /// Estimate the FRF of `response` relative to this reference, together with the coherence.
+    ///
+    /// Both channels must share a sample rate; only their common length is used.
+    pub fn frf(
+        &self,
+        response: &ChannelData,
+        estimator: FrfEstimator,
+    ) -> Result<FrequencyResponse> {
+        if self.sample_rate != response.sample_rate {
+            return Err(anyhow!(
+                "Reference and response sample rates differ ({} Hz and {} Hz).",
+                self.sample_rate,
+                response.sample_rate
+            ));
+        }
+        let block_size = self.settings.block_size;
+        let len = response.time_series.len();
+        if len < block_size {
+            return Err(anyhow!(
+                "Cannot compute FRF: {len} samples available but the block size is {block_size}."
+            ));
+        }
+
+        let bins = block_size / 2 + 1;
+        let mut gxx = vec![0.0f64; bins];
+        let mut gyy = vec![0.0f64; bins];
+        let mut gxy = vec![Complex64::new(0.0, 0.0); bins];
+        // Blocks start at the same samples in both channels, so the common length is covered
+        // by the first blocks of the longer one.
+        let usable = self.blocks.len().min(self.settings.block_count(len));
+        let end = (usable - 1) * self.settings.hop() + block_size;
+        let mut blocks = self.blocks.iter();
+        let averages = for_each_block(
+            &response.time_series[..end],
+            &self.settings,
+            &self.window,
+            |y| {
+                let x = blocks
+                    .next()
+                    .expect("reference and response block counts match");
+                for bin in 0..bins {
+                    gxx[bin] += x[bin].norm_sqr();
+                    gyy[bin] += y[bin].norm_sqr();
+                    gxy[bin] += x[bin].conj() * y[bin];
+                }
+            },
+        );
+
+        // The window and block normalisation cancels in both ratios.
+        let values = (0..bins)
+            .map(|bin| match estimator {
+                FrfEstimator::H1 if gxx[bin] > 0.0 => gxy[bin] / gxx[bin],
+                FrfEstimator::H2 if gxy[bin].norm_sqr() > 0.0 => gyy[bin] / gxy[bin].conj(),
+                _ => Complex64::new(0.0, 0.0),
+            })
+            .collect();
+        let coherence = (0..bins)
+            .map(|bin| {
+                let denominator = gxx[bin] * gyy[bin];
+                if denominator > 0.0 {
+                    (gxy[bin].norm_sqr() / denominator).min(1.0)
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        Ok(FrequencyResponse {
+            estimator,
+            frequency_step: self.sample_rate / block_size as f64,
+            values,
+            coherence,
+            response_units: response.units.clone(),
+            reference_units: self.units.clone(),
+            averages,
+            settings: self.settings.clone(),
+        })
+    }
+}
+
+/// Estimate the FRF of `response` relative to `reference`, together with the coherence.
+///
+/// Both channels must share a sample rate; only their common length is used. Use
+/// [`FrfReference`] to estimate several responses against the same reference.
+pub fn compute_frf(
+    reference: &ChannelData,
+    response: &ChannelData,
+    estimator: FrfEstimator,
+    settings: &SpectralSettings,
+) -> Result<FrequencyResponse> {
+    FrfReference::new(reference, settings)?.frf(response, estimator)
+}
@@ -0,0 +1,276 @@
+use crate::dts::ChannelData;
+use crate::uff::{Abscissa, AxisSpec, Dof, FunctionType, IdLines, Ordinate, Uff1858, Uff58Record};
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::f64::consts::PI;
+use std::io::Write;
+
+/// Which part of the response history an SRS curve is taken from.
+#[derive(ValueEnum, Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SrsKind {
+    /// Largest absolute response at any time.
+    Maximax,
+    /// Largest absolute response over the exported record.
+    Primary,
+    /// Largest absolute response of the free decay after the record ends.
+    Residual,
+}
+
+impl SrsKind {
+    /// Returns the human readable name of the SRS kind.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SrsKind::Maximax => "maximax",
+            SrsKind::Primary => "primary",
+            SrsKind::Residual => "residual",
+        }
+    }
+}
+
+impl std::fmt::Display for SrsKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for SrsKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "maximax" => Ok(SrsKind::Maximax),
+            "primary" => Ok(SrsKind::Primary),
+            "residual" => Ok(SrsKind::Residual),
+            other => Err(format!(
+                "Unsupported SRS kind '{other}'. Expected 'maximax', 'primary' or 'residual'."
+            )),
+        }
+    }
+}
+
+/// Damping and natural frequency range of an SRS.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SrsSettings {
+    /// Fraction of critical damping (0.05 corresponds to Q = 10).
+    pub damping: f64,
+    /// Lowest natural frequency, in Hz.
+    pub min_frequency: f64,
+    /// Highest natural frequency, in Hz. Must stay below the Nyquist frequency.
+    pub max_frequency: f64,
+    /// Number of natural frequencies per octave.
+    pub points_per_octave: u32,
+}
+
+impl Default for SrsSettings {
+    fn default() -> Self {
+        Self {
+            damping: 0.05,
+            min_frequency: 10.0,
+            max_frequency: 10_000.0,
+            points_per_octave: 12,
+        }
+    }
+}
+
+impl SrsSettings {
+    /// Check the settings before any data is processed.
+    pub fn validate(&self) -> Result<()> {
+        if !(self.damping > 0.0 && self.damping < 1.0) {
+            return Err(anyhow!(
+                "Invalid SRS damping ({}): expected a fraction of critical damping between 0 and 1.",
+                self.damping
+            ));
+        }
+        if !(self.min_frequency > 0.0 && self.min_frequency <= self.max_frequency) {
+            return Err(anyhow!(
+                "Invalid SRS frequency range ({} Hz to {} Hz).",
+                self.min_frequency,
+                self.max_frequency
+            ));
+        }
+        if self.points_per_octave == 0 {
+            return Err(anyhow!("SRS points per octave must be at least 1."));
+        }
+        Ok(())
+    }
+
+    /// Log-spaced natural frequencies from `min_frequency` up to `max_frequency`.
+    pub fn natural_frequencies(&self) -> Vec<f64> {
+        let step = 2f64.powf(1.0 / f64::from(self.points_per_octave));
+        let mut frequencies = Vec::new();
+        let mut frequency = self.min_frequency;
+        // Allow for rounding so that an exact octave multiple of the upper bound is included.
+        while frequency <= self.max_frequency * (1.0 + 1e-9) {
+            frequencies.push(frequency);
+            frequency *= step;
+        }
+        frequencies
+    }
+}
+
+/// Absolute acceleration shock response spectrum of one channel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShockResponseSpectrum {
+    /// Natural frequencies, in Hz.
+    pub frequencies: Vec<f64>,
+    pub maximax: Vec<f64>,
+    pub primary: Vec<f64>,
+    pub residual: Vec<f64>,
+    /// Unit label of the response (same as the input channel).
+    pub units: String,
+    pub settings: SrsSettings,
+}
+
+impl ShockResponseSpectrum {
+    /// Values of one SRS curve.
+    pub fn values(&self, kind: SrsKind) -> &[f64] {
+        match kind {
+            SrsKind::Maximax => &self.maximax,
+            SrsKind::Primary => &self.primary,
+            SrsKind::Residual => &self.residual,
+        }
+    }
+
+    /// UFF 58 record (general function type, uneven frequency abscissa) for one SRS curve.
+    pub fn uff58_record<'a>(
+        &'a self,
+        kind: SrsKind,
+        track_name: &str,
+        ids: IdLines,
+    ) -> Uff58Record<'a> {
+        Uff58Record {
+            ids,
+            function_type: FunctionType::General,
+            response: Dof::response(track_name),
+            reference: Dof::no_reference(),
+            abscissa: Abscissa::Uneven(&self.frequencies),
+            abscissa_axis: AxisSpec::frequency(),
+            ordinate_axis: AxisSpec::ordinate(&format!("{track_name} {kind}"), &self.units),
+            denominator_axis: AxisSpec::none(),
+            ordinate: Ordinate::Real(self.values(kind)),
+        }
+    }
+
+    /// Dataset 1858 qualifiers for an SRS curve. The curve is not averaged, so the first user
+    /// value holds the points per octave instead of the number of averages.
+    pub fn uff1858_qualifiers(&self, set_record_number: i32) -> Uff1858 {
+        let mut qualifiers = Uff1858::time_history(set_record_number, self.frequencies.len());
+        qualifiers.user_values[0] = f64::from(self.settings.points_per_octave);
+        qualifiers
+    }
+}
+
+/// Compute the absolute acceleration SRS of `data` with the Smallwood ramp-invariant filter.
+pub fn compute_srs(data: &ChannelData, settings: &SrsSettings) -> Result<ShockResponseSpectrum> {
+    settings.validate()?;
+    let nyquist = data.sample_rate / 2.0;
+    if settings.max_frequency >= nyquist {
+        return Err(anyhow!(
+            "SRS maximum frequency ({} Hz) must be below the Nyquist frequency ({} Hz).",
+            settings.max_frequency,
+            nyquist
+        ));
+    }
+
+    let frequencies = settings.natural_frequencies();
+    let dt = 1.0 / data.sample_rate;
+    let mut primary = Vec::with_capacity(frequencies.len());
+    let mut residual = Vec::with_capacity(frequencies.len());
+
+    for &frequency in &frequencies {
+        let omega = 2.0 * PI * frequency;
+        let damped = omega * (1.0 - settings.damping * settings.damping).sqrt() * dt;
+        let e = (-settings.damping * omega * dt).exp();
+        let c = e * damped.cos();
+        let sp = e * damped.sin() / damped;
+        let (b0, b1, b2) = (1.0 - sp, 2.0 * (sp - c), e * e - sp);
+        let (a1, a2) = (2.0 * c, -e * e);
+
+        let (mut x1, mut x2, mut y1, mut y2) = (0.0, 0.0, 0.0, 0.0);
+        let mut step = |x: f64| {
+            let y = b0 * x + b1 * x1 + b2 * x2 + a1 * y1 + a2 * y2;
+            (x2, x1, y2, y1) = (x1, x, y1, y);
+            y.abs()
+        };
+
+        let primary_peak = data
+            .time_series
+            .iter()
+            .map(|&x| step(x))
+            .fold(0.0, f64::max);
+        // The free decay peaks within one natural period after the input ends.
+        let decay_samples = (data.sample_rate / frequency).ceil() as usize + 2;
+        let residual_peak = (0..decay_samples).map(|_| step(0.0)).fold(0.0, f64::max);
+
+        primary.push(primary_peak);
+        residual.push(residual_peak);
+    }
+
+    let maximax = primary
+        .iter()
+        .zip(&residual)
+        .map(|(p, r)| p.max(*r))
+        .collect();
+
+    Ok(ShockResponseSpectrum {
+        frequencies,
+        maximax,
+        primary,
+        residual,
+        units: data.units.clone(),
+        settings: settings.clone(),
+    })
+}
+
+/// Write SRS curves as CSV: a `frequency_hz` column followed by one column per track and kind.
+///
+/// Every spectrum must share the natural frequencies of the first one.
+pub fn write_srs_csv<W: Write>(
+    writer: &mut W,
+    spectra: &[(&str, &ShockResponseSpectrum)],
+    kinds: &[SrsKind],
+) -> Result<()> {
+    let Some((_, first)) = spectra.first() else {
+        writeln!(writer, "frequency_hz")?;
+        return Ok(());
+    };
+    if let Some((track_name, _)) = spectra
+        .iter()
+        .find(|(_, srs)| srs.frequencies != first.frequencies)
+    {
+        return Err(anyhow!(
+            "SRS of '{track_name}' uses different natural frequencies and cannot share a CSV file."
+        ));
+    }
+
+    write!(writer, "frequency_hz")?;
+    for (track_name, srs) in spectra {
+        for kind in kinds {
+            write!(
+                writer,
+                ",{} {} [{}]",
+                csv_field(track_name),
+                kind,
+                csv_field(&srs.units)
+            )?;
+        }
+    }
+    writeln!(writer)?;
+
+    for (index, frequency) in first.frequencies.iter().enumerate() {
+        write!(writer, "{frequency}")?;
+        for (_, srs) in spectra {
+            for &kind in kinds {
+                write!(writer, ",{}", srs.values(kind)[index])?;
+            }
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+fn csv_field(value: &str) -> String {
+    value.replace([',', '"', '\n', '\r'], "_")
+}
@@ -0,0 +1,138 @@
+use crate::dts::ChannelData;
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::Write;
+
+/// Layout used to print channel statistics.
+#[derive(ValueEnum, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StatisticsFormat {
+    /// Aligned plain text table.
+    Table,
+    /// Pretty-printed JSON array.
+    Json,
+}
+
+/// Summary statistics of one exported time history.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ChannelStatistics {
+    pub track_name: String,
+    pub units: String,
+    pub sample_count: usize,
+    pub sample_rate: f64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub rms: f64,
+    pub peak_to_peak: f64,
+    /// Absolute peak divided by the RMS value. `None` for an all-zero channel.
+    pub crest_factor: Option<f64>,
+    /// Signed value at the absolute peak.
+    pub peak: f64,
+    /// Time of the absolute peak, in seconds.
+    pub peak_time: f64,
+}
+
+impl ChannelStatistics {
+    /// Compute the statistics of `data`, whose first sample is at `start_time` seconds.
+    pub fn compute(track_name: &str, data: &ChannelData, start_time: f64) -> Self {
+        let samples = &data.time_series;
+        let mut min = f64::INFINITY;
+        let mut max = f64::NEG_INFINITY;
+        let mut sum = 0.0;
+        let mut sum_squares = 0.0;
+        let mut peak_index = 0;
+        for (index, &value) in samples.iter().enumerate() {
+            min = min.min(value);
+            max = max.max(value);
+            sum += value;
+            sum_squares += value * value;
+            if value.abs() > samples[peak_index].abs() {
+                peak_index = index;
+            }
+        }
+
+        let count = samples.len();
+        if count == 0 {
+            min = 0.0;
+            max = 0.0;
+        }
+        let (mean, rms) = if count == 0 {
+            (0.0, 0.0)
+        } else {
+            (sum / count as f64, (sum_squares / count as f64).sqrt())
+        };
+        let peak = samples.get(peak_index).copied().unwrap_or(0.0);
+
+        Self {
+            track_name: track_name.to_string(),
+            units: data.units.clone(),
+            sample_count: count,
+            sample_rate: data.sample_rate,
+            min,
+            max,
+            mean,
+            rms,
+            peak_to_peak: max - min,
+            crest_factor: (rms > 0.0).then(|| peak.abs() / rms),
+            peak,
+            peak_time: start_time + peak_index as f64 / data.sample_rate,
+        }
+    }
+}
+
+/// Write `statistics` in the requested layout.
+pub fn write_statistics<W: Write>(
+    writer: &mut W,
+    statistics: &[ChannelStatistics],
+    format: StatisticsFormat,
+) -> Result<()> {
+    match format {
+        StatisticsFormat::Table => write_table(writer, statistics),
+        StatisticsFormat::Json => {
+            serde_json::to_writer_pretty(&mut *writer, statistics)?;
+            writeln!(writer)?;
+            Ok(())
+        }
+    }
+}
+
+fn write_table<W: Write>(writer: &mut W, statistics: &[ChannelStatistics]) -> Result<()> {
+    let name_width = statistics
+        .iter()
+        .map(|stats| stats.track_name.chars().count())
+        .chain(std::iter::once("Track".len()))
+        .max()
+        .unwrap_or_default();
+    let units_width = statistics
+        .iter()
+        .map(|stats| stats.units.chars().count())
+        .chain(std::iter::once("Units".len()))
+        .max()
+        .unwrap_or_default();
+
+    writeln!(
+        writer,
+        "{:<name_width$}  {:<units_width$}  {:>12}  {:>12}  {:>12}  {:>12}  {:>12}  {:>8}  {:>12}",
+        "Track", "Units", "Min", "Max", "Mean", "RMS", "Peak-peak", "Crest", "Peak time s"
+    )?;
+    for stats in statistics {
+        let crest = stats
+            .crest_factor
+            .map_or_else(|| "-".to_string(), |crest| format!("{crest:.3}"));
+        writeln!(
+            writer,
+            "{:<name_width$}  {:<units_width$}  {:>12.5}  {:>12.5}  {:>12.5}  {:>12.5}  {:>12.5}  {:>8}  {:>12.6}",
+            stats.track_name,
+            stats.units,
+            stats.min,
+            stats.max,
+            stats.mean,
+            stats.rms,
+            stats.peak_to_peak,
+            crest,
+            stats.peak_time
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,209 @@
+use crate::dts::TestInfo;
+use crate::export::{ChannelWriter, ExportChannel};
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Group of the expression channels, which belong to no recorder module.
+pub const COMPUTED_GROUP: &str = "Computed";
+/// Group of the tracks whose module is not named in the `.dts` file.
+pub const DEFAULT_GROUP: &str = "Tracks";
+
+/// Version number of the TDMS 2.0 file format.
+const TDMS_VERSION: u32 = 4713;
+const TOC_META_DATA: u32 = 1 << 1;
+const TOC_NEW_OBJECT_LIST: u32 = 1 << 2;
+const TOC_RAW_DATA: u32 = 1 << 3;
+/// Raw data index of an object without data in the segment.
+const NO_RAW_DATA: u32 = u32::MAX;
+/// Length of a raw data index, including the length field itself.
+const RAW_DATA_INDEX_LENGTH: u32 = 20;
+
+const TDS_TYPE_DOUBLE: u32 = 10;
+const TDS_TYPE_STRING: u32 = 0x20;
+
+/// Writes an NI TDMS 2.0 file.
+///
+/// The file object holds the test, every recorder module becomes a group and every track a
+/// channel of its module's group. Derived channels join the group of their source and expression
+/// channels the `Computed` group. Channels carry the `unit_string`, `sensitivity`,
+/// `serial_number`, `wf_start_offset` (relative to the trigger) and `wf_increment` properties and
+/// store their samples as 64-bit floats. Every channel is written as its own segment when it
+/// arrives.
+pub struct TdmsWriter {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    test: TestInfo,
+    /// Groups declared in an earlier segment.
+    groups: Vec<String>,
+    file_written: bool,
+}
+
+/// A property value of a TDMS object.
+enum Property<'a> {
+    Double(f64),
+    String(&'a str),
+}
+
+impl TdmsWriter {
+    /// Create the TDMS file at `path` for `test`.
+    pub fn create(path: &Path, test: &TestInfo) -> Result<Self> {
+        let file =
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer: BufWriter::with_capacity(8 * 1024 * 1024, file),
+            test: test.clone(),
+            groups: Vec::new(),
+            file_written: false,
+        })
+    }
+
+    /// Write one segment with `metadata` describing `object_count` objects, followed by
+    /// `samples`.
+    fn write_segment(&mut self, object_count: u32, metadata: &[u8], samples: &[f64]) -> Result<()> {
+        let metadata_length = 4 + metadata.len() as u64;
+        let data_length = samples.len() as u64 * 8;
+        let mut toc = TOC_META_DATA | TOC_NEW_OBJECT_LIST;
+        if data_length > 0 {
+            toc |= TOC_RAW_DATA;
+        }
+
+        let mut lead_in = Vec::with_capacity(32);
+        lead_in.extend(b"TDSm");
+        lead_in.extend(toc.to_le_bytes());
+        lead_in.extend(TDMS_VERSION.to_le_bytes());
+        lead_in.extend((metadata_length + data_length).to_le_bytes());
+        lead_in.extend(metadata_length.to_le_bytes());
+        lead_in.extend(object_count.to_le_bytes());
+        self.writer.write_all(&lead_in)?;
+        self.writer.write_all(metadata)?;
+
+        let mut block = Vec::with_capacity(8 * 8192.min(samples.len()));
+        for chunk in samples.chunks(8192) {
+            block.clear();
+            block.extend(chunk.iter().flat_map(|value| value.to_le_bytes()));
+            self.writer.write_all(&block)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> ChannelWriter<'a> for TdmsWriter {
+    fn write_channel(&mut self, channel: &ExportChannel<'a>) -> Result<()> {
+        let mut metadata = Vec::new();
+        let mut object_count = 0;
+
+        if !self.file_written {
+            let mut properties = Vec::new();
+            for (name, value) in [
+                ("name", &self.test.id),
+                ("description", &self.test.description),
+                ("test_date", &self.test.date),
+            ] {
+                if !value.trim().is_empty() {
+                    properties.push((name, Property::String(value)));
+                }
+            }
+            object(&mut metadata, "/", None, &properties);
+            object_count += 1;
+            self.file_written = true;
+        }
+
+        let group = match channel.channel {
+            Some(info) if !info.module.trim().is_empty() => info.module.as_str(),
+            Some(_) => DEFAULT_GROUP,
+            None => COMPUTED_GROUP,
+        };
+        let group_path = format!("/'{}'", escape_name(group));
+        if !self.groups.iter().any(|known| known == group) {
+            object(&mut metadata, &group_path, None, &[]);
+            object_count += 1;
+            self.groups.push(group.to_string());
+        }
+
+        let data = channel.data;
+        let mut properties = vec![
+            ("unit_string", Property::String(&data.units)),
+            ("wf_start_offset", Property::Double(channel.trigger_time)),
+            ("wf_increment", Property::Double(1.0 / data.sample_rate)),
+        ];
+        if let Some(info) = channel.channel {
+            if !channel.computed {
+                properties.push(("sensitivity", Property::Double(info.sensitivity)));
+            }
+            properties.push(("serial_number", Property::String(&info.serial_number)));
+            if !info.description.trim().is_empty() {
+                properties.push(("description", Property::String(&info.description)));
+            }
+        }
+        let channel_path = format!("{group_path}/'{}'", escape_name(channel.name));
+        object(
+            &mut metadata,
+            &channel_path,
+            Some(data.time_series.len() as u64),
+            &properties,
+        );
+        object_count += 1;
+
+        self.write_segment(object_count, &metadata, &data.time_series)
+            .with_context(|| format!("Failed to write TDMS channel '{}'", channel.name))
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if !self.file_written {
+            let mut metadata = Vec::new();
+            object(&mut metadata, "/", None, &[]);
+            self.write_segment(1, &metadata, &[])?;
+            self.file_written = true;
+        }
+        self.writer
+            .flush()
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+/// Append the metadata of the object at `path`. `values` is the number of `f64` values the
+/// object holds in the segment's raw data.
+fn object(
+    metadata: &mut Vec<u8>,
+    path: &str,
+    values: Option<u64>,
+    properties: &[(&str, Property)],
+) {
+    string(metadata, path);
+    match values {
+        Some(values) => {
+            metadata.extend(RAW_DATA_INDEX_LENGTH.to_le_bytes());
+            metadata.extend(TDS_TYPE_DOUBLE.to_le_bytes());
+            metadata.extend(1u32.to_le_bytes());
+            metadata.extend(values.to_le_bytes());
+        }
+        None => metadata.extend(NO_RAW_DATA.to_le_bytes()),
+    }
+    metadata.extend((properties.len() as u32).to_le_bytes());
+    for (name, value) in properties {
+        string(metadata, name);
+        match value {
+            Property::Double(value) => {
+                metadata.extend(TDS_TYPE_DOUBLE.to_le_bytes());
+                metadata.extend(value.to_le_bytes());
+            }
+            Property::String(value) => {
+                metadata.extend(TDS_TYPE_STRING.to_le_bytes());
+                string(metadata, value);
+            }
+        }
+    }
+}
+
+fn string(metadata: &mut Vec<u8>, value: &str) {
+    metadata.extend((value.len() as u32).to_le_bytes());
+    metadata.extend(value.as_bytes());
+}
+
+/// Escape a group or channel name for an object path, where quotes are doubled.
+fn escape_name(name: &str) -> String {
+    name.replace('\'', "''")
+}
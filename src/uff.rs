@@ -1,5 +1,6 @@
 use crate::dts::ChannelData;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use num_complex::Complex64;
 use std::fmt::{self, Write as FmtWrite};
 use std::fs::OpenOptions;
 use std::io::{BufWriter, Write as IoWrite};
@@ -32,10 +33,6 @@ impl LineBuffer {
         self.text.push_str(s);
     }
 
-    fn push_char(&mut self, ch: char) {
-        self.text.push(ch);
-    }
-
     fn write_fmt(&mut self, args: fmt::Arguments<'_>) {
         FmtWrite::write_fmt(self, args).expect("writing formatted text into line buffer");
     }
@@ -189,106 +186,366 @@ fn format_data_line(buffer: &mut LineBuffer, values: &[f64]) {
     }
 }
 
-/// Writes a single channel's data to a UFF Type 58 file using the ASCII layout emitted by MATLAB.
-fn write_uff58_impl<W: IoWrite>(
+/// Free-text content of the five ID records (records 1-5) of a UFF 58 block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdLines {
+    pub lines: [String; 5],
+}
+
+impl IdLines {
+    /// ID records written by MATLAB `writeuff58DX`: blank, `Pt=<track>;`, blank, `NONE`, `NONE`.
+    pub fn matlab(track_name: &str) -> Self {
+        let pt_label = truncate_to_width(track_name, 64);
+        Self {
+            lines: [
+                String::new(),
+                format!("Pt={pt_label};"),
+                String::new(),
+                "NONE".to_string(),
+                "NONE".to_string(),
+            ],
+        }
+    }
+}
+
+fn write_id_records<W: IoWrite>(
+    line_buffer: &mut LineBuffer,
     writer: &mut W,
-    data: &ChannelData,
-    track_name: &str,
+    ids: &IdLines,
 ) -> Result<()> {
-    let mut line_buffer = LineBuffer::with_capacity(256);
+    for line in &ids.lines {
+        // Each ID record is a single 80-column line; embedded line breaks would shift every
+        // following record.
+        let single_line = line.replace(['\r', '\n'], " ");
+        line_buffer.clear();
+        line_buffer.push_str(&truncate_to_width(&single_line, 80));
+        line_buffer.write_line(writer)?;
+    }
+    Ok(())
+}
 
-    // --- Block 1: UFF Type 58 Header (ASCII layout) ---
-    line_buffer.clear();
-    line_buffer.push_str(UFF_SEPARATOR);
-    line_buffer.write_line(writer)?;
+/// UFF 58 function types (record 6, field 1) written by the converter.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FunctionType {
+    General,
+    TimeResponse,
+    AutoSpectrum,
+    FrequencyResponse,
+    Coherence,
+    PowerSpectralDensity,
+    Spectrum,
+}
 
-    line_buffer.clear();
-    line_buffer.push_str("    58");
-    line_buffer.write_line(writer)?;
+impl FunctionType {
+    fn code(self) -> i32 {
+        match self {
+            FunctionType::General => 0,
+            FunctionType::TimeResponse => 1,
+            FunctionType::AutoSpectrum => 2,
+            FunctionType::FrequencyResponse => 4,
+            FunctionType::Coherence => 6,
+            FunctionType::PowerSpectralDensity => 9,
+            FunctionType::Spectrum => 12,
+        }
+    }
+}
 
-    line_buffer.clear();
-    line_buffer.write_line(writer)?;
+/// Specific data type, label and units of one axis (records 8 to 11).
+#[derive(Clone, Debug, PartialEq)]
+pub struct AxisSpec {
+    /// UFF specific data type code (e.g. 17 time, 18 frequency).
+    pub spec_data_type: i32,
+    pub label: String,
+    pub units: String,
+}
 
-    let pt_label = truncate_to_width(track_name, 64);
-    line_buffer.clear();
-    line_buffer.push_str("Pt=");
-    line_buffer.push_str(&pt_label);
-    line_buffer.push_char(';');
-    line_buffer.write_line(writer)?;
+impl AxisSpec {
+    /// Unused axis, written as `NONE`.
+    pub fn none() -> Self {
+        Self {
+            spec_data_type: 0,
+            label: "NONE".to_string(),
+            units: "NONE".to_string(),
+        }
+    }
 
-    line_buffer.clear();
-    line_buffer.write_line(writer)?;
+    /// Time abscissa in seconds.
+    pub fn time() -> Self {
+        Self {
+            spec_data_type: 17,
+            label: "Time".to_string(),
+            units: "s".to_string(),
+        }
+    }
 
-    line_buffer.clear();
-    line_buffer.push_str("NONE");
-    line_buffer.write_line(writer)?;
+    /// Frequency abscissa in Hz.
+    pub fn frequency() -> Self {
+        Self {
+            spec_data_type: 18,
+            label: "Frequency".to_string(),
+            units: "Hz".to_string(),
+        }
+    }
 
-    line_buffer.clear();
-    line_buffer.push_str("NONE");
-    line_buffer.write_line(writer)?;
+    /// Ordinate axis as written by MATLAB: data type 8 labelled with the channel name.
+    pub fn ordinate(track_name: &str, units: &str) -> Self {
+        Self {
+            spec_data_type: 8,
+            label: track_name.to_string(),
+            units: units.to_string(),
+        }
+    }
+}
+
+/// Response or reference degree of freedom of a record (record 6).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dof {
+    pub entity: String,
+    pub node: i32,
+    pub direction: i32,
+}
 
-    let channel_label = truncate_to_width(track_name, 19);
-    let channel_field = format!("{:<19}", channel_label);
-    let zero_field = format!("{:>4}", 0);
-    let none_field = format!(" {:<19}", "NONE");
-    let flag_field = format!("{:<4}{}", 1, 0);
+impl Dof {
+    /// Response DOF as written by MATLAB: the channel label at node 0, direction 0.
+    pub fn response(track_name: &str) -> Self {
+        Self {
+            entity: track_name.to_string(),
+            node: 0,
+            direction: 0,
+        }
+    }
+
+    /// Reference DOF of a cross function: the reference channel label at node 0, direction 0.
+    pub fn reference(track_name: &str) -> Self {
+        Self::response(track_name)
+    }
+
+    /// Reference DOF as written by MATLAB when there is no reference: `NONE`, node 1.
+    pub fn no_reference() -> Self {
+        Self {
+            entity: "NONE".to_string(),
+            node: 1,
+            direction: 0,
+        }
+    }
+}
+
+/// Abscissa of a record: evenly spaced (start and increment) or explicit values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Abscissa<'a> {
+    Even { start: f64, step: f64 },
+    Uneven(&'a [f64]),
+}
+
+/// Ordinate values of a record.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Ordinate<'a> {
+    Real(&'a [f64]),
+    Complex(&'a [Complex64]),
+}
+
+impl Ordinate<'_> {
+    fn len(&self) -> usize {
+        match self {
+            Ordinate::Real(values) => values.len(),
+            Ordinate::Complex(values) => values.len(),
+        }
+    }
+}
+
+/// Everything written into one UFF 58 block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Uff58Record<'a> {
+    pub ids: IdLines,
+    pub function_type: FunctionType,
+    pub response: Dof,
+    pub reference: Dof,
+    pub abscissa: Abscissa<'a>,
+    pub abscissa_axis: AxisSpec,
+    pub ordinate_axis: AxisSpec,
+    pub denominator_axis: AxisSpec,
+    pub ordinate: Ordinate<'a>,
+}
+
+impl<'a> Uff58Record<'a> {
+    /// Time response record laid out like the MATLAB reference output.
+    pub fn time_history(data: &'a ChannelData, track_name: &str, ids: IdLines) -> Self {
+        Self {
+            ids,
+            function_type: FunctionType::TimeResponse,
+            response: Dof::response(track_name),
+            reference: Dof::no_reference(),
+            abscissa: Abscissa::Even {
+                start: 0.0,
+                step: 1.0 / data.sample_rate,
+            },
+            abscissa_axis: AxisSpec::time(),
+            ordinate_axis: AxisSpec::ordinate(track_name, &data.units),
+            denominator_axis: AxisSpec::none(),
+            ordinate: Ordinate::Real(&data.time_series),
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if let Abscissa::Uneven(values) = self.abscissa {
+            if values.len() != self.ordinate.len() {
+                return Err(anyhow!(
+                    "Uneven abscissa has {} values but the ordinate has {}.",
+                    values.len(),
+                    self.ordinate.len()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_record6<W: IoWrite>(
+    line_buffer: &mut LineBuffer,
+    writer: &mut W,
+    record: &Uff58Record<'_>,
+) -> Result<()> {
+    // Field layout follows the MATLAB writer: 19-column entity names, an unpadded response
+    // node and a left-aligned reference node.
     line_buffer.clear();
     line_buffer.write_fmt(format_args!(
-        "    1         0    0         0 {}{}{}{}{}",
-        channel_field, 0, zero_field, none_field, flag_field
+        "{:>5}{:>10}{:>5}{:>10} {:<19}{}{:>4} {:<19}{:<4}{}",
+        record.function_type.code(),
+        0,
+        0,
+        0,
+        truncate_to_width(&record.response.entity, 19),
+        record.response.node,
+        record.response.direction,
+        truncate_to_width(&record.reference.entity, 19),
+        record.reference.node,
+        record.reference.direction
     ));
-    line_buffer.write_line(writer)?;
+    line_buffer.write_line(writer)
+}
+
+fn write_record7<W: IoWrite>(
+    line_buffer: &mut LineBuffer,
+    writer: &mut W,
+    record: &Uff58Record<'_>,
+    ord_data_type: i32,
+) -> Result<()> {
+    let (spacing, start, step) = match record.abscissa {
+        Abscissa::Even { start, step } => (1, start, step),
+        Abscissa::Uneven(values) => (0, values.first().copied().unwrap_or(0.0), 0.0),
+    };
 
     line_buffer.clear();
     line_buffer.write_fmt(format_args!(
         "{:>10}{:>10}{:>10}  ",
-        4,
-        data.time_series.len(),
-        1
+        ord_data_type,
+        record.ordinate.len(),
+        spacing
     ));
-    write_scientific(&mut line_buffer, 0.0, 11, 5).expect("writing record2 start time");
+    write_scientific(line_buffer, start, 11, 5).expect("writing abscissa min");
     line_buffer.push_str("  ");
-    write_scientific(&mut line_buffer, 1.0 / data.sample_rate, 11, 5)
-        .expect("writing record2 time step");
+    write_scientific(line_buffer, step, 11, 5).expect("writing abscissa inc");
     line_buffer.push_str("  ");
-    write_scientific(&mut line_buffer, 0.0, 11, 5).expect("writing record2 abscissa start");
-    line_buffer.write_line(writer)?;
+    write_scientific(line_buffer, 0.0, 11, 5).expect("writing z-axis value");
+    line_buffer.write_line(writer)
+}
 
-    let abscissa_name = format!(" {:<19}", "Time");
-    let abscissa_units = format!("{: <48}", format!("  {}", truncate_to_width("s", 46)));
+fn write_axis_record<W: IoWrite>(
+    line_buffer: &mut LineBuffer,
+    writer: &mut W,
+    axis: &AxisSpec,
+    units_width: usize,
+) -> Result<()> {
+    let name_field = format!(" {:<19}", truncate_to_width(&axis.label, 19));
+    let units_field = format!(
+        "{: <width$}",
+        format!("  {}", truncate_to_width(&axis.units, units_width - 2)),
+        width = units_width
+    );
     line_buffer.clear();
     line_buffer.write_fmt(format_args!(
         "{:>10}{:>5}{:>5}{:>5}{}{}",
-        17, 0, 0, 0, abscissa_name, abscissa_units
+        axis.spec_data_type, 0, 0, 0, name_field, units_field
     ));
-    line_buffer.write_line(writer)?;
+    line_buffer.write_line(writer)
+}
 
-    let ordinate_name_field = format!(" {:<19}", channel_label);
-    let ordinate_units_field = format!(
-        "{: <35}",
-        format!("  {}", truncate_to_width(&data.units, 33))
-    );
+/// Writes records 1 to 11 shared by the ASCII and binary layouts.
+fn write_record_header<W: IoWrite>(
+    line_buffer: &mut LineBuffer,
+    writer: &mut W,
+    record: &Uff58Record<'_>,
+    ord_data_type: i32,
+) -> Result<()> {
+    write_id_records(line_buffer, writer, &record.ids)?;
+    write_record6(line_buffer, writer, record)?;
+    write_record7(line_buffer, writer, record, ord_data_type)?;
+    write_axis_record(line_buffer, writer, &record.abscissa_axis, 48)?;
+    write_axis_record(line_buffer, writer, &record.ordinate_axis, 35)?;
+    write_axis_record(line_buffer, writer, &record.denominator_axis, 35)?;
+    write_axis_record(line_buffer, writer, &AxisSpec::none(), 35)
+}
+
+/// Writes a UFF 58 block using the ASCII layout emitted by MATLAB.
+fn write_record_ascii<W: IoWrite>(writer: &mut W, record: &Uff58Record<'_>) -> Result<()> {
+    record.validate()?;
+    let mut line_buffer = LineBuffer::with_capacity(256);
+
+    // --- Block 1: UFF Type 58 Header (ASCII layout) ---
     line_buffer.clear();
-    line_buffer.write_fmt(format_args!(
-        "{:>10}{:>5}{:>5}{:>5}{}{}",
-        8, 0, 0, 0, ordinate_name_field, ordinate_units_field
-    ));
+    line_buffer.push_str(UFF_SEPARATOR);
     line_buffer.write_line(writer)?;
 
-    let none_name_field = format!(" {:<19}", "NONE");
-    let none_units_field = format!("{: <35}", format!("  {}", "NONE"));
     line_buffer.clear();
-    line_buffer.write_fmt(format_args!(
-        "{:>10}{:>5}{:>5}{:>5}{}{}",
-        0, 0, 0, 0, none_name_field, none_units_field
-    ));
-    line_buffer.write_line(writer)?;
+    line_buffer.push_str("    58");
     line_buffer.write_line(writer)?;
 
+    // Real or complex double precision
+    let ord_data_type = match record.ordinate {
+        Ordinate::Real(_) => 4,
+        Ordinate::Complex(_) => 6,
+    };
+    write_record_header(&mut line_buffer, writer, record, ord_data_type)?;
+
     // --- ASCII Data Section ---
-    for chunk in data.time_series.chunks(4) {
-        format_data_line(&mut line_buffer, chunk);
-        line_buffer.write_line(writer)?;
+    match (record.abscissa, record.ordinate) {
+        (Abscissa::Even { .. }, Ordinate::Real(values)) => {
+            for chunk in values.chunks(4) {
+                format_data_line(&mut line_buffer, chunk);
+                line_buffer.write_line(writer)?;
+            }
+        }
+        (Abscissa::Even { .. }, Ordinate::Complex(values)) => {
+            for chunk in values.chunks(2) {
+                let parts: Vec<f64> = chunk.iter().flat_map(|c| [c.re, c.im]).collect();
+                format_data_line(&mut line_buffer, &parts);
+                line_buffer.write_line(writer)?;
+            }
+        }
+        (Abscissa::Uneven(abscissa), Ordinate::Real(values)) => {
+            let pairs: Vec<(f64, f64)> = abscissa
+                .iter()
+                .copied()
+                .zip(values.iter().copied())
+                .collect();
+            for chunk in pairs.chunks(2) {
+                line_buffer.clear();
+                for &(x, y) in chunk {
+                    write_scientific(&mut line_buffer, x, 13, 5).expect("writing abscissa value");
+                    write_scientific(&mut line_buffer, y, 20, 11).expect("writing ordinate value");
+                }
+                line_buffer.write_line(writer)?;
+            }
+        }
+        (Abscissa::Uneven(abscissa), Ordinate::Complex(values)) => {
+            for (&x, y) in abscissa.iter().zip(values) {
+                line_buffer.clear();
+                write_scientific(&mut line_buffer, x, 13, 5).expect("writing abscissa value");
+                write_scientific(&mut line_buffer, y.re, 20, 11).expect("writing real part");
+                write_scientific(&mut line_buffer, y.im, 20, 11).expect("writing imaginary part");
+                line_buffer.write_line(writer)?;
+            }
+        }
     }
 
     // --- End of Block ---
@@ -299,36 +556,12 @@ fn write_uff58_impl<W: IoWrite>(
     Ok(())
 }
 
-/// Writes a single channel to a UFF Type 58 writer without managing the underlying file handle.
-pub fn write_uff58<W: IoWrite>(writer: &mut W, data: &ChannelData, track_name: &str) -> Result<()> {
-    write_uff58_impl(writer, data, track_name)
-}
-
-#[derive(Clone, Copy, Debug)]
-pub enum Uff58Format {
-    Ascii,
-    Binary58b,
-}
-
-pub fn write_uff58_ascii<W: IoWrite>(
-    writer: &mut W,
-    data: &ChannelData,
-    track_name: &str,
-) -> Result<()> {
-    write_uff58_impl(writer, data, track_name)
-}
-
-/// Convenience helper that opens a file handle and writes a single channel.
-///
 /// Write UFF 58 in "58b" (binary) variant:
 /// - ASCII header with a "b" line (byte order, fp format, n_ascii_lines, n_bytes, ...)
 /// - Records mirror our ASCII writer for fields
 /// - Data payload written as f32 in selected endian (like reference Python code)
-pub fn write_uff58b<W: IoWrite>(
-    writer: &mut W,
-    data: &ChannelData,
-    track_name: &str,
-) -> Result<()> {
+fn write_record_binary<W: IoWrite>(writer: &mut W, record: &Uff58Record<'_>) -> Result<()> {
+    record.validate()?;
     let mut line_buffer = LineBuffer::with_capacity(256);
 
     // Separator and type
@@ -344,10 +577,33 @@ pub fn write_uff58b<W: IoWrite>(
     // number of ASCII lines before binary payload (like reference: 11)
     let n_ascii_lines: i32 = 11;
 
-    // Even abscissa (1) – we always write evenly sampled time series
-    let num_pts = data.time_series.len();
-    // We'll write real-even data payload as f32 values (num_pts entries)
-    let n_bytes: i32 = (num_pts as i32) * 4;
+    // Payload: ordinate values (re/im pairs for complex data), each preceded by its abscissa
+    // value when the abscissa is uneven.
+    let mut payload: Vec<f32> = Vec::new();
+    let uneven = match record.abscissa {
+        Abscissa::Even { .. } => None,
+        Abscissa::Uneven(values) => Some(values),
+    };
+    match record.ordinate {
+        Ordinate::Real(values) => {
+            for (index, &value) in values.iter().enumerate() {
+                if let Some(abscissa) = uneven {
+                    payload.push(abscissa[index] as f32);
+                }
+                payload.push(value as f32);
+            }
+        }
+        Ordinate::Complex(values) => {
+            for (index, value) in values.iter().enumerate() {
+                if let Some(abscissa) = uneven {
+                    payload.push(abscissa[index] as f32);
+                }
+                payload.push(value.re as f32);
+                payload.push(value.im as f32);
+            }
+        }
+    }
+    let n_bytes: i32 = (payload.len() as i32) * 4;
 
     // Type line with trailing "b"
     {
@@ -360,114 +616,326 @@ pub fn write_uff58b<W: IoWrite>(
         line_buffer.write_line(writer)?;
     }
 
-    // Record 1..5 (IDs) – mirror ASCII writer layout
-    line_buffer.clear();
-    line_buffer.write_line(writer)?; // blank ID1
+    // Records 1..11 – single precision real (2) or complex (5) payload
+    let ord_data_type = match record.ordinate {
+        Ordinate::Real(_) => 2,
+        Ordinate::Complex(_) => 5,
+    };
+    write_record_header(&mut line_buffer, writer, record, ord_data_type)?;
+
+    // Record 12: binary data payload as f32
+    // We'll write in the byte order indicated by `bo`
+    if cfg!(target_endian = "little") {
+        for &v in &payload {
+            writer.write_all(&v.to_le_bytes())?;
+        }
+    } else {
+        for &v in &payload {
+            writer.write_all(&v.to_be_bytes())?;
+        }
+    }
 
-    let pt_label = truncate_to_width(track_name, 64);
+    // End of block
     line_buffer.clear();
-    line_buffer.push_str("Pt=");
-    line_buffer.push_str(&pt_label);
-    line_buffer.push_char(';');
+    line_buffer.push_str(UFF_SEPARATOR);
     line_buffer.write_line(writer)?;
 
-    line_buffer.clear();
-    line_buffer.write_line(writer)?; // blank ID3
+    Ok(())
+}
 
-    line_buffer.clear();
-    line_buffer.push_str("NONE");
-    line_buffer.write_line(writer)?;
+/// Writes any UFF 58 record in the requested layout.
+pub fn write_uff58_record<W: IoWrite>(
+    writer: &mut W,
+    record: &Uff58Record<'_>,
+    fmt: Uff58Format,
+) -> Result<()> {
+    match fmt {
+        Uff58Format::Ascii => write_record_ascii(writer, record),
+        Uff58Format::Binary58b => write_record_binary(writer, record),
+    }
+}
 
-    line_buffer.clear();
-    line_buffer.push_str("NONE");
-    line_buffer.write_line(writer)?;
+/// Writes a single channel to a UFF Type 58 writer without managing the underlying file handle.
+pub fn write_uff58<W: IoWrite>(writer: &mut W, data: &ChannelData, track_name: &str) -> Result<()> {
+    write_uff58_ascii(writer, data, track_name)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Uff58Format {
+    Ascii,
+    Binary58b,
+}
+
+pub fn write_uff58_ascii<W: IoWrite>(
+    writer: &mut W,
+    data: &ChannelData,
+    track_name: &str,
+) -> Result<()> {
+    write_uff58_ascii_with_ids(writer, data, track_name, &IdLines::matlab(track_name))
+}
+
+/// Writes an ASCII UFF 58 block with caller-provided ID records.
+pub fn write_uff58_ascii_with_ids<W: IoWrite>(
+    writer: &mut W,
+    data: &ChannelData,
+    track_name: &str,
+    ids: &IdLines,
+) -> Result<()> {
+    let record = Uff58Record::time_history(data, track_name, ids.clone());
+    write_record_ascii(writer, &record)
+}
+
+/// Writes a single channel in the binary "58b" variant.
+pub fn write_uff58b<W: IoWrite>(
+    writer: &mut W,
+    data: &ChannelData,
+    track_name: &str,
+) -> Result<()> {
+    write_uff58b_with_ids(writer, data, track_name, &IdLines::matlab(track_name))
+}
+
+/// Writes a binary (58b) UFF block with caller-provided ID records.
+pub fn write_uff58b_with_ids<W: IoWrite>(
+    writer: &mut W,
+    data: &ChannelData,
+    track_name: &str,
+    ids: &IdLines,
+) -> Result<()> {
+    let record = Uff58Record::time_history(data, track_name, ids.clone());
+    write_record_binary(writer, &record)
+}
+
+/// Window qualifier codes of dataset 1858 (record 2, field 2).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum WindowQualifier {
+    /// No window (rectangular / uniform).
+    #[default]
+    None,
+    HanningNarrow,
+    HanningBroad,
+    FlatTop,
+    Exponential,
+    Impact,
+    ImpactExponential,
+}
+
+impl WindowQualifier {
+    fn code(self) -> i32 {
+        match self {
+            WindowQualifier::None => 0,
+            WindowQualifier::HanningNarrow => 1,
+            WindowQualifier::HanningBroad => 2,
+            WindowQualifier::FlatTop => 3,
+            WindowQualifier::Exponential => 4,
+            WindowQualifier::Impact => 5,
+            WindowQualifier::ImpactExponential => 6,
+        }
+    }
+}
+
+/// Frequency weighting codes of dataset 1858 (record 2, field 1).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum WeightingQualifier {
+    #[default]
+    None,
+    A,
+    B,
+    C,
+    D,
+}
+
+impl WeightingQualifier {
+    fn code(self) -> i32 {
+        match self {
+            WeightingQualifier::None => 0,
+            WeightingQualifier::A => 1,
+            WeightingQualifier::B => 2,
+            WeightingQualifier::C => 3,
+            WeightingQualifier::D => 4,
+        }
+    }
+}
+
+/// Amplitude unit codes of dataset 1858 (record 2, field 3).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum AmplitudeUnits {
+    #[default]
+    Unknown,
+    HalfPeak,
+    Peak,
+    Rms,
+}
+
+impl AmplitudeUnits {
+    fn code(self) -> i32 {
+        match self {
+            AmplitudeUnits::Unknown => 0,
+            AmplitudeUnits::HalfPeak => 1,
+            AmplitudeUnits::Peak => 2,
+            AmplitudeUnits::Rms => 3,
+        }
+    }
+}
+
+/// Normalization codes of dataset 1858 (record 2, field 4).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Normalization {
+    #[default]
+    Unknown,
+    /// Units squared.
+    UnitsSquared,
+    /// Units squared per Hz (PSD).
+    UnitsSquaredPerHz,
+    /// Units squared seconds per Hz (ESD).
+    UnitsSquaredSecondsPerHz,
+}
+
+impl Normalization {
+    fn code(self) -> i32 {
+        match self {
+            Normalization::Unknown => 0,
+            Normalization::UnitsSquared => 1,
+            Normalization::UnitsSquaredPerHz => 2,
+            Normalization::UnitsSquaredSecondsPerHz => 3,
+        }
+    }
+}
+
+/// Dataset 1858 qualifiers describing how the data of the following 58 record was processed.
+///
+/// The four user values carry settings that have no dedicated 1858 field:
+/// 1. number of averages (0 for time histories, points per octave for SRS curves),
+/// 2. low-pass cutoff frequency in Hz (0 when unfiltered),
+/// 3. high-pass cutoff frequency in Hz (0 when unfiltered),
+/// 4. scale factor applied to the DTS engineering units (1 when unchanged).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Uff1858 {
+    /// Sequence number of the 58 record in the file (1-based).
+    pub set_record_number: i32,
+    /// Octave format: 0 for narrow band data, `n` for 1/n octave bands.
+    pub octave_format: i32,
+    pub measurement_run: i32,
+    pub weighting: WeightingQualifier,
+    pub window: WindowQualifier,
+    pub amplitude_units: AmplitudeUnits,
+    pub normalization: Normalization,
+    /// Data type qualifiers (0 translation, 1 rotation, 2 translation squared, 3 rotation
+    /// squared) for the abscissa, ordinate numerator, ordinate denominator and Z axis.
+    pub abscissa_qualifier: i32,
+    pub ordinate_numerator_qualifier: i32,
+    pub ordinate_denominator_qualifier: i32,
+    pub z_axis_qualifier: i32,
+    /// Sampling type: 0 dynamic, 1 static, 2 RPM from tach, 3 frequency from tach.
+    pub sampling_type: i32,
+    pub z_rpm: f64,
+    /// Time of the first exported sample, in seconds.
+    pub z_time: f64,
+    pub z_order: f64,
+    pub number_of_samples: f64,
+    pub user_values: [f64; 4],
+    /// Exponential window damping factor.
+    pub exponential_damping: f64,
+}
+
+impl Uff1858 {
+    /// Qualifiers for an unwindowed, unaveraged time history of `number_of_samples` samples.
+    pub fn time_history(set_record_number: i32, number_of_samples: usize) -> Self {
+        Self {
+            set_record_number,
+            octave_format: 0,
+            measurement_run: 0,
+            weighting: WeightingQualifier::None,
+            window: WindowQualifier::None,
+            amplitude_units: AmplitudeUnits::Unknown,
+            normalization: Normalization::Unknown,
+            abscissa_qualifier: 0,
+            ordinate_numerator_qualifier: 0,
+            ordinate_denominator_qualifier: 0,
+            z_axis_qualifier: 0,
+            sampling_type: 0,
+            z_rpm: 0.0,
+            z_time: 0.0,
+            z_order: 0.0,
+            number_of_samples: number_of_samples as f64,
+            user_values: [0.0, 0.0, 0.0, 1.0],
+            exponential_damping: 0.0,
+        }
+    }
+}
+
+/// Writes a dataset 1858 block. The block is always ASCII, also in files holding 58b records.
+pub fn write_uff1858<W: IoWrite>(writer: &mut W, qualifiers: &Uff1858) -> Result<()> {
+    let mut line_buffer = LineBuffer::with_capacity(128);
 
-    // Record 6
-    // Real data at nodal DOF, response entity name is the channel label (first 19 chars)
-    let channel_label = truncate_to_width(track_name, 19);
-    let channel_field = format!("{:<19}", channel_label);
-    let zero_field = format!("{:>4}", 0);
-    let none_field = format!(" {:<19}", "NONE");
-    let flag_field = format!("{:<4}{}", 1, 0);
     line_buffer.clear();
-    line_buffer.write_fmt(format_args!(
-        "    1         0    0         0 {}{}{}{}{}",
-        channel_field, 0, zero_field, none_field, flag_field
-    ));
+    line_buffer.push_str(UFF_SEPARATOR);
     line_buffer.write_line(writer)?;
 
-    // Record 7
     line_buffer.clear();
-    line_buffer.write_fmt(format_args!(
-        "{:>10}{:>10}{:>10}  ",
-        2, // ord_data_type (single precision for binary payload)
-        num_pts,
-        1 // abscissa_spacing (even)
-    ));
-    write_scientific(&mut line_buffer, 0.0, 11, 5).expect("writing abscissa min");
-    line_buffer.push_str("  ");
-    write_scientific(&mut line_buffer, 1.0 / data.sample_rate, 11, 5)
-        .expect("writing abscissa inc");
-    line_buffer.push_str("  ");
-    write_scientific(&mut line_buffer, 0.0, 11, 5).expect("writing z-axis value");
+    line_buffer.push_str("  1858");
     line_buffer.write_line(writer)?;
 
-    // Record 8 (Abscissa characteristics)
-    let abscissa_name = format!(" {:<19}", "Time");
-    let abscissa_units = format!("{: <48}", format!("  {}", truncate_to_width("s", 46)));
+    // Record 1: set record number, octave format, measurement run
     line_buffer.clear();
     line_buffer.write_fmt(format_args!(
-        "{:>10}{:>5}{:>5}{:>5}{}{}",
-        17, 0, 0, 0, abscissa_name, abscissa_units
+        "{:>12}{:>12}{:>12}{:>12}{:>12}{:>12}",
+        qualifiers.set_record_number, qualifiers.octave_format, qualifiers.measurement_run, 0, 0, 0
     ));
     line_buffer.write_line(writer)?;
 
-    // Record 9 (Ordinate characteristics)
-    let ordinate_name_field = format!(" {:<19}", channel_label);
-    let ordinate_units_field = format!(
-        "{: <35}",
-        format!("  {}", truncate_to_width(&data.units, 33))
-    );
+    // Record 2: weighting, window, amplitude units, normalization, data type qualifiers
     line_buffer.clear();
     line_buffer.write_fmt(format_args!(
-        "{:>10}{:>5}{:>5}{:>5}{}{}",
-        8, 0, 0, 0, ordinate_name_field, ordinate_units_field
+        "{:>6}{:>6}{:>6}{:>6}{:>6}{:>6}{:>6}{:>6}{:>6}{:>6}{:>6}{:>6}",
+        qualifiers.weighting.code(),
+        qualifiers.window.code(),
+        qualifiers.amplitude_units.code(),
+        qualifiers.normalization.code(),
+        qualifiers.abscissa_qualifier,
+        qualifiers.ordinate_numerator_qualifier,
+        qualifiers.ordinate_denominator_qualifier,
+        qualifiers.z_axis_qualifier,
+        qualifiers.sampling_type,
+        0,
+        0,
+        0
     ));
     line_buffer.write_line(writer)?;
 
-    // Record 10 (Ordinate denom)
-    let none_name_field = format!(" {:<19}", "NONE");
-    let none_units_field = format!("{: <35}", format!("  {}", "NONE"));
+    // Records 3-5: Z values, user values, unused
+    let real_records = [
+        [
+            qualifiers.z_rpm,
+            qualifiers.z_time,
+            qualifiers.z_order,
+            qualifiers.number_of_samples,
+            0.0,
+        ],
+        [
+            qualifiers.user_values[0],
+            qualifiers.user_values[1],
+            qualifiers.user_values[2],
+            qualifiers.user_values[3],
+            qualifiers.exponential_damping,
+        ],
+        [0.0; 5],
+    ];
+    for values in &real_records {
+        line_buffer.clear();
+        for &value in values {
+            write_scientific(&mut line_buffer, value, 15, 7).expect("writing 1858 real value");
+        }
+        line_buffer.write_line(writer)?;
+    }
+
+    // Record 6: response and reference direction (unused for DTS channels)
     line_buffer.clear();
-    line_buffer.write_fmt(format_args!(
-        "{:>10}{:>5}{:>5}{:>5}{}{}",
-        0, 0, 0, 0, none_name_field, none_units_field
-    ));
     line_buffer.write_line(writer)?;
 
-    // Record 11 (Z-axis characteristics)
+    // Record 7: unused
     line_buffer.clear();
-    line_buffer.write_fmt(format_args!(
-        "{:>10}{:>5}{:>5}{:>5}{}{}",
-        0, 0, 0, 0, none_name_field, none_units_field
-    ));
     line_buffer.write_line(writer)?;
 
-    // Record 12: binary data payload (real, even) as f32
-    // We'll write in the byte order indicated by `bo`
-    if cfg!(target_endian = "little") {
-        for &v in &data.time_series {
-            let b = (v as f32).to_le_bytes();
-            writer.write_all(&b)?;
-        }
-    } else {
-        for &v in &data.time_series {
-            let b = (v as f32).to_be_bytes();
-            writer.write_all(&b)?;
-        }
-    }
-
-    // End of block
     line_buffer.clear();
     line_buffer.push_str(UFF_SEPARATOR);
     line_buffer.write_line(writer)?;
@@ -0,0 +1,339 @@
+use crate::dts::ChannelData;
+use anyhow::{anyhow, Result};
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// Physical quantities the converter knows how to rescale.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Quantity {
+    Acceleration,
+    Force,
+    Pressure,
+}
+
+impl Quantity {
+    /// All supported quantities, in display order.
+    pub const ALL: [Quantity; 3] = [Quantity::Acceleration, Quantity::Force, Quantity::Pressure];
+
+    /// Returns the human readable name of the quantity.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Quantity::Acceleration => "acceleration",
+            Quantity::Force => "force",
+            Quantity::Pressure => "pressure",
+        }
+    }
+
+    /// Returns the SI unit used for this quantity by the `--si` preset.
+    pub fn si_unit(&self) -> &'static Unit {
+        let symbol = match self {
+            Quantity::Acceleration => "m/s^2",
+            Quantity::Force => "N",
+            Quantity::Pressure => "Pa",
+        };
+        lookup_unit(symbol).expect("SI units are part of the unit table")
+    }
+}
+
+impl std::fmt::Display for Quantity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Quantity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "acceleration" | "accel" => Ok(Quantity::Acceleration),
+            "force" => Ok(Quantity::Force),
+            "pressure" => Ok(Quantity::Pressure),
+            other => Err(format!(
+                "Unsupported quantity '{other}'. Expected 'acceleration', 'force' or 'pressure'."
+            )),
+        }
+    }
+}
+
+/// A unit of a supported quantity and its factor to the SI unit of that quantity.
+#[derive(Debug, PartialEq)]
+pub struct Unit {
+    /// Canonical label written into the output files.
+    pub symbol: &'static str,
+    /// Quantity measured by the unit.
+    pub quantity: Quantity,
+    /// Multiplier converting a value in this unit to the SI unit.
+    pub to_si: f64,
+    aliases: &'static [&'static str],
+}
+
+impl Serialize for Unit {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.symbol)
+    }
+}
+
+const STANDARD_GRAVITY: f64 = 9.80665;
+const POUND_FORCE_N: f64 = 4.448_221_615_260_5;
+const PSI_PA: f64 = 6_894.757_293_168_361;
+
+static UNITS: &[Unit] = &[
+    Unit {
+        symbol: "g",
+        quantity: Quantity::Acceleration,
+        to_si: STANDARD_GRAVITY,
+        aliases: &["G", "gn", "g0"],
+    },
+    Unit {
+        symbol: "m/s^2",
+        quantity: Quantity::Acceleration,
+        to_si: 1.0,
+        aliases: &["m/s2", "m/s²", "m/s/s", "m s^-2"],
+    },
+    Unit {
+        symbol: "mm/s^2",
+        quantity: Quantity::Acceleration,
+        to_si: 1e-3,
+        aliases: &["mm/s2", "mm/s²"],
+    },
+    Unit {
+        symbol: "N",
+        quantity: Quantity::Force,
+        to_si: 1.0,
+        aliases: &[],
+    },
+    Unit {
+        symbol: "kN",
+        quantity: Quantity::Force,
+        to_si: 1e3,
+        aliases: &["KN"],
+    },
+    Unit {
+        symbol: "lbf",
+        quantity: Quantity::Force,
+        to_si: POUND_FORCE_N,
+        aliases: &["LBF"],
+    },
+    Unit {
+        symbol: "Pa",
+        quantity: Quantity::Pressure,
+        to_si: 1.0,
+        aliases: &[],
+    },
+    Unit {
+        symbol: "kPa",
+        quantity: Quantity::Pressure,
+        to_si: 1e3,
+        aliases: &[],
+    },
+    Unit {
+        symbol: "MPa",
+        quantity: Quantity::Pressure,
+        to_si: 1e6,
+        aliases: &[],
+    },
+    Unit {
+        symbol: "bar",
+        quantity: Quantity::Pressure,
+        to_si: 1e5,
+        aliases: &[],
+    },
+    Unit {
+        symbol: "psi",
+        quantity: Quantity::Pressure,
+        to_si: PSI_PA,
+        aliases: &["PSI", "lbf/in^2", "lbf/in2"],
+    },
+];
+
+/// Looks up a unit by its symbol or one of its aliases.
+///
+/// Labels are matched exactly: prefixed SI symbols differ only by case (`mPa`/`MPa`), so the
+/// upper-case spellings seen in the wild (`G`, `KN`, `PSI`) are listed as aliases instead.
+pub fn lookup_unit(label: &str) -> Option<&'static Unit> {
+    let label = label.trim();
+    UNITS
+        .iter()
+        .find(|unit| unit.symbol == label || unit.aliases.contains(&label))
+}
+
+/// A `quantity=unit` target, e.g. `acceleration=m/s^2`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuantityTarget {
+    pub quantity: Quantity,
+    pub unit: &'static Unit,
+}
+
+impl std::str::FromStr for QuantityTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (quantity, unit) = s.split_once('=').ok_or_else(|| {
+            "Unit target must be provided in the format quantity=unit".to_string()
+        })?;
+        let quantity: Quantity = quantity.parse()?;
+        let unit = parse_unit(unit)?;
+        if unit.quantity != quantity {
+            return Err(format!(
+                "Unit '{}' measures {}, not {}.",
+                unit.symbol, unit.quantity, quantity
+            ));
+        }
+        Ok(QuantityTarget { quantity, unit })
+    }
+}
+
+/// A `track=unit` target, e.g. `Head X=m/s^2`. The track name may contain `=`; the last one
+/// separates the unit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelUnitTarget {
+    pub track_name: String,
+    pub unit: &'static Unit,
+}
+
+impl std::str::FromStr for ChannelUnitTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (track_name, unit) = s
+            .rsplit_once('=')
+            .ok_or_else(|| "Channel unit must be provided in the format track=unit".to_string())?;
+        let track_name = track_name.trim();
+        if track_name.is_empty() {
+            return Err("Channel unit target is missing a track name".to_string());
+        }
+        Ok(ChannelUnitTarget {
+            track_name: track_name.to_string(),
+            unit: parse_unit(unit)?,
+        })
+    }
+}
+
+fn parse_unit(label: &str) -> Result<&'static Unit, String> {
+    lookup_unit(label).ok_or_else(|| {
+        let known: Vec<&str> = UNITS.iter().map(|unit| unit.symbol).collect();
+        format!(
+            "Unsupported unit '{}'. Expected one of: {}.",
+            label.trim(),
+            known.join(", ")
+        )
+    })
+}
+
+/// Describes a rescaling applied to one channel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnitChange {
+    /// Unit label found in the DTS metadata.
+    pub from: String,
+    /// Unit label written to the output.
+    pub to: &'static str,
+    /// Multiplier applied to every sample.
+    pub factor: f64,
+}
+
+/// Target units requested for the exported channels.
+///
+/// Per-channel targets take precedence over per-quantity targets. Channels whose unit is not in
+/// the unit table are left untouched unless a per-channel target names them explicitly.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct UnitConversion {
+    quantity_targets: BTreeMap<Quantity, &'static Unit>,
+    channel_targets: BTreeMap<String, &'static Unit>,
+}
+
+impl UnitConversion {
+    /// Preset converting every known quantity to its SI unit.
+    pub fn si() -> Self {
+        let mut conversion = Self::default();
+        for quantity in Quantity::ALL {
+            conversion.set_quantity_target(quantity, quantity.si_unit());
+        }
+        conversion
+    }
+
+    /// Request `unit` for every channel measuring `quantity`.
+    pub fn set_quantity_target(&mut self, quantity: Quantity, unit: &'static Unit) {
+        self.quantity_targets.insert(quantity, unit);
+    }
+
+    /// Request `unit` for the channel exported as `track_name`.
+    pub fn set_channel_target(&mut self, track_name: impl Into<String>, unit: &'static Unit) {
+        self.channel_targets.insert(track_name.into(), unit);
+    }
+
+    /// Returns `true` when no rescaling was requested.
+    pub fn is_empty(&self) -> bool {
+        self.quantity_targets.is_empty() && self.channel_targets.is_empty()
+    }
+
+    /// Iterates over the per-channel targets.
+    pub fn channel_targets(&self) -> impl Iterator<Item = (&str, &'static Unit)> {
+        self.channel_targets
+            .iter()
+            .map(|(name, unit)| (name.as_str(), *unit))
+    }
+
+    /// Iterates over the per-quantity targets.
+    pub fn quantity_targets(&self) -> impl Iterator<Item = (Quantity, &'static Unit)> + '_ {
+        self.quantity_targets
+            .iter()
+            .map(|(quantity, unit)| (*quantity, *unit))
+    }
+
+    /// Rescale `data` to the requested unit for `track_name` and update its unit label.
+    ///
+    /// Returns the applied change, or `None` when the channel was left as is.
+    pub fn apply(&self, track_name: &str, data: &mut ChannelData) -> Result<Option<UnitChange>> {
+        let source = lookup_unit(&data.units);
+
+        let target = match self.channel_targets.get(track_name) {
+            Some(target) => {
+                let source = source.ok_or_else(|| {
+                    anyhow!(
+                        "Cannot convert track '{}' to '{}': unit '{}' is not recognised.",
+                        track_name,
+                        target.symbol,
+                        data.units
+                    )
+                })?;
+                if source.quantity != target.quantity {
+                    return Err(anyhow!(
+                        "Cannot convert track '{}' from '{}' ({}) to '{}' ({}).",
+                        track_name,
+                        data.units,
+                        source.quantity,
+                        target.symbol,
+                        target.quantity
+                    ));
+                }
+                *target
+            }
+            None => match source.and_then(|unit| self.quantity_targets.get(&unit.quantity)) {
+                Some(target) => *target,
+                None => return Ok(None),
+            },
+        };
+
+        let source = source.expect("source unit resolved above");
+        if std::ptr::eq(source, target) {
+            if data.units != target.symbol {
+                data.units = target.symbol.to_string();
+            }
+            return Ok(None);
+        }
+
+        let factor = source.to_si / target.to_si;
+        for value in &mut data.time_series {
+            *value *= factor;
+        }
+
+        let change = UnitChange {
+            from: std::mem::replace(&mut data.units, target.symbol.to_string()),
+            to: target.symbol,
+            factor,
+        };
+        Ok(Some(change))
+    }
+}
@@ -0,0 +1,237 @@
+use crate::dts::TestInfo;
+use crate::export::{ChannelWriter, ExportChannel};
+use anyhow::{anyhow, Context, Result};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Identifier of the chunk holding the name, unit and scale factor of every channel.
+pub const SCALE_CHUNK_ID: &[u8; 4] = b"scal";
+
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+/// `KSDATAFORMAT_SUBTYPE_IEEE_FLOAT`.
+const IEEE_FLOAT_SUBFORMAT: [u8; 16] = [
+    0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38, 0x9B, 0x71,
+];
+const BYTES_PER_SAMPLE: u16 = 4;
+/// Size field of the RIFF, `data` and `fact` chunks whose actual size is kept in `ds64`.
+const RF64_PLACEHOLDER: u32 = u32::MAX;
+
+/// Writes a multichannel WAVE file with 32-bit IEEE float samples.
+///
+/// Channels keep their native sample rate, which they must share, and are interleaved in
+/// output order. The track names are stored in the `ICMT` entry of the `LIST`/`INFO` chunk, one
+/// per line, and a `scal` chunk holds one `name<TAB>unit<TAB>scale` line per channel: the
+/// engineering value is the sample multiplied by the scale. Files whose size exceeds the 4 GiB
+/// limit of RIFF are written as RF64.
+pub struct WavWriter<'a> {
+    path: PathBuf,
+    test: TestInfo,
+    normalize: bool,
+    channels: Vec<ExportChannel<'a>>,
+}
+
+impl<'a> WavWriter<'a> {
+    /// Prepare a WAVE export to `path`. With `normalize`, every channel is scaled to a peak of
+    /// 1.0 (full scale).
+    pub fn create(path: &Path, test: &TestInfo, normalize: bool) -> Result<Self> {
+        Ok(Self {
+            path: path.to_path_buf(),
+            test: test.clone(),
+            normalize,
+            channels: Vec::new(),
+        })
+    }
+
+    /// Engineering units per sample value of `channel`.
+    fn scale(&self, channel: &ExportChannel<'_>) -> f64 {
+        let peak = channel
+            .data
+            .time_series
+            .iter()
+            .fold(0.0f64, |peak, value| peak.max(value.abs()));
+        if self.normalize && peak > 0.0 && peak.is_finite() {
+            peak
+        } else {
+            1.0
+        }
+    }
+
+    fn info_chunk(&self) -> Vec<u8> {
+        let names = self
+            .channels
+            .iter()
+            .map(|channel| channel.name)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let title = if self.test.description.trim().is_empty() {
+            &self.test.id
+        } else {
+            &self.test.description
+        };
+        let software = format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+
+        let mut info = b"INFO".to_vec();
+        for (id, value) in [
+            (b"INAM", title.as_str()),
+            (b"ICRD", self.test.date.as_str()),
+            (b"ICMT", names.as_str()),
+            (b"ISFT", software.as_str()),
+        ] {
+            if value.trim().is_empty() {
+                continue;
+            }
+            let mut text = value.as_bytes().to_vec();
+            text.push(0);
+            info.extend(chunk(id, &text));
+        }
+        chunk(b"LIST", &info)
+    }
+
+    fn scale_chunk(&self, scales: &[f64]) -> Vec<u8> {
+        let mut text = String::new();
+        for (channel, scale) in self.channels.iter().zip(scales) {
+            text.push_str(&format!(
+                "{}\t{}\t{scale:E}\n",
+                channel.name, channel.data.units
+            ));
+        }
+        chunk(SCALE_CHUNK_ID, text.as_bytes())
+    }
+}
+
+impl<'a> ChannelWriter<'a> for WavWriter<'a> {
+    fn write_channel(&mut self, channel: &ExportChannel<'a>) -> Result<()> {
+        if let Some(first) = self.channels.first() {
+            let rate = first.data.sample_rate;
+            if (channel.data.sample_rate - rate).abs() > 1e-9 * rate.abs() {
+                return Err(anyhow!(
+                    "Track '{}' is sampled at {} Hz, but the WAV export holds one sample rate \
+                     ({} Hz); resample the channels to a common rate.",
+                    channel.name,
+                    channel.data.sample_rate,
+                    rate
+                ));
+            }
+            if channel.data.time_series.len() != first.data.time_series.len() {
+                return Err(anyhow!(
+                    "Track '{}' has {} samples, but the WAV export holds {} per channel.",
+                    channel.name,
+                    channel.data.time_series.len(),
+                    first.data.time_series.len()
+                ));
+            }
+        }
+        self.channels.push(*channel);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let channel_count = u16::try_from(self.channels.len())
+            .ok()
+            .filter(|&count| (1..=u16::MAX / BYTES_PER_SAMPLE).contains(&count))
+            .ok_or_else(|| {
+                anyhow!(
+                    "The WAV export holds 1 to 16383 channels, not {}.",
+                    self.channels.len()
+                )
+            })?;
+        let rate = self.channels[0].data.sample_rate;
+        if rate.fract() != 0.0 || !(1.0..=u32::MAX as f64).contains(&rate) {
+            return Err(anyhow!(
+                "WAV files store an integer sample rate, but the channels are sampled at {rate} \
+                 Hz; resample the channels to an integer rate."
+            ));
+        }
+        let rate = rate as u32;
+        let frames = self.channels[0].data.time_series.len() as u64;
+        let block_align = channel_count * BYTES_PER_SAMPLE;
+        let byte_rate = rate
+            .checked_mul(u32::from(block_align))
+            .ok_or_else(|| anyhow!("The WAV byte rate exceeds 4 GiB per second."))?;
+        let scales: Vec<f64> = self
+            .channels
+            .iter()
+            .map(|channel| self.scale(channel))
+            .collect();
+
+        let mut format = Vec::with_capacity(40);
+        format.extend(WAVE_FORMAT_EXTENSIBLE.to_le_bytes());
+        format.extend(channel_count.to_le_bytes());
+        format.extend(rate.to_le_bytes());
+        format.extend(byte_rate.to_le_bytes());
+        format.extend(block_align.to_le_bytes());
+        format.extend((BYTES_PER_SAMPLE * 8).to_le_bytes());
+        format.extend(22u16.to_le_bytes());
+        format.extend((BYTES_PER_SAMPLE * 8).to_le_bytes());
+        // No speaker positions: the channels are not loudspeaker feeds.
+        format.extend(0u32.to_le_bytes());
+        format.extend(IEEE_FLOAT_SUBFORMAT);
+        let format = chunk(b"fmt ", &format);
+        let info = self.info_chunk();
+        let scale = self.scale_chunk(&scales);
+
+        let data_size = frames * u64::from(block_align);
+        // `WAVE`, the chunks before the data, and the `fact` and `data` chunk headers.
+        let header_size = 4 + format.len() as u64 + info.len() as u64 + scale.len() as u64 + 20;
+        let riff_size = header_size + data_size;
+        let rf64 = riff_size > u64::from(u32::MAX);
+
+        let file = File::create(&self.path)
+            .with_context(|| format!("Failed to create {}", self.path.display()))?;
+        let mut writer = BufWriter::with_capacity(8 * 1024 * 1024, file);
+        if rf64 {
+            let mut ds64 = Vec::with_capacity(28);
+            // The `ds64` chunk itself is part of the RIFF size.
+            ds64.extend((riff_size + 36).to_le_bytes());
+            ds64.extend(data_size.to_le_bytes());
+            ds64.extend(frames.to_le_bytes());
+            ds64.extend(0u32.to_le_bytes());
+            writer.write_all(b"RF64")?;
+            writer.write_all(&RF64_PLACEHOLDER.to_le_bytes())?;
+            writer.write_all(b"WAVE")?;
+            writer.write_all(&chunk(b"ds64", &ds64))?;
+        } else {
+            writer.write_all(b"RIFF")?;
+            writer.write_all(&(riff_size as u32).to_le_bytes())?;
+            writer.write_all(b"WAVE")?;
+        }
+        writer.write_all(&format)?;
+        writer.write_all(&info)?;
+        writer.write_all(&scale)?;
+        let fact = u32::try_from(frames).unwrap_or(RF64_PLACEHOLDER);
+        writer.write_all(&chunk(b"fact", &fact.to_le_bytes()))?;
+        writer.write_all(b"data")?;
+        let data_size_field = if rf64 {
+            RF64_PLACEHOLDER
+        } else {
+            data_size as u32
+        };
+        writer.write_all(&data_size_field.to_le_bytes())?;
+
+        let mut frame = Vec::with_capacity(usize::from(block_align));
+        for index in 0..frames as usize {
+            frame.clear();
+            for (channel, scale) in self.channels.iter().zip(&scales) {
+                let value = (channel.data.time_series[index] / scale) as f32;
+                frame.extend(value.to_le_bytes());
+            }
+            writer.write_all(&frame)?;
+        }
+        writer
+            .flush()
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+/// A chunk with its identifier, size and a pad byte after odd-sized data.
+fn chunk(id: &[u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(data.len() + 9);
+    bytes.extend(id);
+    bytes.extend((data.len() as u32).to_le_bytes());
+    bytes.extend(data);
+    if data.len() % 2 == 1 {
+        bytes.push(0);
+    }
+    bytes
+}
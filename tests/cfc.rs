@@ -0,0 +1,84 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat};
+use dts_to_uff_converter::filter::{CfcClass, CfcFiltering, ChannelCfcTarget};
+use std::f64::consts::PI;
+use std::fs;
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+fn filtered_amplitude(class: CfcClass, frequency: f64) -> Result<f64> {
+    let sample_rate = 10_000.0;
+    let mut samples: Vec<f64> = (0..20_000)
+        .map(|n| (2.0 * PI * frequency * n as f64 / sample_rate).sin())
+        .collect();
+    class.apply(&mut samples, sample_rate)?;
+    // Ignore the edges, where the forward and backward passes settle.
+    Ok(samples[5_000..15_000]
+        .iter()
+        .fold(0.0, |peak: f64, value| peak.max(value.abs())))
+}
+
+#[test]
+fn cfc_filters_follow_the_j211_corridor() -> Result<()> {
+    for class in [CfcClass::Cfc60, CfcClass::Cfc180, CfcClass::Cfc600] {
+        // Pass band: within the +0.5/-1 dB corridor up to the CFC value.
+        let pass = filtered_amplitude(class, class.value())?;
+        assert!(pass > 0.89 && pass < 1.06, "{class}: {pass}");
+        // About -3 dB at the nominal cutoff.
+        let cutoff = filtered_amplitude(class, class.cutoff_frequency())?;
+        assert!((cutoff - 0.707).abs() < 0.05, "{class}: {cutoff}");
+        // Strong attenuation an octave and a half above the cutoff.
+        let stop = filtered_amplitude(class, class.cutoff_frequency() * 3.0)?;
+        assert!(stop < 0.05, "{class}: {stop}");
+    }
+
+    assert!(CfcClass::Cfc1000.apply(&mut [0.0; 8], 4_000.0).is_err());
+    assert_eq!("cfc180".parse::<CfcClass>(), Ok(CfcClass::Cfc180));
+    let target: ChannelCfcTarget = "A1=none".parse().map_err(anyhow::Error::msg)?;
+    assert_eq!(target.class, None);
+    Ok(())
+}
+
+#[test]
+fn cfc_class_is_recorded_in_id_lines_and_report() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let output = NamedTempFile::new()?;
+
+    let mut cfc = CfcFiltering::default();
+    cfc.set_default(Some(CfcClass::Cfc180));
+    cfc.set_channel("A2", None);
+    let options = ConversionOptions {
+        cfc,
+        ..ConversionOptions::default()
+    };
+    let report = conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        output.path(),
+        OutputFormat::Ascii,
+        &options,
+        |_| {},
+    )?;
+    assert_eq!(report.cfc_classes, [("A1".to_string(), CfcClass::Cfc180)]);
+
+    let text = fs::read_to_string(output.path())?;
+    let reference = fs::read_to_string(data_dir.join("matlab_converted.uff"))?;
+    let lines: Vec<&str> = text.lines().collect();
+    let reference_lines: Vec<&str> = reference.lines().collect();
+    assert_eq!(lines.len(), reference_lines.len());
+
+    let starts: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.trim_end() == "    58")
+        .map(|(index, _)| index)
+        .collect();
+    assert_eq!(lines[starts[0] + 3].trim_end(), "CFC180");
+    assert_eq!(lines[starts[1] + 3].trim_end(), "");
+
+    // A1 is filtered, A2 is written exactly as MATLAB does.
+    assert_ne!(lines[starts[0] + 20], reference_lines[starts[0] + 20]);
+    assert_eq!(lines[starts[1]..], reference_lines[starts[1]..]);
+
+    Ok(())
+}
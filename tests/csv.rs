@@ -0,0 +1,109 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat};
+use dts_to_uff_converter::csv::{CsvRateHandling, CsvSettings, CsvWriter};
+use dts_to_uff_converter::dts::{ChannelData, DtsReader};
+use dts_to_uff_converter::export::{ChannelWriter, ExportChannel};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+#[test]
+fn converts_sample_with_metadata_and_decimal_comma() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let temp = TempDir::new()?;
+    let output = temp.path().join("test.csv");
+    let options = ConversionOptions {
+        csv: CsvSettings {
+            delimiter: Some(';'),
+            decimal_separator: ',',
+            precision: Some(6),
+            metadata: true,
+            ..CsvSettings::default()
+        },
+        ..ConversionOptions::default()
+    };
+    conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        &output,
+        OutputFormat::Csv,
+        &options,
+        |_| {},
+    )?;
+
+    let reader = DtsReader::new(&data_dir)?;
+    let a1 = reader.read_track(0)?;
+    let info = reader.channel_info(0).expect("channel metadata");
+    let text = fs::read_to_string(&output)?;
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 4 + 2000);
+    assert_eq!(lines[0], "Time (s);A1;A2");
+    assert_eq!(lines[1], "Units;g;g");
+    assert!(lines[2].starts_with(&format!(
+        "Sensitivity;{}",
+        format!("{:.6}", info.sensitivity).replace('.', ",")
+    )));
+    assert!(lines[3].starts_with(&format!("Serial number;{}", info.serial_number)));
+
+    let row: Vec<&str> = lines[4 + 10].split(';').collect();
+    let time: f64 = row[0].replace(',', ".").parse()?;
+    assert!((time - 10.0 / 200_000.0).abs() < 1e-12);
+    assert_eq!(
+        row[1],
+        format!("{:.6}", a1.time_series[10]).replace('.', ",")
+    );
+    Ok(())
+}
+
+#[test]
+fn splits_or_aligns_channels_with_different_rates() -> Result<()> {
+    let fast = ChannelData {
+        time_series: vec![0.0, 1.0, 2.0, 3.0, 4.0],
+        sample_rate: 4.0,
+        units: "g".to_string(),
+    };
+    let slow = ChannelData {
+        time_series: vec![10.0, 20.0, 30.0],
+        sample_rate: 2.0,
+        units: "m/s".to_string(),
+    };
+    let channel = |name, data| ExportChannel {
+        name,
+        data,
+        start_time: 0.0,
+        trigger_time: 0.0,
+        channel: None,
+        cfc: None,
+        filters: &[],
+        computed: true,
+        adc_scaling: None,
+    };
+    let temp = TempDir::new()?;
+    let write = |path: &Path, rates| -> Result<()> {
+        let settings = CsvSettings {
+            rates,
+            ..CsvSettings::default()
+        };
+        let mut writer = CsvWriter::create(path, &settings)?;
+        writer.write_channel(&channel("F", &fast))?;
+        writer.write_channel(&channel("S", &slow))?;
+        writer.finish()
+    };
+
+    write(&temp.path().join("split.tsv"), CsvRateHandling::Split)?;
+    assert_eq!(
+        fs::read_to_string(temp.path().join("split_4Hz.tsv"))?,
+        "Time (s)\tF\n0\t0\n0.25\t1\n0.5\t2\n0.75\t3\n1\t4\n"
+    );
+    assert_eq!(
+        fs::read_to_string(temp.path().join("split_2Hz.tsv"))?,
+        "Time (s)\tS\n0\t10\n0.5\t20\n1\t30\n"
+    );
+
+    write(&temp.path().join("align.csv"), CsvRateHandling::Align)?;
+    assert_eq!(
+        fs::read_to_string(temp.path().join("align.csv"))?,
+        "Time (s),F,S\n0,0,10\n0.25,1,15\n0.5,2,20\n0.75,3,25\n1,4,30\n"
+    );
+    Ok(())
+}
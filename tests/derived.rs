@@ -0,0 +1,142 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat};
+use dts_to_uff_converter::derived::{DerivedChannelSpec, DerivedOperation, OffsetRemoval};
+use dts_to_uff_converter::dts::ChannelData;
+use std::f64::consts::PI;
+use std::fs;
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+fn parse(spec: &str) -> Result<DerivedChannelSpec> {
+    spec.parse().map_err(anyhow::Error::msg)
+}
+
+fn channel(time_series: Vec<f64>, units: &str) -> ChannelData {
+    ChannelData {
+        time_series,
+        sample_rate: 1_000.0,
+        units: units.to_string(),
+    }
+}
+
+#[test]
+fn acceleration_integrates_to_velocity_and_displacement() -> Result<()> {
+    // One second of 1 g.
+    let pulse = channel(vec![1.0; 1001], "g");
+
+    let velocity = parse("A1=velocity")?.derive(&pulse)?;
+    assert_eq!(velocity.name, "A1_velocity");
+    assert_eq!(velocity.data.units, "m/s");
+    assert_eq!(velocity.ordinate_axis.spec_data_type, 11);
+    assert!((velocity.data.time_series[1000] - 9.80665).abs() < 1e-9);
+
+    let displacement = parse("A1=double-integrate:name=A1_disp")?.derive(&pulse)?;
+    assert_eq!(displacement.name, "A1_disp");
+    assert_eq!(displacement.data.units, "m");
+    assert_eq!(displacement.ordinate_axis.spec_data_type, 8);
+    assert!((displacement.data.time_series[1000] - 9.80665 / 2.0).abs() < 1e-6);
+
+    // Removing the pre-trigger offset leaves nothing to integrate.
+    let offset = parse("A1=velocity:offset=100")?.derive(&channel(vec![2.0; 500], "g"))?;
+    assert!(offset
+        .data
+        .time_series
+        .iter()
+        .all(|value| value.abs() < 1e-12));
+
+    // Differentiating a sine gives a cosine; unknown units get a `/s` suffix.
+    let omega = 2.0 * PI * 5.0;
+    let sine = channel(
+        (0..1000)
+            .map(|n| (omega * n as f64 / 1_000.0).sin())
+            .collect(),
+        "V",
+    );
+    let derivative = parse("A2=differentiate")?.derive(&sine)?;
+    assert_eq!(derivative.data.units, "V/s");
+    for n in 1..999 {
+        let expected = omega * (omega * n as f64 / 1_000.0).cos();
+        assert!((derivative.data.time_series[n] - expected).abs() < 0.01);
+    }
+
+    // Displacement differentiates to velocity in m/s.
+    let ramp = channel((0..100).map(|n| 2.0 * n as f64).collect(), "mm");
+    let velocity = parse("D1=differentiate")?.derive(&ramp)?;
+    assert_eq!(velocity.data.units, "m/s");
+    assert_eq!(velocity.ordinate_axis.spec_data_type, 11);
+    assert!(velocity
+        .data
+        .time_series
+        .iter()
+        .all(|value| (value - 2.0).abs() < 1e-9));
+    Ok(())
+}
+
+#[test]
+fn derived_specs_parse_options() -> Result<()> {
+    let spec = parse("A1=displacement:offset=mean:highpass=0.5")?;
+    assert_eq!(spec.operation, DerivedOperation::DoubleIntegrate);
+    assert_eq!(spec.offset, Some(OffsetRemoval::Mean));
+    assert_eq!(spec.high_pass, Some(0.5));
+
+    assert!("A1".parse::<DerivedChannelSpec>().is_err());
+    assert!("A1=smooth".parse::<DerivedChannelSpec>().is_err());
+    assert!("A1=velocity:offset=0"
+        .parse::<DerivedChannelSpec>()
+        .is_err());
+    assert!("A1=velocity:window=3"
+        .parse::<DerivedChannelSpec>()
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn derived_channels_are_appended_as_time_histories() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let output = NamedTempFile::new()?;
+
+    let options = ConversionOptions {
+        derived: vec![
+            parse("A1=velocity:offset=mean:highpass=100")?,
+            parse("A2=displacement")?,
+        ],
+        ..ConversionOptions::default()
+    };
+    let report = conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        output.path(),
+        OutputFormat::Ascii,
+        &options,
+        |_| {},
+    )?;
+    let names: Vec<&str> = report
+        .derived_channels
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect();
+    assert_eq!(names, ["A1_velocity", "A2_displacement"]);
+    assert_eq!(report.channel_count, 2);
+
+    let text = fs::read_to_string(output.path())?;
+    let reference = fs::read_to_string(data_dir.join("matlab_converted.uff"))?;
+    // The exported tracks are unchanged; the derived records follow them.
+    assert!(text.starts_with(&reference));
+
+    let lines: Vec<&str> = text.lines().collect();
+    let starts: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.trim_end() == "    58")
+        .map(|(index, _)| index)
+        .collect();
+    assert_eq!(starts.len(), 4);
+
+    let ordinate = |start: usize| -> Vec<&str> { lines[start + 9].split_whitespace().collect() };
+    assert_eq!(lines[starts[2] + 2].trim_end(), "Pt=A1_velocity;");
+    assert_eq!(ordinate(starts[2])[0], "11");
+    assert_eq!(ordinate(starts[2])[4..], ["A1_velocity", "m/s"]);
+    assert_eq!(ordinate(starts[3])[0], "8");
+    assert_eq!(ordinate(starts[3])[4..], ["A2_displacement", "m"]);
+    Ok(())
+}
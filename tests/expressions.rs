@@ -0,0 +1,145 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat};
+use dts_to_uff_converter::expression::{Expression, ExpressionChannel};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+fn evaluate(expression: &str, inputs: &[(&str, &[f64])]) -> Result<Vec<f64>> {
+    let expression: Expression = expression.parse().map_err(anyhow::Error::msg)?;
+    let inputs: BTreeMap<&str, &[f64]> = inputs.iter().copied().collect();
+    expression.evaluate(&inputs)
+}
+
+#[test]
+fn expressions_follow_arithmetic_precedence() -> Result<()> {
+    let x: &[f64] = &[3.0, 1.0];
+    let y: &[f64] = &[4.0, 2.0];
+    let z: &[f64] = &[12.0, 2.0];
+    let inputs = [("X", x), ("Y", y), ("Head Z", z)];
+
+    assert_eq!(evaluate("sqrt(X^2+Y^2+{Head Z}^2)", &inputs)?, [13.0, 3.0]);
+    assert_eq!(evaluate("-X^2 + 2*Y - 1e1/5", &inputs)?, [-3.0, 1.0]);
+    assert_eq!(evaluate("2^X^0.5 * (Y - X)", &inputs)?[1], 2.0);
+    assert_eq!(
+        evaluate("max(X, Y, 3.5) - min(abs(-X), Y)", &inputs)?,
+        [1.0, 2.5]
+    );
+
+    // The shortest input sets the length.
+    assert_eq!(evaluate("X + A", &[("X", x), ("A", &[1.0])])?, [4.0]);
+
+    for invalid in [
+        "sqrt(X",
+        "X +",
+        "2 * 3",
+        "foo(X)",
+        "sqrt(X, Y)",
+        "X $ Y",
+        "X Y",
+    ] {
+        assert!(invalid.parse::<Expression>().is_err(), "{invalid}");
+    }
+    Ok(())
+}
+
+#[test]
+fn expression_channels_parse_names_and_units() -> Result<()> {
+    let channel: ExpressionChannel = "HeadR[g]=sqrt(HX^2 + HY^2)"
+        .parse()
+        .map_err(anyhow::Error::msg)?;
+    assert_eq!(channel.name, "HeadR");
+    assert_eq!(channel.units.as_deref(), Some("g"));
+    assert_eq!(channel.expression.tracks(), ["HX", "HY"]);
+    assert_eq!(channel.to_string(), "HeadR[g]=sqrt(HX^2 + HY^2)");
+
+    let channel: ExpressionChannel = "Fsum = LC1 + LC2".parse().map_err(anyhow::Error::msg)?;
+    assert_eq!(channel.name, "Fsum");
+    assert_eq!(channel.units, None);
+    assert!("=A1".parse::<ExpressionChannel>().is_err());
+    Ok(())
+}
+
+fn record_values(lines: &[&str], start: usize) -> Result<Vec<f64>> {
+    let mut values = Vec::new();
+    for line in &lines[start + 12..] {
+        if line.trim_end() == "    -1" {
+            break;
+        }
+        for value in line.split_whitespace() {
+            values.push(value.parse()?);
+        }
+    }
+    Ok(values)
+}
+
+#[test]
+fn expression_channels_are_appended_after_the_tracks() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let output = NamedTempFile::new()?;
+
+    let expressions = [
+        "Resultant=sqrt(A1^2 + A2^2)",
+        "Diff=A1-A2",
+        "Scaled[m/s^2]=9.81*Diff",
+    ]
+    .iter()
+    .map(|expression| expression.parse().map_err(anyhow::Error::msg))
+    .collect::<Result<Vec<ExpressionChannel>>>()?;
+    let options = ConversionOptions {
+        expressions,
+        ..ConversionOptions::default()
+    };
+    let report = conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        output.path(),
+        OutputFormat::Ascii,
+        &options,
+        |_| {},
+    )?;
+    let units: Vec<Option<&str>> = report
+        .expression_channels
+        .iter()
+        .map(|channel| channel.units.as_deref())
+        .collect();
+    assert_eq!(units, [Some("g"), Some("g"), Some("m/s^2")]);
+
+    let text = fs::read_to_string(output.path())?;
+    let lines: Vec<&str> = text.lines().collect();
+    let starts: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.trim_end() == "    58")
+        .map(|(index, _)| index)
+        .collect();
+    assert_eq!(starts.len(), 5);
+    assert_eq!(lines[starts[2] + 2].trim_end(), "Pt=Resultant;");
+
+    let a1 = record_values(&lines, starts[0])?;
+    let a2 = record_values(&lines, starts[1])?;
+    let resultant = record_values(&lines, starts[2])?;
+    let scaled = record_values(&lines, starts[4])?;
+    assert_eq!(resultant.len(), a1.len());
+    for index in 0..a1.len() {
+        let expected = (a1[index].powi(2) + a2[index].powi(2)).sqrt();
+        assert!((resultant[index] - expected).abs() < 1e-9);
+        assert!((scaled[index] - 9.81 * (a1[index] - a2[index])).abs() < 1e-8);
+    }
+
+    let unknown = ConversionOptions {
+        expressions: vec!["X=A3*2".parse().map_err(anyhow::Error::msg)?],
+        ..ConversionOptions::default()
+    };
+    assert!(conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        output.path(),
+        OutputFormat::Ascii,
+        &unknown,
+        |_| {},
+    )
+    .is_err());
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat};
+use dts_to_uff_converter::filter::{FilterChain, FilterDesign, FilterResponse, FilterSpec};
+use std::f64::consts::PI;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+const SAMPLE_RATE: f64 = 20_000.0;
+
+fn filtered_amplitude(spec: &FilterSpec, frequency: f64) -> Result<f64> {
+    let mut samples: Vec<f64> = (0..40_000)
+        .map(|n| (2.0 * PI * frequency * n as f64 / SAMPLE_RATE).sin())
+        .collect();
+    spec.apply(&mut samples, SAMPLE_RATE)?;
+    // Ignore the edges, where the forward and backward passes settle. The window spans whole
+    // periods of every test frequency, so the RMS gives the amplitude.
+    let settled = &samples[10_000..30_000];
+    let mean_square = settled.iter().map(|value| value * value).sum::<f64>() / settled.len() as f64;
+    Ok((2.0 * mean_square).sqrt())
+}
+
+fn parse(spec: &str) -> Result<FilterSpec> {
+    spec.parse().map_err(anyhow::Error::msg)
+}
+
+#[test]
+fn filter_specs_shape_the_magnitude_response() -> Result<()> {
+    // Zero-phase filters square the magnitude: -6 dB at the corners.
+    for spec in ["lowpass:1000", "lowpass:1000:order=3:design=bessel"] {
+        let spec = parse(spec)?;
+        assert!(
+            (filtered_amplitude(&spec, 100.0)? - 1.0).abs() < 0.01,
+            "{spec}"
+        );
+        assert!(
+            (filtered_amplitude(&spec, 1000.0)? - 0.5).abs() < 0.01,
+            "{spec}"
+        );
+        assert!(filtered_amplitude(&spec, 6000.0)? < 0.01, "{spec}");
+    }
+
+    let causal = parse("lowpass:1000:causal")?;
+    assert!((filtered_amplitude(&causal, 1000.0)? - 0.707).abs() < 0.01);
+
+    let high_pass = parse("highpass:100:order=2")?;
+    assert!(filtered_amplitude(&high_pass, 10.0)? < 0.01);
+    assert!((filtered_amplitude(&high_pass, 100.0)? - 0.5).abs() < 0.01);
+    assert!((filtered_amplitude(&high_pass, 2000.0)? - 1.0).abs() < 0.01);
+
+    let band_pass = parse("bandpass:200-2000")?;
+    assert!((filtered_amplitude(&band_pass, 640.0)? - 1.0).abs() < 0.01);
+    assert!((filtered_amplitude(&band_pass, 200.0)? - 0.5).abs() < 0.01);
+    assert!((filtered_amplitude(&band_pass, 2000.0)? - 0.5).abs() < 0.01);
+
+    let band_stop = parse("bandstop:400-600:order=2")?;
+    assert!(filtered_amplitude(&band_stop, 490.0)? < 0.01);
+    assert!((filtered_amplitude(&band_stop, 100.0)? - 1.0).abs() < 0.01);
+
+    let notch = parse("notch:50:q=10")?;
+    assert!(filtered_amplitude(&notch, 50.0)? < 0.01);
+    assert!((filtered_amplitude(&notch, 500.0)? - 1.0).abs() < 0.01);
+
+    // A high-pass removes a DC offset without a start-up transient.
+    let mut offset = vec![3.0; 1000];
+    high_pass.apply(&mut offset, SAMPLE_RATE)?;
+    assert!(offset.iter().all(|value| value.abs() < 1e-9));
+
+    Ok(())
+}
+
+#[test]
+fn filter_specs_round_trip_and_validate() -> Result<()> {
+    let spec = parse("bandpass:10-2000:order=2:design=bessel:causal")?;
+    assert_eq!(
+        spec.response,
+        FilterResponse::BandPass {
+            low: 10.0,
+            high: 2000.0
+        }
+    );
+    assert_eq!(spec.design, FilterDesign::Bessel);
+    assert!(!spec.zero_phase);
+    assert_eq!(parse(&spec.to_string())?, spec);
+    assert_eq!(parse("notch:50")?.to_string(), "notch:50:q=30");
+
+    assert!("lowpass:1000:order=9".parse::<FilterSpec>().is_err());
+    assert!("bandpass:2000-10".parse::<FilterSpec>().is_err());
+    assert!("lowpass".parse::<FilterSpec>().is_err());
+    assert!("highpass:10:q=2".parse::<FilterSpec>().is_err());
+    assert!(parse("lowpass:12000")?
+        .apply(&mut [0.0; 8], SAMPLE_RATE)
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn filter_config_is_applied_during_conversion() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let output = NamedTempFile::new()?;
+    let mut config = NamedTempFile::new()?;
+    write!(
+        config,
+        r#"{{"filters": [
+            {{"type": "highpass", "cutoff": 100, "order": 2}},
+            {{"type": "lowpass", "cutoff": 5000, "design": "bessel", "tracks": ["A1"]}}
+        ]}}"#
+    )?;
+
+    let mut filters = FilterChain::default();
+    filters.extend_from_file(config.path())?;
+    assert_eq!(filters.stages().len(), 2);
+
+    let options = ConversionOptions {
+        filters,
+        write_1858: true,
+        ..ConversionOptions::default()
+    };
+    let report = conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        output.path(),
+        OutputFormat::Ascii,
+        &options,
+        |_| {},
+    )?;
+    assert_eq!(report.filters.len(), 2);
+    assert_eq!(report.filters[0].1.len(), 2);
+    assert_eq!(report.filters[1].1.len(), 1);
+
+    let text = fs::read_to_string(output.path())?;
+    assert!(text.contains("highpass:100:order=2; lowpass:5000:order=4:design=bessel"));
+
+    // The 1858 user values record the low- and high-pass corners of A1.
+    let lines: Vec<&str> = text.lines().collect();
+    let start = lines
+        .iter()
+        .position(|line| line.trim_end() == "  1858")
+        .expect("1858 block");
+    let user_values: Vec<f64> = lines[start + 4]
+        .split_whitespace()
+        .map(|value| value.replace('D', "E").parse())
+        .collect::<Result<_, _>>()?;
+    assert_eq!(user_values[1], 5000.0);
+    assert_eq!(user_values[2], 100.0);
+
+    let mut unknown = FilterChain::default();
+    assert!(unknown
+        .extend_from_json(r#"{"filters": [{"type": "lowpass", "cuttoff": 5}]}"#)
+        .is_err());
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, FrfOptions, OutputFormat};
+use dts_to_uff_converter::dts::ChannelData;
+use dts_to_uff_converter::spectral::{
+    compute_frf, Averaging, FrfEstimator, FrfReference, SpectralSettings,
+};
+use std::fs;
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+fn noise(len: usize) -> Vec<f64> {
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    (0..len)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (state >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+        })
+        .collect()
+}
+
+#[test]
+fn scaled_response_has_flat_frf_and_unit_coherence() -> Result<()> {
+    let excitation = noise(8192);
+    let reference = ChannelData {
+        time_series: excitation.clone(),
+        sample_rate: 10_000.0,
+        units: "N".to_string(),
+    };
+    let response = ChannelData {
+        time_series: excitation.iter().map(|x| -2.5 * x).collect(),
+        sample_rate: 10_000.0,
+        units: "m/s^2".to_string(),
+    };
+    let settings = SpectralSettings {
+        block_size: 512,
+        ..SpectralSettings::default()
+    };
+
+    for estimator in [FrfEstimator::H1, FrfEstimator::H2] {
+        let frf = compute_frf(&reference, &response, estimator, &settings)?;
+        assert_eq!(frf.values.len(), 257);
+        assert_eq!(frf.averages, 31);
+        for (value, coherence) in frf.values.iter().zip(&frf.coherence).skip(1) {
+            assert!((value.re + 2.5).abs() < 1e-9);
+            assert!(value.im.abs() < 1e-9);
+            assert!((coherence - 1.0).abs() < 1e-9);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn frf_records_carry_the_reference_dof() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let output = NamedTempFile::new()?;
+
+    let options = ConversionOptions {
+        spectral: SpectralSettings {
+            block_size: 256,
+            ..SpectralSettings::default()
+        },
+        frf: Some(FrfOptions {
+            reference: "A1".to_string(),
+            responses: None,
+            estimator: FrfEstimator::H1,
+            coherence: true,
+        }),
+        ..ConversionOptions::default()
+    };
+    let report = conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        output.path(),
+        OutputFormat::Ascii,
+        &options,
+        |_| {},
+    )?;
+    assert_eq!(report.frf_count, 1);
+
+    let text = fs::read_to_string(output.path())?;
+    let lines: Vec<&str> = text.lines().collect();
+    let starts: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.trim_end() == "    58")
+        .map(|(index, _)| index)
+        .collect();
+    assert_eq!(starts.len(), 4);
+
+    let record_6 = |start: usize| -> Vec<&str> { lines[start + 6].split_whitespace().collect() };
+    assert_eq!(
+        record_6(starts[0]),
+        ["1", "0", "0", "0", "A1", "0", "0", "NONE", "1", "0"]
+    );
+    assert_eq!(
+        record_6(starts[2]),
+        ["4", "0", "0", "0", "A2", "0", "0", "A1", "0", "0"]
+    );
+    assert_eq!(
+        record_6(starts[3]),
+        ["6", "0", "0", "0", "A2", "0", "0", "A1", "0", "0"]
+    );
+
+    assert!(lines[starts[2] + 7].trim_start().starts_with("6 "));
+    assert!(lines[starts[3] + 7].trim_start().starts_with("4 "));
+    assert!(lines[starts[2] + 10].contains("A1"));
+
+    Ok(())
+}
+
+#[test]
+fn frf_ignores_the_spectrum_averaging_mode() -> Result<()> {
+    let excitation = noise(8192);
+    let reference = ChannelData {
+        time_series: excitation.clone(),
+        sample_rate: 10_000.0,
+        units: "N".to_string(),
+    };
+    // A shorter response only uses the reference blocks it overlaps.
+    let response = ChannelData {
+        time_series: excitation[..6000].iter().map(|x| 4.0 * x).collect(),
+        sample_rate: 10_000.0,
+        units: "m/s^2".to_string(),
+    };
+    let linear = SpectralSettings {
+        block_size: 512,
+        ..SpectralSettings::default()
+    };
+    let expected = compute_frf(&reference, &response, FrfEstimator::H1, &linear)?;
+    assert_eq!(expected.averages, 22);
+
+    for averaging in [Averaging::PeakHold, Averaging::Vector] {
+        let settings = SpectralSettings {
+            averaging,
+            ..linear.clone()
+        };
+        let shared = FrfReference::new(&reference, &settings)?;
+        let frf = shared.frf(&response, FrfEstimator::H1)?;
+        assert_eq!(frf.values, expected.values);
+        assert_eq!(frf.coherence, expected.coherence);
+        assert_eq!(frf.settings.averaging, Averaging::Linear);
+    }
+
+    Ok(())
+}
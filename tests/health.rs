@@ -0,0 +1,106 @@
+use anyhow::Result;
+use dts_to_uff_converter::dts::DtsReader;
+use dts_to_uff_converter::health::{self, DiagnosticsKind, HealthSettings};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Byte offsets of the header levels in the sample `.chn` files, which have one trigger.
+const PRE_TEST_DIAGNOSTICS: usize = 58;
+const POST_TEST_ZERO: usize = 70;
+
+fn sample_dir() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small")
+}
+
+#[test]
+fn sample_sensors_are_healthy() -> Result<()> {
+    let reader = DtsReader::new(sample_dir())?;
+    let report = health::sensor_health(&reader, &[], &HealthSettings::default())?;
+
+    assert_eq!(report.len(), 2);
+    for sensor in &report {
+        assert!(sensor.is_healthy(), "{:?}", sensor.issues);
+        assert_eq!(sensor.zero_shift, 0.0);
+        assert_eq!(sensor.track_name, "IEPE 100 mV/g");
+        assert!(sensor.diagnostics.is_none());
+        // Three ADC counts of a 16-bit recorder.
+        assert!((sensor.noise_percent * 327.68 - 3.0).abs() < 1e-9);
+    }
+    Ok(())
+}
+
+#[test]
+fn reads_sample_header_levels() -> Result<()> {
+    let reader = DtsReader::new(sample_dir())?;
+    // The removed ADC offset (40 and 42 counts) precedes the diagnostics level in the header.
+    for (index, zero) in [(0, 144), (1, 176)] {
+        let levels = reader.channel_levels(index).expect("levels");
+        assert_eq!(levels.pre_test_zero_adc, zero);
+        assert_eq!(levels.pre_test_diagnostics_adc, 0);
+        assert_eq!(levels.post_test_zero_adc, zero);
+        assert_eq!(levels.post_test_diagnostics_adc, 0);
+        assert_eq!(levels.bits_per_sample, 16);
+    }
+    Ok(())
+}
+
+#[test]
+fn reports_zero_shift_and_diagnostics_deviation() -> Result<()> {
+    let dir = TempDir::new()?;
+    for entry in fs::read_dir(sample_dir())? {
+        let path = entry?.path();
+        fs::copy(&path, dir.path().join(path.file_name().expect("file name")))?;
+    }
+
+    // Enable a 15 mV shunt check on the first channel.
+    let dts_path = dir.path().join("Bancairon_G1_training6.dts");
+    let dts = fs::read_to_string(&dts_path)?.replacen(
+        "ShuntEnabled=\"False\"",
+        "ShuntEnabled=\"True\" TargetShuntDeflectionMv=\"15\"",
+        1,
+    );
+    let dts = dts.replacen("TargetShuntDeflectionMv=\"0\"", "", 1);
+    fs::write(&dts_path, dts)?;
+
+    // Pre-test zero is 144 counts at 0.3745 mV per count: 40 counts is 14.98 mV. The post-test
+    // zero moves by 500 counts (1.53% FS) and the post-test shunt level reads 30 counts.
+    let chn_path = dir.path().join("Bancairon_G1_training6.0.chn");
+    let mut chn = fs::read(&chn_path)?;
+    chn[PRE_TEST_DIAGNOSTICS..PRE_TEST_DIAGNOSTICS + 4].copy_from_slice(&184i32.to_le_bytes());
+    chn[POST_TEST_ZERO..POST_TEST_ZERO + 4].copy_from_slice(&644i32.to_le_bytes());
+    chn[POST_TEST_ZERO + 4..POST_TEST_ZERO + 8].copy_from_slice(&674i32.to_le_bytes());
+    fs::write(&chn_path, chn)?;
+
+    let reader = DtsReader::new(dir.path())?;
+    let names = vec!["A1".to_string(), "A2".to_string()];
+    let report = health::sensor_health(&reader, &names, &HealthSettings::default())?;
+
+    let a1 = &report[0];
+    assert_eq!(a1.track_name, "A1");
+    assert!((a1.zero_shift_percent - 500.0 / 327.68).abs() < 1e-9);
+    let levels = reader.channel_levels(0).expect("levels");
+    assert!((a1.zero_shift - 500.0 * levels.eu_per_count).abs() < 1e-12);
+
+    let check = a1.diagnostics.as_ref().expect("shunt check");
+    assert_eq!(check.kind, DiagnosticsKind::Shunt);
+    assert!((check.pre_test_mv - 40.0 * levels.mv_per_count).abs() < 1e-12);
+    let post_test_mv = check.post_test_mv.expect("post-test level");
+    assert!((post_test_mv - 30.0 * levels.mv_per_count).abs() < 1e-12);
+    assert!(!check.passed);
+    assert_eq!(a1.issues.len(), 2);
+    assert!(a1.issues[0].starts_with("zero shifted by 1.526% FS"));
+    assert!(report[1].is_healthy());
+
+    let relaxed = HealthSettings {
+        zero_shift_tolerance: 2.0,
+        diagnostics_tolerance: 30.0,
+    };
+    let report = health::sensor_health(&reader, &names, &relaxed)?;
+    assert!(report.iter().all(|sensor| sensor.is_healthy()));
+
+    let mut table = Vec::new();
+    health::write_table(&mut table, &report)?;
+    assert_eq!(String::from_utf8(table)?.lines().count(), 3);
+    Ok(())
+}
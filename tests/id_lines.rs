@@ -0,0 +1,110 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat};
+use dts_to_uff_converter::dts;
+use dts_to_uff_converter::id_template::{IdContext, IdLineOverride, IdPreset};
+use std::fs;
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+#[test]
+fn matlab_preset_keeps_reference_output() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let output = NamedTempFile::new()?;
+
+    let options = ConversionOptions {
+        id_template: IdPreset::Matlab.template([]),
+        ..ConversionOptions::default()
+    };
+    conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        output.path(),
+        OutputFormat::Ascii,
+        &options,
+        |_| {},
+    )?;
+
+    let expected = fs::read(data_dir.join("matlab_converted.uff"))?;
+    assert_eq!(fs::read(output.path())?, expected);
+
+    Ok(())
+}
+
+#[test]
+fn metadata_preset_fills_id_records() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let reader = dts::DtsReader::new(&data_dir)?;
+    let data = reader.read_track(0)?;
+
+    let template = IdPreset::Metadata
+        .template([])
+        .expect("metadata preset always yields a template");
+    let ids = template.render(&IdContext {
+        track_name: "A1",
+        test: reader.test_info(),
+        channel: reader.channel_info(0),
+        data: &data,
+        filter: None,
+    });
+
+    assert_eq!(ids.lines[0], "Bancairon_G1_training6");
+    assert_eq!(ids.lines[1], "Pt=A1; IEPE 100 mV/g");
+    assert_eq!(ids.lines[2], "");
+    assert_eq!(ids.lines[3], "SN:PCB_B34_xx 98.5176059 mV/g");
+    // The sensor is self-generating, so the excitation clause is left out.
+    assert_eq!(ids.lines[4], "200000 Hz");
+
+    let mut channel = reader.channel_info(0).expect("track 0 exists").clone();
+    channel.proportional_to_excitation = true;
+    let ids = template.render(&IdContext {
+        track_name: "A1",
+        test: reader.test_info(),
+        channel: Some(&channel),
+        data: &data,
+        filter: None,
+    });
+    assert_eq!(ids.lines[4], "Excitation 25.096 V, 200000 Hz");
+
+    Ok(())
+}
+
+#[test]
+fn unknown_placeholders_are_rejected() {
+    assert!("1={test}".parse::<IdLineOverride>().is_ok());
+    assert!("6={test}".parse::<IdLineOverride>().is_err());
+    assert!("2={serial_no}".parse::<IdLineOverride>().is_err());
+    assert!("3={test".parse::<IdLineOverride>().is_err());
+}
+
+#[test]
+fn excitation_matches_the_scaling_excitation() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let reader = dts::DtsReader::new(&data_dir)?;
+    let data = reader.read_track(1)?;
+
+    let line_override: IdLineOverride = "5={excitation}".parse().map_err(anyhow::Error::msg)?;
+    let template = IdPreset::Metadata
+        .template([line_override])
+        .expect("metadata preset always yields a template");
+    let mut channel = reader.channel_info(1).expect("track 1 exists").clone();
+    let render = |channel: &dts::AnalogInputChannel| {
+        template.render(&IdContext {
+            track_name: "A2",
+            test: reader.test_info(),
+            channel: Some(channel),
+            data: &data,
+            filter: None,
+        })
+    };
+
+    // Self-generating sensors are scaled without excitation.
+    assert!(!channel.proportional_to_excitation);
+    assert_eq!(render(&channel).lines[4], "");
+
+    // The factory excitation takes precedence over the measured one, as in the ADC scaling.
+    channel.proportional_to_excitation = true;
+    assert_eq!(channel.factory_excitation_voltage, 2.5);
+    assert_eq!(render(&channel).lines[4], "2.500");
+
+    Ok(())
+}
@@ -0,0 +1,222 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, InjuryOptions, OutputFormat};
+use dts_to_uff_converter::dts::ChannelData;
+use dts_to_uff_converter::filter::{CfcClass, CfcFiltering};
+use dts_to_uff_converter::injury::{
+    compute_criteria, ChannelRole, NijIntercepts, RoleAssignment, RoleChannel,
+};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::NamedTempFile;
+
+const SAMPLE_RATE: f64 = 10_000.0;
+
+fn channel(time_series: Vec<f64>, units: &str) -> ChannelData {
+    ChannelData {
+        time_series,
+        sample_rate: SAMPLE_RATE,
+        units: units.to_string(),
+    }
+}
+
+/// `level` between 20 ms and 30 ms of a 100 ms record.
+fn square_pulse(level: f64) -> Vec<f64> {
+    (0..1000)
+        .map(|n| if (200..=300).contains(&n) { level } else { 0.0 })
+        .collect()
+}
+
+#[test]
+fn criteria_of_square_pulses() -> Result<()> {
+    let head_x = channel(square_pulse(48.0), "g");
+    let head_y = channel(square_pulse(36.0 * 9.80665), "m/s^2");
+    let deflection = channel((0..1000).map(|n| -(n as f64 * 0.05)).collect(), "mm");
+    let neck_fz = channel(square_pulse(3.403), "kN");
+    let neck_my = channel(square_pulse(-67.5), "Nm");
+
+    let role = |track_name, data| RoleChannel {
+        track_name,
+        data,
+        start_time: -0.01,
+    };
+    let channels = BTreeMap::from([
+        (ChannelRole::HeadX, role("HX", &head_x)),
+        (ChannelRole::HeadY, role("HY", &head_y)),
+        (ChannelRole::ChestDeflection, role("D", &deflection)),
+        (ChannelRole::NeckFz, role("FZ", &neck_fz)),
+        (ChannelRole::NeckMy, role("MY", &neck_my)),
+    ]);
+    let report = compute_criteria(&channels, &NijIntercepts::default())?;
+
+    // A 60 g resultant for 10 ms: HIC = 0.01 * 60^2.5, over the pulse itself.
+    let hic15 = report.get("HIC15").expect("HIC15");
+    assert!((hic15.value - 0.01 * 60f64.powf(2.5)).abs() < 1e-6);
+    assert!((hic15.window_start - 0.01).abs() < 1e-9);
+    assert!((hic15.window_end - 0.02).abs() < 1e-9);
+    assert_eq!(hic15.tracks, ["HX", "HY"]);
+    assert_eq!(report.get("HIC36").expect("HIC36").value, hic15.value);
+
+    let clip = report
+        .get("Head 3ms clip (continuous)")
+        .expect("continuous head 3 ms clip");
+    assert!((clip.value - 60.0).abs() < 1e-9);
+    assert!((clip.window_end - clip.window_start - 0.003).abs() < 1e-9);
+    let clip = report
+        .get("Head 3ms clip (cumulative)")
+        .expect("cumulative head 3 ms clip");
+    assert!((clip.value - 60.0).abs() < 1e-9);
+    assert!((clip.window_start - 0.01).abs() < 1e-9);
+    assert!((clip.window_end - 0.02).abs() < 1e-9);
+    assert!(report.get("Chest 3ms clip (cumulative)").is_none());
+
+    let deflection = report.get("Chest deflection").expect("chest deflection");
+    assert!((deflection.value - 49.95).abs() < 1e-9);
+    assert_eq!(deflection.units, "mm");
+
+    // Half the tension and half the extension intercept.
+    let nte = report.get("Nij NTE").expect("NTE");
+    assert!((nte.value - 1.0).abs() < 1e-9);
+    assert!((nte.window_start - 0.01).abs() < 1e-9);
+    assert_eq!(report.get("Nij NTF").expect("NTF").value, 0.0);
+    assert_eq!(report.get("Nij").expect("Nij").value, nte.value);
+    Ok(())
+}
+
+#[test]
+fn cumulative_three_ms_clip_adds_separate_exceedances() -> Result<()> {
+    // 2 ms at 80 g, then 2 ms at 70 g: 70 g is exceeded for a cumulative 4 ms but never for a
+    // continuous 3 ms.
+    let chest_x = channel(
+        (0..1000)
+            .map(|n| match n {
+                100..=119 => 80.0,
+                300..=319 => 70.0,
+                _ => 5.0,
+            })
+            .collect(),
+        "g",
+    );
+    let channels = BTreeMap::from([(
+        ChannelRole::ChestX,
+        RoleChannel {
+            track_name: "CX",
+            data: &chest_x,
+            start_time: 0.0,
+        },
+    )]);
+    let report = compute_criteria(&channels, &NijIntercepts::default())?;
+
+    let cumulative = report
+        .get("Chest 3ms clip (cumulative)")
+        .expect("cumulative chest 3 ms clip");
+    assert_eq!(cumulative.value, 70.0);
+    assert!((cumulative.window_start - 0.01).abs() < 1e-9);
+    assert!((cumulative.window_end - 0.0319).abs() < 1e-9);
+    let continuous = report
+        .get("Chest 3ms clip (continuous)")
+        .expect("continuous chest 3 ms clip");
+    assert_eq!(continuous.value, 5.0);
+    Ok(())
+}
+
+#[test]
+fn roles_and_intercepts_parse() -> Result<()> {
+    let assignment: RoleAssignment = "neck_fz=F1".parse().map_err(anyhow::Error::msg)?;
+    assert_eq!(assignment.role, ChannelRole::NeckFz);
+    assert_eq!(assignment.track_name, "F1");
+    assert!("pelvis-x=A1".parse::<RoleAssignment>().is_err());
+
+    let intercepts: NijIntercepts = "4287,3880,155,67".parse().map_err(anyhow::Error::msg)?;
+    assert_eq!(intercepts.extension, 67.0);
+    assert!("1,2,3".parse::<NijIntercepts>().is_err());
+    Ok(())
+}
+
+#[test]
+fn conversion_writes_injury_reports() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let output = NamedTempFile::new()?;
+    let json = NamedTempFile::new()?;
+    let csv = NamedTempFile::new()?;
+
+    let mut cfc = CfcFiltering::default();
+    cfc.set_channel("A1", Some(CfcClass::Cfc1000));
+    let options = ConversionOptions {
+        cfc,
+        injury: Some(InjuryOptions {
+            roles: vec![
+                "head-x=A1".parse().map_err(anyhow::Error::msg)?,
+                "head-y=A2".parse().map_err(anyhow::Error::msg)?,
+            ],
+            json_output: Some(json.path().to_path_buf()),
+            csv_output: Some(csv.path().to_path_buf()),
+            ..InjuryOptions::default()
+        }),
+        ..ConversionOptions::default()
+    };
+    let report = conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        output.path(),
+        OutputFormat::Ascii,
+        &options,
+        |_| {},
+    )?;
+    let injury = report.injury.expect("injury report");
+    let names: Vec<&str> = injury
+        .criteria
+        .iter()
+        .map(|result| result.criterion.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "HIC15",
+            "HIC36",
+            "Head 3ms clip (cumulative)",
+            "Head 3ms clip (continuous)"
+        ]
+    );
+    assert!(report
+        .warnings
+        .iter()
+        .any(|warning| warning.contains("'A2' (head-y) is not CFC filtered")));
+
+    let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(json.path())?)?;
+    assert_eq!(json["criteria"][0]["criterion"], "HIC15");
+    assert_eq!(json["criteria"][0]["tracks"][1], "A2");
+
+    let csv = fs::read_to_string(csv.path())?;
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "criterion,value,units,window_start_s,window_end_s,tracks"
+    );
+    assert!(lines[3].starts_with("Head 3ms clip (cumulative),"));
+    assert!(lines[3].contains(",g,"));
+    Ok(())
+}
+
+#[test]
+fn cli_prints_the_missing_cfc_warning() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let output = NamedTempFile::new()?;
+
+    let run = Command::new(env!("CARGO_BIN_EXE_dts_to_uff_converter"))
+        .arg("--input-dir")
+        .arg(&data_dir)
+        .arg("--tracks")
+        .arg(data_dir.join("tracks.txt"))
+        .arg("--output")
+        .arg(output.path())
+        .args(["--injury-role", "head-x=A1"])
+        .output()?;
+    assert!(run.status.success());
+    let stdout = String::from_utf8(run.stdout)?;
+    assert!(stdout
+        .lines()
+        .any(|line| line.starts_with("Warning: Track 'A1' (head-x) is not CFC filtered")));
+    Ok(())
+}
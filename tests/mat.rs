@@ -0,0 +1,225 @@
+use anyhow::{anyhow, Result};
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat, SampleSlice};
+use dts_to_uff_converter::dts::DtsReader;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// The subset of MAT v5 arrays written by the converter.
+#[derive(Debug)]
+enum MatValue {
+    Double(Vec<usize>, Vec<f64>),
+    Char(String),
+    Cell(Vec<usize>, Vec<MatValue>),
+    Struct(Vec<(String, MatValue)>),
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+/// Returns the type, data and length of the data element at the start of `bytes`.
+fn read_element(bytes: &[u8]) -> (u32, &[u8], usize) {
+    let first = u32_at(bytes, 0);
+    if first >> 16 != 0 {
+        let size = (first >> 16) as usize;
+        return (first & 0xffff, &bytes[4..4 + size], 8);
+    }
+    let size = u32_at(bytes, 4) as usize;
+    let padded = (8 + size).next_multiple_of(8);
+    (first, &bytes[8..8 + size], padded)
+}
+
+fn read_matrix(bytes: &[u8]) -> Result<(String, MatValue, usize)> {
+    let (data_type, content, length) = read_element(bytes);
+    assert_eq!(data_type, 14, "expected miMATRIX");
+    let (_, flags, mut offset) = read_element(content);
+    let class = flags[0];
+    let (_, dims, size) = read_element(&content[offset..]);
+    offset += size;
+    let dims: Vec<usize> = dims.chunks(4).map(|dim| u32_at(dim, 0) as usize).collect();
+    let (_, name, size) = read_element(&content[offset..]);
+    offset += size;
+    let name = String::from_utf8(name.to_vec())?;
+    let count = dims.iter().product::<usize>();
+
+    let value = match class {
+        6 => {
+            let (_, real, _) = read_element(&content[offset..]);
+            let values = real
+                .chunks(8)
+                .map(|value| f64::from_le_bytes(value.try_into().unwrap()))
+                .collect();
+            MatValue::Double(dims, values)
+        }
+        4 => {
+            let (_, text, _) = read_element(&content[offset..]);
+            let units: Vec<u16> = text
+                .chunks(2)
+                .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                .collect();
+            MatValue::Char(String::from_utf16(&units)?)
+        }
+        1 => {
+            let mut cells = Vec::with_capacity(count);
+            for _ in 0..count {
+                let (_, cell, size) = read_matrix(&content[offset..])?;
+                offset += size;
+                cells.push(cell);
+            }
+            MatValue::Cell(dims, cells)
+        }
+        2 => {
+            let (_, name_length, size) = read_element(&content[offset..]);
+            offset += size;
+            let name_length = u32_at(name_length, 0) as usize;
+            let (_, names, size) = read_element(&content[offset..]);
+            offset += size;
+            let mut fields = Vec::new();
+            for name in names.chunks(name_length) {
+                let name = String::from_utf8(name.to_vec())?
+                    .trim_end_matches('\0')
+                    .to_string();
+                let (_, field, size) = read_matrix(&content[offset..])?;
+                offset += size;
+                fields.push((name, field));
+            }
+            MatValue::Struct(fields)
+        }
+        other => return Err(anyhow!("unexpected array class {other}")),
+    };
+    Ok((name, value, length))
+}
+
+fn field<'a>(fields: &'a [(String, MatValue)], name: &str) -> &'a MatValue {
+    &fields
+        .iter()
+        .find(|(field, _)| field == name)
+        .unwrap_or_else(|| panic!("missing field {name}"))
+        .1
+}
+
+#[test]
+fn converts_sample_to_dts_read_struct() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let temp = TempDir::new()?;
+    let output = temp.path().join("test.mat");
+    let options = ConversionOptions {
+        expressions: vec!["S=A1+A2".parse().map_err(anyhow::Error::msg)?],
+        ..ConversionOptions::default()
+    };
+    conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        &output,
+        OutputFormat::Mat,
+        &options,
+        |_| {},
+    )?;
+
+    let bytes = std::fs::read(&output)?;
+    assert!(bytes.starts_with(b"MATLAB 5.0 MAT-file"));
+    assert_eq!(&bytes[124..128], &[0x00, 0x01, b'I', b'M']);
+    let (name, value, length) = read_matrix(&bytes[128..])?;
+    assert_eq!(name, "dts");
+    assert_eq!(128 + length, bytes.len());
+    let MatValue::Struct(fields) = value else {
+        panic!("expected a struct");
+    };
+    let names: Vec<&str> = fields.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "data",
+            "sampleRate",
+            "pn",
+            "channelInfoMetadata",
+            "timeOfFirstSamples",
+            "trackNames"
+        ]
+    );
+
+    let reader = DtsReader::new(&data_dir)?;
+    let a1 = reader.read_track(0)?;
+    let a2 = reader.read_track(1)?;
+    let MatValue::Double(dims, data) = field(&fields, "data") else {
+        panic!("expected a double matrix");
+    };
+    assert_eq!(dims, &[2000, 3]);
+    assert_eq!(&data[..2000], a1.time_series.as_slice());
+    assert_eq!(&data[2000..4000], a2.time_series.as_slice());
+    assert_eq!(data[4000 + 7], a1.time_series[7] + a2.time_series[7]);
+
+    let MatValue::Double(_, rate) = field(&fields, "sampleRate") else {
+        panic!("expected a scalar");
+    };
+    assert_eq!(rate, &[200_000.0]);
+    let MatValue::Char(pn) = field(&fields, "pn") else {
+        panic!("expected a char array");
+    };
+    assert_eq!(pn, &data_dir.display().to_string());
+
+    let MatValue::Cell(dims, metadata) = field(&fields, "channelInfoMetadata") else {
+        panic!("expected a cell array");
+    };
+    assert_eq!(dims, &[5, 3]);
+    let info = reader.channel_info(0).expect("channel metadata");
+    assert!(matches!(&metadata[0], MatValue::Char(serial) if *serial == info.serial_number));
+    assert!(matches!(&metadata[2], MatValue::Char(eu) if eu == "g"));
+    assert!(matches!(&metadata[4], MatValue::Double(dims, _) if dims == &[0, 0]));
+
+    // `DTS.read`: (StartRecordSampleNumber - trigger sample number) / sample rate. The sample
+    // starts recording at 0 and triggers at sample 93059356 of the .chn header.
+    let trigger_time = (0.0 - 93_059_356.0) / 200_000.0;
+    assert_eq!(reader.time_of_first_sample(0), Some(trigger_time));
+    let MatValue::Double(dims, times) = field(&fields, "timeOfFirstSamples") else {
+        panic!("expected a double row vector");
+    };
+    assert_eq!(dims, &[1, 3]);
+    assert_eq!(times, &[trigger_time; 3]);
+
+    let MatValue::Cell(_, names) = field(&fields, "trackNames") else {
+        panic!("expected a cell array");
+    };
+    let names: Vec<&str> = names
+        .iter()
+        .map(|name| match name {
+            MatValue::Char(name) => name.as_str(),
+            _ => panic!("expected a char array"),
+        })
+        .collect();
+    assert_eq!(names, ["A1", "A2", "S"]);
+    Ok(())
+}
+
+#[test]
+fn time_of_first_sample_follows_the_slice() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let temp = TempDir::new()?;
+    let output = temp.path().join("slice.mat");
+    let options = ConversionOptions {
+        slice: Some(SampleSlice {
+            start: 100,
+            end: 600,
+        }),
+        ..ConversionOptions::default()
+    };
+    conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        &output,
+        OutputFormat::Mat,
+        &options,
+        |_| {},
+    )?;
+
+    let bytes = std::fs::read(&output)?;
+    let (_, MatValue::Struct(fields), _) = read_matrix(&bytes[128..])? else {
+        panic!("expected a struct");
+    };
+    let MatValue::Double(_, times) = field(&fields, "timeOfFirstSamples") else {
+        panic!("expected a double row vector");
+    };
+    let expected = (100.0 - 93_059_356.0) / 200_000.0;
+    assert!(times.iter().all(|time| (time - expected).abs() < 1e-9));
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat};
+use dts_to_uff_converter::dts::DtsReader;
+use dts_to_uff_converter::filter::{CfcClass, CfcFiltering};
+use std::path::Path;
+use tempfile::TempDir;
+
+/// An MDF block with its identifier, links and data section.
+struct Block<'a> {
+    id: &'a [u8],
+    links: Vec<usize>,
+    data: &'a [u8],
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+fn f64_at(bytes: &[u8], offset: usize) -> f64 {
+    f64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+fn block(bytes: &[u8], address: usize) -> Block<'_> {
+    assert_eq!(address % 8, 0, "aligned block");
+    let length = u64_at(bytes, address + 8) as usize;
+    let link_count = u64_at(bytes, address + 16) as usize;
+    let links = (0..link_count)
+        .map(|link| u64_at(bytes, address + 24 + link * 8) as usize)
+        .collect();
+    Block {
+        id: &bytes[address..address + 4],
+        links,
+        data: &bytes[address + 24 + link_count * 8..address + length],
+    }
+}
+
+fn text(bytes: &[u8], address: usize) -> String {
+    let block = block(bytes, address);
+    assert_eq!(block.id, b"##TX");
+    let end = block.data.iter().position(|&byte| byte == 0).unwrap();
+    String::from_utf8(block.data[..end].to_vec()).unwrap()
+}
+
+#[test]
+fn converts_sample_to_mdf4_with_lossless_adc_counts() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let temp = TempDir::new()?;
+    let output = temp.path().join("test.mf4");
+    let mut cfc = CfcFiltering::default();
+    cfc.set_channel("A2", Some(CfcClass::Cfc60));
+    let options = ConversionOptions {
+        cfc,
+        expressions: vec!["S=A1+A2".parse().map_err(anyhow::Error::msg)?],
+        ..ConversionOptions::default()
+    };
+    let report = conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        &output,
+        OutputFormat::Mdf,
+        &options,
+        |_| {},
+    )?;
+
+    let bytes = std::fs::read(&output)?;
+    assert_eq!(&bytes[..16], b"MDF     4.10    ");
+    let header = block(&bytes, 64);
+    assert_eq!(header.id, b"##HD");
+    let data_group = block(&bytes, header.links[0]);
+    assert_eq!(data_group.id, b"##DG");
+    assert_eq!(data_group.links[0], 0, "a single time base");
+    let channel_group = block(&bytes, data_group.links[1]);
+    assert_eq!(channel_group.id, b"##CG");
+    let records = u64_at(channel_group.data, 8) as usize;
+    let record_size = u32::from_le_bytes(channel_group.data[24..28].try_into()?) as usize;
+    assert_eq!(records, 2000);
+    // A1 as ADC counts, A2 (filtered) and S as floats.
+    assert_eq!(record_size, 2 + 8 + 8);
+    let records_data = &block(&bytes, data_group.links[2]).data;
+    assert_eq!(records_data.len(), records * record_size);
+
+    let reader = DtsReader::new(&data_dir)?;
+    let a1 = reader.read_track(0)?;
+    let a2 = &report.statistics[1];
+    let mut address = channel_group.links[1];
+    let mut names = Vec::new();
+    while address != 0 {
+        let channel = block(&bytes, address);
+        assert_eq!(channel.id, b"##CN");
+        let name = text(&bytes, channel.links[2]);
+        let (channel_type, data_type) = (channel.data[0], channel.data[2]);
+        let byte_offset = u32::from_le_bytes(channel.data[4..8].try_into()?) as usize;
+        // Offset and factor of a linear conversion.
+        let linear = || {
+            let conversion = block(&bytes, channel.links[4]);
+            assert_eq!(conversion.id, b"##CC");
+            assert_eq!(conversion.data[0], 1, "linear conversion");
+            (f64_at(conversion.data, 24), f64_at(conversion.data, 32))
+        };
+        match name.as_str() {
+            "time" => {
+                assert_eq!(channel_type, 3, "virtual master");
+                assert_eq!(linear(), (0.0, 1.0 / 200_000.0));
+                assert_eq!(text(&bytes, channel.links[6]), "s");
+            }
+            "A1" => {
+                assert_eq!(data_type, 2, "signed integer");
+                let (offset, factor) = linear();
+                for (index, expected) in a1.time_series.iter().enumerate() {
+                    let at = index * record_size + byte_offset;
+                    let counts = i16::from_le_bytes([records_data[at], records_data[at + 1]]);
+                    assert_eq!(factor * f64::from(counts) + offset, *expected);
+                }
+                assert_eq!(text(&bytes, channel.links[6]), "g");
+            }
+            "A2" => {
+                assert_eq!(data_type, 4, "float");
+                assert_eq!(channel.links[4], 0, "no conversion");
+                let max = (0..records)
+                    .map(|index| f64_at(records_data, index * record_size + byte_offset))
+                    .fold(f64::NEG_INFINITY, f64::max);
+                assert_eq!(max, a2.max);
+            }
+            _ => {}
+        }
+        names.push(name);
+        address = channel.links[0];
+    }
+    assert_eq!(names, ["time", "A1", "A2", "S"]);
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat};
+use dts_to_uff_converter::filter::{CfcClass, CfcFiltering};
+use dts_to_uff_converter::mme::{MmeChannelCode, MmeChannelCodes};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn header_value<'a>(text: &'a str, keyword: &str) -> Option<&'a str> {
+    text.lines()
+        .find(|line| line.get(..28).map(str::trim_end) == Some(keyword))
+        .map(|line| line[29..].trim_end())
+}
+
+#[test]
+fn channel_codes_are_validated() {
+    let code: MmeChannelCode = "A1=11head0000h3acx?".parse().expect("valid code");
+    assert_eq!(code.track_name, "A1");
+    assert_eq!(code.code, "11HEAD0000H3ACX?");
+
+    assert!("A1=11HEAD0000H3ACX".parse::<MmeChannelCode>().is_err());
+    assert!("A1=11HEAD?000H3ACXA".parse::<MmeChannelCode>().is_err());
+    assert!("11HEAD0000H3ACXA".parse::<MmeChannelCode>().is_err());
+}
+
+#[test]
+fn converts_sample_to_mme_directory() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let temp = TempDir::new()?;
+    let output = temp.path().join("T001");
+
+    let mut cfc = CfcFiltering::default();
+    cfc.set_channel("A1", Some(CfcClass::Cfc180));
+    let mut mme_codes = MmeChannelCodes::default();
+    mme_codes.insert("A1=11HEAD0000H3ACX?".parse().map_err(anyhow::Error::msg)?);
+    let options = ConversionOptions {
+        cfc,
+        mme_codes,
+        ..ConversionOptions::default()
+    };
+    let report = conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        &output,
+        OutputFormat::Mme,
+        &options,
+        |_| {},
+    )?;
+    assert_eq!(report.processed_track_names, ["A1", "A2"]);
+    assert!(report
+        .warnings
+        .iter()
+        .any(|warning| warning.contains("'A2' has no MME channel code")));
+
+    let description = fs::read_to_string(output.join("T001.mme"))?;
+    assert_eq!(
+        header_value(&description, "Data format edition number"),
+        Some("1.6")
+    );
+    assert!(description.contains("\r\n"));
+
+    let channel_list = fs::read_to_string(output.join("Channel").join("T001.chn"))?;
+    assert_eq!(header_value(&channel_list, "Number of channels"), Some("2"));
+    assert_eq!(
+        header_value(&channel_list, "Name of channel 001"),
+        Some("11HEAD0000H3ACXC / A1")
+    );
+
+    let channel = fs::read_to_string(output.join("Channel").join("T001.001"))?;
+    assert_eq!(
+        header_value(&channel, "Channel code"),
+        Some("11HEAD0000H3ACXC")
+    );
+    assert_eq!(header_value(&channel, "Dimension"), Some("AC"));
+    assert_eq!(header_value(&channel, "Direction"), Some("X"));
+    assert_eq!(
+        header_value(&channel, "Channel frequency class"),
+        Some("180")
+    );
+    assert_eq!(header_value(&channel, "Unit"), Some("g"));
+    assert_eq!(header_value(&channel, "Number of samples"), Some("2000"));
+    assert_eq!(header_value(&channel, "Transducer id"), Some("PCB_B34_xx"));
+    let comments: Vec<&str> = channel
+        .lines()
+        .filter(|line| line.starts_with("Comments "))
+        .map(|line| line[29..].trim_end())
+        .collect();
+    assert_eq!(comments, ["IEPE 100 mV/g", "Sensitivity 98.5176059 mV/g"]);
+    let interval: f64 = header_value(&channel, "Sampling interval")
+        .unwrap_or_default()
+        .parse()?;
+    assert!((interval - 5e-6).abs() < 1e-15);
+    // Like `DTS.read`, the first sample is timed from the trigger at sample 93059356.
+    let first_sample: f64 = header_value(&channel, "Time of first sample")
+        .unwrap_or_default()
+        .parse()?;
+    assert!((first_sample - -93_059_356.0 / 200_000.0).abs() < 1e-9);
+
+    let values = channel
+        .lines()
+        .filter(|line| line.get(28..29) != Some(":"))
+        .map(str::parse::<f64>)
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(values.len(), 2000);
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    assert_eq!(mean, report.statistics[0].mean);
+
+    let unmapped = fs::read_to_string(output.join("Channel").join("T001.002"))?;
+    assert_eq!(header_value(&unmapped, "Channel code"), Some("NOVALUE"));
+    assert_eq!(
+        header_value(&unmapped, "Channel frequency class"),
+        Some("NOVALUE")
+    );
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat};
+use dts_to_uff_converter::dts::DtsReader;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn u16_at(bytes: &[u8], offset: usize) -> usize {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]]) as usize
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> usize {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize
+}
+
+/// Returns the stored entries of a ZIP archive, keyed by name, from its central directory.
+fn entries(bytes: &[u8]) -> BTreeMap<String, &[u8]> {
+    let end = bytes.len() - 22;
+    assert_eq!(u32_at(bytes, end), 0x0605_4b50, "end of central directory");
+    let count = u16_at(bytes, end + 10);
+    let mut offset = u32_at(bytes, end + 16);
+    let mut entries = BTreeMap::new();
+    for _ in 0..count {
+        assert_eq!(u32_at(bytes, offset), 0x0201_4b50);
+        assert_eq!(u16_at(bytes, offset + 10), 0, "stored entry");
+        let size = u32_at(bytes, offset + 20);
+        let name_length = u16_at(bytes, offset + 28);
+        let extra_length = u16_at(bytes, offset + 30);
+        let local = u32_at(bytes, offset + 42);
+        let name =
+            String::from_utf8(bytes[offset + 46..offset + 46 + name_length].to_vec()).unwrap();
+
+        let data = local + 30 + u16_at(bytes, local + 26) + u16_at(bytes, local + 28);
+        entries.insert(name, &bytes[data..data + size]);
+        offset += 46 + name_length + extra_length;
+    }
+    entries
+}
+
+/// Returns the header dictionary and data of a `.npy` array.
+fn npy(bytes: &[u8]) -> (&str, &[u8]) {
+    assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+    let length = u16_at(bytes, 8);
+    assert_eq!((10 + length) % 64, 0, "aligned data");
+    let header = std::str::from_utf8(&bytes[10..10 + length]).unwrap();
+    (header.trim_end(), &bytes[10 + length..])
+}
+
+fn doubles(data: &[u8]) -> Vec<f64> {
+    data.chunks(8)
+        .map(|value| f64::from_le_bytes(value.try_into().unwrap()))
+        .collect()
+}
+
+#[test]
+fn converts_sample_to_npz_archive() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let temp = TempDir::new()?;
+    let output = temp.path().join("test.npz");
+    let options = ConversionOptions {
+        derived: vec!["A1=integrate".parse().map_err(anyhow::Error::msg)?],
+        ..ConversionOptions::default()
+    };
+    conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        &output,
+        OutputFormat::Npz,
+        &options,
+        |_| {},
+    )?;
+
+    let bytes = std::fs::read(&output)?;
+    let entries = entries(&bytes);
+    let names: Vec<&str> = entries.keys().map(String::as_str).collect();
+    assert_eq!(
+        names,
+        [
+            "A1.npy",
+            "A1_velocity.npy",
+            "A2.npy",
+            "sample_rates.npy",
+            "start_times.npy",
+            "track_names.npy",
+            "units.npy"
+        ]
+    );
+
+    let reader = DtsReader::new(&data_dir)?;
+    let (header, data) = npy(entries["A2.npy"]);
+    assert_eq!(
+        header,
+        "{'descr': '<f8', 'fortran_order': False, 'shape': (2000,), }"
+    );
+    assert_eq!(doubles(data), reader.read_track(1)?.time_series);
+
+    let (header, data) = npy(entries["sample_rates.npy"]);
+    assert!(header.contains("'shape': (3,)"));
+    assert_eq!(doubles(data), [200_000.0; 3]);
+
+    let (header, data) = npy(entries["units.npy"]);
+    assert!(header.contains("'descr': '<U3'"));
+    let units: Vec<String> = data
+        .chunks(12)
+        .map(|value| {
+            value
+                .chunks(4)
+                .map(|c| u32_at(c, 0) as u8 as char)
+                .filter(|&c| c != '\0')
+                .collect()
+        })
+        .collect();
+    assert_eq!(units, ["g", "g", "m/s"]);
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{read_track_names, SampleSlice};
+use dts_to_uff_converter::dts::DtsReader;
+use dts_to_uff_converter::plot::{self, PlotFormat, PlotSettings};
+use std::path::Path;
+use tempfile::TempDir;
+
+fn data_dir() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small")
+}
+
+#[test]
+fn renders_track_as_svg_with_name_units_and_markers() -> Result<()> {
+    let reader = DtsReader::new(data_dir())?;
+    let track_names = read_track_names(&data_dir().join("tracks.txt"))?;
+    let tracks = plot::read_tracks(
+        &reader,
+        &track_names,
+        Some(&["A2".to_string()]),
+        Some(SampleSlice {
+            start: 100,
+            end: 1500,
+        }),
+    )?;
+    assert_eq!(tracks.len(), 1);
+    assert_eq!(tracks[0].data.time_series.len(), 1400);
+    assert_eq!(tracks[0].start_time, 100.0 / 200_000.0);
+
+    let settings = PlotSettings {
+        markers: true,
+        ..PlotSettings::default()
+    };
+    let svg = String::from_utf8(plot::render(&tracks, PlotFormat::Svg, &settings)?)?;
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.contains(r#"width="800" height="300""#));
+    assert!(svg.contains(">A2</text>"));
+    assert!(svg.contains(">g</text>"));
+    assert!(svg.contains(">Time (s)</text>"));
+    assert!(svg.contains(">max "));
+    assert!(svg.contains(">min "));
+    assert!(svg.trim_end().ends_with("</svg>"));
+
+    let missing = plot::read_tracks(&reader, &track_names, Some(&["A3".to_string()]), None);
+    assert!(missing.is_err());
+    Ok(())
+}
+
+#[test]
+fn renders_grid_of_tracks_as_png() -> Result<()> {
+    let reader = DtsReader::new(data_dir())?;
+    let tracks = plot::read_tracks(&reader, &[], None, None)?;
+    assert_eq!(tracks.len(), 2);
+
+    let settings = PlotSettings {
+        width: 400,
+        height: 200,
+        columns: 2,
+        markers: false,
+    };
+    let png = plot::render(&tracks, PlotFormat::Png, &settings)?;
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+
+    let mut chunks = Vec::new();
+    let mut idat = Vec::new();
+    let mut palette_len = 0;
+    let mut offset = 8;
+    while offset < png.len() {
+        let length = u32::from_be_bytes(png[offset..offset + 4].try_into()?) as usize;
+        let kind = std::str::from_utf8(&png[offset + 4..offset + 8])?.to_string();
+        let data = &png[offset + 8..offset + 8 + length];
+        let crc = u32::from_be_bytes(png[offset + 8 + length..offset + 12 + length].try_into()?);
+        assert_eq!(crc, crc32fast::hash(&png[offset + 4..offset + 8 + length]));
+        match kind.as_str() {
+            "IHDR" => {
+                assert_eq!(u32::from_be_bytes(data[0..4].try_into()?), 800);
+                assert_eq!(u32::from_be_bytes(data[4..8].try_into()?), 200);
+                // 8-bit palette image.
+                assert_eq!(&data[8..10], &[8, 3]);
+            }
+            "PLTE" => palette_len = length / 3,
+            "IDAT" => idat.extend_from_slice(data),
+            _ => {}
+        }
+        chunks.push(kind);
+        offset += length + 12;
+    }
+    assert_eq!(offset, png.len());
+    assert_eq!(chunks, ["IHDR", "PLTE", "IDAT", "IEND"]);
+
+    // 200 scanlines of a filter byte (0, none) followed by one palette index per pixel.
+    let scanlines = miniz_oxide::inflate::decompress_to_vec_zlib(&idat)
+        .map_err(|err| anyhow::anyhow!("Invalid IDAT stream: {err:?}"))?;
+    assert_eq!(scanlines.len(), 200 * 801);
+    assert!(scanlines.chunks(801).all(|row| row[0] == 0));
+    // Every pixel indexes the palette, and the plot uses more than the background colour.
+    let pixels: Vec<u8> = scanlines
+        .chunks(801)
+        .flat_map(|row| row[1..].iter().copied())
+        .collect();
+    assert!(pixels.iter().all(|&index| usize::from(index) < palette_len));
+    assert!(pixels.iter().any(|&index| index != pixels[0]));
+    Ok(())
+}
+
+#[test]
+fn writes_one_image_per_track_named_after_dts_channels() -> Result<()> {
+    // Without a track names file both sample tracks are named "IEPE 100 mV/g".
+    let reader = DtsReader::new(data_dir())?;
+    let tracks = plot::read_tracks(&reader, &[], None, None)?;
+    assert!(tracks.iter().all(|track| track.name == "IEPE 100 mV/g"));
+
+    let temp = TempDir::new()?;
+    let output = temp.path().join("plots");
+    let written =
+        plot::write_track_images(&output, &tracks, PlotFormat::Svg, &PlotSettings::default())?;
+    assert_eq!(
+        written,
+        [
+            output.join("IEPE_100_mV_g.svg"),
+            output.join("IEPE_100_mV_g_2.svg")
+        ]
+    );
+    for path in &written {
+        let svg = std::fs::read_to_string(path)?;
+        assert!(svg.contains(">IEPE 100 mV/g</text>"));
+    }
+    Ok(())
+}
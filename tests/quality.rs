@@ -0,0 +1,144 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat};
+use dts_to_uff_converter::dts::{DtsReader, RawTrack};
+use dts_to_uff_converter::quality::{check_track, QualityIssue, QualitySettings};
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+
+fn data_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small")
+}
+
+fn raw_track(adc: Vec<i16>) -> RawTrack {
+    RawTrack {
+        adc,
+        bits_per_sample: 16,
+        pre_test_noise_percent: 0.01,
+    }
+}
+
+#[test]
+fn detects_clipping_flat_and_noisy_channels() {
+    let settings = QualitySettings::default();
+
+    // A pulse that saturates the positive rail for five samples.
+    let clipped: Vec<i16> = (0..1000)
+        .map(|n| match n {
+            500..505 => i16::MAX,
+            _ => ((n % 7) as i16 - 3) * 2,
+        })
+        .collect();
+    assert_eq!(
+        check_track(&raw_track(clipped), &settings),
+        [QualityIssue::Clipped {
+            low: 0,
+            high: 5,
+            total: 1000
+        }]
+    );
+
+    let dead = raw_track(vec![12; 1000]);
+    assert_eq!(
+        check_track(&dead, &settings),
+        [QualityIssue::Constant { adc: 12 }]
+    );
+
+    let nearly_dead: Vec<i16> = (0..1000).map(|n| i16::from(n == 10)).collect();
+    assert!(matches!(
+        check_track(&raw_track(nearly_dead), &settings)[..],
+        [QualityIssue::Flat { .. }]
+    ));
+
+    // Uniform noise of +-100 counts is far above 0.01 % FS of pre-test noise.
+    let mut state = 12345u32;
+    let noisy: Vec<i16> = (0..1000)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            ((state >> 16) % 201) as i16 - 100
+        })
+        .collect();
+    let issues = check_track(&raw_track(noisy), &settings);
+    assert!(matches!(issues[..], [QualityIssue::Noisy { .. }]));
+
+    // A 12-bit recorder clips at +-2048.
+    let twelve_bit = RawTrack {
+        bits_per_sample: 12,
+        ..raw_track(vec![-2048, 0, 5, 2047, 3])
+    };
+    assert!(matches!(
+        check_track(&twelve_bit, &settings)[0],
+        QualityIssue::Clipped {
+            low: 1,
+            high: 1,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn reads_raw_samples_and_header_noise() -> Result<()> {
+    let reader = DtsReader::new(data_dir())?;
+    let raw = reader.read_track_raw(0)?;
+    assert_eq!(raw.bits_per_sample, 16);
+    assert_eq!(raw.adc.len(), 2000);
+    assert!((raw.pre_test_noise_percent - 0.0091552734375).abs() < 1e-12);
+    assert!(check_track(&raw, &QualitySettings::default()).is_empty());
+
+    let scaled = reader.scale_track(0, raw);
+    assert_eq!(scaled.time_series, reader.read_track(0)?.time_series);
+    Ok(())
+}
+
+#[test]
+fn strict_mode_fails_the_conversion() -> Result<()> {
+    let data_dir = data_dir();
+    let output = NamedTempFile::new()?;
+    let convert = |quality: QualitySettings| {
+        let options = ConversionOptions {
+            quality,
+            ..ConversionOptions::default()
+        };
+        conversion::convert_with_progress_and_options(
+            &data_dir,
+            &data_dir.join("tracks.txt"),
+            output.path(),
+            OutputFormat::Ascii,
+            &options,
+            |_| {},
+        )
+    };
+
+    let report = convert(QualitySettings::default())?;
+    assert!(report.quality_issues.is_empty());
+
+    // Both tracks vary by only a few counts, so a threshold of 10 counts flags them.
+    let flat = QualitySettings {
+        flat_threshold: 10.0,
+        ..QualitySettings::default()
+    };
+    let report = convert(flat.clone())?;
+    let tracks: Vec<&str> = report
+        .quality_issues
+        .iter()
+        .map(|(track_name, _)| track_name.as_str())
+        .collect();
+    assert_eq!(tracks, ["A1", "A2"]);
+    assert!(report
+        .warnings
+        .iter()
+        .any(|warning| warning.starts_with("Track 'A1': near-zero variance")));
+
+    let error = convert(QualitySettings {
+        strict: true,
+        ..flat.clone()
+    })
+    .expect_err("strict mode should fail");
+    assert!(error.to_string().contains("Track 'A2'"));
+
+    let report = convert(QualitySettings {
+        enabled: false,
+        ..flat
+    })?;
+    assert!(report.quality_issues.is_empty());
+    Ok(())
+}
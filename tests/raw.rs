@@ -0,0 +1,79 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat, SampleSlice};
+use dts_to_uff_converter::dts::DtsReader;
+use dts_to_uff_converter::filter::{CfcClass, CfcFiltering};
+use dts_to_uff_converter::raw;
+use std::path::Path;
+use tempfile::TempDir;
+
+#[test]
+fn raw_export_reconstructs_tracks_bit_for_bit() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let temp = TempDir::new()?;
+    let output = temp.path().join("raw");
+    let mut cfc = CfcFiltering::default();
+    cfc.set_channel("A2", Some(CfcClass::Cfc60));
+    let options = ConversionOptions {
+        cfc,
+        slice: Some(SampleSlice {
+            start: 100,
+            end: 1500,
+        }),
+        expressions: vec!["S=A1+A2".parse().map_err(anyhow::Error::msg)?],
+        ..ConversionOptions::default()
+    };
+    let report = conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        &output,
+        OutputFormat::Raw,
+        &options,
+        |_| {},
+    )?;
+    assert!(report
+        .warnings
+        .iter()
+        .any(|warning| warning.starts_with("Track 'A2' is stored as unprocessed ADC counts")));
+    assert!(report
+        .warnings
+        .contains(&"Expression channels are not part of the raw export.".to_string()));
+
+    let manifest = raw::read_manifest(&output)?;
+    let names: Vec<&str> = manifest
+        .tracks
+        .iter()
+        .map(|track| track.name.as_str())
+        .collect();
+    assert_eq!(names, ["A1", "A2"]);
+    let a1 = &manifest.tracks[0];
+    assert_eq!(a1.file, "A1.i16");
+    assert_eq!((a1.first_sample, a1.sample_count), (100, 1400));
+    assert_eq!(a1.start_time, 100.0 / 200_000.0);
+    assert_eq!(std::fs::metadata(output.join(&a1.file))?.len(), 2800);
+
+    let reader = DtsReader::new(&data_dir)?;
+    let tracks = raw::reconstruct(&output)?;
+    for (index, (name, data)) in tracks.iter().enumerate() {
+        let expected = reader.read_track(index)?;
+        let scaling = reader.adc_scaling(index);
+        let entry = &manifest.tracks[index];
+        assert_eq!(
+            entry.scaling.eu_per_count.to_bits(),
+            scaling.eu_per_count.to_bits()
+        );
+        assert_eq!(entry.scaling.offset.to_bits(), scaling.offset.to_bits());
+        assert_eq!(data.sample_rate, expected.sample_rate);
+        assert_eq!(data.units, expected.units);
+        let expected_bits: Vec<u64> = expected.time_series[100..1500]
+            .iter()
+            .map(|value| value.to_bits())
+            .collect();
+        let bits: Vec<u64> = data
+            .time_series
+            .iter()
+            .map(|value| value.to_bits())
+            .collect();
+        assert_eq!(bits, expected_bits, "track {name}");
+    }
+    Ok(())
+}
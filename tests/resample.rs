@@ -0,0 +1,124 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat, SampleSlice};
+use dts_to_uff_converter::dts::ChannelData;
+use dts_to_uff_converter::resample::{resample, Resampling};
+use std::f64::consts::PI;
+use std::fs;
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+fn sine(frequency: f64, sample_rate: f64, len: usize) -> ChannelData {
+    ChannelData {
+        time_series: (0..len)
+            .map(|n| 1.5 + (2.0 * PI * frequency * n as f64 / sample_rate).sin())
+            .collect(),
+        sample_rate,
+        units: "g".to_string(),
+    }
+}
+
+#[test]
+fn resampling_preserves_in_band_signals() -> Result<()> {
+    for (from, to) in [
+        (10_000.0, 4_000.0),
+        (10_000.0, 25_000.0),
+        (200_000.0, 44_100.0),
+    ] {
+        let mut data = sine(500.0, from, 20_000);
+        let resampling = resample(&mut data, to)?.expect("rates differ");
+        assert_eq!(resampling.to, to);
+        assert_eq!(data.sample_rate, to);
+        assert_eq!(data.time_series.len(), resampling.output_len(20_000));
+
+        // Away from the edges every output sample matches the signal at its time.
+        let len = data.time_series.len();
+        for (k, value) in data.time_series.iter().enumerate().take(len - 50).skip(50) {
+            let expected = 1.5 + (2.0 * PI * 500.0 * k as f64 / to).sin();
+            assert!(
+                (value - expected).abs() < 0.002,
+                "{from}->{to} at {k}: {}",
+                value - expected
+            );
+        }
+    }
+
+    // The record is extended with its edge values, so an offset does not droop at the edges.
+    let mut offset = ChannelData {
+        time_series: vec![2.0; 1000],
+        sample_rate: 10_000.0,
+        units: "g".to_string(),
+    };
+    resample(&mut offset, 3_000.0)?;
+    assert!(offset
+        .time_series
+        .iter()
+        .all(|value| (value - 2.0).abs() < 1e-3));
+
+    // Content above the new Nyquist frequency is removed instead of aliased.
+    let mut data = sine(3_000.0, 10_000.0, 20_000);
+    resample(&mut data, 4_000.0)?;
+    let interior = &data.time_series[100..7_900];
+    assert!(interior.iter().all(|value| (value - 1.5).abs() < 0.01));
+
+    assert_eq!(Resampling::new(200_000.0, 200_000.0)?, None);
+    let ratio = Resampling::new(200_000.0, 44_100.0)?.expect("rates differ");
+    assert_eq!((ratio.up, ratio.down), (441, 2000));
+    assert!(Resampling::new(200_000.0, 0.0).is_err());
+    // 20 kHz from a 1 Hz channel needs an upsampling factor above the limit.
+    let error = Resampling::new(1.0, 20_000.0).expect_err("ratio above the limit");
+    assert!(error.to_string().contains("too high"), "{error}");
+    let ratio = Resampling::new(1.0, 10_000.0)?.expect("rates differ");
+    assert_eq!((ratio.up, ratio.down), (10_000, 1));
+    Ok(())
+}
+
+#[test]
+fn resampled_records_use_the_new_abscissa_increment() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let output = NamedTempFile::new()?;
+
+    let options = ConversionOptions {
+        slice: Some(SampleSlice {
+            start: 100,
+            end: 1100,
+        }),
+        resample_rate: Some(50_000.0),
+        write_1858: true,
+        ..ConversionOptions::default()
+    };
+    let report = conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        output.path(),
+        OutputFormat::Ascii,
+        &options,
+        |_| {},
+    )?;
+    assert_eq!(report.resampling.len(), 2);
+    assert_eq!(report.resampling[0].1.to, 50_000.0);
+
+    let text = fs::read_to_string(output.path())?;
+    let lines: Vec<&str> = text.lines().collect();
+    let start = lines
+        .iter()
+        .position(|line| line.trim_end() == "    58")
+        .expect("58 record");
+    let record_7: Vec<&str> = lines[start + 7].split_whitespace().collect();
+    assert_eq!(record_7[1], "250");
+    assert_eq!(
+        record_7[4].replace('D', "E").parse::<f64>()?,
+        1.0 / 50_000.0
+    );
+
+    // The slice start keeps its native time in the 1858 Z time.
+    let qualifiers = lines
+        .iter()
+        .position(|line| line.trim_end() == "  1858")
+        .expect("1858 block");
+    let z_values: Vec<f64> = lines[qualifiers + 3]
+        .split_whitespace()
+        .map(|value| value.replace('D', "E").parse())
+        .collect::<Result<_, _>>()?;
+    assert!((z_values[1] - 100.0 / 200_000.0).abs() < 1e-12);
+    Ok(())
+}
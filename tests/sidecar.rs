@@ -0,0 +1,91 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat, SampleSlice};
+use dts_to_uff_converter::filter::{CfcClass, CfcFiltering};
+use dts_to_uff_converter::id_template::IdTemplate;
+use serde_json::{json, Value};
+use std::path::Path;
+use tempfile::TempDir;
+
+#[test]
+fn writes_metadata_sidecar_with_provenance() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let temp = TempDir::new()?;
+    let output = temp.path().join("test.uff");
+    let sidecar_path = temp.path().join("test.json");
+    let mut cfc = CfcFiltering::default();
+    cfc.set_channel("A1", Some(CfcClass::Cfc180));
+    let mut id_template = IdTemplate::metadata();
+    id_template.set_line("5={{rig}} {test}".parse().map_err(anyhow::Error::msg)?);
+    let options = ConversionOptions {
+        slice: Some(SampleSlice {
+            start: 100,
+            end: 1500,
+        }),
+        track_list_filter: Some(vec!["A2".to_string(), "A1".to_string()]),
+        cfc,
+        id_template: Some(id_template),
+        metadata_json: Some(sidecar_path.clone()),
+        ..ConversionOptions::default()
+    };
+    conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        &output,
+        OutputFormat::Ascii,
+        &options,
+        |_| {},
+    )?;
+
+    let sidecar: Value = serde_json::from_str(&std::fs::read_to_string(&sidecar_path)?)?;
+    assert_eq!(sidecar["sidecar_version"], 1);
+    assert_eq!(sidecar["converter"]["version"], env!("CARGO_PKG_VERSION"));
+    // `sha256sum Bancairon_G1_training6.dts`
+    assert_eq!(
+        sidecar["source"]["dts_sha256"],
+        "7886a0c947bff9a544466c07fcddce234a807f91d32bc7b14ad64bea1624db8e"
+    );
+    assert_eq!(sidecar["output"]["format"], "ascii");
+    assert_eq!(
+        sidecar["channel_plan"],
+        json!([
+            {
+                "order": 0,
+                "channel_index": 1,
+                "track_name": "A2",
+                "chn_file": "Bancairon_G1_training6.1.chn"
+            },
+            {
+                "order": 1,
+                "channel_index": 0,
+                "track_name": "A1",
+                "chn_file": "Bancairon_G1_training6.0.chn"
+            }
+        ])
+    );
+
+    let channels = sidecar["channels"].as_array().expect("channel list");
+    assert_eq!(channels.len(), 2);
+    assert_eq!(channels[0]["module"], "BA00344");
+    assert_eq!(channels[0]["eu"], "g");
+    assert!(channels[0]["zero_method"].is_string());
+    assert!(channels[0]["sensitivity"].is_number());
+
+    let processing = &sidecar["processing"];
+    assert_eq!(processing["slice"], json!([100, 1500]));
+    assert_eq!(processing["tracks"][0]["cfc"], Value::Null);
+    assert_eq!(processing["tracks"][1]["cfc"], CfcClass::Cfc180.to_string());
+
+    let options = &sidecar["options"];
+    assert_eq!(options["track_list_filter"], json!(["A2", "A1"]));
+    assert_eq!(options["cfc"]["channel_classes"]["A1"], "CFC180");
+    assert_eq!(options["spectral"]["window"], "hann");
+    assert_eq!(options["quality"]["strict"], false);
+    assert_eq!(options["csv"]["rates"], "split");
+    assert_eq!(options["write_1858"], false);
+    assert_eq!(
+        options["id_template"]["lines"][3],
+        "SN:{serial} {sensitivity} mV/{eu}"
+    );
+    assert_eq!(options["id_template"]["lines"][4], "{{rig}} {test}");
+    Ok(())
+}
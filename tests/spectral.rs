@@ -0,0 +1,190 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat};
+use dts_to_uff_converter::dts::ChannelData;
+use dts_to_uff_converter::spectral::{
+    compute_spectra, Averaging, SpectralSettings, SpectrumKind, SpectrumValues, Window,
+};
+use std::f64::consts::PI;
+use std::fs;
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+fn sine(amplitude: f64, frequency: f64, sample_rate: f64, len: usize) -> ChannelData {
+    ChannelData {
+        time_series: (0..len)
+            .map(|n| amplitude * (2.0 * PI * frequency * n as f64 / sample_rate).sin())
+            .collect(),
+        sample_rate,
+        units: "g".to_string(),
+    }
+}
+
+#[test]
+fn autopower_and_psd_preserve_signal_power() -> Result<()> {
+    // 1 kHz sits exactly on line 64 of a 1024-point block at 16 kHz.
+    let data = sine(2.0, 1000.0, 16_000.0, 8192);
+    let settings = SpectralSettings {
+        window: Window::Hann,
+        block_size: 1024,
+        ..SpectralSettings::default()
+    };
+    let spectra = compute_spectra(
+        &data,
+        &[
+            SpectrumKind::AutoPower,
+            SpectrumKind::Psd,
+            SpectrumKind::Linear,
+        ],
+        &settings,
+    )?;
+
+    assert_eq!(spectra.len(), 3);
+    assert_eq!(spectra[0].frequency_step, 16_000.0 / 1024.0);
+    assert_eq!(spectra[0].averages, 15);
+    assert_eq!(spectra[0].units, "g^2");
+    assert_eq!(spectra[1].units, "g^2/Hz");
+
+    let SpectrumValues::Real(autopower) = &spectra[0].values else {
+        panic!("auto-power spectrum must be real");
+    };
+    assert_eq!(autopower.len(), 513);
+    // RMS² of a sine with amplitude 2.
+    assert!((autopower[64] - 2.0).abs() < 1e-9);
+
+    let SpectrumValues::Real(psd) = &spectra[1].values else {
+        panic!("PSD must be real");
+    };
+    let power: f64 = psd.iter().sum::<f64>() * spectra[1].frequency_step;
+    assert!((power - 2.0).abs() < 1e-6);
+
+    let SpectrumValues::Real(linear) = &spectra[2].values else {
+        panic!("magnitude-averaged linear spectrum must be real");
+    };
+    assert!((linear[64] - 2.0).abs() < 1e-9);
+
+    Ok(())
+}
+
+#[test]
+fn linear_spectrum_keeps_tones_not_aligned_to_the_hop() -> Result<()> {
+    // 1015.625 Hz sits on line 65, but the 512-sample hop is 32.5 periods, so the phase of the
+    // tone flips between consecutive blocks.
+    let data = sine(2.0, 1015.625, 16_000.0, 8192);
+    let linear_line = |averaging| -> Result<f64> {
+        let settings = SpectralSettings {
+            window: Window::Hann,
+            block_size: 1024,
+            averaging,
+            ..SpectralSettings::default()
+        };
+        let spectra = compute_spectra(&data, &[SpectrumKind::Linear], &settings)?;
+        // Only the vector average keeps a phase.
+        match (&spectra[0].values, averaging) {
+            (SpectrumValues::Complex(linear), Averaging::Vector) => Ok(linear[65].norm()),
+            (SpectrumValues::Real(linear), Averaging::Linear | Averaging::PeakHold) => {
+                Ok(linear[65])
+            }
+            _ => panic!("linear spectrum type does not match {averaging} averaging"),
+        }
+    };
+
+    assert!((linear_line(Averaging::Linear)? - 2.0).abs() < 1e-9);
+    assert!((linear_line(Averaging::PeakHold)? - 2.0).abs() < 1e-9);
+    // Synchronous averaging cancels all but one of the 15 blocks.
+    assert!((linear_line(Averaging::Vector)? - 2.0 / 15.0).abs() < 1e-9);
+    Ok(())
+}
+
+#[test]
+fn spectra_follow_each_time_history() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let output = NamedTempFile::new()?;
+
+    let options = ConversionOptions {
+        spectra: vec![SpectrumKind::AutoPower, SpectrumKind::Linear],
+        spectral: SpectralSettings {
+            block_size: 512,
+            ..SpectralSettings::default()
+        },
+        ..ConversionOptions::default()
+    };
+    let report = conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        output.path(),
+        OutputFormat::Ascii,
+        &options,
+        |_| {},
+    )?;
+    assert_eq!(report.spectrum_count, 4);
+
+    let text = fs::read_to_string(output.path())?;
+    let lines: Vec<&str> = text.lines().collect();
+    let starts: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.trim_end() == "    58")
+        .map(|(index, _)| index)
+        .collect();
+    assert_eq!(starts.len(), 6);
+
+    let fields =
+        |line: &str| -> Vec<String> { line.split_whitespace().map(ToOwned::to_owned).collect() };
+    // The magnitude-averaged linear spectrum is a real record (ordinate data type 4).
+    let expected = [("1", "17", "4"), ("2", "18", "4"), ("12", "18", "4")];
+    for (record, &start) in starts.iter().enumerate() {
+        let (function_type, abscissa_type, ordinate_type) = expected[record % 3];
+        assert_eq!(fields(lines[start + 6])[0], function_type);
+        assert_eq!(fields(lines[start + 7])[0], ordinate_type);
+        assert_eq!(fields(lines[start + 8])[0], abscissa_type);
+        if record % 3 != 0 {
+            let record_7 = fields(lines[start + 7]);
+            assert_eq!(record_7[1], "257");
+            assert_eq!(record_7[4].parse::<f64>()?, 200_000.0 / 512.0);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn vector_averaged_linear_spectrum_is_a_complex_record() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let output = NamedTempFile::new()?;
+
+    let options = ConversionOptions {
+        spectra: vec![SpectrumKind::Linear],
+        spectral: SpectralSettings {
+            block_size: 512,
+            averaging: Averaging::Vector,
+            ..SpectralSettings::default()
+        },
+        ..ConversionOptions::default()
+    };
+    conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        output.path(),
+        OutputFormat::Ascii,
+        &options,
+        |_| {},
+    )?;
+
+    let text = fs::read_to_string(output.path())?;
+    let lines: Vec<&str> = text.lines().collect();
+    let spectrum_starts: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.trim_end() == "    58")
+        .map(|(index, _)| index)
+        .skip(1)
+        .step_by(2)
+        .collect();
+    assert_eq!(spectrum_starts.len(), 2);
+    for start in spectrum_starts {
+        assert_eq!(lines[start + 6].split_whitespace().next(), Some("12"));
+        assert_eq!(lines[start + 7].split_whitespace().next(), Some("6"));
+    }
+
+    Ok(())
+}
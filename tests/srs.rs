@@ -0,0 +1,159 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat, SrsOptions};
+use dts_to_uff_converter::dts::ChannelData;
+use dts_to_uff_converter::srs::{compute_srs, SrsKind, SrsSettings};
+use std::f64::consts::PI;
+use std::fs;
+use std::path::Path;
+use tempfile::{NamedTempFile, TempDir};
+
+#[test]
+fn half_sine_pulse_matches_known_srs_shape() -> Result<()> {
+    // 10 g, 11 ms half-sine sampled at 50 kHz. The primary response covers the whole record.
+    let sample_rate = 50_000.0;
+    let pulse_samples = (0.011 * sample_rate) as usize;
+    let time_series = (0..=pulse_samples)
+        .map(|n| 10.0 * (PI * n as f64 / pulse_samples as f64).sin())
+        .collect();
+    let data = ChannelData {
+        time_series,
+        sample_rate,
+        units: "g".to_string(),
+    };
+    let settings = SrsSettings {
+        min_frequency: 5.0,
+        max_frequency: 5_000.0,
+        ..SrsSettings::default()
+    };
+    let srs = compute_srs(&data, &settings)?;
+
+    assert_eq!(srs.frequencies.len(), 120);
+    assert_eq!(srs.frequencies[0], 5.0);
+    assert!((srs.frequencies[12] - 10.0).abs() < 1e-9);
+
+    // High natural frequencies follow the input: the maximax tends to the peak acceleration.
+    let last = *srs.maximax.last().unwrap();
+    assert!(
+        (last - 10.0).abs() < 0.2,
+        "high-frequency maximax was {last}"
+    );
+    // Well below the pulse frequency the residual response dominates.
+    assert!(srs.residual[0] > srs.primary[0]);
+    for ((maximax, primary), residual) in srs.maximax.iter().zip(&srs.primary).zip(&srs.residual) {
+        assert_eq!(*maximax, primary.max(*residual));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn srs_is_exported_as_uneven_records_and_csv() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let output = NamedTempFile::new()?;
+    let csv_dir = TempDir::new()?;
+    let csv_path = csv_dir.path().join("srs.csv");
+
+    let options = ConversionOptions {
+        srs: Some(SrsOptions {
+            kinds: vec![SrsKind::Maximax, SrsKind::Primary],
+            settings: SrsSettings {
+                min_frequency: 100.0,
+                max_frequency: 12_800.0,
+                points_per_octave: 6,
+                ..SrsSettings::default()
+            },
+            csv_output: Some(csv_path.clone()),
+        }),
+        ..ConversionOptions::default()
+    };
+    let report = conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        output.path(),
+        OutputFormat::Ascii,
+        &options,
+        |_| {},
+    )?;
+    assert_eq!(report.srs_count, 4);
+
+    let text = fs::read_to_string(output.path())?;
+    let lines: Vec<&str> = text.lines().collect();
+    let starts: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.trim_end() == "    58")
+        .map(|(index, _)| index)
+        .collect();
+    assert_eq!(starts.len(), 6);
+
+    let srs_start = starts[1];
+    assert!(lines[srs_start + 6].trim_start().starts_with("0 "));
+    let record_7: Vec<&str> = lines[srs_start + 7].split_whitespace().collect();
+    assert_eq!(&record_7[..3], ["4", "43", "0"]);
+    assert!(lines[srs_start + 9].contains("A1 maximax"));
+    let first_point: Vec<f64> = lines[srs_start + 12]
+        .split_whitespace()
+        .map(str::parse)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(first_point[0], 100.0);
+    assert!((first_point[2] - 100.0 * 2f64.powf(1.0 / 6.0)).abs() < 1e-3);
+
+    let csv = fs::read_to_string(&csv_path)?;
+    let csv_lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        csv_lines[0],
+        "frequency_hz,A1 maximax [g],A1 primary [g],A2 maximax [g],A2 primary [g]"
+    );
+    assert_eq!(csv_lines.len(), 44);
+
+    Ok(())
+}
+
+#[test]
+fn srs_1858_block_keeps_narrow_band_octave_format() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let output = NamedTempFile::new()?;
+
+    let options = ConversionOptions {
+        write_1858: true,
+        srs: Some(SrsOptions {
+            kinds: vec![SrsKind::Maximax],
+            settings: SrsSettings {
+                min_frequency: 100.0,
+                max_frequency: 12_800.0,
+                points_per_octave: 6,
+                ..SrsSettings::default()
+            },
+            csv_output: None,
+        }),
+        ..ConversionOptions::default()
+    };
+    conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        output.path(),
+        OutputFormat::Ascii,
+        &options,
+        |_| {},
+    )?;
+
+    let text = fs::read_to_string(output.path())?;
+    let lines: Vec<&str> = text.lines().collect();
+    let blocks: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.trim_end() == "  1858")
+        .map(|(index, _)| index)
+        .collect();
+    // Time history of A1, then its SRS.
+    let srs_block = blocks[1];
+    let record_1: Vec<&str> = lines[srs_block + 1].split_whitespace().collect();
+    assert_eq!(&record_1[..2], ["2", "0"]);
+    let user_values: Vec<f64> = lines[srs_block + 4]
+        .split_whitespace()
+        .map(|value| value.replace('D', "E").parse())
+        .collect::<Result<_, _>>()?;
+    assert_eq!(user_values[0], 6.0);
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat, SampleSlice};
+use dts_to_uff_converter::dts::ChannelData;
+use dts_to_uff_converter::statistics::{self, ChannelStatistics, StatisticsFormat};
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+#[test]
+fn statistics_of_known_signal() {
+    let data = ChannelData {
+        time_series: vec![1.0, -3.0, 2.0, 0.0],
+        sample_rate: 100.0,
+        units: "N".to_string(),
+    };
+    let stats = ChannelStatistics::compute("F1", &data, 0.5);
+
+    assert_eq!(stats.sample_count, 4);
+    assert_eq!(stats.min, -3.0);
+    assert_eq!(stats.max, 2.0);
+    assert_eq!(stats.mean, 0.0);
+    assert!((stats.rms - 3.5f64.sqrt()).abs() < 1e-12);
+    assert_eq!(stats.peak_to_peak, 5.0);
+    assert!((stats.crest_factor.expect("crest factor") - 3.0 / 3.5f64.sqrt()).abs() < 1e-12);
+    assert_eq!(stats.peak, -3.0);
+    assert!((stats.peak_time - 0.51).abs() < 1e-12);
+
+    let silent = ChannelData {
+        time_series: vec![0.0; 8],
+        ..data
+    };
+    assert_eq!(
+        ChannelStatistics::compute("F1", &silent, 0.0).crest_factor,
+        None
+    );
+}
+
+#[test]
+fn conversion_reports_statistics_in_output_order() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let output = NamedTempFile::new()?;
+    let options = ConversionOptions {
+        slice: Some(SampleSlice {
+            start: 100,
+            end: 600,
+        }),
+        derived: vec!["A1=velocity".parse().map_err(anyhow::Error::msg)?],
+        expressions: vec!["S=A1+A2".parse().map_err(anyhow::Error::msg)?],
+        ..ConversionOptions::default()
+    };
+    let report = conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        output.path(),
+        OutputFormat::Ascii,
+        &options,
+        |_| {},
+    )?;
+
+    let names: Vec<&str> = report
+        .statistics
+        .iter()
+        .map(|stats| stats.track_name.as_str())
+        .collect();
+    assert_eq!(names, ["A1", "A2", "A1_velocity", "S"]);
+    for stats in &report.statistics {
+        assert_eq!(stats.sample_count, 500);
+        assert!(stats.min <= stats.mean && stats.mean <= stats.max);
+        assert!((0.0005..0.003).contains(&stats.peak_time));
+    }
+    let sum = &report.statistics[3];
+    assert!((sum.mean - report.statistics[0].mean - report.statistics[1].mean).abs() < 1e-12);
+
+    let mut json = Vec::new();
+    statistics::write_statistics(&mut json, &report.statistics, StatisticsFormat::Json)?;
+    let parsed: serde_json::Value = serde_json::from_slice(&json)?;
+    assert_eq!(parsed[2]["units"], "m/s");
+
+    let mut table = Vec::new();
+    statistics::write_statistics(&mut table, &report.statistics, StatisticsFormat::Table)?;
+    let table = String::from_utf8(table)?;
+    assert_eq!(table.lines().count(), 5);
+    assert!(table
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .starts_with("Track"));
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use anyhow::{anyhow, Result};
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat};
+use dts_to_uff_converter::dts::DtsReader;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tempfile::TempDir;
+
+#[derive(Debug, PartialEq)]
+enum Value {
+    Double(f64),
+    String(String),
+}
+
+/// An object read back from the segments, with its properties and `f64` samples.
+#[derive(Debug, Default)]
+struct Object {
+    properties: BTreeMap<String, Value>,
+    samples: Vec<f64>,
+}
+
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Cursor<'_> {
+    fn take(&mut self, length: usize) -> &[u8] {
+        let bytes = &self.bytes[self.position..self.position + length];
+        self.position += length;
+        bytes
+    }
+
+    fn u32(&mut self) -> u32 {
+        u32::from_le_bytes(self.take(4).try_into().unwrap())
+    }
+
+    fn u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.take(8).try_into().unwrap())
+    }
+
+    fn string(&mut self) -> String {
+        let length = self.u32() as usize;
+        String::from_utf8(self.take(length).to_vec()).unwrap()
+    }
+}
+
+/// Reads every segment of a TDMS file written with new object lists and `f64` raw data.
+fn read_tdms(bytes: &[u8]) -> Result<Vec<(String, Object)>> {
+    let mut objects: Vec<(String, Object)> = Vec::new();
+    let mut cursor = Cursor { bytes, position: 0 };
+    while cursor.position < bytes.len() {
+        if cursor.take(4) != b"TDSm" {
+            return Err(anyhow!("missing segment tag at {}", cursor.position - 4));
+        }
+        let toc = cursor.u32();
+        assert_eq!(toc & 0b110, 0b110, "metadata with a new object list");
+        assert_eq!(cursor.u32(), 4713);
+        let segment_length = cursor.u64() as usize;
+        let metadata_length = cursor.u64() as usize;
+        let segment_start = cursor.position;
+
+        let mut with_data = Vec::new();
+        for _ in 0..cursor.u32() {
+            let path = cursor.string();
+            let index = match objects.iter().position(|(known, _)| *known == path) {
+                Some(index) => index,
+                None => {
+                    objects.push((path, Object::default()));
+                    objects.len() - 1
+                }
+            };
+            if cursor.u32() != u32::MAX {
+                assert_eq!(cursor.u32(), 10, "f64 raw data");
+                assert_eq!(cursor.u32(), 1, "one dimension");
+                with_data.push((index, cursor.u64() as usize));
+            }
+            for _ in 0..cursor.u32() {
+                let name = cursor.string();
+                let value = match cursor.u32() {
+                    10 => Value::Double(f64::from_le_bytes(cursor.take(8).try_into()?)),
+                    0x20 => Value::String(cursor.string()),
+                    other => return Err(anyhow!("unexpected property type {other}")),
+                };
+                objects[index].1.properties.insert(name, value);
+            }
+        }
+        assert_eq!(cursor.position, segment_start + metadata_length);
+        for (index, count) in with_data {
+            let samples = cursor
+                .take(count * 8)
+                .chunks(8)
+                .map(|value| f64::from_le_bytes(value.try_into().unwrap()));
+            objects[index].1.samples.extend(samples);
+        }
+        assert_eq!(cursor.position, segment_start + segment_length);
+    }
+    Ok(objects)
+}
+
+#[test]
+fn round_trips_sample_through_tdms_segments() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let temp = TempDir::new()?;
+    let output = temp.path().join("test.tdms");
+    let options = ConversionOptions {
+        expressions: vec!["S[g]=A1-A2".parse().map_err(anyhow::Error::msg)?],
+        ..ConversionOptions::default()
+    };
+    conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        &output,
+        OutputFormat::Tdms,
+        &options,
+        |_| {},
+    )?;
+
+    let objects = read_tdms(&std::fs::read(&output)?)?;
+    let paths: Vec<&str> = objects.iter().map(|(path, _)| path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "/",
+            "/'BA00344'",
+            "/'BA00344'/'A1'",
+            "/'BA00344'/'A2'",
+            "/'Computed'",
+            "/'Computed'/'S'"
+        ]
+    );
+
+    let reader = DtsReader::new(&data_dir)?;
+    let info = reader.channel_info(1).expect("channel metadata");
+    let a2 = &objects[3].1;
+    assert_eq!(a2.samples, reader.read_track(1)?.time_series);
+    assert_eq!(a2.properties["unit_string"], Value::String("g".to_string()));
+    assert_eq!(
+        a2.properties["sensitivity"],
+        Value::Double(info.sensitivity)
+    );
+    assert_eq!(
+        a2.properties["serial_number"],
+        Value::String(info.serial_number.clone())
+    );
+    // Time of the first sample relative to the trigger at sample 93059356.
+    assert_eq!(
+        a2.properties["wf_start_offset"],
+        Value::Double(-93_059_356.0 / 200_000.0)
+    );
+    assert_eq!(
+        a2.properties["wf_increment"],
+        Value::Double(1.0 / 200_000.0)
+    );
+
+    let computed = &objects[5].1;
+    assert_eq!(computed.samples.len(), 2000);
+    assert!(!computed.properties.contains_key("sensitivity"));
+    Ok(())
+}
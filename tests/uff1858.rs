@@ -0,0 +1,66 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat, SampleSlice};
+use dts_to_uff_converter::units::UnitConversion;
+use std::fs;
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+#[test]
+fn qualifiers_precede_each_58_record() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let output = NamedTempFile::new()?;
+
+    let options = ConversionOptions {
+        slice: Some(SampleSlice {
+            start: 200,
+            end: 1200,
+        }),
+        units: UnitConversion::si(),
+        write_1858: true,
+        ..ConversionOptions::default()
+    };
+    conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        output.path(),
+        OutputFormat::Ascii,
+        &options,
+        |_| {},
+    )?;
+
+    let text = fs::read_to_string(output.path())?;
+    let lines: Vec<&str> = text.lines().collect();
+    let starts: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.trim_end() == "  1858")
+        .map(|(index, _)| index)
+        .collect();
+    assert_eq!(starts.len(), 2);
+
+    for (record, &start) in starts.iter().enumerate() {
+        let record_1: Vec<i64> = lines[start + 1]
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+        assert_eq!(record_1[0], record as i64 + 1);
+
+        let record_3: Vec<f64> = lines[start + 3]
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+        assert!((record_3[1] - 200.0 / 200_000.0).abs() < 1e-12);
+        assert_eq!(record_3[3], 1000.0);
+
+        let record_4: Vec<f64> = lines[start + 4]
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+        assert!((record_4[3] - 9.80665).abs() < 1e-6);
+
+        assert_eq!(lines[start + 8].trim_end(), "    -1");
+        assert_eq!(lines[start + 10].trim_end(), "    58");
+    }
+
+    Ok(())
+}
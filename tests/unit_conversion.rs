@@ -0,0 +1,104 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat, SampleSlice};
+use dts_to_uff_converter::dts;
+use dts_to_uff_converter::units::{lookup_unit, ChannelUnitTarget, QuantityTarget, UnitConversion};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+#[test]
+fn si_preset_rescales_g_to_metres_per_second_squared() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let reader = dts::DtsReader::new(&data_dir)?;
+    let original = reader.read_track(0)?;
+    let mut converted = reader.read_track(0)?;
+
+    let change = UnitConversion::si()
+        .apply("A1", &mut converted)?
+        .expect("g channel should be rescaled");
+
+    assert_eq!(change.from, "g");
+    assert_eq!(change.to, "m/s^2");
+    assert_eq!(converted.units, "m/s^2");
+    for (raw, scaled) in original.time_series.iter().zip(&converted.time_series) {
+        assert!((raw * 9.80665 - scaled).abs() < 1e-12);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn channel_target_overrides_quantity_target() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let reader = dts::DtsReader::new(&data_dir)?;
+    let mut data = reader.read_track(1)?;
+
+    let mut conversion = UnitConversion::si();
+    let target: ChannelUnitTarget = "A2=mm/s^2".parse().map_err(anyhow::Error::msg)?;
+    conversion.set_channel_target(target.track_name, target.unit);
+
+    let change = conversion.apply("A2", &mut data)?.expect("rescaled");
+    assert_eq!(data.units, "mm/s^2");
+    assert!((change.factor - 9_806.65).abs() < 1e-9);
+
+    Ok(())
+}
+
+#[test]
+fn quantity_targets_reject_mismatched_units() {
+    assert!("force=kN".parse::<QuantityTarget>().is_ok());
+    assert!("force=psi".parse::<QuantityTarget>().is_err());
+    assert!("torque=Nm".parse::<QuantityTarget>().is_err());
+
+    let lbf = lookup_unit("lbf").expect("lbf is known");
+    let kn = lookup_unit("kN").expect("kN is known");
+    assert!((lbf.to_si / kn.to_si - 0.004_448_221_615_260_5).abs() < 1e-15);
+}
+
+#[test]
+fn slice_and_filter_signature_matches_default_options() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let temp = TempDir::new()?;
+    let slice = SampleSlice { start: 0, end: 500 };
+    let filter = vec!["A2".to_string()];
+
+    let legacy = temp.path().join("legacy.uff");
+    let report = conversion::convert_with_progress(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        &legacy,
+        OutputFormat::Ascii,
+        Some(slice),
+        Some(&filter),
+        |_| {},
+    )?;
+    assert_eq!(report.processed_track_names, ["A2"]);
+    assert!(report.unit_changes.is_empty());
+
+    let options = ConversionOptions {
+        slice: Some(slice),
+        track_list_filter: Some(filter),
+        ..ConversionOptions::default()
+    };
+    let current = temp.path().join("current.uff");
+    conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        &current,
+        OutputFormat::Ascii,
+        &options,
+        |_| {},
+    )?;
+    assert_eq!(fs::read(legacy)?, fs::read(current)?);
+    Ok(())
+}
+
+#[test]
+fn unit_lookup_does_not_fold_prefix_case() {
+    assert!(lookup_unit("mPa").is_none());
+    assert!(lookup_unit("lb").is_none());
+    assert_eq!(lookup_unit("MPa").map(|unit| unit.symbol), Some("MPa"));
+    assert_eq!(lookup_unit("G").map(|unit| unit.symbol), Some("g"));
+    assert_eq!(lookup_unit("KN").map(|unit| unit.symbol), Some("kN"));
+    assert_eq!(lookup_unit("PSI").map(|unit| unit.symbol), Some("psi"));
+}
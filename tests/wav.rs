@@ -0,0 +1,93 @@
+use anyhow::Result;
+use dts_to_uff_converter::conversion::{self, ConversionOptions, OutputFormat};
+use dts_to_uff_converter::dts::DtsReader;
+use std::collections::HashMap;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Returns the chunks of a RIFF/WAVE file keyed by identifier.
+fn chunks(bytes: &[u8]) -> HashMap<String, &[u8]> {
+    assert_eq!(&bytes[..4], b"RIFF");
+    assert_eq!(
+        u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize,
+        bytes.len() - 8
+    );
+    assert_eq!(&bytes[8..12], b"WAVE");
+    let mut chunks = HashMap::new();
+    let mut offset = 12;
+    while offset < bytes.len() {
+        let id = String::from_utf8_lossy(&bytes[offset..offset + 4]).into_owned();
+        let size = u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into().unwrap()) as usize;
+        chunks.insert(id, &bytes[offset + 8..offset + 8 + size]);
+        offset += 8 + size + size % 2;
+    }
+    chunks
+}
+
+#[test]
+fn converts_sample_to_normalized_float_wav() -> Result<()> {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("Bancairon_G1_training6_small");
+    let temp = TempDir::new()?;
+    let output = temp.path().join("test.wav");
+    let options = ConversionOptions {
+        wav_normalize: true,
+        ..ConversionOptions::default()
+    };
+    conversion::convert_with_progress_and_options(
+        &data_dir,
+        &data_dir.join("tracks.txt"),
+        &output,
+        OutputFormat::Wav,
+        &options,
+        |_| {},
+    )?;
+
+    let bytes = std::fs::read(&output)?;
+    let chunks = chunks(&bytes);
+    let format = chunks["fmt "];
+    assert_eq!(u16::from_le_bytes([format[0], format[1]]), 0xFFFE);
+    assert_eq!(u16::from_le_bytes([format[2], format[3]]), 2);
+    assert_eq!(
+        u32::from_le_bytes(format[4..8].try_into().unwrap()),
+        200_000
+    );
+    assert_eq!(u16::from_le_bytes([format[14], format[15]]), 32);
+    assert_eq!(format[24], 3, "IEEE float subformat");
+    assert_eq!(u32::from_le_bytes(chunks["fact"].try_into().unwrap()), 2000);
+
+    let info = String::from_utf8_lossy(chunks["LIST"]);
+    assert!(info.starts_with("INFO"));
+    assert!(info.contains("A1\nA2\0"));
+
+    let scales: Vec<(String, f64)> = std::str::from_utf8(chunks["scal"])?
+        .lines()
+        .map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            assert_eq!(fields[1], "g");
+            (fields[0].to_string(), fields[2].parse().unwrap())
+        })
+        .collect();
+    assert_eq!(scales.len(), 2);
+    assert_eq!(scales[0].0, "A1");
+
+    let reader = DtsReader::new(&data_dir)?;
+    let a1 = reader.read_track(0)?;
+    let peak = a1
+        .time_series
+        .iter()
+        .fold(0.0f64, |peak, v| peak.max(v.abs()));
+    assert_eq!(scales[0].1, peak);
+
+    let data = chunks["data"];
+    assert_eq!(data.len(), 2000 * 2 * 4);
+    let samples: Vec<f32> = data
+        .chunks(4)
+        .map(|sample| f32::from_le_bytes(sample.try_into().unwrap()))
+        .collect();
+    assert!(samples.iter().all(|sample| sample.abs() <= 1.0));
+    for index in [0, 10, 1999] {
+        let recovered = f64::from(samples[index * 2]) * scales[0].1;
+        assert!((recovered - a1.time_series[index]).abs() <= 1e-6 * peak);
+    }
+    Ok(())
+}